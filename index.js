@@ -4,49 +4,104 @@ require('dotenv').config();
 const { program } = require('commander');
 const chalk = require('chalk');
 const path = require('path');
+const fs = require('fs-extra');
 const { 
   analyzeSmartContract, 
   analyzeCodebase,
   analyzeSolidityContract, 
   analyzeRustContract,
-  explainCode
+  analyzeRustCrate,
+  explainCode,
+  explainRustCrate
 } = require('./src/analyzer');
 const { generateDiagram } = require('./src/diagrammer');
 const { generateSemgrepRules } = require('./src/semgrep-generator');
 const { staticAnalyzeContract } = require('./src/static-analyzer');
 const { generateRustSemgrepRules } = require('./src/rust-semgrep-generator');
+const { writeRustRulePack } = require('./src/rust-semgrep-pack');
+const { runRustSemgrep } = require('./src/semgrep-runner');
+const { findSemgrep } = require('./src/semgrep-validator');
 const { staticAnalyzeRustContract } = require('./src/rust-static-analyzer');
-const { generateRustDiagram } = require('./src/rust-diagrammer');
+const { generateRustDiagram, generateRustCrateCallGraph } = require('./src/rust-diagrammer');
+const { buildCrateCallGraph } = require('./src/rust-callgraph');
 const { processInput } = require('./src/repo-handler');
+const { parseFormats, reportName } = require('./src/report-formats');
+const { parseProfiles, PROFILE_NAMES } = require('./src/rust-profiles');
+const { configureProvider, PROVIDERS } = require('./src/llm-provider');
+const { startMockServer } = require('./src/llm-mock-server');
+const { configureCache } = require('./src/llm-cache');
+const { describeFileChange } = require('./src/diff-scope');
+const { BASELINE_FILE, loadBaseline, writeBaseline } = require('./src/baseline');
 
 console.log(chalk.blue.bold('\n🛡️  WINSTON - Web3 AI Security Auditor 🛡️\n'));
 
 program
   .version('1.0.0')
-  .description('Web3 AI Security Auditing Tool powered by Claude 3.7');
+  .description('Web3 AI Security Auditing Tool powered by Claude, GPT or a local LLM');
 
 program
   .command('audit <input>')
   .description('Run a full security audit on Solidity/Rust contracts (file, directory, or git repository)')
   .option('-d, --diagram', 'Generate code diagram')
   .option('-a, --analysis', 'Generate functionality analysis')
-  .option('-s, --semgrep', 'Generate semgrep rules')
+  .option('-s, --semgrep', 'Generate semgrep rules (for Rust, the curated rule pack plus model-generated rules)')
   .option('-t, --static', 'Run static analysis')
   .option('-e, --explain', 'Generate detailed function explanations and logic vulnerability analysis')
   .option('-r, --rust', 'Analyze only Rust files')
   .option('-g, --git', 'Treat the input URL as a git repository')
   .option('-o, --output <directory>', 'Output directory for results', './output')
+  .option('--crates <names>', 'Rust crates to audit, comma separated (default: all first-party workspace crates)')
+  .option('--targets <kinds>', 'Cargo target kinds to audit: lib, bin, build, test, bench, example or all (comma separated)', 'lib,bin')
+  .option('-c, --crate-context', 'Run --analysis and --explain once per Rust crate target with cross-module context instead of per file')
+  .option('--context-tokens <n>', 'Token budget for the combined crate context', '60000')
+  .option('--chunk-tokens <n>', 'Rust files larger than this many tokens are analyzed and explained in chunks of this size', '12000')
+  .option('-f, --format <formats>', 'Format(s) of the static and structured Rust analysis reports: markdown, json, sarif (comma separated)', 'markdown')
+  .option('-p, --profile <names>', `Rust framework profiles for static analysis: auto, none or ${PROFILE_NAMES.join(', ')} (comma separated)`, 'auto')
+  .option('--run-semgrep', 'Run the local semgrep binary with the curated and generated Rust rules and merge its matches into the Rust static analysis')
+  .option('--entry-points <names>', 'Rust functions to treat as entry points for reachability, comma separated (default: pub fns, main and framework handlers)')
+  .option('--provider <name>', `LLM provider: ${Object.keys(PROVIDERS).join(', ')} (default: anthropic, or WINSTON_LLM_PROVIDER)`)
+  .option('--model <name>', 'LLM model (default depends on the provider, or WINSTON_LLM_MODEL)')
+  .option('--max-tokens <n>', 'Maximum tokens per LLM response (default: 4000, or WINSTON_LLM_MAX_TOKENS)')
+  .option('--temperature <t>', 'LLM sampling temperature from 0 to 2 (default: the provider\'s, or WINSTON_LLM_TEMPERATURE)')
+  .option('--base-url <url>', 'Base URL of the LLM API, e.g. http://localhost:11434/v1 for Ollama (or WINSTON_LLM_BASE_URL)')
+  .option('--mock-responses <directory>', 'Replay recorded LLM responses from a directory instead of calling a model (implies --provider mock)')
+  .option('--record-responses <directory>', 'Record every LLM request and response to a directory, for later use with --mock-responses')
+  .option('--no-cache', 'Always call the model instead of reusing cached responses (or WINSTON_CACHE=off)')
+  .option('--refresh', 'Ignore cached responses but cache the new ones')
+  .option('--diff <base-ref>', 'Audit only the .rs and .sol files and functions changed since a git ref, e.g. origin/main')
+  .option('--baseline <file>', 'Baseline of accepted static analysis findings, which reports list as suppressed (relative to the audited repository)', BASELINE_FILE)
+  .option('--update-baseline', 'Write the current static analysis findings to the baseline file (implies --static)')
   .action(async (input, options) => {
     try {
+      // Fail fast on an unknown report format, profile or LLM setting before any analysis runs
+      parseFormats(options.format);
+      parseProfiles(options.profile);
+      configureProvider(options);
+      configureCache(options);
+      const entryPoints = options.entryPoints
+        ? options.entryPoints.split(',').map(name => name.trim()).filter(Boolean)
+        : null;
+      // Semgrep is optional: without the binary the run is skipped and the rest of the audit proceeds
+      if (options.runSemgrep && !findSemgrep()) {
+        console.log(chalk.yellow('semgrep is not installed (or SEMGREP_BIN is not set); skipping --run-semgrep'));
+        options.runSemgrep = false;
+      }
+      
       console.log(chalk.yellow(`Processing input: ${input}`));
       
       // Process the input (file, directory, or repository)
-      const { solidityFiles, rustFiles } = await processInput(input, { 
+      const { solidityFiles, rustFiles, rustGroups, root, diff } = await processInput(input, { 
         ...options,
         forceGit: options.git // Pass the git flag to processInput
       });
+      // A relative baseline path is in the audited repository, not the working directory
+      const baseline = loadBaseline(options.baseline, root);
       
       const totalFiles = solidityFiles.length + rustFiles.length;
+      if (totalFiles === 0 && diff) {
+        console.log(chalk.green(`No Solidity or Rust files changed since ${options.diff}; nothing to audit`));
+        return;
+      }
       if (totalFiles === 0) {
         console.error(chalk.red('No Solidity or Rust files found to analyze'));
         process.exit(1);
@@ -55,74 +110,158 @@ program
       console.log(chalk.green(`Found ${solidityFiles.length} Solidity files and ${rustFiles.length} Rust files to analyze`));
       
       // By default, run all analyses if no specific ones are selected
-      const runAll = !options.diagram && !options.analysis && !options.semgrep && !options.static && !options.explain;
+      const runAll = !options.diagram && !options.analysis && !options.semgrep && !options.static && !options.explain && !options.updateBaseline;
+      // The baseline is built from the static analysis, so --update-baseline runs it
+      const runStatic = runAll || options.static || options.updateBaseline;
       
       // Analyze Solidity files
       if (solidityFiles.length > 0 && !options.rust) {
         for (const file of solidityFiles) {
           const filename = path.basename(file);
+          const change = describeFileChange(diff, file);
+          // Reports are named after the path below the audit root, so same-named contracts keep their own
+          const name = reportName(file, root);
           console.log(chalk.yellow(`\nAnalyzing contract: ${filename}`));
           
           if (runAll || options.diagram) {
             console.log(chalk.green('🔍 Generating code diagram...'));
-            await generateDiagram(file, options.output);
+            await generateDiagram(file, options.output, { reportName: name });
           }
           
           if (runAll || options.analysis) {
             console.log(chalk.green('🔍 Analyzing functionality and business logic...'));
-            await analyzeSolidityContract(file, options.output);
+            await analyzeSolidityContract(file, options.output, { change, reportName: name });
           }
           
           if (runAll || options.semgrep) {
             console.log(chalk.green('🔍 Generating semgrep templates...'));
-            await generateSemgrepRules(file, options.output);
+            await generateSemgrepRules(file, options.output, { reportName: name });
           }
           
-          if (runAll || options.static) {
+          if (runStatic) {
             console.log(chalk.green('🔍 Performing static security analysis...'));
-            await staticAnalyzeContract(file, options.output);
+            await staticAnalyzeContract(file, options.output, { format: options.format, change, baseline, root, reportName: name });
           }
           
           if (runAll || options.explain) {
             console.log(chalk.green('🔍 Generating detailed function explanations and logic vulnerability analysis...'));
-            await explainCode(file, options.output, false);
+            await explainCode(file, options.output, false, { reportName: name });
           }
         }
       }
       
-      // Analyze Rust files
-      if (rustFiles.length > 0) {
-        for (const file of rustFiles) {
+      // Analyze Rust files, one crate target at a time; each crate gets its own output directory
+      const rulePackDirs = new Set();
+      // A file in the module trees of several targets of a crate (e.g. `mod util;` in lib.rs and main.rs) is audited once
+      const auditedFiles = new Set();
+      for (const group of rustGroups) {
+        const outputDir = group.crate ? path.join(options.output, group.crate) : options.output;
+        await fs.ensureDir(outputDir);
+        // Reports are named after the path below the crate (or audit root), so `mod.rs` files keep their own
+        const reportDir = group.crate ? path.dirname(group.manifestPath) : root;
+        
+        if (group.crate) {
+          console.log(chalk.magenta.bold(`\n📦 Crate ${group.crate} — ${group.target.kind} target ${group.target.name}`));
+        }
+        
+        // In crate mode the model sees the whole target at once; per-file runs would repeat the same work
+        const crateMode = Boolean(options.crateContext && group.crate);
+        if (crateMode && (runAll || options.analysis)) {
+          console.log(chalk.green('🔍 Analyzing Rust crate for cross-module security vulnerabilities...'));
+          await analyzeRustCrate(group, outputDir, { contextTokens: options.contextTokens });
+        }
+        if (crateMode && (runAll || options.explain)) {
+          console.log(chalk.green('🔍 Generating crate-level explanations and logic vulnerability analysis...'));
+          await explainRustCrate(group, outputDir, { contextTokens: options.contextTokens });
+        }
+        
+        // The curated rule pack needs no API key; write it once per output directory
+        if ((runAll || options.semgrep) && !rulePackDirs.has(outputDir)) {
+          rulePackDirs.add(outputDir);
+          writeRustRulePack(outputDir);
+        }
+        
+        // The crate call graph lets reachability follow calls across the crate's files
+        let callGraph = null;
+        if (group.crate && (runAll || options.diagram)) {
+          console.log(chalk.green('🔍 Building the crate call graph...'));
+          await generateRustCrateCallGraph(group, outputDir, { entryPoints });
+        }
+        if (group.crate && (runStatic || options.runSemgrep)) {
+          try {
+            callGraph = buildCrateCallGraph(group);
+          } catch (error) {
+            console.error(chalk.yellow(`Could not build the call graph of crate ${group.crate}: ${error.message}`));
+          }
+        }
+        
+        // With --diff the group keeps its unchanged files for crate context, but only changed files are audited
+        const files = group.files.filter(file => {
+          if (diff && !diff.files.has(path.resolve(file))) return false;
+          if (auditedFiles.has(path.resolve(file))) return false;
+          auditedFiles.add(path.resolve(file));
+          return true;
+        });
+        
+        // Rules are generated for every file first, so one Semgrep run over the group uses all of them
+        if (runAll || options.semgrep) {
+          for (const file of files) {
+            console.log(chalk.green(`🔍 Generating Rust semgrep rules for ${path.basename(file)}...`));
+            await generateRustSemgrepRules(file, outputDir, { reportName: reportName(file, reportDir) });
+          }
+        }
+        
+        // Semgrep matches are merged into the static analysis, so --run-semgrep runs it too
+        let semgrepMatches = null;
+        if (options.runSemgrep && files.length > 0) {
+          console.log(chalk.green('🔍 Running semgrep rules...'));
+          semgrepMatches = runRustSemgrep(files, outputDir);
+        }
+        
+        for (const file of files) {
           const filename = path.basename(file);
+          const change = describeFileChange(diff, file);
+          const name = reportName(file, reportDir);
           console.log(chalk.yellow(`\nAnalyzing Rust code: ${filename}`));
           
           if (runAll || options.diagram) {
             console.log(chalk.green('🔍 Generating Rust code diagram...'));
-            await generateRustDiagram(file, options.output);
+            await generateRustDiagram(file, outputDir, { reportName: name });
           }
           
-          if (runAll || options.analysis) {
+          if ((runAll || options.analysis) && !crateMode) {
             console.log(chalk.green('🔍 Analyzing Rust code for security vulnerabilities...'));
-            await analyzeRustContract(file, options.output);
-          }
-          
-          if (runAll || options.semgrep) {
-            console.log(chalk.green('🔍 Generating Rust semgrep rules...'));
-            await generateRustSemgrepRules(file, options.output);
+            await analyzeRustContract(file, outputDir, { format: options.format, chunkTokens: options.chunkTokens, change, root, reportName: name });
           }
           
-          if (runAll || options.static) {
+          if (runStatic || options.runSemgrep) {
             console.log(chalk.green('🔍 Performing Rust static security analysis...'));
-            await staticAnalyzeRustContract(file, options.output);
+            await staticAnalyzeRustContract(file, outputDir, {
+              format: options.format,
+              crate: group.crate ? { name: group.crate, target: group.target } : null,
+              profile: options.profile,
+              callGraph,
+              entryPoints,
+              semgrepMatches: semgrepMatches ? semgrepMatches.get(path.resolve(file)) || [] : null,
+              change,
+              baseline,
+              root,
+              reportName: name
+            });
           }
           
-          if (runAll || options.explain) {
+          if ((runAll || options.explain) && !crateMode) {
             console.log(chalk.green('🔍 Generating detailed function explanations and logic vulnerability analysis...'));
-            await explainCode(file, options.output, true);
+            await explainCode(file, outputDir, true, { chunkTokens: options.chunkTokens, change, reportName: name });
           }
         }
       }
       
+      if (options.updateBaseline) {
+        const written = writeBaseline(baseline);
+        console.log(chalk.green(`\nBaseline updated: ${written.count} finding(s) from ${written.files} analyzed file(s) saved to ${written.path}`));
+      }
+      
       console.log(chalk.green.bold('\n✅ Audit completed successfully!'));
       console.log(chalk.blue(`Results saved to: ${options.output}`));
     } catch (error) {
@@ -131,6 +270,17 @@ program
     }
   });
 
+program
+  .command('mock-llm <recordings>')
+  .description('Serve recorded LLM responses on OpenAI- and Anthropic-compatible endpoints, for offline runs')
+  .option('--port <port>', 'Port to listen on', '8787')
+  .action(async (recordings, options) => {
+    const server = await startMockServer({ recordings, port: Number(options.port) });
+    const { port } = server.address();
+    console.log(chalk.green(`Mock LLM server replaying ${recordings} on http://localhost:${port}`));
+    console.log(chalk.blue(`Use --provider openai --base-url http://localhost:${port}/v1 or --provider anthropic --base-url http://localhost:${port}`));
+  });
+
 program.parse(process.argv);
 
 // Show help if no commands provided
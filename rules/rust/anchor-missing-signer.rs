@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = authority)]
+    pub vault: Account<'info, Vault>,
+    /// CHECK: compared against vault.authority
+    // ruleid: anchor-missing-signer
+    pub authority: AccountInfo<'info>,
+    /// CHECK: only receives lamports
+    // ok: anchor-missing-signer
+    pub recipient: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Close<'info> {
+    #[account(mut, has_one = owner, close = owner)]
+    pub vault: Account<'info, Vault>,
+    // ok: anchor-missing-signer
+    pub owner: Signer<'info>,
+}
+
@@ -0,0 +1,16 @@
+use std::fs;
+
+pub fn save(path: &str, data: &[u8]) {
+    // ruleid: rust-ignored-result
+    let _ = fs::write(path, data);
+    // ruleid: rust-ignored-result
+    fs::remove_file("cache.tmp").ok();
+}
+
+pub fn save_checked(path: &str, data: &[u8]) -> std::io::Result<()> {
+    // ok: rust-ignored-result
+    fs::write(path, data)?;
+    // ok: rust-ignored-result
+    let _guard = fs::File::open(path)?;
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use std::convert::TryFrom;
+
+pub fn to_lamports(amount: u128) -> u64 {
+    // ruleid: rust-truncating-cast
+    amount as u64
+}
+
+pub fn decimals(value: i64) -> u8 {
+    // ruleid: rust-truncating-cast
+    value as u8
+}
+
+pub fn to_lamports_checked(amount: u128) -> Option<u64> {
+    // ok: rust-truncating-cast
+    u64::try_from(amount).ok()
+}
+
+pub fn widen(value: u32) -> u128 {
+    // ok: rust-truncating-cast
+    value as u128
+}
+
+pub fn one() -> u32 {
+    // ok: rust-truncating-cast
+    1 as u32
+}
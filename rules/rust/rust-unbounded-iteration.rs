@@ -0,0 +1,36 @@
+use cosmwasm_std::{Deps, Order, StdResult, Uint128};
+use cw_storage_plus::Map;
+
+const BALANCES: Map<&str, Uint128> = Map::new("balances");
+
+pub fn all_balances(deps: Deps) -> StdResult<Vec<(String, Uint128)>> {
+    // ruleid: rust-unbounded-iteration
+    BALANCES.range(deps.storage, None, None, Order::Ascending).collect()
+}
+
+pub fn balances_page(deps: Deps, limit: usize) -> StdResult<Vec<(String, Uint128)>> {
+    // ok: rust-unbounded-iteration
+    BALANCES.range(deps.storage, None, None, Order::Ascending).take(limit).collect()
+}
+
+pub struct Pool {
+    stakers: Vec<u64>,
+}
+
+impl Pool {
+    pub fn distribute(&mut self, reward: u64) {
+        // ruleid: rust-unbounded-iteration
+        for staker in self.stakers.iter() {
+            credit(*staker, reward);
+        }
+    }
+
+    pub fn distribute_to(&mut self, recipients: &[u64], reward: u64) {
+        // ok: rust-unbounded-iteration
+        for staker in recipients.iter() {
+            credit(*staker, reward);
+        }
+    }
+}
+
+fn credit(_staker: u64, _reward: u64) {}
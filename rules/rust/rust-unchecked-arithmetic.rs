@@ -0,0 +1,24 @@
+pub struct Vault {
+    total_supply: u64,
+    balances: Vec<u64>,
+    nonce: u64,
+}
+
+impl Vault {
+    pub fn deposit(&mut self, user: usize, amount: u64) {
+        // ruleid: rust-unchecked-arithmetic
+        self.balances[user] += amount;
+        // ruleid: rust-unchecked-arithmetic
+        self.total_supply = self.total_supply + amount;
+    }
+
+    pub fn withdraw(&mut self, user: usize, amount: u64) -> Option<()> {
+        // ok: rust-unchecked-arithmetic
+        self.balances[user] = self.balances[user].checked_sub(amount)?;
+        // ok: rust-unchecked-arithmetic
+        self.total_supply = self.total_supply.checked_sub(amount)?;
+        // ok: rust-unchecked-arithmetic
+        self.nonce += 1;
+        Some(())
+    }
+}
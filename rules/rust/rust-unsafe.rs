@@ -0,0 +1,14 @@
+pub fn first_byte(data: &[u8]) -> u8 {
+    // ruleid: rust-unsafe
+    unsafe { *data.get_unchecked(0) }
+}
+
+// ruleid: rust-unsafe
+pub unsafe fn read_at(data: *const u8, offset: usize) -> u8 {
+    *data.add(offset)
+}
+
+pub fn first_byte_checked(data: &[u8]) -> Option<u8> {
+    // ok: rust-unsafe
+    data.first().copied()
+}
@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+
+pub struct Registry {
+    owners: HashMap<String, String>,
+}
+
+impl Registry {
+    pub fn owner_of(&self, name: &str) -> String {
+        // ruleid: rust-unwrap-in-entry-point
+        self.owners.get(name).unwrap().clone()
+    }
+
+    pub fn transfer(&mut self, name: &str, to: String) {
+        // ruleid: rust-unwrap-in-entry-point
+        let entry = self.owners.get_mut(name).expect("unknown name");
+        *entry = to;
+    }
+
+    pub fn try_owner_of(&self, name: &str) -> Option<String> {
+        // ok: rust-unwrap-in-entry-point
+        self.owners.get(name).cloned()
+    }
+
+    fn first_owner(&self) -> String {
+        // ok: rust-unwrap-in-entry-point
+        self.owners.values().next().unwrap().clone()
+    }
+}
@@ -0,0 +1,82 @@
+// Panic-path fixture: each public method reaches a different panic source.
+use std::collections::HashMap;
+
+const FEE_DENOMINATOR: u64 = 10_000;
+
+pub struct Pool {
+    owner: String,
+    shares: HashMap<String, u64>,
+    total_shares: u64,
+    reserves: Vec<u64>,
+    fee_bps: u64,
+    root: [u8; 32],
+}
+
+impl Pool {
+    pub fn new(owner: String) -> Self {
+        Pool { owner, shares: HashMap::with_capacity(16), total_shares: 0, reserves: vec![0, 0], fee_bps: 30, root: [0; 32] }
+    }
+
+    // Division by `total_shares`, which is zero before the first deposit
+    pub fn share_price(&self, token: usize) -> u64 {
+        self.reserve(token) / self.total_shares
+    }
+
+    // Guarded division: not a panic source
+    pub fn share_value(&self, account: &str) -> u64 {
+        if self.total_shares == 0 {
+            return 0;
+        }
+        let shares = self.shares.get(account).copied().unwrap_or(0);
+        shares * self.reserves[0] / self.total_shares
+    }
+
+    pub fn fee(&self, amount: u64) -> u64 {
+        amount * self.fee_bps / FEE_DENOMINATOR
+    }
+
+    pub fn withdraw(&mut self, account: &str, amount: u64) {
+        let shares = self.shares.get_mut(account).expect("unknown account");
+        assert!(*shares >= amount, "not enough shares");
+        *shares -= amount;
+        self.total_shares -= amount;
+    }
+
+    pub fn migrate(&mut self, version: u8) {
+        match version {
+            1 => self.reserves.push(0),
+            2 => todo!("migration to v2"),
+            _ => panic!("unknown version"),
+        }
+    }
+
+    pub fn prefix(&self, data: &[u8], len: usize) -> Vec<u8> {
+        data[..len].to_vec()
+    }
+
+    pub fn root_byte(&self) -> u8 {
+        self.root[0]
+    }
+
+    // Indexing a Vec with a caller-supplied index
+    fn reserve(&self, token: usize) -> u64 {
+        self.reserves[token]
+    }
+
+    // Not reachable from any public method
+    fn unused(&self) -> u64 {
+        self.reserves[5]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_of_empty_pool_panics() {
+        let pool = Pool::new("owner".to_string());
+        pool.share_price(0);
+        assert_eq!(pool.fee(10_000), 30);
+    }
+}
@@ -0,0 +1,178 @@
+// Anchor fixture: vulnerable_vault.rs with every issue fixed. The Anchor profile reports nothing here.
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+declare_id!("Vau1t11111111111111111111111111111111111111");
+
+#[program]
+pub mod secure_vault {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.authority = ctx.accounts.authority.key();
+        vault.bump = ctx.bumps.vault;
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        let cpi_accounts = system_program::Transfer {
+            from: ctx.accounts.depositor.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+        };
+        system_program::transfer(CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts), amount)
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.sub_lamports(amount)?;
+        ctx.accounts.recipient.add_lamports(amount)?;
+        Ok(())
+    }
+
+    pub fn set_authority(ctx: Context<SetAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.vault.authority = new_authority;
+        Ok(())
+    }
+
+    pub fn record_price(ctx: Context<RecordPrice>) -> Result<()> {
+        require_keys_eq!(*ctx.accounts.price_feed.owner, ORACLE_PROGRAM_ID, VaultError::InvalidOracle);
+        let data = ctx.accounts.price_feed.try_borrow_data()?;
+        let bytes: [u8; 8] = data.get(8..16).ok_or(VaultError::InvalidOracle)?.try_into().map_err(|_| VaultError::InvalidOracle)?;
+        ctx.accounts.vault.last_price = u64::from_le_bytes(bytes);
+        Ok(())
+    }
+
+    pub fn create_profile(ctx: Context<CreateProfile>, first_name: String, last_name: String) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        profile.user = ctx.accounts.user.key();
+        profile.first_name = first_name;
+        profile.last_name = last_name;
+        Ok(())
+    }
+
+    pub fn close_vault(_ctx: Context<CloseVault>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn sweep_fees(ctx: Context<SweepFees>, amount: u64) -> Result<()> {
+        let authority_key = ctx.accounts.authority.key();
+        let bump = ctx.bumps.fee_authority;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"fees", authority_key.as_ref(), &[bump]]];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.fee_account.to_account_info(),
+            to: ctx.accounts.treasury.to_account_info(),
+            authority: ctx.accounts.fee_authority.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds),
+            amount,
+        )
+    }
+}
+
+pub const ORACLE_PROGRAM_ID: Pubkey = pubkey!("Orac1e1111111111111111111111111111111111111");
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = authority, space = 8 + Vault::LEN, seeds = [b"vault", authority.key().as_ref()], bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = authority @ VaultError::Unauthorized)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordPrice<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    /// CHECK: owner is checked against ORACLE_PROGRAM_ID before the data is read
+    pub price_feed: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(first_name: String, last_name: String)]
+pub struct CreateProfile<'info> {
+    #[account(init, payer = user, space = 8 + Profile::LEN, seeds = [b"profile", user.key().as_ref(), first_name.as_bytes()], bump)]
+    pub profile: Account<'info, Profile>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut, has_one = authority, seeds = [b"vault", authority.key().as_ref()], bump = vault.bump, close = receiver)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub receiver: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    #[account(mut)]
+    pub fee_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub treasury: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns the fee account, derived from the seeds below
+    #[account(seeds = [b"fees", authority.key().as_ref()], bump)]
+    pub fee_authority: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub last_price: u64,
+    pub bump: u8,
+}
+
+impl Vault {
+    pub const LEN: usize = 32 + 8 + 1;
+}
+
+#[account]
+pub struct Profile {
+    pub user: Pubkey,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+impl Profile {
+    pub const LEN: usize = 32 + 4 + 32 + 4 + 32;
+}
+
+#[error_code]
+pub enum VaultError {
+    #[msg("The signer is not the vault authority")]
+    Unauthorized,
+    #[msg("The price feed is not owned by the oracle program")]
+    InvalidOracle,
+}
@@ -0,0 +1,173 @@
+// Anchor fixture with one instance of each issue the Anchor profile reports.
+// Compare with secure_vault.rs, the same program with the issues fixed.
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+declare_id!("Vau1t11111111111111111111111111111111111111");
+
+#[program]
+pub mod vulnerable_vault {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.authority = ctx.accounts.authority.key();
+        vault.bump = ctx.bumps.vault;
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        let cpi_accounts = system_program::Transfer {
+            from: ctx.accounts.depositor.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+        };
+        system_program::transfer(CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts), amount)
+    }
+
+    // anchor-missing-has-one: any signer can drain any vault
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+
+    // anchor-missing-signer, anchor-unchecked-account: the authority only has to be named, not sign
+    pub fn set_authority(ctx: Context<SetAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.vault.authority = new_authority;
+        Ok(())
+    }
+
+    // anchor-missing-owner-check, anchor-missing-mut: the price feed can be forged and the update is lost
+    pub fn record_price(ctx: Context<RecordPrice>) -> Result<()> {
+        let data = ctx.accounts.price_feed.try_borrow_data()?;
+        let price = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        ctx.accounts.vault.last_price = price;
+        Ok(())
+    }
+
+    // anchor-pda-seed-collision: ("ab", "c") and ("a", "bc") derive the same profile
+    pub fn create_profile(ctx: Context<CreateProfile>, first_name: String, last_name: String) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        profile.user = ctx.accounts.user.key();
+        profile.first_name = first_name;
+        profile.last_name = last_name;
+        Ok(())
+    }
+
+    // anchor-pda-noncanonical-bump, anchor-missing-mut: caller-chosen bump, and the receiver is not mutable
+    pub fn close_vault(ctx: Context<CloseVault>, bump: u8) -> Result<()> {
+        msg!("closing vault with bump {}", bump);
+        Ok(())
+    }
+
+    // anchor-pda-noncanonical-bump: the vault signs with a bump passed by the caller
+    pub fn sweep_fees(ctx: Context<SweepFees>, amount: u64, bump: u8) -> Result<()> {
+        let authority_key = ctx.accounts.authority.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"fees", authority_key.as_ref(), &[bump]]];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.fee_account.to_account_info(),
+            to: ctx.accounts.treasury.to_account_info(),
+            authority: ctx.accounts.fee_authority.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds),
+            amount,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = authority, space = 8 + Vault::LEN, seeds = [b"vault", authority.key().as_ref()], bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    pub vault: Account<'info, Vault>,
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordPrice<'info> {
+    pub vault: Account<'info, Vault>,
+    /// CHECK: price feed published by the oracle
+    pub price_feed: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(first_name: String, last_name: String)]
+pub struct CreateProfile<'info> {
+    #[account(init, payer = user, space = 8 + Profile::LEN, seeds = [b"profile", first_name.as_bytes(), last_name.as_bytes()], bump)]
+    pub profile: Account<'info, Profile>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct CloseVault<'info> {
+    #[account(mut, has_one = authority, seeds = [b"vault", authority.key().as_ref()], bump = bump, close = receiver)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+    pub receiver: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    #[account(mut)]
+    pub fee_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub treasury: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns the fee account
+    #[account(seeds = [b"fees", authority.key().as_ref()], bump)]
+    pub fee_authority: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub last_price: u64,
+    pub bump: u8,
+}
+
+impl Vault {
+    pub const LEN: usize = 32 + 8 + 1;
+}
+
+#[account]
+pub struct Profile {
+    pub user: Pubkey,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+impl Profile {
+    pub const LEN: usize = 32 + 4 + 32 + 4 + 32;
+}
@@ -0,0 +1,148 @@
+// CosmWasm fixture: vulnerable_escrow.rs with every issue fixed. The CosmWasm profile reports nothing here.
+use cosmwasm_std::{
+    ensure_eq, entry_point, to_json_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Order,
+    Response, StdResult, Uint128,
+};
+use cw_storage_plus::{Bound, Item, Map};
+use cw_utils::{must_pay, nonpayable};
+use serde::{Deserialize, Serialize};
+
+pub const DENOM: &str = "uatom";
+pub const MAX_DISTRIBUTION_BATCH: usize = 50;
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const PAUSED: Item<bool> = Item::new("paused");
+pub const TOTAL_DEPOSITS: Item<Uint128> = Item::new("total_deposits");
+pub const BALANCES: Map<&Addr, Uint128> = Map::new("balances");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Config {
+    pub owner: Addr,
+    pub fee_bps: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InstantiateMsg {
+    pub fee_bps: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ExecuteMsg {
+    Deposit {},
+    Withdraw { amount: Uint128 },
+    UpdateConfig { owner: String, fee_bps: u64 },
+    Distribute { reward: Uint128, start_after: Option<String> },
+    Pause {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum QueryMsg {
+    Balance { address: String },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ContractError {
+    Std(cosmwasm_std::StdError),
+    Payment(cw_utils::PaymentError),
+    Unauthorized {},
+}
+
+impl From<cosmwasm_std::StdError> for ContractError {
+    fn from(error: cosmwasm_std::StdError) -> Self {
+        ContractError::Std(error)
+    }
+}
+
+impl From<cw_utils::PaymentError> for ContractError {
+    fn from(error: cw_utils::PaymentError) -> Self {
+        ContractError::Payment(error)
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, msg: InstantiateMsg) -> Result<Response, ContractError> {
+    CONFIG.save(deps.storage, &Config { owner: info.sender, fee_bps: msg.fee_bps })?;
+    PAUSED.save(deps.storage, &false)?;
+    TOTAL_DEPOSITS.save(deps.storage, &Uint128::zero())?;
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Deposit {} => execute_deposit(deps, info),
+        ExecuteMsg::Withdraw { amount } => execute_withdraw(deps, info, amount),
+        ExecuteMsg::UpdateConfig { owner, fee_bps } => execute_update_config(deps, info, owner, fee_bps),
+        ExecuteMsg::Distribute { reward, start_after } => execute_distribute(deps, env, info, reward, start_after),
+        ExecuteMsg::Pause {} => {
+            nonpayable(&info)?;
+            let config = CONFIG.load(deps.storage)?;
+            ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+            PAUSED.save(deps.storage, &true)?;
+            Ok(Response::new().add_attribute("action", "pause"))
+        }
+    }
+}
+
+pub fn execute_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let amount = must_pay(&info, DENOM)?;
+    BALANCES.update(deps.storage, &info.sender, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(amount)?)
+    })?;
+    let total = TOTAL_DEPOSITS.load(deps.storage)?;
+    TOTAL_DEPOSITS.save(deps.storage, &total.checked_add(amount)?)?;
+    Ok(Response::new().add_attribute("action", "deposit"))
+}
+
+pub fn execute_withdraw(deps: DepsMut, info: MessageInfo, amount: Uint128) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let balance = BALANCES.load(deps.storage, &info.sender)?;
+    BALANCES.save(deps.storage, &info.sender, &balance.checked_sub(amount)?)?;
+    let send = BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![Coin { denom: DENOM.to_string(), amount }],
+    };
+    Ok(Response::new().add_message(send))
+}
+
+pub fn execute_update_config(deps: DepsMut, info: MessageInfo, owner: String, fee_bps: u64) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let owner = deps.api.addr_validate(&owner)?;
+    CONFIG.save(deps.storage, &Config { owner, fee_bps })?;
+    Ok(Response::new().add_attribute("updated_by", info.sender))
+}
+
+pub fn execute_distribute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    reward: Uint128,
+    start_after: Option<String>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {});
+    let start = start_after.map(|address| deps.api.addr_validate(&address)).transpose()?;
+    let holders: Vec<(Addr, Uint128)> = BALANCES
+        .range(deps.storage, start.as_ref().map(Bound::exclusive), None, Order::Ascending)
+        .take(MAX_DISTRIBUTION_BATCH)
+        .collect::<StdResult<_>>()?;
+    for (holder, balance) in holders {
+        BALANCES.save(deps.storage, &holder, &balance.checked_add(reward)?)?;
+    }
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Balance { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&BALANCES.may_load(deps.storage, &address)?.unwrap_or_default())
+        }
+    }
+}
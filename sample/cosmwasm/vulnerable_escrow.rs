@@ -0,0 +1,127 @@
+// CosmWasm fixture with one instance of each issue the CosmWasm profile reports.
+// Compare with secure_escrow.rs, the same contract with the issues fixed.
+use cosmwasm_std::{
+    entry_point, to_json_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Order, Response,
+    StdResult, Uint128,
+};
+use cw_storage_plus::{Item, Map};
+use serde::{Deserialize, Serialize};
+
+pub const DENOM: &str = "uatom";
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const PAUSED: Item<bool> = Item::new("paused");
+pub const TOTAL_DEPOSITS: Item<Uint128> = Item::new("total_deposits");
+pub const BALANCES: Map<&Addr, Uint128> = Map::new("balances");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Config {
+    pub owner: Addr,
+    pub fee_bps: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InstantiateMsg {
+    pub fee_bps: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ExecuteMsg {
+    Deposit {},
+    Withdraw { amount: Uint128 },
+    UpdateConfig { owner: String, fee_bps: u64 },
+    Distribute { reward: Uint128 },
+    Pause {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum QueryMsg {
+    Balance { address: String },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ContractError {
+    Std(cosmwasm_std::StdError),
+    Unauthorized {},
+}
+
+impl From<cosmwasm_std::StdError> for ContractError {
+    fn from(error: cosmwasm_std::StdError) -> Self {
+        ContractError::Std(error)
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, msg: InstantiateMsg) -> Result<Response, ContractError> {
+    CONFIG.save(deps.storage, &Config { owner: info.sender, fee_bps: msg.fee_bps })?;
+    PAUSED.save(deps.storage, &false)?;
+    TOTAL_DEPOSITS.save(deps.storage, &Uint128::zero())?;
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Deposit {} => execute_deposit(deps, info),
+        ExecuteMsg::Withdraw { amount } => execute_withdraw(deps, info, amount),
+        ExecuteMsg::UpdateConfig { owner, fee_bps } => execute_update_config(deps, info, owner, fee_bps),
+        ExecuteMsg::Distribute { reward } => execute_distribute(deps, env, reward),
+        // cosmwasm-missing-sender-check: anyone can pause the contract
+        ExecuteMsg::Pause {} => {
+            PAUSED.save(deps.storage, &true)?;
+            Ok(Response::new().add_attribute("action", "pause"))
+        }
+    }
+}
+
+// cosmwasm-unvalidated-funds: any denomination is accepted, and only the first coin is counted
+// cosmwasm-unchecked-uint-arithmetic: `total + amount` panics on overflow
+pub fn execute_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let amount: Uint128 = info.funds[0].amount;
+    BALANCES.update(deps.storage, &info.sender, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default() + amount)
+    })?;
+    let total = TOTAL_DEPOSITS.load(deps.storage)?;
+    TOTAL_DEPOSITS.save(deps.storage, &(total + amount))?;
+    Ok(Response::new().add_attribute("action", "deposit"))
+}
+
+// cosmwasm-unchecked-uint-arithmetic: `balance - amount` panics when withdrawing more than the balance
+// cosmwasm-funds-not-rejected: coins attached to a withdrawal are kept
+pub fn execute_withdraw(deps: DepsMut, info: MessageInfo, amount: Uint128) -> Result<Response, ContractError> {
+    let balance = BALANCES.load(deps.storage, &info.sender)?;
+    BALANCES.save(deps.storage, &info.sender, &(balance - amount))?;
+    let send = BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![Coin { denom: DENOM.to_string(), amount }],
+    };
+    Ok(Response::new().add_message(send))
+}
+
+// cosmwasm-missing-sender-check: anyone can take ownership
+pub fn execute_update_config(deps: DepsMut, info: MessageInfo, owner: String, fee_bps: u64) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    CONFIG.save(deps.storage, &Config { owner, fee_bps })?;
+    Ok(Response::new().add_attribute("updated_by", info.sender))
+}
+
+// cosmwasm-missing-sender-check, cosmwasm-unbounded-iteration: anyone can trigger a distribution over every depositor
+pub fn execute_distribute(deps: DepsMut, _env: Env, reward: Uint128) -> Result<Response, ContractError> {
+    let holders: Vec<(Addr, Uint128)> = BALANCES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    for (holder, balance) in holders {
+        BALANCES.save(deps.storage, &holder, &balance.checked_add(reward).map_err(cosmwasm_std::StdError::from)?)?;
+    }
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Balance { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&BALANCES.may_load(deps.storage, &address)?.unwrap_or_default())
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// ink! fixture: vulnerable_vault.rs with every issue fixed. The ink! profile only reports
+// the owner-gated upgrade at info level here.
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[ink::contract]
+mod vault {
+    use ink::storage::Mapping;
+
+    #[ink(storage)]
+    pub struct Vault {
+        owner: AccountId,
+        fee: Balance,
+        total_deposits: Balance,
+        balances: Mapping<AccountId, Balance>,
+    }
+
+    #[ink(event)]
+    pub struct Deposited {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        NotOwner,
+        ZeroAmount,
+        Overflow,
+        InsufficientBalance,
+        TransferFailed,
+        UpgradeFailed,
+    }
+
+    impl Vault {
+        #[ink(constructor)]
+        pub fn new(fee: Balance) -> Self {
+            Self { owner: Self::env().caller(), fee, total_deposits: 0, balances: Mapping::default() }
+        }
+
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            let balance = self.balances.get(caller).unwrap_or(0);
+            let new_balance = balance.checked_add(amount).ok_or(Error::Overflow)?;
+            self.balances.insert(caller, &new_balance);
+            self.total_deposits = self.total_deposits.checked_add(amount).ok_or(Error::Overflow)?;
+            self.env().emit_event(Deposited { account: caller, amount });
+            Ok(())
+        }
+
+        #[ink(message, payable)]
+        pub fn tip(&mut self) -> Result<(), Error> {
+            if self.env().transferred_value() == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn set_fee(&mut self, fee: Balance) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.fee = fee;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.owner = new_owner;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: Hash) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.env().set_code_hash(&code_hash).map_err(|_| Error::UpgradeFailed)
+        }
+
+        #[ink(message)]
+        pub fn withdraw(&mut self, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let balance = self.balances.get(caller).unwrap_or(0);
+            if balance < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            self.balances.insert(caller, &balance.saturating_sub(amount));
+            self.env().transfer(caller, amount).map_err(|_| Error::TransferFailed)
+        }
+
+        #[ink(message)]
+        pub fn balance_of(&self, account: AccountId) -> Balance {
+            self.balances.get(account).unwrap_or(0)
+        }
+
+        fn ensure_owner(&self) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// ink! fixture with one instance of each issue the ink! profile reports.
+// Compare with secure_vault.rs, the same contract with the issues fixed.
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[ink::contract]
+mod vault {
+    use ink::storage::Mapping;
+
+    #[ink(storage)]
+    pub struct Vault {
+        owner: AccountId,
+        fee: Balance,
+        total_deposits: Balance,
+        balances: Mapping<AccountId, Balance>,
+    }
+
+    #[ink(event)]
+    pub struct Deposited {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        NotOwner,
+        InsufficientBalance,
+        TransferFailed,
+    }
+
+    impl Vault {
+        #[ink(constructor)]
+        pub fn new(fee: Balance) -> Self {
+            Self { owner: Self::env().caller(), fee, total_deposits: 0, balances: Mapping::default() }
+        }
+
+        // ink-unchecked-balance-arithmetic: both sums can overflow
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) {
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            let balance = self.balances.get(caller).unwrap_or(0);
+            self.balances.insert(caller, &(balance + amount));
+            self.total_deposits += amount;
+            self.env().emit_event(Deposited { account: caller, amount });
+        }
+
+        // ink-payable-without-value-check: the tip amount is never looked at
+        #[ink(message, payable)]
+        pub fn tip(&mut self) {
+            ink::env::debug_println!("thanks");
+        }
+
+        // ink-missing-caller-check: anyone can change the fee
+        #[ink(message)]
+        pub fn set_fee(&mut self, fee: Balance) {
+            self.fee = fee;
+        }
+
+        // ink-missing-caller-check: anyone can take over the vault
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) {
+            self.owner = new_owner;
+        }
+
+        // ink-unprotected-set-code-hash: anyone can replace the contract code
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: Hash) {
+            self.env().set_code_hash(&code_hash).unwrap();
+        }
+
+        #[ink(message)]
+        pub fn withdraw(&mut self, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let balance = self.balances.get(caller).unwrap_or(0);
+            if balance < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            self.balances.insert(caller, &balance.saturating_sub(amount));
+            self.env().transfer(caller, amount).map_err(|_| Error::TransferFailed)
+        }
+
+        #[ink(message)]
+        pub fn balance_of(&self, account: AccountId) -> Balance {
+            self.balances.get(account).unwrap_or(0)
+        }
+    }
+}
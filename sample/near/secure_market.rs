@@ -0,0 +1,89 @@
+// NEAR fixture: vulnerable_market.rs with every issue fixed. The NEAR profile reports nothing here.
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap};
+use near_sdk::json_types::U128;
+use near_sdk::{env, ext_contract, near_bindgen, require, AccountId, Gas, NearToken, PanicOnDefault, Promise, PromiseError};
+
+const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+const GAS_FOR_RESOLVE: Gas = Gas::from_tgas(5);
+const LISTING_FEE: NearToken = NearToken::from_millinear(100);
+
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Market {
+    owner: AccountId,
+    token: AccountId,
+    balances: LookupMap<AccountId, u128>,
+    listings: UnorderedMap<u64, Listing>,
+    next_listing_id: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Listing {
+    seller: AccountId,
+    price: u128,
+}
+
+#[near_bindgen]
+impl Market {
+    #[init]
+    pub fn new(owner: AccountId, token: AccountId) -> Self {
+        Self {
+            owner,
+            token,
+            balances: LookupMap::new(b"b"),
+            listings: UnorderedMap::new(b"l"),
+            next_listing_id: 0,
+        }
+    }
+
+    #[payable]
+    pub fn pay_listing_fee(&mut self) {
+        require!(env::attached_deposit() == LISTING_FEE, "Attach exactly the listing fee");
+        env::log_str(&format!("fee paid by {}", env::predecessor_account_id()));
+    }
+
+    #[payable]
+    pub fn list(&mut self, price: U128) -> u64 {
+        let initial_storage = env::storage_usage();
+        let id = self.next_listing_id;
+        self.listings.insert(&id, &Listing { seller: env::predecessor_account_id(), price: price.0 });
+        self.next_listing_id += 1;
+        let storage_cost = env::storage_byte_cost().saturating_mul((env::storage_usage() - initial_storage) as u128);
+        require!(env::attached_deposit() >= storage_cost, "Attach enough NEAR to cover storage");
+        id
+    }
+
+    pub fn withdraw(&mut self, amount: U128) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let balance = self.balances.get(&account_id).unwrap_or(0);
+        require!(balance >= amount.0, "Not enough balance");
+        self.balances.insert(&account_id, &(balance - amount.0));
+        ext_ft::ext(self.token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(account_id.clone(), amount, None)
+            .then(Self::ext(env::current_account_id()).with_static_gas(GAS_FOR_RESOLVE).on_refund(account_id, amount))
+    }
+
+    pub fn claim(&mut self, amount: U128) -> Promise {
+        self.withdraw(amount)
+    }
+
+    #[private]
+    pub fn on_refund(&mut self, account_id: AccountId, amount: U128, #[callback_result] result: Result<(), PromiseError>) {
+        if result.is_err() {
+            let balance = self.balances.get(&account_id).unwrap_or(0);
+            self.balances.insert(&account_id, &(balance + amount.0));
+        }
+    }
+
+    pub fn balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.balances.get(&account_id).unwrap_or(0))
+    }
+}
@@ -1,12 +1,95 @@
 const fs = require('fs');
 const path = require('path');
-const Anthropic = require('@anthropic-ai/sdk');
 const ora = require('ora');
 const parser = require('@solidity-parser/parser');
+const { buildCrateContext } = require('./rust-crate-context');
+const { chunkRustFile, renderChunk, describeChunk, mergeChunkReports } = require('./rust-chunker');
+const { completeResult, modelName } = require('./llm-provider');
+const { withCacheNote } = require('./llm-cache');
+const { rustFunctionSpans } = require('./rust-ast');
+const { writeReports } = require('./report-formats');
+const {
+  parseAnalysis,
+  dropInvalidLines,
+  describeAnalysisFormat,
+  analysisToFindings,
+  mergeAnalyses,
+  renderAnalysisMarkdown
+} = require('./structured-analysis');
+const { buildSourceIndex, groundFinding, groundMarkdown } = require('./rust-grounding');
+const { classifyFindings, formatFileChange } = require('./diff-scope');
+
+// Versions of the prompt templates, part of the response cache key (see llm-cache.js). The cache already misses
+// when a prompt's text changes; bump a version to drop cached responses when only their handling changes.
+const PROMPT_VERSIONS = {
+  'solidity-analysis': 1,
+  'rust-analysis': 1,
+  'rust-crate-analysis': 1,
+  'explanations': 1,
+  'logic-vulnerabilities': 1
+};
+
+// Vulnerability checklist shared by the per-file and crate-level Rust analyses
+const RUST_VULNERABILITY_CATEGORIES = `Specifically check for the following vulnerability categories:
 
-const anthropic = new Anthropic({
-  apiKey: process.env.ANTHROPIC_API_KEY,
-});
+ACCESS CONTROL:
+- Authorization Issues
+- Insufficient Access Control
+- Insecure Permission Management
+- Signature Verification Flaws
+- Missing Protection against Signature Replay Attacks
+
+MATH:
+- Integer Overflow and Underflow
+- Off-By-One Errors
+- Lack of Precision
+- Arithmetic Errors
+
+CONTROL FLOW:
+- Reentrancy Equivalents
+- Deadlocks
+- Race Conditions
+- Unexpected Panics
+- Error Handling Weaknesses
+
+DATA HANDLING:
+- Unchecked Return Values
+- Memory Safety Issues
+- Improper State Management
+- Uninitialized Variables
+- Unsafe Type Conversions
+
+UNSAFE LOGIC:
+- Weak Sources of Randomness
+- Inadequate Cryptographic Practices
+- Timestamp Dependence
+- Unsafe Block Operations
+- Incorrect Error Handling
+- Unencrypted Private Data
+- Improper Use of 'unsafe' Blocks
+
+CODE QUALITY:
+- Outdated Dependencies
+- Use of Deprecated Functions
+- Inefficient Resource Management
+- Memory Leaks
+- Inadequate Error Handling
+- Presence of Unused Variables
+- Insufficient Documentation
+- Improper Testing Coverage
+- Violation of Rust Best Practices
+
+SUBTLE LOGIC FLAWS:
+- Logical constraints that contradict business requirements
+- Incorrect assumptions about external system behavior
+- State inconsistencies under specific sequences of operations
+- Incentive misalignments leading to economic exploits
+- Multi-operation attack vectors
+- Inadequate validation of complex data structures
+- Timing issues in multi-step processes
+- Missing edge cases in business logic
+- Ownership and borrowing patterns that could lead to unexpected behavior
+- Thread-safety issues in concurrent contexts`;
 
 /**
  * Main function to analyze a smart contract file (Solidity or Rust)
@@ -28,13 +111,13 @@ async function analyzeSmartContract(filePath, outputDir, options = {}) {
     
     // Analyze based on file type
     const securityAnalysisPath = isRust 
-      ? await analyzeRustContract(filePath, outputDir)
+      ? await analyzeRustContract(filePath, outputDir, options)
       : await analyzeSolidityContract(filePath, outputDir);
     
     // Generate detailed code explanations if requested
     let explanationResults = null;
     if (options.generateExplanations !== false) {
-      explanationResults = await explainCode(filePath, outputDir, isRust);
+      explanationResults = await explainCode(filePath, outputDir, isRust, options);
     }
     
     return {
@@ -51,10 +134,12 @@ async function analyzeSmartContract(filePath, outputDir, options = {}) {
  * Analyzes a Solidity smart contract for functionality and security issues
  * @param {string} filePath - Path to the Solidity file
  * @param {string} outputDir - Directory to save analysis results
+ * @param {Object} options - `change`: the file's change when auditing a diff (see diff-scope.js), whose changed
+ *   functions the analysis focuses on; `reportName`: name of the report file (default: the file name, see reportName)
  * @returns {string} - Path to the generated analysis file
  */
-async function analyzeSolidityContract(filePath, outputDir) {
-  const spinner = ora('Analyzing Solidity contract with Claude 3.7...').start();
+async function analyzeSolidityContract(filePath, outputDir, options = {}) {
+  const spinner = ora(`Analyzing Solidity contract with ${modelName()}...`).start();
   
   try {
     // Read the contract code
@@ -70,12 +155,18 @@ async function analyzeSolidityContract(filePath, outputDir) {
       console.warn(`Could not parse Solidity code: ${parseError.message}`);
     }
     
-    // Prompt for Claude 3.7
+    // When auditing a diff, point the model at the changed functions; the rest of the contract is their context
+    const { change } = options;
+    const focus = change && change.status !== 'added' && change.functions.length > 0
+      ? `\nThe change under review modifies ${change.functions.map(fn => `\`${fn.name}\``).join(', ')}. Focus the analysis on these functions and on how the rest of the contract interacts with them; report issues elsewhere only if the change introduces or exposes them.\n`
+      : '';
+    
+    // Prompt for the configured LLM
     const prompt = `
 You are an expert smart contract auditor with exceptional skill at finding subtle logic errors that automated tools cannot detect. Analyze the following Web3/blockchain contract:
 
 ${contractCode}
-
+${focus}
 Your PRIMARY goal is to identify SUBTLE LOGIC VULNERABILITIES that automated scanning tools would miss and that only an experienced human auditor could find. Pay special attention to:
 - Business logic flaws in how contract components interact
 - Edge cases in state transitions 
@@ -160,13 +251,13 @@ SUBTLE LOGIC FLAWS:
 Focus on finding hidden logic issues that even most security auditors would miss. Analyze the contract as if you were the most skilled attacker in the world with perfect understanding of the codebase. Identify vulnerabilities that can only be discovered through deep reasoning about the contract's behavior.
 `;
 
-    const response = await callAnthropicAPI(prompt, "You are an expert smart contract security auditor with deep knowledge of Web3 vulnerabilities and best practices.");
+    const response = await callLLM(prompt, "You are an expert smart contract security auditor with deep knowledge of Web3 vulnerabilities and best practices.", promptCache('solidity-analysis', contractCode));
     
     // Save the response to a file
-    const outputPath = path.join(outputDir, `${fileName}.analysis.md`);
-    fs.writeFileSync(outputPath, response);
+    const outputPath = path.join(outputDir, `${options.reportName || fileName}.analysis.md`);
+    fs.writeFileSync(outputPath, withCacheNote(response.text, [response]));
     
-    spinner.succeed(`Solidity security analysis complete! Saved to: ${outputPath}`);
+    spinner.succeed(`Solidity security analysis complete!${cachedLabel([response])} Saved to: ${outputPath}`);
     return outputPath;
   } catch (error) {
     spinner.fail('Solidity analysis failed');
@@ -174,25 +265,119 @@ Focus on finding hidden logic issues that even most security auditors would miss
   }
 }
 
+// Answers that do not match the analysis schema are sent back to the model with the problems found, this many times in all
+const MAX_ANALYSIS_ATTEMPTS = 3;
+
 /**
- * Analyzes a Rust smart contract for Web3/blockchain functionality and security issues
+ * Analyzes a Rust smart contract for Web3/blockchain functionality and security issues. The model answers with
+ * structured findings (see structured-analysis.js) that are validated against the schema and the file; the
+ * Markdown report and a JSON findings report are rendered from them. Files too large for one prompt are analyzed
+ * chunk by chunk (see rust-chunker.js) and the partial analyses merged.
  * @param {string} filePath - Path to the Rust file
  * @param {string} outputDir - Directory to save analysis results
- * @returns {string} - Path to the generated analysis file
+ * @param {Object} options - `chunkTokens`: size of the chunks large files are split into; `change`: the file's
+ *   change when auditing a diff (see diff-scope.js), to analyze only the changed items; `root`: the audited
+ *   repository or directory, which finding ids are relative to; `reportName`: name of the report files (default:
+ *   the file name, see reportName); `format`: markdown, json and/or sarif (comma separated)
+ * @returns {string|null} - Path to the first report written, or null when a diff changed no item of the file
  */
-async function analyzeRustContract(filePath, outputDir) {
-  const spinner = ora('Analyzing Rust contract with Claude 3.7...').start();
+async function analyzeRustContract(filePath, outputDir, options = {}) {
+  const spinner = ora(`Analyzing Rust contract with ${modelName()}...`).start();
   
   try {
     // Read the contract code
     const contractCode = fs.readFileSync(filePath, 'utf8');
     const fileName = path.basename(filePath);
+    const source = { code: contractCode, functions: rustFunctionSpans(contractCode) };
+    const chunks = chunkRustFile(contractCode, { chunkTokens: options.chunkTokens, focus: options.change });
+    if (chunks && chunks.length === 0) {
+      spinner.info(`Rust security analysis skipped: the change touches no function or item of ${fileName}`);
+      return null;
+    }
     
-    // Prompt for Claude 3.7
-    const prompt = `
-You are an expert Rust and blockchain security auditor with exceptional skill at finding subtle logic errors that automated tools cannot detect. Analyze the following Rust Web3/blockchain code:
+    const parts = [];
+    const responses = [];
+    for (const chunk of chunks || [null]) {
+      const label = chunk ? ` (${describeChunk(chunk)})` : '';
+      spinner.text = `Analyzing Rust contract${label} with ${modelName()}...`;
+      const part = await requestRustAnalysis(rustAnalysisPrompt(contractCode, chunk), source, contractCode, (attempt, problems) => {
+        spinner.text = `Analyzing Rust contract${label} with ${modelName()} (answer ${attempt} rejected: ${problems} problem(s), retrying)...`;
+      });
+      responses.push(...part.responses);
+      parts.push({ chunk, ...part });
+    }
 
-${contractCode}
+    const outputPath = path.join(outputDir, `${options.reportName || fileName}.rust-analysis.md`);
+    const valid = parts.filter(part => part.result.analysis);
+    if (valid.length === 0) {
+      // Keep the model's answer rather than losing the analysis
+      const answers = parts.map(part => (part.chunk ? `## ${capitalize(describeChunk(part.chunk))}\n\n${part.response}` : part.response)).join('\n\n');
+      fs.writeFileSync(outputPath, withCacheNote(`> **Warning:** the analysis below does not match the findings schema (${parts[0].result.errors.slice(0, 3).join('; ')}); it is the model's answer as given.\n\n${answers}`, responses));
+      spinner.warn(`Rust security analysis saved unvalidated after ${MAX_ANALYSIS_ATTEMPTS} attempts: ${outputPath}`);
+      return outputPath;
+    }
+
+    valid.forEach(part => dropInvalidLines(part.result.analysis, part.result.lineIssues));
+    const merged = chunks
+      ? mergeAnalyses(valid.map(part => ({ label: capitalize(describeChunk(part.chunk)), analysis: part.result.analysis })))
+      : { analysis: valid[0].result.analysis, duplicates: 0 };
+    const { analysis } = merged;
+    const findings = analysisToFindings(analysis, { filePath, code: contractCode, analyzer: 'rust-llm', ruleIdPrefix: 'rust-llm', root: options.root });
+    const sourceIndex = buildSourceIndex([{ file: filePath, code: contractCode }]);
+    findings.forEach(finding => {
+      finding.grounding = groundFinding(finding, sourceIndex);
+    });
+    if (options.change) classifyFindings(findings, options.change);
+    const chunking = chunks ? `\n${describeChunking(parts, merged.duplicates, options.change)}` : '';
+    const outputPaths = writeReports({
+      outputDir,
+      baseName: `${options.reportName || fileName}.rust-analysis`,
+      formats: options.format,
+      analyzer: 'rust-llm',
+      filePath,
+      findings,
+      renderMarkdown: () => withCacheNote(renderAnalysisMarkdown(`Rust Security Analysis: ${fileName}`, analysis, findings), responses) + chunking
+    });
+    
+    const lineIssues = valid.reduce((count, part) => count + part.result.lineIssues.length, 0);
+    const dropped = lineIssues > 0 ? `; ${lineIssues} invalid line reference(s) dropped` : '';
+    const ungrounded = findings.filter(finding => finding.grounding.unverified.length > 0).length;
+    const unverified = ungrounded > 0 ? `; ${ungrounded} with unverified claims` : '';
+    const chunked = chunks ? `; ${chunks.length} chunks, ${merged.duplicates} duplicate(s) merged${valid.length < parts.length ? `, ${parts.length - valid.length} unusable` : ''}` : '';
+    spinner.succeed(`Rust security analysis complete!${cachedLabel(responses)} ${findings.length} finding(s)${dropped}${unverified}${chunked}. Saved to: ${outputPaths.join(', ')}`);
+    return outputPaths[0];
+  } catch (error) {
+    spinner.fail('Rust analysis failed');
+    throw error;
+  }
+}
+
+/**
+ * Prompt of the Rust security analysis, for a whole file or for one of its chunks
+ * @param {string} code - Rust source
+ * @param {Object|null} chunk - Chunk from chunkRustFile, or null for the whole file
+ * @returns {string}
+ */
+function rustAnalysisPrompt(code, chunk) {
+  const scope = chunk && chunk.focus
+    ? `Analyze the code that the change under review adds or modifies in the following Rust Web3/blockchain file, ${describeChunk(chunk)}. Only the changed items are shown, so report only issues in this code.`
+    : `Analyze the following part of a Rust Web3/blockchain file, ${chunk && describeChunk(chunk)}. The file is too large to analyze at once and its other parts are analyzed separately, so report only issues in the code of this part.`;
+  const subject = chunk
+    ? `${scope} Each line is prefixed with its line number in the file; \`...\` marks lines left out:
+
+${numberChunk(code, chunk)}
+
+Definitions and signatures from the rest of the file that this part uses, for reference only:
+
+\`\`\`rust
+${chunk.context}
+\`\`\``
+    : `Analyze the following Rust Web3/blockchain code (each line is prefixed with its line number):
+
+${numberLines(code)}`;
+
+  return `
+You are an expert Rust and blockchain security auditor with exceptional skill at finding subtle logic errors that automated tools cannot detect. ${subject}
 
 Your PRIMARY goal is to identify SUBTLE LOGIC VULNERABILITIES that automated scanning tools would miss and that only an experienced human auditor could find. Pay special attention to:
 - Business logic flaws in how components interact
@@ -208,91 +393,179 @@ Provide a detailed security analysis that includes:
 1. SUMMARY: High-level overview of what this Rust code does (2-3 sentences)
 2. CODE ARCHITECTURE: Key components and their relationships
 3. BUSINESS LOGIC ANALYSIS: Detailed explanation of core functionality
-4. RISK ASSESSMENT:
-   a. High-risk issues (critical vulnerabilities)
-   b. Medium-risk issues (potential vulnerabilities)
-   c. Low-risk issues (code improvement opportunities)
-5. BLOCKCHAIN-SPECIFIC ISSUES: Analysis of any Web3/blockchain-specific vulnerabilities
-6. RECOMMENDATIONS: Specific code changes to improve security
+4. FINDINGS: Every vulnerability, including Web3/blockchain-specific ones, with its severity (critical and high for exploitable vulnerabilities, medium for potential vulnerabilities, low and info for code improvement opportunities), the function and lines it is in, an exploit scenario and a fix
+5. RECOMMENDATIONS: Specific code changes to improve security
 
-Specifically check for the following vulnerability categories:
+${RUST_VULNERABILITY_CATEGORIES}
 
-ACCESS CONTROL:
-- Authorization Issues
-- Insufficient Access Control
-- Insecure Permission Management
-- Signature Verification Flaws
-- Missing Protection against Signature Replay Attacks
+Focus on finding hidden logic issues that even most security auditors would miss. Analyze the code as if you were the most skilled attacker in the world with perfect understanding of the codebase. Identify vulnerabilities that can only be discovered through deep reasoning about the code's behavior.
 
-MATH:
-- Integer Overflow and Underflow
-- Off-By-One Errors
-- Lack of Precision
-- Arithmetic Errors
+${describeAnalysisFormat()}
+`;
+}
 
-CONTROL FLOW:
-- Reentrancy Equivalents
-- Deadlocks
-- Race Conditions
-- Unexpected Panics
-- Error Handling Weaknesses
+/**
+ * Ask for a structured analysis, sending answers that do not validate back to the model with the problems found
+ * @param {string} prompt - Result of rustAnalysisPrompt
+ * @param {Object} source - `{ code, functions }` to check cited lines against
+ * @param {string} code - Audited source, for the cache key
+ * @param {Function} onRetry - Called with the attempt number and the number of problems before each retry
+ * @returns {Object} - `{ result, response, responses }`: the last parseAnalysis result and answer, and all responses
+ */
+async function requestRustAnalysis(prompt, source, code, onRetry) {
+  const systemPrompt = "You are an expert Rust and blockchain security auditor with deep knowledge of Web3 vulnerabilities, Rust's memory safety features, and smart contract best practices. You answer with JSON only.";
+  const messages = [{ role: 'user', content: prompt }];
+  const responses = [];
+  let response;
+  let result;
+  for (let attempt = 1; attempt <= MAX_ANALYSIS_ATTEMPTS; attempt++) {
+    responses.push(await callLLM(messages, systemPrompt, promptCache('rust-analysis', code)));
+    response = responses[responses.length - 1].text;
+    result = parseAnalysis(response, source);
+    const problems = [...result.errors, ...result.lineIssues.map(issue => issue.message)];
+    if (problems.length === 0 || attempt === MAX_ANALYSIS_ATTEMPTS) break;
+
+    onRetry(attempt, problems.length);
+    messages.push(
+      { role: 'assistant', content: response },
+      { role: 'user', content: `Your answer cannot be used:\n${problems.map(problem => `- ${problem}`).join('\n')}\n\nReply with the complete corrected JSON object only.` }
+    );
+  }
+  return { result, response, responses };
+}
 
-DATA HANDLING:
-- Unchecked Return Values
-- Memory Safety Issues
-- Improper State Management
-- Uninitialized Variables
-- Unsafe Type Conversions
+/**
+ * Markdown appendix listing the chunks a large file was analyzed in
+ */
+function describeChunking(parts, duplicates, change) {
+  let output = `---\n\n## Chunks\n\n`;
+  if (change) {
+    output += `${formatFileChange(change)}. Only the changed items were analyzed, in ${parts.length} chunk(s), each with the definitions and signatures it uses from the rest of the file. `;
+  } else {
+    output += `The file was too large to analyze at once and was analyzed in ${parts.length} chunks, each with the definitions and signatures it uses from the rest of the file. `;
+  }
+  output += `${duplicates} finding(s) reported by more than one chunk were merged.\n\n`;
+  parts.forEach(part => {
+    const notes = [
+      part.chunk.oversized ? 'larger than the chunk size' : null,
+      part.result.analysis ? null : `answer unusable: ${part.result.errors.slice(0, 2).join('; ')}`
+    ].filter(Boolean);
+    output += `- ${capitalize(describeChunk(part.chunk))}: ${part.chunk.items.length} item(s)${notes.length > 0 ? ` (${notes.join('; ')})` : ''}\n`;
+  });
+  return `${output}\n`;
+}
 
-UNSAFE LOGIC:
-- Weak Sources of Randomness
-- Inadequate Cryptographic Practices
-- Timestamp Dependence
-- Unsafe Block Operations
-- Incorrect Error Handling
-- Unencrypted Private Data
-- Improper Use of 'unsafe' Blocks
+function capitalize(text) {
+  return text[0].toUpperCase() + text.slice(1);
+}
 
-CODE QUALITY:
-- Outdated Dependencies
-- Use of Deprecated Functions
-- Inefficient Resource Management
-- Memory Leaks
-- Inadequate Error Handling
-- Presence of Unused Variables
-- Insufficient Documentation
-- Improper Testing Coverage
-- Violation of Rust Best Practices
+/**
+ * Prefix each line of code with its 1-based number, so that the model can cite lines
+ */
+function numberLines(code) {
+  const lines = code.split('\n');
+  const width = String(lines.length).length;
+  return lines.map((line, index) => `${String(index + 1).padStart(width)} | ${line}`).join('\n');
+}
 
-SUBTLE LOGIC FLAWS:
-- Logical constraints that contradict business requirements
-- Incorrect assumptions about external system behavior
-- State inconsistencies under specific sequences of operations
-- Incentive misalignments leading to economic exploits
-- Multi-operation attack vectors
-- Inadequate validation of complex data structures
-- Timing issues in multi-step processes
-- Missing edge cases in business logic
-- Ownership and borrowing patterns that could lead to unexpected behavior
-- Thread-safety issues in concurrent contexts
+/**
+ * numberLines for a chunk: its lines keep their numbers in the file
+ */
+function numberChunk(code, chunk) {
+  const width = String(code.split('\n').length).length;
+  return renderChunk(code, chunk, (number, line) => `${String(number).padStart(width)} | ${line}`);
+}
+
+/**
+ * Analyzes a whole Rust crate target at once so that interactions between modules are visible,
+ * e.g. invariants declared in `mod state` that `mod instructions` breaks
+ * @param {Object} group - Crate target `{ crate, manifestPath, target, files }` from the repo handler
+ * @param {string} outputDir - Directory to save analysis results
+ * @param {Object} options - `contextTokens`: token budget for the combined crate context
+ * @returns {string} - Path to the generated analysis file
+ */
+async function analyzeRustCrate(group, outputDir, options = {}) {
+  const spinner = ora(`Analyzing Rust crate ${group.crate} with ${modelName()}...`).start();
+  
+  try {
+    const context = buildCrateContext(group, options);
+    spinner.text = `Analyzing Rust crate ${group.crate} with ${modelName()} (${context.included.length} items, ~${context.tokens} tokens)...`;
+    
+    const prompt = `
+You are an expert Rust and blockchain security auditor with exceptional skill at finding subtle logic errors that automated tools cannot detect. Analyze the following Rust Web3/blockchain crate.
+
+The crate is given as one combined context: a module map, the cross-module references between items, then the most relevant items of every module, each preceded by a comment with its path and source location. Items whose body is omitted were ranked lower to fit the context; reason about them from their signatures only.
+
+${context.text}
+
+Your PRIMARY goal is to identify SUBTLE LOGIC VULNERABILITIES that span several modules and would be invisible when reading any single file. Pay special attention to:
+- Invariants established in one module (e.g. state types and their methods) that are broken by code in another (e.g. instruction handlers)
+- Checks performed in one entry point but missing from a sibling entry point that reaches the same state
+- Helpers whose assumptions are not met by all of their callers
+- Business logic flaws in how modules interact
+- Economic attack vectors that exploit incentive misalignments
+- Rust-specific logical issues that arise from ownership, borrowing, and lifetime constraints
+
+Provide a single detailed security analysis of the crate that includes:
+
+1. SUMMARY: High-level overview of what this crate does (2-3 sentences)
+2. CRATE ARCHITECTURE: Modules, key types and how they depend on each other
+3. CROSS-MODULE INTERACTIONS: For each important flow, which modules it passes through and which invariants it relies on
+4. RISK ASSESSMENT:
+   a. High-risk issues (critical vulnerabilities)
+   b. Medium-risk issues (potential vulnerabilities)
+   c. Low-risk issues (code improvement opportunities)
+5. BLOCKCHAIN-SPECIFIC ISSUES: Analysis of any Web3/blockchain-specific vulnerabilities
+6. RECOMMENDATIONS: Specific code changes to improve security
+
+For every issue, name the items involved by their crate paths (e.g. \`crate::state::Pool::debit\`) and files.
+
+${RUST_VULNERABILITY_CATEGORIES}
 
 Focus on finding hidden logic issues that even most security auditors would miss. Analyze the code as if you were the most skilled attacker in the world with perfect understanding of the codebase. Identify vulnerabilities that can only be discovered through deep reasoning about the code's behavior.
 `;
 
-    const response = await callAnthropicAPI(prompt, "You are an expert Rust and blockchain security auditor with deep knowledge of Web3 vulnerabilities, Rust's memory safety features, and smart contract best practices.");
+    const crateSource = group.files.map(file => fs.readFileSync(file, 'utf8'));
+    const response = await callLLM(prompt, "You are an expert Rust and blockchain security auditor with deep knowledge of Web3 vulnerabilities, Rust's memory safety features, and smart contract best practices.", promptCache('rust-crate-analysis', crateSource));
     
-    // Save the response to a file
-    const outputPath = path.join(outputDir, `${fileName}.rust-analysis.md`);
-    fs.writeFileSync(outputPath, response);
+    // Save the response to a file, with a note on what the context covered
+    const outputPath = path.join(outputDir, `${crateReportName(group)}.crate-analysis.md`);
+    fs.writeFileSync(outputPath, `${withCacheNote(response.text, [response])}\n\n${describeCrateContext(context)}`);
     
-    spinner.succeed(`Rust security analysis complete! Saved to: ${outputPath}`);
+    spinner.succeed(`Rust crate analysis complete!${cachedLabel([response])} Saved to: ${outputPath}`);
     return outputPath;
   } catch (error) {
-    spinner.fail('Rust analysis failed');
+    spinner.fail('Rust crate analysis failed');
     throw error;
   }
 }
 
+/**
+ * Base name of crate-level reports, e.g. `lending.lib-lending`
+ */
+function crateReportName(group) {
+  return group.target ? `${group.crate}.${group.target.kind}-${group.target.name}` : group.crate;
+}
+
+/**
+ * Markdown appendix listing which items of the crate were sent to the model
+ */
+function describeCrateContext(context) {
+  let output = `---\n\n## Crate Context\n\n`;
+  output += `The analysis above was produced from a combined context of ${context.modules.length} modules `;
+  output += `(~${context.tokens} of ${context.budget} tokens).\n\n`;
+  output += `- Items included in full: ${context.included.length}\n`;
+  output += `- Items included by signature only: ${context.outlined.length}\n`;
+  output += `- Items left out: ${context.omitted.length}\n\n`;
+  if (context.omitted.length > 0) {
+    output += `Left out: ${context.omitted.map(id => `\`${id}\``).join(', ')}\n\n`;
+  }
+  if (context.parseErrors.length > 0) {
+    output += `Could not be parsed: ${context.parseErrors.map(error => `\`${error.file}\``).join(', ')}\n\n`;
+  }
+  return output;
+}
+
 /**
  * Analyzes a codebase (directory) containing multiple smart contract files
  * @param {string} inputPath - Path to directory containing contract files
@@ -344,9 +617,12 @@ async function analyzeCodebase(inputPath, outputDir, options = {}) {
  * @param {string} filePath - Path to the smart contract file
  * @param {string} outputDir - Directory to save analysis results
  * @param {boolean} isRust - Whether the file is Rust code
- * @returns {Object} - Paths to explanation and vulnerability files
+ * @param {Object} options - `chunkTokens`: size of the chunks large Rust files are split into; `change`: the
+ *   file's change when auditing a diff (see diff-scope.js), to explain only the changed items of Rust files;
+ *   `reportName`: name of the report files (default: the file name, see reportName)
+ * @returns {Object|null} - Paths to explanation and vulnerability files, or null when a diff changed no item
  */
-async function explainCode(filePath, outputDir, isRust = false) {
+async function explainCode(filePath, outputDir, isRust = false, options = {}) {
   const spinner = ora('Generating detailed function explanations...').start();
   
   try {
@@ -371,21 +647,53 @@ async function explainCode(filePath, outputDir, isRust = false) {
       ? `\nIdentified functions:\n${functionList.map(f => `- ${f.name || 'constructor/fallback'} (${f.visibility || 'default'})`).join('\n')}`
       : '';
     
+    // Rust reports are checked against the parsed source before they are saved
+    const ground = isRust
+      ? (report) => groundMarkdown(report, buildSourceIndex([{ file: filePath, code }])).markdown
+      : (report) => report;
+    const outputPath = path.join(outputDir, `${options.reportName || fileName}.explanations.md`);
+    const vulnOutputPath = path.join(outputDir, `${options.reportName || fileName}.logic-vulnerabilities.md`);
+    
+    // Large Rust files are explained chunk by chunk, and the chunk reports merged
+    const chunks = isRust ? chunkRustFile(code, { chunkTokens: options.chunkTokens, focus: options.change }) : null;
+    if (chunks && chunks.length === 0) {
+      spinner.info(`Code explanations skipped: the change touches no function or item of ${fileName}`);
+      return null;
+    }
+    if (chunks) {
+      const explanations = [];
+      const vulnerabilityReports = [];
+      for (const chunk of chunks) {
+        spinner.text = `Generating detailed function explanations (${describeChunk(chunk)})...`;
+        const chunkCode = renderChunk(code, chunk);
+        const scope = chunk.focus ? 'the code the change under review adds or modifies' : 'the file is too large to explain at once';
+        const chunkContext = `\nThis is ${describeChunk(chunk)} of ${fileName} (${scope}); explain only the code above (\`...\` marks lines left out). It uses these definitions and signatures from the rest of the file:\n\`\`\`rust\n${chunk.context}\n\`\`\``;
+        explanations.push(await generateExplanations(chunkCode, fileName, chunkContext, true));
+        vulnerabilityReports.push(await generateVulnerabilityReport(explanations[explanations.length - 1].text, chunkCode, true));
+      }
+      
+      const mergedExplanations = mergeChunkReports(`Code Explanations: ${fileName}`, explanations.map((response, index) => ({ chunk: chunks[index], text: response.text })));
+      fs.writeFileSync(outputPath, withCacheNote(ground(mergedExplanations.markdown), explanations));
+      const mergedReports = mergeChunkReports(`Logic Vulnerabilities: ${fileName}`, vulnerabilityReports.map((response, index) => ({ chunk: chunks[index], text: response.text })), { dedupe: true });
+      fs.writeFileSync(vulnOutputPath, withCacheNote(ground(mergedReports.markdown), vulnerabilityReports));
+      
+      spinner.succeed(`Code explanations and logic vulnerability analysis complete!${cachedLabel([...explanations, ...vulnerabilityReports])} ${chunks.length} chunks, ${mergedReports.duplicates} duplicate finding(s) dropped\nSaved to:\n- ${outputPath}\n- ${vulnOutputPath}`);
+      return { explanationsPath: outputPath, vulnerabilitiesPath: vulnOutputPath };
+    }
+    
     // Generate detailed explanations
     const explanations = await generateExplanations(code, fileName, functionContext, isRust);
     
     // Save explanations to file
-    const outputPath = path.join(outputDir, `${fileName}.explanations.md`);
-    fs.writeFileSync(outputPath, explanations);
+    fs.writeFileSync(outputPath, withCacheNote(ground(explanations.text), [explanations]));
     
     // Generate vulnerability report based on explanations
-    const vulnerabilityReport = await generateVulnerabilityReport(explanations, code, isRust);
+    const vulnerabilityReport = await generateVulnerabilityReport(explanations.text, code, isRust);
     
     // Save vulnerability report to file
-    const vulnOutputPath = path.join(outputDir, `${fileName}.logic-vulnerabilities.md`);
-    fs.writeFileSync(vulnOutputPath, vulnerabilityReport);
+    fs.writeFileSync(vulnOutputPath, withCacheNote(ground(vulnerabilityReport.text), [vulnerabilityReport]));
     
-    spinner.succeed(`Code explanations and logic vulnerability analysis complete!\nSaved to:\n- ${outputPath}\n- ${vulnOutputPath}`);
+    spinner.succeed(`Code explanations and logic vulnerability analysis complete!${cachedLabel([explanations, vulnerabilityReport])}\nSaved to:\n- ${outputPath}\n- ${vulnOutputPath}`);
     return { explanationsPath: outputPath, vulnerabilitiesPath: vulnOutputPath };
   } catch (error) {
     spinner.fail('Code explanation failed');
@@ -393,6 +701,40 @@ async function explainCode(filePath, outputDir, isRust = false) {
   }
 }
 
+/**
+ * Deep-explains a whole Rust crate target and identifies logic vulnerabilities across its modules
+ * @param {Object} group - Crate target `{ crate, manifestPath, target, files }` from the repo handler
+ * @param {string} outputDir - Directory to save analysis results
+ * @param {Object} options - `contextTokens`: token budget for the combined crate context
+ * @returns {Object} - Paths to explanation and vulnerability files
+ */
+async function explainRustCrate(group, outputDir, options = {}) {
+  const spinner = ora(`Generating crate-level explanations for ${group.crate}...`).start();
+  
+  try {
+    const context = buildCrateContext(group, options);
+    const reportName = crateReportName(group);
+    
+    // The module map and cross-module references are already part of the context
+    const moduleContext = `\nThis is a multi-module crate. Explain how the modules interact, referring to items by their crate paths.`;
+    
+    const explanations = await generateExplanations(context.text, reportName, moduleContext, true);
+    const sourceIndex = buildSourceIndex(group.files.map(file => ({ file, code: fs.readFileSync(file, 'utf8') })));
+    const outputPath = path.join(outputDir, `${reportName}.explanations.md`);
+    fs.writeFileSync(outputPath, withCacheNote(groundMarkdown(explanations.text, sourceIndex).markdown, [explanations]));
+    
+    const vulnerabilityReport = await generateVulnerabilityReport(explanations.text, context.text, true);
+    const vulnOutputPath = path.join(outputDir, `${reportName}.logic-vulnerabilities.md`);
+    fs.writeFileSync(vulnOutputPath, `${withCacheNote(groundMarkdown(vulnerabilityReport.text, sourceIndex).markdown, [vulnerabilityReport])}\n\n${describeCrateContext(context)}`);
+    
+    spinner.succeed(`Crate explanations and logic vulnerability analysis complete!${cachedLabel([explanations, vulnerabilityReport])}\nSaved to:\n- ${outputPath}\n- ${vulnOutputPath}`);
+    return { explanationsPath: outputPath, vulnerabilitiesPath: vulnOutputPath };
+  } catch (error) {
+    spinner.fail('Crate explanation failed');
+    throw error;
+  }
+}
+
 /**
  * Extract basic information from the Solidity contract AST
  * @param {Object} ast - The parsed AST of the contract
@@ -503,10 +845,10 @@ function extractSolidityFunctions(ast) {
  * @param {string} fileName - Name of the file
  * @param {string} functionContext - Context about extracted functions
  * @param {boolean} isRust - Whether the file is Rust code
- * @returns {string} - Markdown formatted explanations
+ * @returns {Object} - Markdown formatted explanations, as a response `{ text, cached, ... }` of completeResult
  */
 async function generateExplanations(code, fileName, functionContext, isRust) {
-  // Prompt for Claude 3.7
+  // Prompt for the configured LLM
   const language = isRust ? 'Rust' : 'Solidity';
   const prompt = `
 You are an expert ${language} developer and security auditor. I need you to create a detailed explanation of the following ${language} code:
@@ -536,7 +878,7 @@ Provide an extremely detailed function-by-function breakdown that includes:
 Format each function explanation with clear headings and sub-sections. Be extremely thorough in explaining the logic.
 `;
 
-  return await callAnthropicAPI(prompt, `You are an expert ${language} code explainer. Generate extremely detailed and precise explanations of code functions, describing exactly how they work line-by-line.`);
+  return await callLLM(prompt, `You are an expert ${language} code explainer. Generate extremely detailed and precise explanations of code functions, describing exactly how they work line-by-line.`, promptCache('explanations', code));
 }
 
 /**
@@ -544,10 +886,10 @@ Format each function explanation with clear headings and sub-sections. Be extrem
  * @param {string} explanations - Detailed code explanations
  * @param {string} originalCode - Original source code
  * @param {boolean} isRust - Whether the file is Rust code
- * @returns {string} - Markdown formatted vulnerability report
+ * @returns {Object} - Markdown formatted vulnerability report, as a response `{ text, cached, ... }` of completeResult
  */
 async function generateVulnerabilityReport(explanations, originalCode, isRust) {
-  // Prompt for Claude 3.7
+  // Prompt for the configured LLM
   const language = isRust ? 'Rust' : 'Solidity';
   const prompt = `
 You are an expert ${language} security auditor. I have a detailed explanation of ${language} code and the original code. 
@@ -580,51 +922,51 @@ For each vulnerability:
 ONLY include vulnerabilities that have a realistic exploitation path. Do not include code style issues, gas optimizations, or theoretical concerns that don't present actual exploitable attack vectors.
 `;
 
-  return await callAnthropicAPI(prompt, `You are an expert ${language} security auditor specializing in identifying exploitable vulnerabilities. For each vulnerability, you must assign a confidence rating from 1-5 based on how certain you are that it's a real, exploitable issue.`);
+  return await callLLM(prompt, `You are an expert ${language} security auditor specializing in identifying exploitable vulnerabilities. For each vulnerability, you must assign a confidence rating from 1-5 based on how certain you are that it's a real, exploitable issue.`, promptCache('logic-vulnerabilities', originalCode));
 }
 
 /**
- * Helper function to call the Anthropic API
- * @param {string} prompt - The prompt to send to Claude
- * @param {string} systemPrompt - The system prompt to guide Claude
- * @returns {string} - The response text
+ * Helper function to call the configured model provider (see llm-provider.js)
+ * @param {string|Array} prompt - The prompt, or a whole conversation of `{ role, content }` turns, e.g. to ask
+ *   for corrections of an earlier answer
+ * @param {string} systemPrompt - The system prompt to guide the model
+ * @param {Object} cache - Cache key parts from promptCache
+ * @returns {Object} - The response `{ text, cached, cachedAt, ... }`
  */
-async function callAnthropicAPI(prompt, systemPrompt) {
-  // Check if API key is available
-  if (!process.env.ANTHROPIC_API_KEY) {
-    throw new Error('ANTHROPIC_API_KEY not found in environment variables');
-  }
-
+async function callLLM(prompt, systemPrompt, cache) {
   try {
-    // Call Claude 3.7 API
-    const response = await anthropic.messages.create({
-      model: "claude-3-sonnet-20240229",
-      max_tokens: 4000,
-      messages: [
-        { 
-          role: "user", 
-          content: prompt 
-        }
-      ],
-      system: systemPrompt
-    });
-
-    // Validate response format
-    if (!response || !response.content || !Array.isArray(response.content) || response.content.length === 0) {
-      throw new Error('Invalid response format from Anthropic API');
-    }
-
-    return response.content[0].text;
+    return await completeResult(prompt, systemPrompt, cache);
   } catch (error) {
     console.error('API call failed:', error);
     throw error;
   }
 }
 
+/**
+ * Cache key parts of a prompt template applied to some source
+ * @param {string} template - Key of PROMPT_VERSIONS
+ * @param {string|string[]} source - Audited source code
+ * @returns {Object}
+ */
+function promptCache(template, source) {
+  return { template, version: PROMPT_VERSIONS[template], source };
+}
+
+/**
+ * Spinner label for results built from cached responses, e.g. " (cached)"
+ */
+function cachedLabel(responses) {
+  const cached = responses.filter(response => response.cached).length;
+  if (cached === 0) return '';
+  return cached === responses.length ? ' (cached)' : ` (${cached} of ${responses.length} responses cached)`;
+}
+
 module.exports = {
   analyzeSmartContract,
   analyzeCodebase,
   analyzeSolidityContract,
   analyzeRustContract,
-  explainCode
+  analyzeRustCrate,
+  explainCode,
+  explainRustCrate
 }; 
\ No newline at end of file
@@ -0,0 +1,211 @@
+const fs = require('fs');
+const path = require('path');
+const { toPortablePath } = require('./finding');
+
+/**
+ * Accepted findings of the static analyzers. A finding is suppressed when its id is recorded in the baseline file
+ * (`.winston-baseline.json`, written with --update-baseline) or when the source carries an inline suppression:
+ *
+ *   // winston-ignore: <rule-id> reason
+ *
+ * at the end of the finding's first line, or on its own line above it (blank lines, other comments and `#[...]`
+ * attributes in between are skipped). Reports list the suppressed findings apart, and the baseline entries of a
+ * file that no longer match a finding as fixed.
+ *
+ * Finding ids and baseline entries hold file paths relative to the audited root (the git repository holding the
+ * input, or the input directory), and the baseline file itself lives in that root, so the baseline matches wherever
+ * Winston runs from.
+ */
+
+const BASELINE_FILE = '.winston-baseline.json';
+
+// Bump when the file format changes
+const BASELINE_VERSION = 1;
+
+const INLINE_SUPPRESSION = /\/\/+\s*winston-ignore:\s*([\w.-]+)\s*(.*)$/;
+
+/**
+ * Load the baseline file, or start an empty one when it does not exist
+ * @param {string} file - Path of the baseline file (default `.winston-baseline.json`)
+ * @param {string} root - Audited repository or directory a relative path is resolved against (default: the
+ *   working directory)
+ * @returns {Object} - `{ path, exists, entries, current }` where entries maps finding ids to baseline entries and
+ *   current collects this run's findings per file for writeBaseline
+ */
+function loadBaseline(file = BASELINE_FILE, root = null) {
+  const baselinePath = path.resolve(root || '.', file);
+  const baseline = { path: baselinePath, exists: false, entries: new Map(), current: new Map() };
+  if (!fs.existsSync(baselinePath)) return baseline;
+
+  let data;
+  try {
+    data = JSON.parse(fs.readFileSync(baselinePath, 'utf8'));
+  } catch (error) {
+    throw new Error(`Invalid baseline file ${file}: ${error.message}`);
+  }
+  if (!data || !Array.isArray(data.findings)) {
+    throw new Error(`Invalid baseline file ${file}: expected a "findings" list`);
+  }
+  data.findings.filter(entry => entry && entry.id).forEach(entry => baseline.entries.set(entry.id, entry));
+  baseline.exists = true;
+  return baseline;
+}
+
+/**
+ * Inline suppressions of a file, by the line they apply to
+ * @param {string} code - Source code
+ * @returns {Map} - Line number to `[{ ruleId, reason, line }]`, `line` being the line of the comment
+ */
+function findInlineSuppressions(code) {
+  const lines = code.split('\n');
+  const suppressions = new Map();
+  lines.forEach((text, index) => {
+    const match = text.match(INLINE_SUPPRESSION);
+    if (!match) return;
+    let target = index;
+    if (text.slice(0, match.index).trim() === '') {
+      // A comment on its own line applies to the next line of code
+      target = index + 1;
+      while (target < lines.length && /^\s*(\/\/|#\[|$)/.test(lines[target])) target++;
+    }
+    const line = target + 1;
+    if (!suppressions.has(line)) suppressions.set(line, []);
+    suppressions.get(line).push({ ruleId: match[1], reason: match[2].replace(/^[-–—:]\s*/, '').trim() || null, line: index + 1 });
+  });
+  return suppressions;
+}
+
+/**
+ * Split a file's Findings into reported and suppressed ones, and find the baseline entries of the file that no
+ * longer match a finding. Records the file's findings in the baseline for writeBaseline.
+ * @param {Array} findings - Findings of one file
+ * @param {string} code - Source of the file, for inline suppressions
+ * @param {string} filePath - Path of the file
+ * @param {Object} baseline - Result of loadBaseline, or null to honour inline suppressions only
+ * @param {string} root - Audited repository or directory, which the file's baseline entries are relative to
+ * @returns {Object} - `{ findings, suppressed, fixed, baseline }`: the findings to report, the suppressed findings
+ *   (each with `suppression: { kind, reason }`, kind being `inline` or `baseline`), the fixed baseline entries and
+ *   the path of the baseline file relative to the working directory (null when there is none)
+ */
+function applySuppressions(findings, code, filePath, baseline, root = null) {
+  const inline = findInlineSuppressions(code);
+  const reported = [];
+  const suppressed = [];
+  const kept = [];
+
+  findings.forEach(finding => {
+    const comment = (inline.get(finding.location.startLine) || []).find(entry => entry.ruleId === finding.ruleId);
+    if (comment) {
+      suppressed.push({ ...finding, suppression: { kind: 'inline', reason: comment.reason } });
+      return;
+    }
+    kept.push(finding);
+    const entry = baseline && baseline.entries.get(finding.id);
+    if (entry) {
+      suppressed.push({ ...finding, suppression: { kind: 'baseline', reason: entry.reason || null } });
+    } else {
+      reported.push(finding);
+    }
+  });
+
+  const file = toPortablePath(filePath, root);
+  const ids = new Set(findings.map(finding => finding.id));
+  const fixed = baseline
+    ? [...baseline.entries.values()].filter(entry => entry.file === file && !ids.has(entry.id))
+    : [];
+  if (baseline) baseline.current.set(file, kept);
+
+  return { findings: reported, suppressed, fixed, baseline: baseline && baseline.exists ? toPortablePath(baseline.path) : null };
+}
+
+/**
+ * Write the baseline file: the findings of the files analyzed in this run (except inline suppressed ones), and the
+ * entries of the other files as they were. Reasons written into existing entries are kept.
+ * @param {Object} baseline - Result of loadBaseline, after the run
+ * @returns {Object} - `{ path, count, files }`: the number of entries and of files analyzed in this run
+ */
+function writeBaseline(baseline) {
+  const entries = [...baseline.entries.values()].filter(entry => !baseline.current.has(entry.file));
+  baseline.current.forEach((findings, file) => {
+    findings.forEach(finding => {
+      const previous = baseline.entries.get(finding.id);
+      entries.push({
+        id: finding.id,
+        ruleId: finding.ruleId,
+        severity: finding.severity,
+        title: finding.title,
+        file,
+        function: finding.location.function,
+        line: finding.location.startLine,
+        reason: (previous && previous.reason) || null
+      });
+    });
+  });
+  entries.sort((a, b) => a.file.localeCompare(b.file) || (a.line || 0) - (b.line || 0) || a.id.localeCompare(b.id));
+
+  fs.writeFileSync(baseline.path, `${JSON.stringify({
+    version: BASELINE_VERSION,
+    tool: 'Winston',
+    generatedAt: new Date().toISOString(),
+    findings: entries
+  }, null, 2)}\n`);
+  return { path: baseline.path, count: entries.length, files: baseline.current.size };
+}
+
+/**
+ * Markdown summary line of the baseline comparison, e.g. "- Not in the baseline: 2 (5 suppressed, 1 fixed)\n",
+ * or '' when nothing was suppressed and there is no baseline
+ * @param {Object} result - Result of applySuppressions
+ * @returns {string}
+ */
+function formatSuppressionSummary(result) {
+  if (result.baseline) {
+    return `- Not in the baseline: ${result.findings.length} (${result.suppressed.length} suppressed, ${result.fixed.length} fixed)\n`;
+  }
+  return result.suppressed.length > 0 ? `- Suppressed inline: ${result.suppressed.length}\n` : '';
+}
+
+/**
+ * Markdown section listing the suppressed findings and the fixed baseline entries, or '' when there are none
+ * @param {Object} result - Result of applySuppressions
+ * @returns {string}
+ */
+function formatSuppressionReport(result) {
+  if (result.suppressed.length === 0 && result.fixed.length === 0) return '';
+  let report = `## Suppressed and Fixed Findings\n\n`;
+  if (result.baseline) {
+    report += `Compared with the baseline \`${result.baseline}\`; the findings above are not in it.\n\n`;
+  }
+
+  if (result.suppressed.length > 0) {
+    report += `### Suppressed\n\n`;
+    result.suppressed.forEach(finding => {
+      const { kind, reason } = finding.suppression;
+      const line = finding.location.startLine ? ` line ${finding.location.startLine}` : '';
+      const fn = finding.location.function ? ` in \`${finding.location.function}\`` : '';
+      report += `- **${finding.title}** (\`${finding.ruleId}\`, ${finding.severity},${line}${fn}): `;
+      report += `${kind === 'inline' ? 'inline `winston-ignore`' : 'baseline'}${reason ? ` — ${reason}` : ''}\n`;
+    });
+    report += `\n`;
+  }
+
+  if (result.fixed.length > 0) {
+    report += `### Fixed Since the Baseline\n\n`;
+    result.fixed.forEach(entry => {
+      const fn = entry.function ? ` in \`${entry.function}\`` : '';
+      report += `- **${entry.title}** (\`${entry.ruleId}\`${entry.line ? `, was line ${entry.line}` : ''}${fn}): no longer reported\n`;
+    });
+    report += `\n`;
+  }
+  return report;
+}
+
+module.exports = {
+  BASELINE_FILE,
+  loadBaseline,
+  findInlineSuppressions,
+  applySuppressions,
+  writeBaseline,
+  formatSuppressionSummary,
+  formatSuppressionReport
+};
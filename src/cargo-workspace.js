@@ -0,0 +1,673 @@
+const fs = require('fs');
+const path = require('path');
+const { spawnSync } = require('child_process');
+const { parse } = require('./rust-parser');
+
+/**
+ * Cargo target kinds, in the order they are reported. Only `lib` and `bin` hold on-chain/program code;
+ * the rest are build scripts, test harnesses and samples that are skipped unless asked for.
+ */
+const TARGET_KINDS = ['lib', 'bin', 'build', 'test', 'bench', 'example'];
+const DEFAULT_TARGET_KINDS = ['lib', 'bin'];
+
+// `cargo metadata` target kinds that are not one of TARGET_KINDS
+const CARGO_TARGET_KINDS = {
+  rlib: 'lib', dylib: 'lib', cdylib: 'lib', staticlib: 'lib', 'proc-macro': 'lib', 'custom-build': 'build'
+};
+
+// Directories that never contain first-party crates
+const SKIPPED_DIRS = new Set(['target', 'node_modules']);
+// Path segments that mark third-party sources checked into the tree
+const VENDOR_DIRS = new Set(['vendor', 'vendored', 'third_party', 'third-party']);
+
+/**
+ * Parse the subset of TOML used by Cargo manifests: tables, arrays of tables, dotted keys,
+ * strings, arrays and inline tables. Scalars other than strings and booleans are kept as text.
+ * @param {string} text - Manifest contents
+ * @returns {Object} - Parsed document
+ */
+function parseToml(text) {
+  const root = {};
+  let table = root;
+  let pos = 0;
+
+  const error = (message) => {
+    const line = text.slice(0, pos).split('\n').length;
+    return new Error(`${message} at line ${line}`);
+  };
+
+  const skipSpace = (newlines) => {
+    while (pos < text.length) {
+      const ch = text[pos];
+      if (ch === ' ' || ch === '\t' || ch === '\r' || (newlines && ch === '\n')) {
+        pos++;
+      } else if (ch === '#') {
+        while (pos < text.length && text[pos] !== '\n') pos++;
+      } else {
+        break;
+      }
+    }
+  };
+
+  const parseKey = () => {
+    const parts = [];
+    for (;;) {
+      skipSpace(false);
+      if (text[pos] === '"' || text[pos] === "'") {
+        parts.push(parseString());
+      } else {
+        const match = /^[A-Za-z0-9_-]+/.exec(text.slice(pos));
+        if (!match) throw error('Expected a key');
+        parts.push(match[0]);
+        pos += match[0].length;
+      }
+      skipSpace(false);
+      if (text[pos] !== '.') return parts;
+      pos++;
+    }
+  };
+
+  const parseString = () => {
+    const quote = text[pos];
+    const multiline = text.startsWith(quote.repeat(3), pos);
+    const delimiter = multiline ? quote.repeat(3) : quote;
+    pos += delimiter.length;
+    if (multiline && text[pos] === '\n') pos++;
+    let value = '';
+    while (pos < text.length && !text.startsWith(delimiter, pos)) {
+      const ch = text[pos];
+      if (!multiline && ch === '\n') throw error('Unterminated string');
+      if (ch === '\\' && quote === '"') {
+        const escape = text[pos + 1];
+        const simple = { n: '\n', t: '\t', r: '\r', '"': '"', '\\': '\\', b: '\b', f: '\f' };
+        if (escape in simple) {
+          value += simple[escape];
+          pos += 2;
+        } else if (escape === 'u' || escape === 'U') {
+          const length = escape === 'u' ? 4 : 8;
+          value += String.fromCodePoint(parseInt(text.slice(pos + 2, pos + 2 + length), 16));
+          pos += 2 + length;
+        } else if (multiline && /\s/.test(escape)) {
+          // Line-ending backslash trims the following whitespace
+          pos++;
+          while (pos < text.length && /\s/.test(text[pos])) pos++;
+        } else {
+          throw error(`Invalid escape \\${escape}`);
+        }
+        continue;
+      }
+      value += ch;
+      pos++;
+    }
+    if (pos >= text.length) throw error('Unterminated string');
+    pos += delimiter.length;
+    return value;
+  };
+
+  const parseValue = () => {
+    skipSpace(false);
+    const ch = text[pos];
+    if (ch === '"' || ch === "'") return parseString();
+    if (ch === '[') {
+      pos++;
+      const values = [];
+      for (;;) {
+        skipSpace(true);
+        if (text[pos] === ']') break;
+        values.push(parseValue());
+        skipSpace(true);
+        if (text[pos] === ',') pos++;
+        else if (text[pos] !== ']') throw error('Expected , or ] in array');
+      }
+      pos++;
+      return values;
+    }
+    if (ch === '{') {
+      pos++;
+      const inline = {};
+      skipSpace(false);
+      while (text[pos] !== '}') {
+        const key = parseKey();
+        if (text[pos] !== '=') throw error('Expected = in inline table');
+        pos++;
+        assign(inline, key, parseValue());
+        skipSpace(false);
+        if (text[pos] === ',') pos++;
+        skipSpace(false);
+        if (pos >= text.length) throw error('Unterminated inline table');
+      }
+      pos++;
+      return inline;
+    }
+    const match = /^[^\s,\]}#]+/.exec(text.slice(pos));
+    if (!match) throw error('Expected a value');
+    pos += match[0].length;
+    if (match[0] === 'true') return true;
+    if (match[0] === 'false') return false;
+    return match[0];
+  };
+
+  const descend = (target, key, arrayTable) => {
+    let current = target;
+    key.forEach((part, index) => {
+      const last = index === key.length - 1;
+      if (last && arrayTable) {
+        if (!Array.isArray(current[part])) current[part] = [];
+        const entry = {};
+        current[part].push(entry);
+        current = entry;
+        return;
+      }
+      if (current[part] === undefined) current[part] = {};
+      current = current[part];
+      // `[bin.extra]` after `[[bin]]` refers to the latest array entry
+      if (Array.isArray(current)) current = current[current.length - 1];
+    });
+    return current;
+  };
+
+  const assign = (target, key, value) => {
+    const parent = descend(target, key.slice(0, -1), false);
+    parent[key[key.length - 1]] = value;
+  };
+
+  while (pos < text.length) {
+    skipSpace(true);
+    if (pos >= text.length) break;
+    if (text[pos] === '[') {
+      const arrayTable = text[pos + 1] === '[';
+      pos += arrayTable ? 2 : 1;
+      const key = parseKey();
+      const close = arrayTable ? ']]' : ']';
+      if (!text.startsWith(close, pos)) throw error(`Expected ${close}`);
+      pos += close.length;
+      table = descend(root, key, arrayTable);
+    } else {
+      const key = parseKey();
+      if (text[pos] !== '=') throw error('Expected =');
+      pos++;
+      assign(table, key, parseValue());
+    }
+    skipSpace(false);
+    if (pos < text.length && text[pos] !== '\n') throw error('Expected end of line');
+  }
+
+  return root;
+}
+
+/**
+ * Read and parse a Cargo.toml
+ * @param {string} manifestPath - Path to the manifest
+ * @returns {Object} - `{ path, dir, data }`
+ */
+function readManifest(manifestPath) {
+  const data = parseToml(fs.readFileSync(manifestPath, 'utf8'));
+  return { path: manifestPath, dir: path.dirname(manifestPath), data };
+}
+
+let cargoBinary;
+
+/**
+ * Locate a `cargo` binary on the PATH (or in CARGO, as Cargo sets it for its own subcommands)
+ * @returns {string|null} - Command to run, or null when Cargo is not installed
+ */
+function findCargo() {
+  if (cargoBinary !== undefined) return cargoBinary;
+  const candidate = process.env.CARGO || 'cargo';
+  const probe = spawnSync(candidate, ['--version'], { encoding: 'utf8' });
+  cargoBinary = probe.status === 0 ? candidate : null;
+  return cargoBinary;
+}
+
+/**
+ * Ask Cargo for the packages of the workspace a manifest belongs to
+ * @param {string} manifestPath - Path to a Cargo.toml
+ * @returns {Object|null} - `cargo metadata --no-deps` output, or null when Cargo is missing or rejects the manifest
+ */
+function cargoMetadata(manifestPath) {
+  const cargo = findCargo();
+  if (!cargo) return null;
+  const result = spawnSync(cargo, ['metadata', '--no-deps', '--format-version', '1', '--offline', '--manifest-path', manifestPath], {
+    encoding: 'utf8',
+    maxBuffer: 64 * 1024 * 1024
+  });
+  if (result.status !== 0) return null;
+  try {
+    return JSON.parse(result.stdout);
+  } catch (error) {
+    return null;
+  }
+}
+
+/**
+ * Resolve packages, targets and workspace membership with `cargo metadata`, one workspace at a time.
+ * Manifests Cargo rejects (or all of them, without Cargo) are left to the manifest parser.
+ * @param {string[]} manifests - Cargo.toml files found under the audited directory
+ * @param {string} dirPath - Audited directory
+ * @returns {Object} - `{ packages, workspaces }`: packages keyed by manifest path as `{ name, targets }`,
+ *   workspaces as `{ dir, path, members }` with the member manifest paths
+ */
+function loadCargoMetadata(manifests, dirPath) {
+  const packages = new Map();
+  const workspaces = [];
+  if (manifests.length === 0 || !findCargo()) return { packages, workspaces };
+
+  // Cargo reports canonical paths; map them back under the directory as it was given
+  const base = path.resolve(dirPath);
+  const realBase = fs.realpathSync(base);
+  const local = (target) => {
+    const relative = path.relative(realBase, target);
+    return relative.startsWith('..') || path.isAbsolute(relative) ? target : path.join(base, relative);
+  };
+
+  const done = new Set();
+  // Workspace roots are shallower than their members, so one call usually covers a whole workspace
+  const byDepth = [...manifests].sort((a, b) => a.split(path.sep).length - b.split(path.sep).length || a.localeCompare(b));
+  for (const manifestPath of byDepth) {
+    if (done.has(manifestPath)) continue;
+    done.add(manifestPath);
+    const metadata = cargoMetadata(manifestPath);
+    if (!metadata) continue;
+
+    const memberIds = new Set(metadata.workspace_members || []);
+    const workspace = { dir: local(metadata.workspace_root), path: path.join(local(metadata.workspace_root), 'Cargo.toml'), members: new Set() };
+    for (const pkg of metadata.packages || []) {
+      if (!memberIds.has(pkg.id)) continue;
+      const pkgManifest = local(pkg.manifest_path);
+      done.add(pkgManifest);
+      workspace.members.add(pkgManifest);
+      const targets = [];
+      for (const target of pkg.targets || []) {
+        const kind = target.kind.map(name => CARGO_TARGET_KINDS[name] || name).find(name => TARGET_KINDS.includes(name));
+        if (kind) targets.push({ kind, name: target.name, root: local(target.src_path) });
+      }
+      targets.sort((a, b) => TARGET_KINDS.indexOf(a.kind) - TARGET_KINDS.indexOf(b.kind));
+      packages.set(pkgManifest, { name: pkg.name, targets });
+    }
+    done.add(workspace.path);
+    workspaces.push(workspace);
+  }
+  return { packages, workspaces };
+}
+
+function globToRegExp(pattern) {
+  const source = pattern
+    .split('')
+    .map(ch => (ch === '*' ? '[^/]*' : ch === '?' ? '[^/]' : ch.replace(/[.+^${}()|[\]\\]/g, '\\$&')))
+    .join('');
+  return new RegExp(`^${source}$`);
+}
+
+/**
+ * Expand workspace `members`/`exclude` patterns to crate directories
+ * @param {string} rootDir - Workspace root
+ * @param {string[]} patterns - Paths relative to the root; `*` and `?` match within one path segment
+ * @returns {string[]} - Absolute directories
+ */
+function expandWorkspacePaths(rootDir, patterns = []) {
+  const dirs = [];
+  for (const pattern of patterns) {
+    let candidates = [path.resolve(rootDir)];
+    for (const segment of pattern.split('/').filter(part => part && part !== '.')) {
+      const next = [];
+      for (const dir of candidates) {
+        if (!/[*?]/.test(segment)) {
+          next.push(path.join(dir, segment));
+          continue;
+        }
+        const matcher = globToRegExp(segment);
+        for (const entry of safeReaddir(dir)) {
+          if (matcher.test(entry) && isDirectory(path.join(dir, entry))) next.push(path.join(dir, entry));
+        }
+      }
+      candidates = next;
+    }
+    dirs.push(...candidates.filter(isDirectory));
+  }
+  return [...new Set(dirs)];
+}
+
+function safeReaddir(dir) {
+  try {
+    return fs.readdirSync(dir);
+  } catch (error) {
+    return [];
+  }
+}
+
+function isDirectory(target) {
+  try {
+    return fs.statSync(target).isDirectory();
+  } catch (error) {
+    return false;
+  }
+}
+
+function isFile(target) {
+  try {
+    return fs.statSync(target).isFile();
+  } catch (error) {
+    return false;
+  }
+}
+
+/**
+ * List the auto-discovered targets of one kind: `<dir>/*.rs` and `<dir>/<name>/main.rs`
+ */
+function discoverTargetFiles(crateDir, subdir) {
+  const dir = path.join(crateDir, subdir);
+  const found = [];
+  for (const entry of safeReaddir(dir).sort()) {
+    const entryPath = path.join(dir, entry);
+    if (entry.endsWith('.rs') && isFile(entryPath)) {
+      found.push({ name: entry.slice(0, -3), root: entryPath });
+    } else if (isFile(path.join(entryPath, 'main.rs'))) {
+      found.push({ name: entry, root: path.join(entryPath, 'main.rs') });
+    }
+  }
+  return found;
+}
+
+/**
+ * Resolve the targets of a package the way Cargo does: explicit `[lib]`, `[[bin]]`, `[[test]]`,
+ * `[[bench]]` and `[[example]]` sections plus auto-discovery, and the build script.
+ * @param {Object} manifest - Result of readManifest
+ * @returns {Array} - `{ kind, name, root }` per target
+ */
+function resolveTargets(manifest) {
+  const { dir, data } = manifest;
+  const pkg = data.package || {};
+  const crateName = (pkg.name || path.basename(dir)).replace(/-/g, '_');
+  const targets = [];
+  const seen = new Set();
+
+  const add = (kind, name, root) => {
+    const resolved = path.resolve(dir, root);
+    if (seen.has(`${kind}:${resolved}`) || !isFile(resolved)) return;
+    seen.add(`${kind}:${resolved}`);
+    targets.push({ kind, name, root: resolved });
+  };
+
+  // Library
+  const lib = data.lib || {};
+  add('lib', (lib.name || crateName).replace(/-/g, '_'), lib.path || 'src/lib.rs');
+
+  // Explicit targets, then auto-discovered ones unless `autobins = false` etc.
+  const sections = [
+    { kind: 'bin', key: 'bin', auto: 'autobins', subdir: 'src/bin' },
+    { kind: 'test', key: 'test', auto: 'autotests', subdir: 'tests' },
+    { kind: 'bench', key: 'bench', auto: 'autobenches', subdir: 'benches' },
+    { kind: 'example', key: 'example', auto: 'autoexamples', subdir: 'examples' }
+  ];
+  for (const section of sections) {
+    const explicit = Array.isArray(data[section.key]) ? data[section.key] : [];
+    for (const entry of explicit) {
+      if (!entry.name && !entry.path) continue;
+      const name = entry.name || path.basename(entry.path, '.rs');
+      const root = entry.path ||
+        (section.kind === 'bin' && name === pkg.name && isFile(path.join(dir, 'src/main.rs'))
+          ? 'src/main.rs'
+          : isFile(path.join(dir, section.subdir, `${name}.rs`))
+            ? path.join(section.subdir, `${name}.rs`)
+            : path.join(section.subdir, name, 'main.rs'));
+      add(section.kind, name, root);
+    }
+    // Edition 2015 packages turn auto-discovery off for a kind once any target of it is listed
+    const edition2015 = !pkg.edition || pkg.edition === '2015';
+    if (pkg[section.auto] === false || (pkg[section.auto] === undefined && edition2015 && explicit.length > 0)) continue;
+    if (section.kind === 'bin') add('bin', pkg.name || crateName, 'src/main.rs');
+    for (const found of discoverTargetFiles(dir, section.subdir)) {
+      add(section.kind, found.name, found.root);
+    }
+  }
+
+  // Build script
+  if (pkg.build !== false) {
+    add('build', 'build-script-build', typeof pkg.build === 'string' ? pkg.build : 'build.rs');
+  }
+
+  return targets.sort((a, b) => TARGET_KINDS.indexOf(a.kind) - TARGET_KINDS.indexOf(b.kind));
+}
+
+function pathAttribute(attrs = []) {
+  const attr = attrs.find(candidate => candidate.path === 'path' && candidate.args);
+  return attr ? attr.args.replace(/^"|"$/g, '') : null;
+}
+
+/**
+ * Walk a target's module tree by following `mod name;` declarations from its root
+ * @param {string} rootFile - Crate root (lib.rs, main.rs, build.rs, ...)
+ * @returns {Array} - `{ file, modulePath }` per file in the order they were reached;
+ *   `modulePath` is the list of module names below the crate root
+ */
+function moduleTree(rootFile) {
+  const modules = [];
+  const visited = new Set();
+
+  const visitFile = (file, isModRoot, modulePath) => {
+    const resolved = path.resolve(file);
+    if (visited.has(resolved) || !isFile(resolved)) return;
+    visited.add(resolved);
+    modules.push({ file: resolved, modulePath });
+
+    const fileDir = path.dirname(resolved);
+    // Children of `foo.rs` live in `foo/`; children of crate roots and `mod.rs` live next to them
+    const childDir = isModRoot ? fileDir : path.join(fileDir, path.basename(resolved, '.rs'));
+
+    let items;
+    try {
+      items = parse(fs.readFileSync(resolved, 'utf8')).items;
+    } catch (error) {
+      // Fall back to a line scan so one unparseable file does not hide its submodules
+      items = [];
+      const pattern = /^\s*(?:pub(?:\s*\([^)]*\))?\s+)?mod\s+(?:r#)?(\w+)\s*;/gm;
+      let match;
+      while ((match = pattern.exec(fs.readFileSync(resolved, 'utf8')))) {
+        items.push({ type: 'Mod', name: match[1], external: true, attrs: [] });
+      }
+    }
+    visitItems(items, fileDir, childDir, modulePath);
+  };
+
+  const visitItems = (items, fileDir, moduleDir, modulePath) => {
+    for (const item of items) {
+      if (item.type !== 'Mod') continue;
+      const name = item.name.replace(/^r#/, '');
+      const childPath = [...modulePath, name];
+      const explicitPath = pathAttribute(item.attrs);
+      if (!item.external) {
+        visitItems(item.items, fileDir, explicitPath ? path.join(moduleDir, explicitPath) : path.join(moduleDir, name), childPath);
+        continue;
+      }
+      if (explicitPath) {
+        visitFile(path.join(fileDir, explicitPath), true, childPath);
+      } else if (isFile(path.join(moduleDir, `${name}.rs`))) {
+        visitFile(path.join(moduleDir, `${name}.rs`), false, childPath);
+      } else {
+        visitFile(path.join(moduleDir, name, 'mod.rs'), true, childPath);
+      }
+    }
+  };
+
+  visitFile(rootFile, true, []);
+  return modules;
+}
+
+/**
+ * Collect every file in a target's module tree
+ * @param {string} rootFile - Crate root (lib.rs, main.rs, build.rs, ...)
+ * @returns {string[]} - Files in the order they were reached
+ */
+function moduleTreeFiles(rootFile) {
+  return moduleTree(rootFile).map(module => module.file);
+}
+
+function listFiles(dirPath, onFile) {
+  for (const entry of safeReaddir(dirPath)) {
+    const entryPath = path.join(dirPath, entry);
+    if (isDirectory(entryPath)) {
+      if (!entry.startsWith('.') && !SKIPPED_DIRS.has(entry)) listFiles(entryPath, onFile);
+    } else {
+      onFile(entryPath, entry);
+    }
+  }
+}
+
+function isInside(dir, target) {
+  const relative = path.relative(dir, target);
+  return relative === '' || (!relative.startsWith('..') && !path.isAbsolute(relative));
+}
+
+/**
+ * Discover the Cargo packages under a directory and the files of each of their targets.
+ * Packages and targets come from `cargo metadata` when Cargo is installed, and from the manifests otherwise.
+ * Rust files that belong to no package are returned as loose files.
+ * @param {string} dirPath - Directory to search
+ * @returns {Object} - `{ crates, looseFiles }`; each crate is
+ *   `{ name, manifestPath, dir, workspace, vendored, excluded, targets, unreferencedFiles }`
+ */
+function discoverCargoWorkspace(dirPath) {
+  const manifests = [];
+  const rustFiles = [];
+  listFiles(path.resolve(dirPath), (filePath, name) => {
+    if (name === 'Cargo.toml') manifests.push(filePath);
+    else if (name.endsWith('.rs')) rustFiles.push(filePath);
+  });
+
+  const cargo = loadCargoMetadata(manifests, dirPath);
+  const parsed = [];
+  const packages = [];
+  for (const manifestPath of manifests.sort()) {
+    if (cargo.packages.has(manifestPath)) {
+      packages.push({ manifestPath, ...cargo.packages.get(manifestPath) });
+      continue;
+    }
+    // Virtual manifest of a workspace Cargo already resolved
+    if (cargo.workspaces.some(workspace => workspace.path === manifestPath)) continue;
+    try {
+      const manifest = readManifest(manifestPath);
+      parsed.push(manifest);
+      if (manifest.data.package) {
+        packages.push({ manifestPath, name: manifest.data.package.name || path.basename(manifest.dir), targets: resolveTargets(manifest) });
+      }
+    } catch (error) {
+      console.warn(`Warning: could not parse ${manifestPath}: ${error.message}`);
+    }
+  }
+
+  // Workspace roots with their member directories. Cargo reports every package as its own workspace;
+  // only virtual manifests and roots with several members are workspaces others can be excluded from.
+  const workspaceRoots = cargo.workspaces
+    .filter(workspace => workspace.members.size > 1 || !workspace.members.has(workspace.path))
+    .map(workspace => ({ dir: workspace.dir, path: workspace.path, members: new Set([...workspace.members].map(member => path.dirname(member))) }));
+  for (const root of parsed.filter(manifest => manifest.data.workspace)) {
+    const excluded = new Set(expandWorkspacePaths(root.dir, root.data.workspace.exclude));
+    const members = new Set(expandWorkspacePaths(root.dir, root.data.workspace.members).filter(dir => !excluded.has(dir)));
+    // The root package is a member of its own workspace
+    if (root.data.package) members.add(root.dir);
+    workspaceRoots.push({ dir: root.dir, path: root.path, members });
+  }
+  // The innermost workspace wins
+  const workspaceOf = (dir) => workspaceRoots
+    .filter(root => isInside(root.dir, dir))
+    .sort((a, b) => b.dir.length - a.dir.length)[0];
+
+  const crates = packages.map(({ manifestPath, name, targets }) => {
+    const dir = path.dirname(manifestPath);
+    const workspace = workspaceOf(dir);
+    const relative = path.relative(path.resolve(dirPath), dir).split(path.sep);
+    return {
+      name,
+      manifestPath,
+      dir,
+      workspace: workspace ? workspace.path : null,
+      vendored: relative.some(segment => VENDOR_DIRS.has(segment)),
+      excluded: Boolean(workspace && !workspace.members.has(dir)),
+      targets: targets.map(target => ({ ...target, files: moduleTreeFiles(target.root) })),
+      unreferencedFiles: []
+    };
+  });
+
+  // Assign every file to the innermost crate that contains it
+  const looseFiles = [];
+  const byDepth = [...crates].sort((a, b) => b.dir.length - a.dir.length);
+  for (const file of rustFiles) {
+    const owner = byDepth.find(crate => isInside(crate.dir, file));
+    if (!owner) {
+      looseFiles.push(file);
+    } else if (!owner.targets.some(target => target.files.includes(file))) {
+      owner.unreferencedFiles.push(file);
+    }
+  }
+
+  return { crates, looseFiles };
+}
+
+/**
+ * Parse a comma separated list option (`--crates a,b`)
+ */
+function parseList(value) {
+  if (!value) return null;
+  const list = (Array.isArray(value) ? value : String(value).split(','))
+    .map(item => item.trim())
+    .filter(Boolean);
+  return list.length > 0 ? list : null;
+}
+
+/**
+ * Pick the crates and targets to audit
+ * @param {Object} discovery - Result of discoverCargoWorkspace
+ * @param {Object} options - `crates`: names to audit (default: every first-party crate);
+ *   `targets`: target kinds or `all` (default: lib,bin)
+ * @returns {Array} - Groups `{ crate, manifestPath, target, files }`; `crate` is null for loose files
+ */
+function selectRustTargets(discovery, options = {}) {
+  const crateNames = parseList(options.crates);
+  const kinds = parseList(options.targets) || DEFAULT_TARGET_KINDS;
+  const allKinds = kinds.includes('all');
+  const unknown = kinds.filter(kind => kind !== 'all' && !TARGET_KINDS.includes(kind));
+  if (unknown.length > 0) {
+    throw new Error(`Unknown target kind: ${unknown.join(', ')} (expected one of ${TARGET_KINDS.join(', ')}, all)`);
+  }
+  if (crateNames) {
+    const missing = crateNames.filter(name => !discovery.crates.some(crate => crate.name === name));
+    if (missing.length > 0) throw new Error(`Crate not found: ${missing.join(', ')}`);
+  }
+
+  const groups = [];
+  for (const crate of discovery.crates) {
+    // Vendored and workspace-excluded crates are only audited when named explicitly
+    const selected = crateNames ? crateNames.includes(crate.name) : !crate.vendored && !crate.excluded;
+    if (!selected) continue;
+    for (const target of crate.targets) {
+      if (!allKinds && !kinds.includes(target.kind)) continue;
+      groups.push({
+        crate: crate.name,
+        manifestPath: crate.manifestPath,
+        target: { kind: target.kind, name: target.name, root: target.root },
+        files: target.files
+      });
+    }
+  }
+
+  if (!crateNames && discovery.looseFiles.length > 0) {
+    groups.push({ crate: null, manifestPath: null, target: null, files: discovery.looseFiles });
+  }
+
+  return groups;
+}
+
+module.exports = {
+  TARGET_KINDS,
+  DEFAULT_TARGET_KINDS,
+  parseToml,
+  readManifest,
+  findCargo,
+  cargoMetadata,
+  expandWorkspacePaths,
+  resolveTargets,
+  moduleTree,
+  moduleTreeFiles,
+  discoverCargoWorkspace,
+  selectRustTargets
+};
@@ -3,53 +3,55 @@ const path = require('path');
 const graphviz = require('graphviz');
 const parser = require('@solidity-parser/parser');
 const ora = require('ora');
-const Anthropic = require('@anthropic-ai/sdk');
+const { completeResult, isLLMAvailable, unavailableReason } = require('./llm-provider');
+const { withCacheNote } = require('./llm-cache');
 
-const anthropic = new Anthropic({
-  apiKey: process.env.ANTHROPIC_API_KEY,
-});
+// Version of the Mermaid diagram prompt, part of the response cache key
+const DIAGRAM_PROMPT_VERSION = 1;
 
 /**
  * Generates a diagram of the smart contract code
  * @param {string} filePath - Path to the smart contract file
  * @param {string} outputDir - Directory to save diagram results
+ * @param {Object} options - `reportName`: name of the diagram files (default: the file name, see reportName)
  */
-async function generateDiagram(filePath, outputDir) {
+async function generateDiagram(filePath, outputDir, options = {}) {
   const spinner = ora('Generating contract diagram...').start();
   
   try {
     // Read the contract code
     const contractCode = fs.readFileSync(filePath, 'utf8');
     const fileName = path.basename(filePath);
+    const reportName = options.reportName || fileName;
     
     // First approach: Generate DOT diagram using GraphViz
     try {
       const dotGraph = generateDotDiagram(contractCode);
-      const dotOutputPath = path.join(outputDir, `${fileName}.dot`);
+      const dotOutputPath = path.join(outputDir, `${reportName}.dot`);
       fs.writeFileSync(dotOutputPath, dotGraph);
     } catch (dotError) {
       console.error('Error generating dot diagram:', dotError);
       // Create a simple fallback diagram if the main one fails
       const fallbackDot = `digraph G {\n  label="Contract: ${fileName}";\n  node [shape=box];\n  "${fileName}" [fillcolor=lightblue, style=filled];\n}`;
-      const dotOutputPath = path.join(outputDir, `${fileName}.dot`);
+      const dotOutputPath = path.join(outputDir, `${reportName}.dot`);
       fs.writeFileSync(dotOutputPath, fallbackDot);
     }
     
-    // Second approach: Use Claude 3.7 to create a mermaid diagram
+    // Second approach: Use the configured LLM to create a mermaid diagram
     try {
       const mermaidDiagram = await generateMermaidDiagram(contractCode);
-      const mermaidOutputPath = path.join(outputDir, `${fileName}.mermaid.md`);
+      const mermaidOutputPath = path.join(outputDir, `${reportName}.mermaid.md`);
       fs.writeFileSync(mermaidOutputPath, mermaidDiagram);
     } catch (mermaidError) {
       console.error('Error generating mermaid diagram:', mermaidError);
       // Create a simple fallback mermaid diagram
       const fallbackMermaid = `# Contract Diagram\n\`\`\`mermaid\nclassDiagram\n  class ${fileName} {\n    +Contract Structure: Failed to generate\n  }\n\`\`\``;
-      const mermaidOutputPath = path.join(outputDir, `${fileName}.mermaid.md`);
+      const mermaidOutputPath = path.join(outputDir, `${reportName}.mermaid.md`);
       fs.writeFileSync(mermaidOutputPath, fallbackMermaid);
     }
     
-    spinner.succeed(`Diagrams generated! Saved to:\n- ${path.join(outputDir, `${fileName}.dot`)}\n- ${path.join(outputDir, `${fileName}.mermaid.md`)}`);
-    return { dotOutputPath: path.join(outputDir, `${fileName}.dot`), mermaidOutputPath: path.join(outputDir, `${fileName}.mermaid.md`) };
+    spinner.succeed(`Diagrams generated! Saved to:\n- ${path.join(outputDir, `${reportName}.dot`)}\n- ${path.join(outputDir, `${reportName}.mermaid.md`)}`);
+    return { dotOutputPath: path.join(outputDir, `${reportName}.dot`), mermaidOutputPath: path.join(outputDir, `${reportName}.mermaid.md`) };
     
   } catch (error) {
     spinner.fail('Diagram generation failed');
@@ -180,13 +182,13 @@ function generateDotDiagram(contractCode) {
 }
 
 /**
- * Generates a mermaid diagram using Claude 3.7
+ * Generates a mermaid diagram using the configured LLM
  * @param {string} contractCode - Source code of the contract
  * @returns {string} - Mermaid markdown diagram
  */
 async function generateMermaidDiagram(contractCode) {
   try {
-    // Prompt for Claude 3.7
+    // Prompt for the configured LLM
     const prompt = `
 Analyze the following smart contract and create a detailed Mermaid diagram that visually represents:
 1. Contract inheritance hierarchy
@@ -216,32 +218,15 @@ Format your response like this:
 \`\`\`
 `;
 
-    // Check if API key is available
-    if (!process.env.ANTHROPIC_API_KEY) {
-      console.warn('Warning: ANTHROPIC_API_KEY not found in environment variables');
-      return "# No API Key Available\nMermaid diagram generation requires an Anthropic API key.";
+    // Check if a model provider is available
+    if (!isLLMAvailable()) {
+      console.warn(`Warning: ${unavailableReason()}`);
+      return "# No API Key Available\nMermaid diagram generation requires an LLM provider (e.g. an Anthropic API key).";
     }
 
     try {
-      // Call Claude 3.7 API
-      const response = await anthropic.messages.create({
-        model: "claude-3-sonnet-20240229",
-        max_tokens: 4000,
-        messages: [
-          { 
-            role: "user", 
-            content: prompt 
-          }
-        ],
-        system: "You are an expert smart contract diagramming assistant. Generate comprehensive, correct mermaid diagrams that accurately represent contract code structure and logic."
-      });
-
-      if (response && response.content && Array.isArray(response.content) && response.content.length > 0) {
-        return response.content[0].text;
-      } else {
-        console.error('Invalid response format from Anthropic API:', response);
-        return "# Error in API Response\nUnable to create mermaid diagram due to an invalid API response format.";
-      }
+      const response = await completeResult(prompt, "You are an expert smart contract diagramming assistant. Generate comprehensive, correct mermaid diagrams that accurately represent contract code structure and logic.", { template: 'solidity-diagram', version: DIAGRAM_PROMPT_VERSION, source: contractCode });
+      return withCacheNote(response.text, [response]);
     } catch (apiError) {
       console.error('API call error:', apiError);
       return "# API Call Error\nUnable to create mermaid diagram due to an API call error.";
@@ -0,0 +1,234 @@
+const fs = require('fs');
+const path = require('path');
+const { simpleGit } = require('simple-git');
+const parser = require('@solidity-parser/parser');
+const { rustFunctionSpans } = require('./rust-ast');
+
+/**
+ * Incremental audits against a git base ref (--diff): which `.rs` and `.sol` files a change adds or modifies,
+ * which of their lines and functions it touches, and whether a finding lies in that changed code.
+ *
+ * The change is everything between the merge base of the base ref and HEAD and the working tree, so that a pull
+ * request branch is compared with the point it forked from, uncommitted edits included. Untracked files count as
+ * added.
+ */
+
+const AUDITED_PATHSPECS = ['*.rs', '*.sol'];
+// Escapes of the quoted paths in git's output, besides `\\`, `\"` and octal bytes
+const C_ESCAPES = { a: 7, b: 8, t: 9, n: 10, v: 11, f: 12, r: 13 };
+
+/**
+ * Find the files and lines changed since a base ref
+ * @param {string} inputPath - File or directory being audited, inside the git repository
+ * @param {string} baseRef - Branch, tag or commit to compare with, e.g. `origin/main`
+ * @returns {Object} - `{ base, mergeBase, root, files }` where files maps absolute paths to
+ *   `{ file, status, ranges }`: status is added, modified or renamed, ranges the changed `{ start, end }` lines
+ */
+async function resolveDiff(inputPath, baseRef) {
+  const stat = fs.statSync(inputPath);
+  const git = simpleGit(stat.isDirectory() ? inputPath : path.dirname(inputPath));
+  let root;
+  try {
+    root = (await git.raw(['rev-parse', '--show-toplevel'])).trim();
+  } catch (error) {
+    throw new Error(`--diff needs a git repository, and ${inputPath} is not in one`);
+  }
+
+  const repo = simpleGit(root);
+  const base = await resolveRef(repo, baseRef);
+  const mergeBase = (await repo.raw(['merge-base', base, 'HEAD'])).trim();
+  // Without core.quotePath, git quotes and octal-escapes every path with non-ASCII characters
+  const diff = await repo.raw(['-c', 'core.quotePath=false', 'diff', '--no-color', '--no-ext-diff', '-U0', '-M', '--diff-filter=ACMR', mergeBase, '--', ...AUDITED_PATHSPECS]);
+  const files = parseUnifiedDiff(diff, root);
+
+  const untracked = await repo.raw(['ls-files', '-z', '--others', '--exclude-standard', '--', ...AUDITED_PATHSPECS]);
+  untracked.split('\0').filter(Boolean).forEach(relative => {
+    const file = path.join(root, relative);
+    files.set(file, { file, status: 'added', ranges: [wholeFile(file)] });
+  });
+
+  return { base: baseRef, mergeBase, root, files };
+}
+
+/**
+ * Resolve a ref, falling back to the `origin` remote-tracking branch (e.g. `main` in a fresh clone)
+ */
+async function resolveRef(git, ref) {
+  for (const candidate of [ref, `origin/${ref}`]) {
+    try {
+      await git.raw(['rev-parse', '--verify', '--quiet', `${candidate}^{commit}`]);
+      return candidate;
+    } catch (error) {
+      // Try the next candidate
+    }
+  }
+  throw new Error(`Unknown git base ref for --diff: ${ref}`);
+}
+
+/**
+ * Changed files and line ranges of a `git diff -U0`, on the new side of each hunk. A hunk that only deletes lines
+ * marks the lines around the deletion.
+ * @param {string} diff - Output of git diff
+ * @param {string} root - Repository root the paths are relative to
+ * @returns {Map} - Absolute path to `{ file, status, ranges }`
+ */
+function parseUnifiedDiff(diff, root) {
+  const files = new Map();
+  let current = null;
+  let status = 'modified';
+  for (const line of diff.split('\n')) {
+    if (line.startsWith('diff --git ')) {
+      current = null;
+      status = 'modified';
+    } else if (line.startsWith('new file mode')) {
+      status = 'added';
+    } else if (line.startsWith('rename from ')) {
+      status = 'renamed';
+    } else if (line.startsWith('+++ ')) {
+      const target = unquotePath(line.slice(4));
+      if (target === '/dev/null') continue;
+      const file = path.join(root, target.replace(/^b\//, ''));
+      current = { file, status, ranges: [] };
+      files.set(file, current);
+    } else if (current && line.startsWith('@@')) {
+      const hunk = line.match(/^@@ -\d+(?:,\d+)? \+(\d+)(?:,(\d+))? @@/);
+      if (!hunk) continue;
+      const start = Number(hunk[1]);
+      const count = hunk[2] === undefined ? 1 : Number(hunk[2]);
+      current.ranges.push(count > 0
+        ? { start, end: start + count - 1 }
+        : { start: Math.max(start, 1), end: start + 1 });
+    }
+  }
+  return files;
+}
+
+/**
+ * Path of a `+++` line: git quotes paths with special characters C-style (`"b/a\"b.rs"`, octal escapes for
+ * non-ASCII bytes unless core.quotePath is off) and ends paths that contain a space with a tab
+ */
+function unquotePath(text) {
+  if (!text.startsWith('"')) return text.replace(/\t$/, '');
+  const bytes = [];
+  for (let i = 1; i < text.length && text[i] !== '"'; i++) {
+    if (text[i] !== '\\') {
+      const char = String.fromCodePoint(text.codePointAt(i));
+      bytes.push(...Buffer.from(char));
+      i += char.length - 1;
+    } else if (/[0-7]/.test(text[i + 1])) {
+      bytes.push(parseInt(text.slice(i + 1, i + 4), 8));
+      i += 3;
+    } else {
+      i++;
+      bytes.push(C_ESCAPES[text[i]] !== undefined ? C_ESCAPES[text[i]] : text.charCodeAt(i));
+    }
+  }
+  return Buffer.from(bytes).toString('utf8');
+}
+
+function wholeFile(file) {
+  let lines = 1;
+  try {
+    lines = fs.readFileSync(file, 'utf8').split('\n').length;
+  } catch (error) {
+    // Unreadable files are reported by the analyzers
+  }
+  return { start: 1, end: lines };
+}
+
+/**
+ * Line spans of the functions of a Rust or Solidity file
+ * @param {string} filePath - Path of the file, for its language
+ * @param {string} code - Source
+ * @returns {Array} - `{ name, startLine, endLine }`
+ */
+function functionSpans(filePath, code) {
+  if (filePath.endsWith('.rs')) return rustFunctionSpans(code);
+  const spans = [];
+  try {
+    const ast = parser.parse(code, { loc: true });
+    parser.visit(ast, {
+      ContractDefinition(contract) {
+        contract.subNodes
+          .filter(node => (node.type === 'FunctionDefinition' || node.type === 'ModifierDefinition') && node.loc)
+          .forEach(node => spans.push({
+            name: `${contract.name}.${node.name || (node.isConstructor ? 'constructor' : 'fallback')}`,
+            startLine: node.loc.start.line,
+            endLine: node.loc.end.line
+          }));
+      }
+    });
+  } catch (error) {
+    // Files that do not parse are compared line by line only
+  }
+  return spans;
+}
+
+/**
+ * What a change touches in one file: its changed lines and the functions that contain them
+ * @param {Object} diff - Result of resolveDiff, or null when not auditing a diff
+ * @param {string} filePath - File to describe
+ * @returns {Object|null} - `{ base, status, ranges, functions }` where functions are the changed
+ *   `{ name, startLine, endLine }`; null without a diff or for a file the change did not touch
+ */
+function describeFileChange(diff, filePath) {
+  const fileDiff = diff && diff.files.get(path.resolve(filePath));
+  if (!fileDiff) return null;
+  const code = fs.readFileSync(filePath, 'utf8');
+  const overlaps = (span) => fileDiff.ranges.some(range => range.start <= span.endLine && span.startLine <= range.end);
+  return {
+    base: diff.base,
+    status: fileDiff.status,
+    ranges: fileDiff.ranges,
+    functions: functionSpans(filePath, code).filter(overlaps)
+  };
+}
+
+/**
+ * Mark each Finding as in new or changed code (`change: 'new'`) or in code the change did not touch
+ * (`change: 'existing'`). A finding is new when the file is new, when its lines overlap a changed line or a
+ * changed function, or, without lines, when it names a changed function.
+ * @param {Array} findings - Findings, modified in place
+ * @param {Object} fileChange - Result of describeFileChange
+ * @returns {Array} - The same findings
+ */
+function classifyFindings(findings, fileChange) {
+  const spans = [
+    ...fileChange.ranges,
+    ...fileChange.functions.map(fn => ({ start: fn.startLine, end: fn.endLine }))
+  ];
+  const bare = (name) => name.replace(/\(.*$/, '').split(/::|\./).pop().trim();
+  const changedNames = new Set(fileChange.functions.map(fn => bare(fn.name)));
+
+  findings.forEach(finding => {
+    const { startLine, endLine, function: fn } = finding.location;
+    let isNew = fileChange.status === 'added';
+    if (!isNew && startLine) {
+      isNew = spans.some(span => span.start <= (endLine || startLine) && startLine <= span.end);
+    } else if (!isNew && fn) {
+      isNew = changedNames.has(bare(fn));
+    }
+    finding.change = isNew ? 'new' : 'existing';
+  });
+  return findings;
+}
+
+/**
+ * One-line description of a file's change for reports, e.g. "Modified since `main`: lines 10-24; changed functions ..."
+ */
+function formatFileChange(fileChange) {
+  const { base } = fileChange;
+  if (fileChange.status === 'added') return `Added since \`${base}\``;
+  const lines = fileChange.ranges.map(range => (range.start === range.end ? `${range.start}` : `${range.start}-${range.end}`));
+  const functions = fileChange.functions.map(fn => `\`${fn.name}\``);
+  return `${fileChange.status === 'renamed' ? 'Renamed and modified' : 'Modified'} since \`${base}\`: lines ${lines.join(', ')}${functions.length > 0 ? `; changed functions ${functions.join(', ')}` : ''}`;
+}
+
+module.exports = {
+  resolveDiff,
+  parseUnifiedDiff,
+  functionSpans,
+  describeFileChange,
+  classifyFindings,
+  formatFileChange
+};
@@ -0,0 +1,250 @@
+const crypto = require('crypto');
+const path = require('path');
+
+/**
+ * The Finding model shared by every analyzer. Analyzers collect findings in severity buckets
+ * (`{ high: [...], medium: [...] }`) of `{ ruleId, title, description, recommendation, location }` objects and
+ * turn them into Findings with toFindings; every report format renders from the resulting list.
+ *
+ * A Finding is:
+ *   id              stable identifier: the same issue in the same code keeps its id across runs, even when
+ *                   unrelated edits move it to another line or Winston runs from another directory
+ *   ruleId          check that produced it, e.g. `rust-unwrap` or `semgrep.rust-unsafe`
+ *   severity        critical, high, medium, low or info
+ *   confidence      high, medium or low: how likely the finding is a true positive
+ *   category        `{ cwe, swc }` weakness classification (either may be null)
+ *   title, description, recommendation
+ *   location        `{ file, startLine, startColumn, endLine, endColumn, function, snippet }`
+ *   evidence        `[{ kind, text }]` supporting the finding: `code` excerpts and analyzer `note`s
+ *   analyzer        analyzer that reported it, e.g. `rust-static`, `rust-static:anchor`, `semgrep`
+ *   grounding       for findings written by the model: `{ score, verified, unverified }`, how much of the code
+ *                   it cites exists in the source (see rust-grounding.js)
+ *   change          when auditing a diff (--diff): `new` for findings in code the change adds or modifies,
+ *                   `existing` for findings in code it left alone (see diff-scope.js)
+ */
+
+const SEVERITIES = ['critical', 'high', 'medium', 'low', 'info'];
+const CONFIDENCES = ['high', 'medium', 'low'];
+
+// Classification, confidence and description of the built-in rules. Findings may override the classification and
+// confidence.
+const RULES = {
+  'rust-parse-error': { confidence: 'high', description: 'The file could not be parsed, so the static checks did not run on it.' },
+  'rust-unsafe-code': { cwe: 'CWE-119', confidence: 'high', description: 'An unsafe block bypasses the compiler\'s memory safety guarantees.' },
+  'rust-unwrap': { cwe: 'CWE-248', confidence: 'high', description: 'unwrap() panics when the Option is None or the Result is an Err.' },
+  'rust-expect': { cwe: 'CWE-248', confidence: 'high', description: 'expect() panics when the Option is None or the Result is an Err.' },
+  'rust-index-panic': { cwe: 'CWE-129', confidence: 'medium', description: 'Indexing panics when the index is out of bounds.' },
+  'rust-slice-panic': { cwe: 'CWE-129', confidence: 'medium', description: 'Slicing panics when the range is out of bounds or inverted.' },
+  'rust-explicit-panic': { cwe: 'CWE-617', confidence: 'high', description: 'A panic!, unreachable!, todo! or unimplemented! macro aborts the call when it is reached.' },
+  'rust-assert-panic': { cwe: 'CWE-617', confidence: 'medium', description: 'An assertion panics when its condition does not hold.' },
+  'rust-division-by-zero': { cwe: 'CWE-369', confidence: 'medium', description: 'Division or remainder by a divisor that may be zero panics.' },
+  'rust-integer-overflow': { cwe: 'CWE-190', swc: 'SWC-101', confidence: 'medium', description: 'Unchecked addition or multiplication can overflow: it panics in debug builds and wraps in release builds.' },
+  'rust-integer-underflow': { cwe: 'CWE-191', swc: 'SWC-101', confidence: 'medium', description: 'Unchecked subtraction can underflow: it panics in debug builds and wraps in release builds.' },
+  'rust-missing-authorization': { cwe: 'CWE-862', swc: 'SWC-105', confidence: 'medium', description: 'A function changes privileged state without checking who the caller is.' },
+  'rust-basic-owner-check': { cwe: 'CWE-284', confidence: 'low', description: 'Authorization relies on a basic owner comparison, which lacks flexibility for more complex requirements.' },
+  'rust-ignored-result': { cwe: 'CWE-252', swc: 'SWC-104', confidence: 'high', description: 'A Result is discarded, so its error is silently ignored.' },
+  'rust-hashmap-without-capacity': { cwe: 'CWE-400', confidence: 'low', description: 'A HashMap grows without a capacity hint, which can be costly when callers control its size.' },
+  'anchor-missing-signer': { cwe: 'CWE-862', swc: 'SWC-105', confidence: 'high', description: 'An authority account of an instruction is not required to sign it.' },
+  'anchor-missing-has-one': { cwe: 'CWE-639', confidence: 'medium', description: 'An account is not tied to the authority stored in the state it controls.' },
+  'anchor-missing-owner-check': { cwe: 'CWE-345', confidence: 'medium', description: 'An account is read without checking which program owns it.' },
+  'anchor-unchecked-account': { cwe: 'CWE-345', confidence: 'medium', description: 'An account Anchor does not validate has no /// CHECK: comment explaining why that is safe.' },
+  'anchor-missing-mut': { cwe: 'CWE-665', confidence: 'high', description: 'An account written by the instruction is not marked mut.' },
+  'anchor-pda-noncanonical-bump': { cwe: 'CWE-345', confidence: 'medium', description: 'A PDA is derived with a bump the caller supplies instead of the canonical one.' },
+  'anchor-pda-seed-collision': { cwe: 'CWE-694', confidence: 'medium', description: 'PDA seeds place two variable-length values next to each other, so different inputs derive the same address.' },
+  'cosmwasm-missing-sender-check': { cwe: 'CWE-862', swc: 'SWC-105', confidence: 'medium', description: 'An execute handler changes privileged state without checking info.sender.' },
+  'cosmwasm-unbounded-iteration': { cwe: 'CWE-400', swc: 'SWC-128', confidence: 'medium', description: 'Iteration over storage without a limit can run out of gas as the storage grows.' },
+  'cosmwasm-unchecked-uint-arithmetic': { cwe: 'CWE-190', swc: 'SWC-101', confidence: 'medium', description: 'Uint128 arithmetic with the panicking operators aborts the message on overflow or underflow.' },
+  'cosmwasm-unvalidated-funds': { cwe: 'CWE-20', confidence: 'medium', description: 'Funds sent with a message are used without checking their denomination or amount.' },
+  'cosmwasm-funds-not-rejected': { cwe: 'CWE-20', confidence: 'low', description: 'A message that never looks at info.funds keeps the coins sent with it by mistake.' },
+  'near-payable-without-deposit-check': { cwe: 'CWE-20', confidence: 'medium', description: 'A #[payable] method does not check the attached deposit.' },
+  'near-callback-not-private': { cwe: 'CWE-862', swc: 'SWC-105', confidence: 'high', description: 'A promise callback is not marked #[private], so anyone can call it.' },
+  'near-state-change-before-promise': { cwe: 'CWE-841', swc: 'SWC-107', confidence: 'medium', description: 'State changes before a cross-contract call are not rolled back when the call fails.' },
+  'near-storage-growth-unpaid': { cwe: 'CWE-400', confidence: 'low', description: 'Storage grows without the caller paying for it.' },
+  'ink-missing-caller-check': { cwe: 'CWE-862', swc: 'SWC-105', confidence: 'medium', description: 'A message changes privileged state without checking the caller.' },
+  'ink-payable-without-value-check': { cwe: 'CWE-20', confidence: 'medium', description: 'A payable message does not check the transferred value.' },
+  'ink-transferred-value-not-payable': { cwe: 'CWE-670', confidence: 'high', description: 'A message reads the transferred value but is not payable, so the value is always zero.' },
+  'ink-unprotected-set-code-hash': { cwe: 'CWE-862', swc: 'SWC-112', confidence: 'high', description: 'Anyone can replace the contract code with set_code_hash.' },
+  'ink-set-code-hash': { cwe: 'CWE-284', confidence: 'high', description: 'An authorized caller can replace the contract code with set_code_hash.' },
+  'ink-unchecked-balance-arithmetic': { cwe: 'CWE-190', swc: 'SWC-101', confidence: 'medium', description: 'Balance arithmetic is unchecked and can overflow or underflow.' },
+  'sol-parse-error': { confidence: 'high', description: 'The contract could not be parsed, so the static checks did not run on it.' },
+  'sol-missing-access-control': { cwe: 'CWE-284', swc: 'SWC-105', confidence: 'medium', description: 'A function changes privileged state without access control.' },
+  'sol-reentrancy': { cwe: 'CWE-841', swc: 'SWC-107', confidence: 'medium', description: 'State is updated after an external call, which allows reentrancy.' },
+  'sol-no-ownership-transfer': { cwe: 'CWE-284', confidence: 'low', description: 'The contract has an owner but no way to transfer ownership.' },
+  'sol-unchecked-arithmetic': { cwe: 'CWE-190', swc: 'SWC-101', confidence: 'medium', description: 'Arithmetic without SafeMath or Solidity 0.8 can overflow or underflow.' },
+  'sol-missing-events': { cwe: 'CWE-778', confidence: 'low', description: 'Important state changes emit no event, which makes them hard to track off-chain.' },
+  'sol-unrestricted-burn': { cwe: 'CWE-284', swc: 'SWC-105', confidence: 'low', description: 'Anyone can burn their tokens with the burn function.' }
+};
+
+/**
+ * Build a Finding
+ * @param {Object} fields - `ruleId`, `severity`, `title`, `description`, `recommendation`, `location`, `analyzer`
+ *   and optionally `confidence`, `category`, `evidence`; other fields (e.g. `reachable`) are kept as they are
+ * @returns {Object} - Finding without an `id`; toFindings assigns ids so that repeated issues stay distinct
+ */
+function createFinding(fields) {
+  // A top-level `function` names the enclosing function of findings without a source location
+  const { ruleId, severity, title, function: fn, description, recommendation, location, analyzer, confidence, category, evidence, ...extra } = fields;
+  if (!SEVERITIES.includes(severity)) throw new Error(`Unknown severity for ${ruleId}: ${severity}`);
+  const rule = RULES[ruleId] || {};
+  const normalizedLocation = normalizeLocation({ ...location, function: (location && location.function) || fn });
+
+  return {
+    ruleId,
+    severity,
+    confidence: CONFIDENCES.includes(confidence) ? confidence : rule.confidence || 'medium',
+    category: {
+      cwe: (category && category.cwe) || rule.cwe || null,
+      swc: (category && category.swc) || rule.swc || null
+    },
+    title: title || ruleId,
+    description: description || '',
+    recommendation: recommendation || '',
+    location: normalizedLocation,
+    evidence: evidence || (normalizedLocation.snippet ? [{ kind: 'code', text: normalizedLocation.snippet }] : []),
+    analyzer: analyzer || null,
+    ...extra
+  };
+}
+
+/**
+ * Static description of a rule, the same for all of its findings
+ * @param {string} ruleId - Rule id
+ * @returns {string|null} - null for rules without one, such as Semgrep rules and findings written by the model
+ */
+function ruleDescription(ruleId) {
+  return (RULES[ruleId] && RULES[ruleId].description) || null;
+}
+
+function normalizeLocation(location) {
+  return {
+    file: location.file || null,
+    startLine: location.startLine || null,
+    startColumn: location.startColumn || null,
+    endLine: location.endLine || location.startLine || null,
+    endColumn: location.endColumn || null,
+    function: location.function || null,
+    snippet: location.snippet || null
+  };
+}
+
+/**
+ * Convert severity buckets into a list of Findings, most severe first
+ * @param {Object} buckets - `{ critical, high, medium, low, info }` lists (any may be missing)
+ * @param {Object} defaults - Fields for findings that do not set them, e.g. `{ analyzer, file }`, and the `root`
+ *   their ids are relative to (see assignIds)
+ * @returns {Array} - Findings with ids
+ */
+function toFindings(buckets, defaults = {}) {
+  const findings = [];
+  SEVERITIES.forEach(severity => {
+    (buckets[severity] || []).forEach(entry => {
+      findings.push(createFinding({
+        ...entry,
+        severity,
+        analyzer: entry.analyzer || defaults.analyzer,
+        location: { ...entry.location, file: (entry.location && entry.location.file) || defaults.file }
+      }));
+    });
+  });
+  return assignIds(findings, defaults.root);
+}
+
+/**
+ * Give each Finding its stable id: a hash of its rule, file, function and code (the line when there is no
+ * excerpt), plus an occurrence number when the same code holds the same issue more than once
+ * @param {Array} findings - Findings, modified in place
+ * @param {string} root - Directory the file path is taken relative to: the audited repository or directory
+ *   (default: the working directory)
+ * @returns {Array} - The same findings
+ */
+function assignIds(findings, root) {
+  const seen = new Map();
+  findings.forEach(finding => {
+    if (finding.id) return;
+    const { file, function: fn, snippet, startLine } = finding.location;
+    const anchor = snippet ? snippet.replace(/\s+/g, ' ').trim() : String(startLine || '');
+    const key = [finding.ruleId, file ? toPortablePath(file, root) : '', fn || '', anchor].join('|');
+    const occurrence = (seen.get(key) || 0) + 1;
+    seen.set(key, occurrence);
+    const hash = crypto.createHash('sha256').update(`${key}|${occurrence}`).digest('hex').slice(0, 16);
+    finding.id = `${finding.ruleId}:${hash}`;
+  });
+  return findings;
+}
+
+/**
+ * A file path relative to a directory, with forward slashes
+ * @param {string} file - Absolute path, or relative to the working directory
+ * @param {string} root - Directory (default: the working directory)
+ * @returns {string}
+ */
+function toPortablePath(file, root) {
+  return path.relative(root || process.cwd(), path.resolve(file)).split(path.sep).join('/');
+}
+
+/**
+ * Group Findings by severity, for reports that list them per severity
+ * @param {Array} findings - Findings
+ * @returns {Object} - `{ critical, high, medium, low, info }` lists
+ */
+function groupBySeverity(findings) {
+  const groups = {};
+  SEVERITIES.forEach(severity => {
+    groups[severity] = findings.filter(finding => finding.severity === severity);
+  });
+  return groups;
+}
+
+/**
+ * Count Findings per severity
+ * @param {Array} findings - Findings
+ * @returns {Object} - `{ critical, high, medium, low, info }` counts
+ */
+function countBySeverity(findings) {
+  const counts = {};
+  SEVERITIES.forEach(severity => {
+    counts[severity] = findings.filter(finding => finding.severity === severity).length;
+  });
+  return counts;
+}
+
+/**
+ * Markdown summary line of how many Findings are in changed code, for reports of a diff audit
+ * @param {Array} findings - Findings classified by diff-scope.js
+ * @returns {string} - e.g. "- In new or changed code: 2 (5 pre-existing)\n", or '' when not auditing a diff
+ */
+function formatChangeSummary(findings) {
+  const classified = findings.filter(finding => finding.change);
+  if (classified.length === 0) return '';
+  const added = classified.filter(finding => finding.change === 'new').length;
+  return `- In new or changed code: ${added} (${classified.length - added} pre-existing)\n`;
+}
+
+/**
+ * One-line Markdown summary of a Finding's rule, confidence and category, e.g.
+ * "*Rule `rust-unwrap` · confidence high · CWE-248 · id `rust-unwrap:1a2b...`*"
+ * @param {Object} finding - Finding
+ * @returns {string}
+ */
+function formatFindingMetadata(finding) {
+  const parts = [`Rule \`${finding.ruleId}\``, `confidence ${finding.confidence}`];
+  if (finding.category.cwe) parts.push(finding.category.cwe);
+  if (finding.category.swc) parts.push(finding.category.swc);
+  parts.push(`id \`${finding.id}\``);
+  if (finding.change) parts.push(finding.change === 'new' ? '**new or changed code**' : 'pre-existing code');
+  return `*${parts.join(' · ')}*`;
+}
+
+module.exports = {
+  SEVERITIES,
+  CONFIDENCES,
+  createFinding,
+  toFindings,
+  assignIds,
+  ruleDescription,
+  toPortablePath,
+  groupBySeverity,
+  countBySeverity,
+  formatChangeSummary,
+  formatFindingMetadata
+};
@@ -0,0 +1,135 @@
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+
+/**
+ * Content-addressed on-disk cache of model responses. An entry is keyed by the prompt template and its version,
+ * the hash of the audited source, the provider, model and sampling options, and the exact request, so an
+ * unchanged file audited with the same prompts and settings reuses the earlier response.
+ */
+
+// Bump when the entry format changes; older entries are then ignored
+const CACHE_FORMAT = 1;
+
+let settings = null;
+
+/**
+ * Configure the cache
+ * @param {Object} options - `{ cache, refresh, cacheDir }`: `cache: false` (--no-cache) disables it, `refresh`
+ *   ignores existing entries but stores the new responses
+ * @returns {Object} - The resulting settings
+ */
+function configureCache(options = {}) {
+  settings = {
+    enabled: options.cache !== false && process.env.WINSTON_CACHE !== 'off',
+    refresh: Boolean(options.refresh),
+    dir: options.cacheDir || process.env.WINSTON_CACHE_DIR || path.join(process.cwd(), '.winston-cache')
+  };
+  return settings;
+}
+
+function getCacheSettings() {
+  return settings || configureCache();
+}
+
+/**
+ * Hash of the audited source: a file's content or, for crates, the contents of all its files
+ * @param {string|string[]} source - Source code
+ * @returns {string}
+ */
+function hashSource(source) {
+  const hash = crypto.createHash('sha256');
+  [].concat(source).forEach(text => hash.update(text).update('\0'));
+  return hash.digest('hex');
+}
+
+/**
+ * Cache key of a request
+ * @param {Object} params - `{ template, version, source, provider, model, maxTokens, temperature, request }` where
+ *   request is `{ system, messages }`
+ * @returns {string}
+ */
+function cacheKey({ template, version, source, provider, model, maxTokens, temperature, request }) {
+  return crypto.createHash('sha256').update(JSON.stringify({
+    format: CACHE_FORMAT,
+    template,
+    version,
+    sourceHash: hashSource(source),
+    provider,
+    model,
+    maxTokens,
+    temperature,
+    request
+  })).digest('hex');
+}
+
+function entryPath(key) {
+  return path.join(getCacheSettings().dir, key.slice(0, 2), `${key}.json`);
+}
+
+/**
+ * Cached entry for a key, unless the cache is disabled or refreshed
+ * @param {string} key - Result of cacheKey
+ * @returns {Object|null} - `{ key, template, version, model, createdAt, response }`
+ */
+function readCache(key) {
+  const { enabled, refresh } = getCacheSettings();
+  if (!enabled || refresh) return null;
+  try {
+    return JSON.parse(fs.readFileSync(entryPath(key), 'utf8'));
+  } catch (error) {
+    return null;
+  }
+}
+
+/**
+ * Store a response
+ * @param {string} key - Result of cacheKey
+ * @param {Object} entry - `{ template, version, model, response }`
+ */
+function writeCache(key, entry) {
+  if (!getCacheSettings().enabled) return;
+  const file = entryPath(key);
+  fs.mkdirSync(path.dirname(file), { recursive: true });
+  fs.writeFileSync(file, JSON.stringify({ key, ...entry, createdAt: new Date().toISOString() }, null, 2));
+}
+
+/**
+ * Note for reports built from cached responses
+ * @param {Array} results - Results of llm-provider's completeResult that the report was built from
+ * @returns {string|null} - Markdown note, or null when no response came from the cache
+ */
+function describeCacheUse(results) {
+  const cached = results.filter(result => result && result.cached);
+  if (cached.length === 0) return null;
+  const oldest = cached.map(result => result.cachedAt).sort()[0];
+  const { template, version, model } = cached[0];
+  const which = cached.length === results.length ? 'Cached result' : `${cached.length} of ${results.length} responses cached`;
+  return `*${which} from ${oldest} (prompt ${template} v${version}, model ${model}); rerun with --refresh to regenerate.*`;
+}
+
+/**
+ * Add the cache note of describeCacheUse under the report's title (or at its top)
+ * @param {string} markdown - Report
+ * @param {Array} results - Results the report was built from
+ * @returns {string}
+ */
+function withCacheNote(markdown, results) {
+  const note = describeCacheUse(results);
+  if (!note) return markdown;
+  const match = markdown.match(/^\s*#[^\n]*\n/);
+  return match
+    ? `${match[0]}\n${note}\n${markdown.slice(match[0].length)}`
+    : `${note}\n\n${markdown}`;
+}
+
+module.exports = {
+  configureCache,
+  getCacheSettings,
+  hashSource,
+  cacheKey,
+  readCache,
+  writeCache,
+  describeCacheUse,
+  withCacheNote
+};
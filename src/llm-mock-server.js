@@ -0,0 +1,60 @@
+const express = require('express');
+const { requestKey, replayResponse } = require('./llm-provider');
+
+/**
+ * Local mock LLM server replaying recorded responses over HTTP, with an OpenAI-compatible
+ * `/v1/chat/completions` and an Anthropic-compatible `/v1/messages` endpoint. Point the `openai` or `anthropic`
+ * provider at it with `--base-url` to exercise the whole HTTP path offline.
+ */
+
+/**
+ * Start the server
+ * @param {Object} options - `{ recordings, port }` where recordings is a directory written by --record-responses
+ * @returns {Promise<Object>} - The listening http.Server
+ */
+function startMockServer({ recordings, port = 8787 }) {
+  const app = express();
+  app.use(express.json({ limit: '50mb' }));
+
+  app.post('/v1/chat/completions', (req, res) => {
+    const messages = req.body.messages || [];
+    const request = {
+      system: messages.filter(message => message.role === 'system').map(message => message.content).join('\n'),
+      messages: messages.filter(message => message.role !== 'system')
+    };
+    replay(res, request, text => ({
+      id: `chatcmpl-${requestKey(request)}`,
+      object: 'chat.completion',
+      model: req.body.model || 'mock',
+      choices: [{ index: 0, message: { role: 'assistant', content: text }, finish_reason: 'stop' }]
+    }), message => ({ error: { message, type: 'invalid_request_error' } }));
+  });
+
+  app.post('/v1/messages', (req, res) => {
+    const request = { system: req.body.system || '', messages: req.body.messages || [] };
+    replay(res, request, text => ({
+      id: `msg_${requestKey(request)}`,
+      type: 'message',
+      role: 'assistant',
+      model: req.body.model || 'mock',
+      content: [{ type: 'text', text }],
+      stop_reason: 'end_turn'
+    }), message => ({ type: 'error', error: { type: 'invalid_request_error', message } }));
+  });
+
+  function replay(res, request, toBody, toError) {
+    try {
+      res.json(toBody(replayResponse(recordings, request)));
+    } catch (error) {
+      res.status(404).json(toError(error.message));
+    }
+  }
+
+  return new Promise(resolve => {
+    const server = app.listen(port, () => resolve(server));
+  });
+}
+
+module.exports = {
+  startMockServer
+};
@@ -0,0 +1,245 @@
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+const axios = require('axios');
+const Anthropic = require('@anthropic-ai/sdk');
+const { cacheKey, readCache, writeCache } = require('./llm-cache');
+
+/**
+ * The model provider every prompt goes through. The provider, model, token limit, temperature and base URL are
+ * configured once (from the command line or the environment); `anthropic` uses the Anthropic API, `openai`,
+ * `ollama` and `llamacpp` any OpenAI-compatible chat completions endpoint, and `mock` replays recorded responses
+ * so that the pipeline runs offline and deterministically.
+ */
+
+const PROVIDERS = {
+  anthropic: { model: 'claude-3-sonnet-20240229', baseUrl: null, apiKeyEnv: 'ANTHROPIC_API_KEY' },
+  openai: { model: 'gpt-4o', baseUrl: 'https://api.openai.com/v1', apiKeyEnv: 'OPENAI_API_KEY' },
+  ollama: { model: 'llama3.1', baseUrl: 'http://localhost:11434/v1', apiKeyEnv: null },
+  llamacpp: { model: 'local', baseUrl: 'http://localhost:8080/v1', apiKeyEnv: null },
+  mock: { model: 'mock', baseUrl: null, apiKeyEnv: null }
+};
+
+const DEFAULT_MAX_TOKENS = 4000;
+
+let settings = null;
+let anthropicClient = null;
+
+/**
+ * Configure the provider. Options take precedence over the `WINSTON_LLM_*` environment variables.
+ * @param {Object} options - `{ provider, model, maxTokens, temperature, baseUrl, mockResponses, recordResponses }`
+ * @returns {Object} - The resulting settings
+ */
+function configureProvider(options = {}) {
+  const env = process.env;
+  const mockResponses = options.mockResponses || env.WINSTON_LLM_RECORDINGS || null;
+  const provider = (options.provider || env.WINSTON_LLM_PROVIDER || (mockResponses ? 'mock' : 'anthropic')).toLowerCase();
+  if (!PROVIDERS[provider]) {
+    throw new Error(`Unknown LLM provider: ${provider} (expected one of ${Object.keys(PROVIDERS).join(', ')})`);
+  }
+
+  const defaults = PROVIDERS[provider];
+  const maxTokens = Number(options.maxTokens || env.WINSTON_LLM_MAX_TOKENS || DEFAULT_MAX_TOKENS);
+  if (!Number.isInteger(maxTokens) || maxTokens <= 0) {
+    throw new Error(`Invalid max tokens: ${options.maxTokens || env.WINSTON_LLM_MAX_TOKENS}`);
+  }
+  const rawTemperature = options.temperature !== undefined ? options.temperature : env.WINSTON_LLM_TEMPERATURE;
+  const temperature = rawTemperature === undefined || rawTemperature === '' ? null : Number(rawTemperature);
+  if (temperature !== null && !(temperature >= 0 && temperature <= 2)) {
+    throw new Error(`Invalid temperature: ${rawTemperature} (expected a number from 0 to 2)`);
+  }
+  if (provider === 'mock' && !mockResponses) {
+    throw new Error('The mock provider needs a directory of recorded responses (--mock-responses or WINSTON_LLM_RECORDINGS)');
+  }
+
+  settings = {
+    provider,
+    model: options.model || env.WINSTON_LLM_MODEL || defaults.model,
+    maxTokens,
+    temperature,
+    baseUrl: (options.baseUrl || env.WINSTON_LLM_BASE_URL || defaults.baseUrl || '').replace(/\/+$/, '') || null,
+    apiKey: env.WINSTON_LLM_API_KEY || (defaults.apiKeyEnv && env[defaults.apiKeyEnv]) || null,
+    mockResponses,
+    recordResponses: options.recordResponses || env.WINSTON_LLM_RECORD || null
+  };
+  anthropicClient = null;
+  return settings;
+}
+
+/**
+ * Current provider settings, configured from the environment on first use
+ * @returns {Object}
+ */
+function getLLMSettings() {
+  return settings || configureProvider();
+}
+
+/**
+ * The configured provider and model for progress messages, e.g. "claude-3-sonnet-20240229 (anthropic)"
+ * @returns {string}
+ */
+function modelName() {
+  const { provider, model } = getLLMSettings();
+  return `${model} (${provider})`;
+}
+
+/**
+ * Whether prompts can be sent: hosted providers need an API key, local servers (including a hosted provider's
+ * API at another base URL) and the mock provider do not
+ * @returns {boolean}
+ */
+function isLLMAvailable() {
+  const { provider, apiKey, baseUrl } = getLLMSettings();
+  const { apiKeyEnv, baseUrl: defaultBaseUrl } = PROVIDERS[provider];
+  return !apiKeyEnv || Boolean(apiKey) || Boolean(baseUrl && baseUrl !== defaultBaseUrl);
+}
+
+/**
+ * Why isLLMAvailable is false, for warnings, e.g. "ANTHROPIC_API_KEY not found in environment variables"
+ * @returns {string}
+ */
+function unavailableReason() {
+  const { provider } = getLLMSettings();
+  return `${PROVIDERS[provider].apiKeyEnv} not found in environment variables`;
+}
+
+/**
+ * Send a prompt (or a conversation) to the configured provider
+ * @param {string|Array} messages - Prompt, or `{ role, content }` turns starting and ending with the user
+ * @param {string} systemPrompt - System prompt
+ * @param {Object} cache - `{ template, version, source }` to cache the response (see llm-cache.js): the prompt
+ *   template's name and version and the audited source; omit to always call the provider
+ * @returns {string} - Text of the response
+ */
+async function complete(messages, systemPrompt, cache) {
+  return (await completeResult(messages, systemPrompt, cache)).text;
+}
+
+/**
+ * Like complete, but also says whether the response came from the cache
+ * @returns {Object} - `{ text, cached, cachedAt, template, version, model }`
+ */
+async function completeResult(messages, systemPrompt, cache) {
+  const config = getLLMSettings();
+  if (!isLLMAvailable()) throw new Error(unavailableReason());
+
+  const request = {
+    system: systemPrompt || '',
+    messages: typeof messages === 'string' ? [{ role: 'user', content: messages }] : messages
+  };
+  const meta = { template: cache ? cache.template : null, version: cache ? cache.version : null, model: config.model };
+
+  // Recorded responses are replayed as they are, without the cache
+  const key = cache && config.provider !== 'mock' ? cacheKey({
+    ...cache,
+    provider: config.provider,
+    model: config.model,
+    maxTokens: config.maxTokens,
+    temperature: config.temperature,
+    request
+  }) : null;
+  const entry = key && readCache(key);
+  if (entry) return { text: entry.response, cached: true, cachedAt: entry.createdAt, ...meta };
+
+  let text;
+  if (config.provider === 'mock') text = replayResponse(config.mockResponses, request);
+  else if (config.provider === 'anthropic') text = await completeAnthropic(config, request);
+  else text = await completeOpenAI(config, request);
+
+  if (config.recordResponses) recordResponse(config.recordResponses, request, text, config);
+  if (key) writeCache(key, { ...meta, response: text });
+  return { text, cached: false, cachedAt: null, ...meta };
+}
+
+async function completeAnthropic(config, request) {
+  if (!anthropicClient) {
+    // The SDK insists on a key even for servers that do not check it
+    anthropicClient = new Anthropic({ apiKey: config.apiKey || 'none', ...(config.baseUrl ? { baseURL: config.baseUrl } : {}) });
+  }
+  const response = await anthropicClient.messages.create({
+    model: config.model,
+    max_tokens: config.maxTokens,
+    ...(config.temperature !== null ? { temperature: config.temperature } : {}),
+    messages: request.messages,
+    system: request.system
+  });
+
+  if (!response || !Array.isArray(response.content) || response.content.length === 0) {
+    throw new Error('Invalid response format from Anthropic API');
+  }
+  return response.content[0].text;
+}
+
+async function completeOpenAI(config, request) {
+  const response = await axios.post(`${config.baseUrl}/chat/completions`, {
+    model: config.model,
+    max_tokens: config.maxTokens,
+    ...(config.temperature !== null ? { temperature: config.temperature } : {}),
+    messages: [
+      ...(request.system ? [{ role: 'system', content: request.system }] : []),
+      ...request.messages
+    ]
+  }, {
+    headers: config.apiKey ? { Authorization: `Bearer ${config.apiKey}` } : {}
+  });
+
+  const choice = response.data && Array.isArray(response.data.choices) && response.data.choices[0];
+  if (!choice || !choice.message || typeof choice.message.content !== 'string') {
+    throw new Error(`Invalid response format from ${config.baseUrl}`);
+  }
+  return choice.message.content;
+}
+
+/**
+ * Key of a request in a recordings directory: a hash of the system prompt and the conversation, so that
+ * recordings replay whatever the provider, model or sampling settings
+ * @param {Object} request - `{ system, messages }`
+ * @returns {string}
+ */
+function requestKey(request) {
+  return crypto.createHash('sha256')
+    .update(JSON.stringify({ system: request.system || '', messages: request.messages }))
+    .digest('hex')
+    .slice(0, 16);
+}
+
+/**
+ * Recorded response to a request
+ * @param {string} dir - Recordings directory
+ * @param {Object} request - `{ system, messages }`
+ * @returns {string} - Text of the response
+ */
+function replayResponse(dir, request) {
+  const key = requestKey(request);
+  const file = path.join(dir, `${key}.json`);
+  if (!fs.existsSync(file)) {
+    throw new Error(`No recorded response for request ${key} in ${dir} (record one with --record-responses)`);
+  }
+  return JSON.parse(fs.readFileSync(file, 'utf8')).response;
+}
+
+function recordResponse(dir, request, response, config) {
+  fs.mkdirSync(dir, { recursive: true });
+  const key = requestKey(request);
+  fs.writeFileSync(path.join(dir, `${key}.json`), JSON.stringify({
+    key,
+    provider: config.provider,
+    model: config.model,
+    system: request.system,
+    messages: request.messages,
+    response
+  }, null, 2));
+}
+
+module.exports = {
+  PROVIDERS,
+  configureProvider,
+  getLLMSettings,
+  modelName,
+  isLLMAvailable,
+  unavailableReason,
+  complete,
+  completeResult,
+  requestKey,
+  replayResponse
+};
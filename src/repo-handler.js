@@ -4,6 +4,8 @@ const { simpleGit } = require('simple-git');
 const ora = require('ora');
 const chalk = require('chalk');
 const axios = require('axios');
+const { discoverCargoWorkspace, selectRustTargets } = require('./cargo-workspace');
+const { resolveDiff } = require('./diff-scope');
 
 /**
  * Checks if a path is a git repository URL
@@ -100,44 +102,50 @@ async function findSolidityFiles(dirPath) {
 }
 
 /**
- * Find all Rust files in a directory (recursively)
+ * Find the Rust files to audit in a directory, grouped by Cargo crate and target.
+ * `Cargo.toml` and workspace manifests decide which files belong to which crate; files
+ * outside any crate are returned as a group without a crate.
  * @param {string} dirPath - The directory to search
- * @returns {Promise<string[]>} - Array of file paths
+ * @param {Object} options - `crates` and `targets` selections (comma separated)
+ * @returns {Promise<Object[]>} - Groups `{ crate, manifestPath, target, files }`
  */
-async function findRustFiles(dirPath) {
-  const spinner = ora(`Finding Rust files in: ${dirPath}`).start();
+async function findRustCrates(dirPath, options = {}) {
+  const spinner = ora(`Finding Rust crates in: ${dirPath}`).start();
   
   try {
-    const rustFiles = [];
+    const discovery = discoverCargoWorkspace(dirPath);
+    const groups = selectRustTargets(discovery, options);
     
-    // Walk through the directory recursively
-    async function walk(dir) {
-      const files = await fs.readdir(dir);
-      
-      for (const file of files) {
-        const filePath = path.join(dir, file);
-        const stat = await fs.stat(filePath);
-        
-        if (stat.isDirectory() && !file.startsWith('.') && file !== 'node_modules' && file !== 'target') {
-          // Recursively walk subdirectories, excluding Rust build directories
-          await walk(filePath);
-        } else if (file.endsWith('.rs')) {
-          // Add Rust files to the list
-          rustFiles.push(filePath);
-        }
+    const fileCount = new Set(groups.flatMap(group => group.files)).size;
+    spinner.succeed(`Found ${discovery.crates.length} Rust crates, selected ${fileCount} Rust files in ${groups.length} targets`);
+    
+    for (const crate of discovery.crates) {
+      if (crate.vendored || crate.excluded) {
+        console.log(chalk.gray(`  Skipping ${crate.vendored ? 'vendored' : 'workspace-excluded'} crate ${crate.name} (select it with --crates ${crate.name})`));
+      }
+      if (crate.unreferencedFiles.length > 0) {
+        console.log(chalk.gray(`  ${crate.name}: ${crate.unreferencedFiles.length} Rust files are not part of any target's module tree and were skipped`));
       }
     }
     
-    await walk(dirPath);
-    
-    spinner.succeed(`Found ${rustFiles.length} Rust files`);
-    return rustFiles;
+    return groups;
   } catch (error) {
-    spinner.fail(`Failed to find Rust files: ${error.message}`);
+    spinner.fail(`Failed to find Rust crates: ${error.message}`);
     throw error;
   }
 }
 
+/**
+ * Find all Rust files to audit in a directory
+ * @param {string} dirPath - The directory to search
+ * @param {Object} options - `crates` and `targets` selections (comma separated)
+ * @returns {Promise<string[]>} - Array of file paths
+ */
+async function findRustFiles(dirPath, options = {}) {
+  const groups = await findRustCrates(dirPath, options);
+  return [...new Set(groups.flatMap(group => group.files))];
+}
+
 /**
  * Download a smart contract file from a URL
  * @param {string} url - The URL to download the contract from
@@ -179,13 +187,67 @@ function isDownloadUrl(input) {
   );
 }
 
+/**
+ * Group for a single Rust file given directly on the command line
+ */
+function singleFileGroup(filePath) {
+  return { crate: null, manifestPath: null, target: null, files: [filePath] };
+}
+
 /**
  * Process an input which can be a file, directory, or git repository
  * @param {string} input - File path, directory path, or git URL
- * @param {Object} options - Additional options
- * @returns {Promise<Object>} - Object with file paths to process
+ * @param {Object} options - Additional options; `diff`: a git base ref, to keep only the files changed since it
+ * @returns {Promise<Object>} - Object with file paths to process; `rustGroups` groups the Rust files by crate and
+ *   target, `root` is the directory finding ids are relative to (see findAuditRoot) and `diff` is the change (see
+ *   diff-scope.js) when auditing a diff
  */
 async function processInput(input, options) {
+  const found = await findInputFiles(input, options);
+  const root = found.inputPath ? await findAuditRoot(found.inputPath) : null;
+  if (!options.diff || !found.inputPath) {
+    return { solidityFiles: found.solidityFiles, rustFiles: found.rustFiles, rustGroups: found.rustGroups, root, diff: null };
+  }
+  
+  // Incremental audit: only the changed files are analyzed. Crate groups keep all their files, which the
+  // crate-wide call graph and context still need.
+  const spinner = ora(`Finding files changed since ${options.diff}`).start();
+  try {
+    const diff = await resolveDiff(found.inputPath, options.diff);
+    const changed = (file) => diff.files.has(path.resolve(file));
+    const solidityFiles = found.solidityFiles.filter(changed);
+    const rustFiles = found.rustFiles.filter(changed);
+    const rustGroups = found.rustGroups.filter(group => group.files.some(changed));
+    spinner.succeed(`${solidityFiles.length + rustFiles.length} of ${found.solidityFiles.length + found.rustFiles.length} files changed since ${options.diff} (merge base ${diff.mergeBase.slice(0, 12)})`);
+    return { solidityFiles, rustFiles, rustGroups, root, diff };
+  } catch (error) {
+    spinner.fail(`Could not diff against ${options.diff}: ${error.message}`);
+    throw error;
+  }
+}
+
+/**
+ * Directory the file paths in finding ids and baseline entries are relative to, so they do not depend on where
+ * Winston runs: the root of the git repository holding the input, or the input directory (a file's directory) when
+ * it is not in one
+ * @param {string} inputPath - Local file or directory being audited
+ * @returns {Promise<string>} - Absolute path
+ */
+async function findAuditRoot(inputPath) {
+  const dir = path.resolve((await fs.stat(inputPath)).isDirectory() ? inputPath : path.dirname(inputPath));
+  try {
+    const top = (await simpleGit(dir).raw(['rev-parse', '--show-toplevel'])).trim();
+    // git reports the real path; keep the input's spelling of it (e.g. through a symlinked /tmp)
+    return path.resolve(dir, path.relative(fs.realpathSync(dir), top));
+  } catch (error) {
+    return dir;
+  }
+}
+
+/**
+ * Find the files of an input; `inputPath` is the local file or directory they were found in
+ */
+async function findInputFiles(input, options) {
   try {
     let inputPath = input;
     let isSolidity = false;
@@ -209,14 +271,18 @@ async function processInput(input, options) {
           isSolidity = true;
           return { 
             solidityFiles: [inputPath],
-            rustFiles: []
+            rustFiles: [],
+            rustGroups: [],
+            inputPath
           };
         } else if (inputPath.endsWith('.rs')) {
           console.log(chalk.blue(`Downloaded Rust file: ${inputPath}`));
           isRust = true;
           return {
             solidityFiles: [],
-            rustFiles: [inputPath]
+            rustFiles: [inputPath],
+            rustGroups: [singleFileGroup(inputPath)],
+            inputPath
           };
         }
       }
@@ -227,14 +293,18 @@ async function processInput(input, options) {
         isSolidity = true;
         return { 
           solidityFiles: [input],
-          rustFiles: []
+          rustFiles: [],
+          rustGroups: [],
+          inputPath: input
         };
       } else if (input.endsWith('.rs')) {
         console.log(chalk.blue(`Processing single Rust file: ${input}`));
         isRust = true;
         return {
           solidityFiles: [],
-          rustFiles: [input]
+          rustFiles: [input],
+          rustGroups: [singleFileGroup(input)],
+          inputPath: input
         };
       }
     }
@@ -244,24 +314,29 @@ async function processInput(input, options) {
     if (stat.isDirectory()) {
       console.log(chalk.blue(`Input is a directory, searching for Solidity and Rust files`));
       const solidityFiles = await findSolidityFiles(inputPath);
-      const rustFiles = await findRustFiles(inputPath);
+      const rustGroups = await findRustCrates(inputPath, options);
+      const rustFiles = [...new Set(rustGroups.flatMap(group => group.files))];
       
       return {
         solidityFiles,
-        rustFiles
+        rustFiles,
+        rustGroups,
+        inputPath
       };
     }
     
     console.warn(chalk.yellow(`Warning: Input is not a Solidity or Rust file, directory, or git repository`));
     return {
       solidityFiles: [],
-      rustFiles: []
+      rustFiles: [],
+      rustGroups: []
     };
   } catch (error) {
     console.error(chalk.red(`Error processing input: ${error.message}`));
     return {
       solidityFiles: [],
-      rustFiles: []
+      rustFiles: [],
+      rustGroups: []
     };
   }
 }
@@ -273,5 +348,6 @@ module.exports = {
   downloadContract,
   findSolidityFiles,
   findRustFiles,
+  findRustCrates,
   processInput
 }; 
\ No newline at end of file
@@ -0,0 +1,238 @@
+const fs = require('fs');
+const path = require('path');
+const { SEVERITIES, countBySeverity, ruleDescription, toPortablePath } = require('./finding');
+
+const TOOL_NAME = 'Winston';
+const TOOL_VERSION = '1.0.0';
+const JSON_SCHEMA_VERSION = '1.4';
+
+const SUPPORTED_FORMATS = ['markdown', 'json', 'sarif'];
+
+const SARIF_LEVELS = {
+  critical: 'error',
+  high: 'error',
+  medium: 'warning',
+  low: 'note',
+  info: 'note'
+};
+
+// GitHub code scanning reads `security-severity` (0.0-10.0) to rank alerts
+const SECURITY_SEVERITY = {
+  critical: '9.5',
+  high: '8.0',
+  medium: '5.5',
+  low: '3.0',
+  info: '1.0'
+};
+
+/**
+ * Parse the --format option. Several formats may be given separated by commas.
+ * @param {string|string[]} format - e.g. "json" or "markdown,sarif"
+ * @returns {string[]} - Normalized list of formats
+ */
+function parseFormats(format) {
+  if (!format) return ['markdown'];
+  const formats = (Array.isArray(format) ? format : String(format).split(','))
+    .map(value => value.trim().toLowerCase())
+    .filter(Boolean)
+    .map(value => (value === 'md' ? 'markdown' : value));
+  const unknown = formats.filter(value => !SUPPORTED_FORMATS.includes(value));
+  if (unknown.length > 0) {
+    throw new Error(`Unsupported output format: ${unknown.join(', ')} (expected one of ${SUPPORTED_FORMATS.join(', ')})`);
+  }
+  return formats.length > 0 ? [...new Set(formats)] : ['markdown'];
+}
+
+/**
+ * Order Findings from most to least severe, keeping the analyzer's order within a severity
+ */
+function bySeverity(findings) {
+  return [...findings].sort((a, b) => SEVERITIES.indexOf(a.severity) - SEVERITIES.indexOf(b.severity));
+}
+
+/**
+ * JSON form of a Finding
+ */
+function jsonFinding(finding, analyzer, filePath) {
+  return {
+    id: finding.id,
+    ruleId: finding.ruleId,
+    severity: finding.severity,
+    confidence: finding.confidence,
+    category: finding.category,
+    title: finding.title,
+    description: finding.description,
+    recommendation: finding.recommendation,
+    location: { ...finding.location, file: finding.location.file || filePath },
+    evidence: finding.evidence,
+    analyzer: finding.analyzer || analyzer,
+    grounding: finding.grounding || null,
+    change: finding.change || null
+  };
+}
+
+/**
+ * Build the JSON report. The schema is versioned by `schemaVersion`; fields are only ever added.
+ * @param {Object} params - `{ analyzer, filePath, crate, findings, suppressions }` where findings are Findings (see
+ *   finding.js), `crate` optionally names the Cargo crate and target of the file and `suppressions` is the result
+ *   of applySuppressions (see baseline.js), whose suppressed findings and fixed baseline entries are listed apart
+ * @returns {Object} - JSON-serializable report
+ */
+function toJsonReport({ analyzer, filePath, crate, findings, suppressions }) {
+  const flat = bySeverity(findings);
+
+  return {
+    schemaVersion: JSON_SCHEMA_VERSION,
+    tool: { name: TOOL_NAME, version: TOOL_VERSION },
+    analyzer,
+    file: filePath,
+    crate: crate || null,
+    generatedAt: new Date().toISOString(),
+    summary: countBySeverity(flat),
+    findings: flat.map(finding => jsonFinding(finding, analyzer, filePath)),
+    baseline: suppressions ? suppressions.baseline : null,
+    suppressed: suppressions
+      ? bySeverity(suppressions.suppressed).map(finding => ({ ...jsonFinding(finding, analyzer, filePath), suppression: finding.suppression }))
+      : [],
+    fixed: suppressions ? suppressions.fixed : []
+  };
+}
+
+/**
+ * Build a SARIF 2.1.0 log with one run. Suppressed findings are included with their `suppressions`, and with a
+ * baseline every result has a `baselineState`.
+ * @param {Object} params - `{ analyzer, filePath, crate, findings, suppressions }` as for toJsonReport
+ * @returns {Object} - SARIF log
+ */
+function toSarifReport({ analyzer, filePath, crate, findings, suppressions }) {
+  const flat = bySeverity(findings.concat(suppressions ? suppressions.suppressed : []));
+  const hasBaseline = Boolean(suppressions && suppressions.baseline);
+  const rules = [];
+  const ruleIndex = new Map();
+
+  for (const finding of flat) {
+    if (ruleIndex.has(finding.ruleId)) continue;
+    ruleIndex.set(finding.ruleId, rules.length);
+    // GitHub code scanning shows CWE tags in the `external/cwe/cwe-<n>` form
+    const tags = ['security', finding.analyzer || analyzer];
+    if (finding.category.cwe) tags.push(`external/cwe/${finding.category.cwe.toLowerCase()}`);
+    if (finding.category.swc) tags.push(finding.category.swc);
+    rules.push({
+      id: finding.ruleId,
+      name: finding.title,
+      shortDescription: { text: finding.title },
+      fullDescription: { text: ruleDescription(finding.ruleId) || finding.title },
+      help: { text: finding.recommendation },
+      defaultConfiguration: { level: SARIF_LEVELS[finding.severity] },
+      properties: {
+        tags,
+        precision: finding.confidence,
+        'security-severity': SECURITY_SEVERITY[finding.severity]
+      }
+    });
+  }
+
+  const results = flat.map(finding => {
+    const location = { ...finding.location, file: finding.location.file || filePath };
+    const physicalLocation = {
+      artifactLocation: { uri: toPortablePath(location.file) }
+    };
+    if (location.startLine) {
+      // SARIF rejects null region properties, so unknown ones are left out
+      physicalLocation.region = { startLine: location.startLine };
+      ['startColumn', 'endLine', 'endColumn'].forEach(key => {
+        if (location[key]) physicalLocation.region[key] = location[key];
+      });
+    }
+
+    const result = {
+      ruleId: finding.ruleId,
+      ruleIndex: ruleIndex.get(finding.ruleId),
+      level: SARIF_LEVELS[finding.severity],
+      message: { text: `${finding.title}: ${finding.description}` },
+      locations: [{ physicalLocation }],
+      partialFingerprints: { 'winstonFindingId/v1': finding.id },
+      properties: { severity: finding.severity, confidence: finding.confidence, analyzer: finding.analyzer || analyzer }
+    };
+    if (finding.grounding) result.properties.grounding = finding.grounding.score;
+    if (finding.change) result.properties.change = finding.change;
+    if (finding.suppression) {
+      result.suppressions = [{
+        kind: finding.suppression.kind === 'inline' ? 'inSource' : 'external',
+        ...(finding.suppression.reason ? { justification: finding.suppression.reason } : {})
+      }];
+    }
+    if (hasBaseline) {
+      result.baselineState = finding.suppression && finding.suppression.kind === 'baseline' ? 'unchanged' : 'new';
+    }
+    if (location.function) {
+      result.locations[0].logicalLocations = [{ fullyQualifiedName: location.function, kind: 'function' }];
+    }
+    return result;
+  });
+
+  return {
+    $schema: 'https://json.schemastore.org/sarif-2.1.0.json',
+    version: '2.1.0',
+    runs: [{
+      tool: {
+        driver: {
+          name: TOOL_NAME,
+          version: TOOL_VERSION,
+          informationUri: 'https://github.com/GerhardBotha97/winston',
+          rules
+        }
+      },
+      automationDetails: { id: crate ? `${analyzer}/${crate.name}/` : `${analyzer}/` },
+      ...(crate ? { properties: { crate } } : {}),
+      results
+    }]
+  };
+}
+
+/**
+ * Name the reports of a source file after its path below a directory, so that same-named files in different
+ * directories (`mod.rs`, `lib.rs`) get their own reports: `src/foo/mod.rs` becomes `src_foo_mod.rs`
+ * @param {string} filePath - Audited file
+ * @param {string} baseDir - Crate or audit root the name is relative to
+ * @returns {string} - Report name; the file name when the file is not below `baseDir`
+ */
+function reportName(filePath, baseDir) {
+  const relative = baseDir ? path.relative(baseDir, path.resolve(filePath)) : '';
+  if (!relative || relative.startsWith('..') || path.isAbsolute(relative)) return path.basename(filePath);
+  return relative.split(path.sep).join('_');
+}
+
+/**
+ * Write a report in each requested format
+ * @param {Object} params - `{ outputDir, baseName, formats, analyzer, filePath, crate, findings, suppressions,
+ *   renderMarkdown }`
+ * @returns {string[]} - Paths of the written files, in the order of `formats`
+ */
+function writeReports({ outputDir, baseName, formats, analyzer, filePath, crate, findings, suppressions, renderMarkdown }) {
+  const outputPaths = [];
+  for (const format of parseFormats(formats)) {
+    let outputPath;
+    if (format === 'markdown') {
+      outputPath = path.join(outputDir, `${baseName}.md`);
+      fs.writeFileSync(outputPath, renderMarkdown());
+    } else if (format === 'json') {
+      outputPath = path.join(outputDir, `${baseName}.json`);
+      fs.writeFileSync(outputPath, JSON.stringify(toJsonReport({ analyzer, filePath, crate, findings, suppressions }), null, 2));
+    } else {
+      outputPath = path.join(outputDir, `${baseName}.sarif`);
+      fs.writeFileSync(outputPath, JSON.stringify(toSarifReport({ analyzer, filePath, crate, findings, suppressions }), null, 2));
+    }
+    outputPaths.push(outputPath);
+  }
+  return outputPaths;
+}
+
+module.exports = {
+  SUPPORTED_FORMATS,
+  parseFormats,
+  toJsonReport,
+  toSarifReport,
+  reportName,
+  writeReports
+};
@@ -0,0 +1,772 @@
+const { parse, visit, typeBaseName } = require('./rust-parser');
+
+// Fields whose writes need authorization, and the fields that name the accounts allowed to make them
+const PRIVILEGED_FIELD = /owner|admin|authority|governance|operator|minter|supply|balance|allowance|deposit|stake|share|treasury|reserve|vault|fee|paused|config|whitelist|blacklist|role|code_hash/i;
+const ROLE_FIELD = /owner|admin|authority|governance|operator|minter|controller|manager/i;
+
+const INTEGER_TYPES = new Set([
+  'u8', 'u16', 'u32', 'u64', 'u128', 'usize',
+  'i8', 'i16', 'i32', 'i64', 'i128', 'isize'
+]);
+
+/**
+ * Collect the top-level definitions of a parsed Rust file (structs, enums, traits, impls and functions),
+ * descending into inline modules.
+ * @param {Object} ast - SourceFile node from rust-parser
+ * @returns {Object} - Definitions indexed by name
+ */
+function collectDefinitions(ast) {
+  const definitions = {
+    structs: new Map(),
+    enums: new Map(),
+    traits: new Map(),
+    impls: [],
+    functions: []
+  };
+
+  function walkItems(items, modulePath, enclosingModule) {
+    for (const item of items || []) {
+      switch (item.type) {
+        case 'Struct':
+        case 'Union': {
+          const fields = new Map();
+          item.fields.forEach(field => fields.set(field.name, field.fieldType));
+          definitions.structs.set(item.name, { node: item, fields, modulePath });
+          break;
+        }
+        case 'Enum':
+          definitions.enums.set(item.name, { node: item, modulePath });
+          break;
+        case 'Trait':
+          definitions.traits.set(item.name, { node: item, modulePath });
+          item.items.filter(i => i.type === 'Fn' && i.body).forEach(fn => {
+            definitions.functions.push(describeFunction(fn, { owner: item.name, trait: item.name, modulePath, container: item, module: enclosingModule }));
+          });
+          break;
+        case 'Impl':
+          definitions.impls.push({ node: item, modulePath });
+          item.items.filter(i => i.type === 'Fn').forEach(fn => {
+            definitions.functions.push(describeFunction(fn, { owner: item.selfName, trait: item.traitName, modulePath, container: item, module: enclosingModule }));
+          });
+          break;
+        case 'Fn':
+          definitions.functions.push(describeFunction(item, { owner: null, trait: null, modulePath, container: null, module: enclosingModule }));
+          break;
+        case 'Mod':
+          if (item.items) walkItems(item.items, [...modulePath, item.name], item);
+          break;
+        default:
+          break;
+      }
+    }
+  }
+
+  walkItems(ast.items, [], null);
+  return definitions;
+}
+
+/**
+ * Line spans of the functions of a Rust file, e.g. to check the lines a model cites or to find changed functions
+ * @param {string} code - Rust source
+ * @returns {Array} - `{ name, startLine, endLine }` with qualified names; empty when the file does not parse
+ */
+function rustFunctionSpans(code) {
+  try {
+    return collectDefinitions(parse(code)).functions.map(fn => ({
+      name: fn.qualifiedName,
+      startLine: (fn.node.declarationLoc || fn.node.loc).start.line,
+      endLine: fn.node.loc.end.line
+    }));
+  } catch (error) {
+    return [];
+  }
+}
+
+function describeFunction(node, { owner, trait, modulePath, container, module }) {
+  return {
+    node,
+    name: node.name,
+    owner,
+    trait,
+    modulePath,
+    container,
+    module,
+    qualifiedName: owner ? `${owner}::${node.name}` : [...modulePath, node.name].join('::')
+  };
+}
+
+/**
+ * Check whether a type node (or type text) is a primitive integer
+ * @param {Object|string} typeNode - Type node or its text
+ * @returns {boolean}
+ */
+function isIntegerType(typeNode) {
+  if (!typeNode) return false;
+  const text = typeof typeNode === 'string' ? typeNode : typeNode.text;
+  return INTEGER_TYPES.has(stripReferences(text)) || text === '{integer}';
+}
+
+function stripReferences(text) {
+  return (text || '').replace(/^(&\s*('\w+\s+)?(mut\s+)?)+/, '').trim();
+}
+
+/**
+ * Split a generic type such as `HashMap<String, u64>` into its base name and top-level arguments
+ * @param {string} text - Type text
+ * @returns {{base: string, args: string[]}}
+ */
+function splitGenericType(text) {
+  const stripped = stripReferences(text);
+  const open = stripped.indexOf('<');
+  if (open === -1 || !stripped.endsWith('>')) return { base: stripped, args: [] };
+  const base = stripped.slice(0, open).split('::').pop();
+  const inner = stripped.slice(open + 1, -1);
+  const args = [];
+  let depth = 0;
+  let current = '';
+  for (const ch of inner) {
+    if (ch === '<' || ch === '(' || ch === '[') depth++;
+    if (ch === '>' || ch === ')' || ch === ']') depth--;
+    if (ch === ',' && depth === 0) {
+      args.push(current.trim());
+      current = '';
+    } else {
+      current += ch;
+    }
+  }
+  if (current.trim()) args.push(current.trim());
+  return { base, args };
+}
+
+/**
+ * A lexical scope of local bindings and their (declared or inferred) types, used for light type inference
+ */
+class TypeScope {
+  constructor(definitions, selfType, parent = null) {
+    this.definitions = definitions;
+    this.selfType = selfType;
+    this.parent = parent;
+    this.bindings = new Map();
+  }
+
+  child() {
+    return new TypeScope(this.definitions, this.selfType, this);
+  }
+
+  declare(name, typeText) {
+    if (name) this.bindings.set(name, typeText || null);
+  }
+
+  lookup(name) {
+    if (this.bindings.has(name)) return this.bindings.get(name);
+    return this.parent ? this.parent.lookup(name) : undefined;
+  }
+
+  /**
+   * Declare the bindings introduced by a pattern given the type of the matched value
+   */
+  declarePattern(pattern, typeText) {
+    if (!pattern) return;
+    switch (pattern.type) {
+      case 'IdentPat':
+        this.declare(pattern.name, typeText);
+        break;
+      case 'RefPat':
+        this.declarePattern(pattern.pattern, typeText ? stripReferences(typeText) : null);
+        break;
+      case 'ParenPat':
+        this.declarePattern(pattern.pattern, typeText);
+        break;
+      case 'TupleStructPat': {
+        const { base, args } = splitGenericType(typeText || '');
+        const inner = (base === 'Some' || base === 'Option' || base === 'Ok' || base === 'Result') ? args[0] : null;
+        pattern.elems.forEach(elem => this.declarePattern(elem, inner));
+        break;
+      }
+      default:
+        visit(pattern, {
+          IdentPat: (node) => {
+            this.declare(node.name, null);
+          }
+        });
+        break;
+    }
+  }
+}
+
+/**
+ * Build the scope for a function's parameters
+ * @param {Object} fnInfo - Entry from collectDefinitions().functions
+ * @param {Object} definitions - Result of collectDefinitions
+ * @returns {TypeScope}
+ */
+function functionScope(fnInfo, definitions) {
+  const scope = new TypeScope(definitions, fnInfo.owner);
+  for (const param of fnInfo.node.params) {
+    scope.declarePattern(param.pattern, param.paramType ? param.paramType.text : null);
+  }
+  return scope;
+}
+
+/**
+ * Infer the type of an expression from declarations in scope. Returns type text or null when unknown.
+ * @param {Object} expr - Expression node
+ * @param {TypeScope} scope - Current scope
+ * @returns {string|null}
+ */
+function inferType(expr, scope) {
+  if (!expr) return null;
+  switch (expr.type) {
+    case 'Literal':
+      if (expr.kind === 'int') return expr.suffix || '{integer}';
+      if (expr.kind === 'float') return expr.suffix || 'f64';
+      if (expr.kind === 'bool') return 'bool';
+      if (expr.kind === 'string') return '&str';
+      return null;
+    case 'PathExpr': {
+      if (expr.path.segments.length !== 1) return null;
+      const name = expr.path.segments[0].name;
+      if (name === 'self') return scope.selfType;
+      const found = scope.lookup(name);
+      return found === undefined ? null : found;
+    }
+    case 'Paren':
+      return inferType(expr.expr, scope);
+    case 'Cast':
+      return expr.castType.text;
+    case 'Ref':
+      return (expr.mutable ? '&mut ' : '&') + (inferType(expr.argument, scope) || '_');
+    case 'Unary':
+      if (expr.operator === '*') {
+        const inner = inferType(expr.argument, scope);
+        return inner ? stripOneReference(inner) : null;
+      }
+      if (expr.operator === '-') return inferType(expr.argument, scope);
+      return expr.operator === '!' ? inferType(expr.argument, scope) : null;
+    case 'Binary': {
+      if (['==', '!=', '<', '>', '<=', '>=', '&&', '||'].includes(expr.operator)) return 'bool';
+      const left = inferType(expr.left, scope);
+      const right = inferType(expr.right, scope);
+      if (left && left !== '{integer}') return stripReferences(left);
+      if (right && right !== '{integer}') return stripReferences(right);
+      return left || right;
+    }
+    case 'FieldAccess': {
+      const objectType = inferType(expr.object, scope);
+      return fieldType(objectType, expr.field, scope.definitions);
+    }
+    case 'Index': {
+      const objectType = inferType(expr.object, scope);
+      if (!objectType) return null;
+      const stripped = stripReferences(objectType);
+      const array = /^\[(.+?)(;.*)?\]$/.exec(stripped);
+      if (array) return array[1].trim();
+      const { base, args } = splitGenericType(stripped);
+      if (base === 'Vec' || base === 'VecDeque') return args[0] || null;
+      if (base === 'HashMap' || base === 'BTreeMap') return args[1] || null;
+      return null;
+    }
+    case 'MethodCall':
+      return methodReturnType(expr, scope);
+    case 'Call': {
+      // `Type::new(..)`, `Self::helper(..)` and local free functions with a declared return type
+      if (expr.callee.type !== 'PathExpr') return null;
+      const segments = expr.callee.path.segments.map(segment => segment.name);
+      const name = segments[segments.length - 1];
+      const owner = segments.length > 1 ? segments[segments.length - 2] : null;
+      const fnInfo = findMethod(scope.definitions, owner === 'Self' ? scope.selfType : owner, name);
+      return fnInfo ? returnTypeOf(fnInfo) : null;
+    }
+    case 'Try': {
+      const inner = inferType(expr.argument, scope);
+      if (!inner) return null;
+      const { base, args } = splitGenericType(inner);
+      return (base === 'Option' || base === 'Result') ? args[0] || null : null;
+    }
+    case 'Block':
+    case 'If':
+      return null;
+    default:
+      return null;
+  }
+}
+
+function stripOneReference(text) {
+  return text.replace(/^&\s*('\w+\s+)?(mut\s+)?/, '').trim();
+}
+
+function fieldType(objectType, field, definitions) {
+  if (!objectType) return null;
+  const name = splitGenericType(objectType).base;
+  const struct = definitions.structs.get(name);
+  if (!struct) return null;
+  const type = struct.fields.get(field);
+  return type ? type.text : null;
+}
+
+const ARITHMETIC_METHOD_PREFIXES = ['checked_', 'saturating_', 'wrapping_', 'overflowing_'];
+
+function methodReturnType(call, scope) {
+  const receiverType = inferType(call.receiver, scope);
+  const method = call.method;
+  if (ARITHMETIC_METHOD_PREFIXES.some(prefix => method.startsWith(prefix))) {
+    if (!receiverType) return null;
+    const base = stripReferences(receiverType);
+    if (method.startsWith('checked_')) return `Option<${base}>`;
+    if (method.startsWith('overflowing_')) return `(${base}, bool)`;
+    return base;
+  }
+  if (['len', 'count'].includes(method)) return 'usize';
+  if (['clone', 'to_owned'].includes(method)) return receiverType ? stripReferences(receiverType) : null;
+  if (['to_string', 'to_uppercase', 'to_lowercase'].includes(method)) return 'String';
+  if (['min', 'max', 'pow', 'abs', 'unsigned_abs'].includes(method)) return receiverType ? stripReferences(receiverType) : null;
+  if (!receiverType) return null;
+  const { base, args } = splitGenericType(receiverType);
+  switch (method) {
+    case 'get':
+      if (base === 'HashMap' || base === 'BTreeMap') return `Option<&${args[1] || '_'}>`;
+      // ink! storage mappings return owned values
+      if (base === 'Mapping') return `Option<${args[1] || '_'}>`;
+      if (base === 'Vec' || base === 'VecDeque') return `Option<&${args[0] || '_'}>`;
+      return null;
+    case 'get_mut':
+      if (base === 'HashMap' || base === 'BTreeMap') return `Option<&mut ${args[1] || '_'}>`;
+      if (base === 'Vec' || base === 'VecDeque') return `Option<&mut ${args[0] || '_'}>`;
+      return null;
+    case 'entry':
+      return (base === 'HashMap' || base === 'BTreeMap') ? `Entry<${args[0] || '_'}, ${args[1] || '_'}>` : null;
+    case 'or_insert':
+    case 'or_default':
+    case 'or_insert_with':
+      return base === 'Entry' ? `&mut ${args[1] || '_'}` : null;
+    case 'insert':
+      return (base === 'HashMap' || base === 'BTreeMap') ? `Option<${args[1] || '_'}>` : null;
+    case 'remove':
+      if (base === 'HashMap' || base === 'BTreeMap') return `Option<${args[1] || '_'}>`;
+      if (base === 'Vec') return args[0] || null;
+      return null;
+    case 'unwrap':
+    case 'expect':
+    case 'unwrap_or':
+    case 'unwrap_or_default':
+    case 'unwrap_or_else':
+      return (base === 'Option' || base === 'Result') ? args[0] || null : null;
+    case 'copied':
+    case 'cloned':
+      return base === 'Option' && args[0] ? `Option<${stripReferences(args[0])}>` : null;
+    default: {
+      const fnInfo = findMethod(scope.definitions, splitGenericType(stripReferences(receiverType)).base, method);
+      return fnInfo ? returnTypeOf(fnInfo) : null;
+    }
+  }
+}
+
+/**
+ * Find a function defined in the file: a method or associated function of `owner`
+ * (inherent or trait impl), or a free function when `owner` is null
+ * @param {Object} definitions - Result of collectDefinitions
+ * @param {string|null} owner - Type name
+ * @param {string} name - Function name
+ * @returns {Object|null} - Entry from definitions.functions
+ */
+function findMethod(definitions, owner, name) {
+  if (!definitions) return null;
+  return definitions.functions.find(fn => fn.name === name && (fn.owner || null) === (owner || null)) || null;
+}
+
+/**
+ * Declared return type of a function with `Self` replaced by its owner; `()` when none is declared
+ */
+function returnTypeOf(fnInfo) {
+  if (!fnInfo.node.returnType) return '()';
+  const text = fnInfo.node.returnType.text;
+  return fnInfo.owner ? text.replace(/\bSelf\b/g, fnInfo.owner) : text;
+}
+
+const WALK_SKIPPED_KEYS = new Set(['loc', 'range', 'tokens', 'methodLoc', 'declarationLoc', 'attrs', 'path', 'castType', 'letType', 'paramType', 'returnType', 'turbofish']);
+
+/**
+ * Walk a function body while tracking lexical scopes so callbacks can infer the types of local bindings.
+ * `callback(node, scope, ancestors)` is called for every node; returning false skips its children.
+ * Nested item definitions (fns inside fns) are not entered.
+ * @param {Object} fnInfo - Entry from collectDefinitions().functions
+ * @param {Object} definitions - Result of collectDefinitions
+ * @param {Function} callback - Visitor callback
+ */
+function walkFunction(fnInfo, definitions, callback) {
+  if (!fnInfo.node.body) return;
+  const ancestors = [];
+
+  function walk(node, scope) {
+    if (Array.isArray(node)) {
+      node.forEach(child => walk(child, scope));
+      return;
+    }
+    if (!node || typeof node !== 'object' || typeof node.type !== 'string') return;
+    if (node.type === 'ItemStmt') return;
+    if (callback(node, scope, ancestors) === false) return;
+
+    ancestors.push(node);
+    switch (node.type) {
+      case 'Block': {
+        const inner = scope.child();
+        for (const stmt of node.stmts) {
+          if (stmt.type === 'Let') {
+            ancestors.push(stmt);
+            callback(stmt, inner, ancestors.slice(0, -1));
+            walk(stmt.init, inner);
+            walk(stmt.elseBlock, inner);
+            ancestors.pop();
+            const declared = stmt.letType ? stmt.letType.text : inferType(stmt.init, inner);
+            inner.declarePattern(stmt.pattern, declared);
+          } else {
+            walk(stmt, inner);
+          }
+        }
+        break;
+      }
+      case 'Closure': {
+        const inner = scope.child();
+        node.params.forEach(param => inner.declarePattern(param.pattern, param.paramType ? param.paramType.text : null));
+        walk(node.body, inner);
+        break;
+      }
+      case 'For': {
+        walk(node.iter, scope);
+        const inner = scope.child();
+        const iterType = node.iter.type === 'Range' ? inferType(node.iter.from || node.iter.to, scope) : null;
+        inner.declarePattern(node.pattern, iterType);
+        walk(node.body, inner);
+        break;
+      }
+      case 'Match': {
+        walk(node.expr, scope);
+        const scrutineeType = inferType(node.expr, scope);
+        for (const arm of node.arms) {
+          const inner = scope.child();
+          inner.declarePattern(arm.pattern, scrutineeType);
+          ancestors.push(arm);
+          callback(arm, inner, ancestors.slice(0, -1));
+          walk(arm.guard, inner);
+          walk(arm.body, inner);
+          ancestors.pop();
+        }
+        break;
+      }
+      case 'If':
+      case 'While': {
+        const inner = scope.child();
+        walk(node.cond, inner);
+        walk(node.type === 'If' ? node.consequent : node.body, inner);
+        if (node.type === 'If') walk(node.alternate, scope);
+        break;
+      }
+      case 'LetExpr':
+        walk(node.expr, scope);
+        scope.declarePattern(node.pattern, inferType(node.expr, scope));
+        break;
+      default:
+        for (const key of Object.keys(node)) {
+          if (WALK_SKIPPED_KEYS.has(key)) continue;
+          const child = node[key];
+          if (child && typeof child === 'object') walk(child, scope);
+        }
+        break;
+    }
+    ancestors.pop();
+  }
+
+  walk(fnInfo.node.body, functionScope(fnInfo, definitions));
+}
+
+const DIVERGING_MACROS = new Set(['panic', 'unreachable', 'unimplemented', 'todo', 'bail', 'err']);
+const ASSERTION_MACROS = new Set(['assert', 'require', 'ensure', 'require_keys_eq', 'require_eq', 'assert_eq', 'ensure_eq', 'debug_assert']);
+
+/**
+ * True when executing the node always leaves the enclosing function or loop body (return, break, panic!, ...)
+ * @param {Object} node - Statement, block or expression
+ * @returns {boolean}
+ */
+function diverges(node) {
+  if (!node) return false;
+  switch (node.type) {
+    case 'Return':
+    case 'Break':
+    case 'Continue':
+      return true;
+    case 'Macro':
+      return DIVERGING_MACROS.has(node.name);
+    case 'ExprStmt':
+      return diverges(node.expr);
+    case 'Block':
+      return node.stmts.some(stmt => diverges(stmt));
+    case 'If':
+      return !!node.alternate && diverges(node.consequent) && diverges(node.alternate);
+    case 'Match':
+      return node.arms.length > 0 && node.arms.every(arm => diverges(arm.body));
+    default:
+      return false;
+  }
+}
+
+/**
+ * List the conditions known to hold when `node` executes, given the ancestor chain from walkFunction:
+ * enclosing `if` conditions, earlier early-exit checks (`if cond { return ... }`) and assertion macros
+ * (`assert!`, `require!`, ...). Each entry is `{ condition, holds, source }`, where `holds` is false when
+ * the condition is known to be false (the early-exit case).
+ * @param {Array} ancestors - Ancestor chain, outermost first
+ * @param {Object} node - The node being guarded
+ * @returns {Array}
+ */
+function dominatingConditions(ancestors, node) {
+  const conditions = [];
+  const chain = [...ancestors, node];
+
+  for (let i = chain.length - 2; i >= 0; i--) {
+    const ancestor = chain[i];
+    const child = chain[i + 1];
+
+    if (ancestor.type === 'If') {
+      if (child === ancestor.consequent) conditions.push({ condition: ancestor.cond, holds: true, source: ancestor });
+      if (child === ancestor.alternate) conditions.push({ condition: ancestor.cond, holds: false, source: ancestor });
+    } else if (ancestor.type === 'While' && child === ancestor.body) {
+      conditions.push({ condition: ancestor.cond, holds: true, source: ancestor });
+    } else if (ancestor.type === 'MatchArm' && child === ancestor.body && ancestor.guard) {
+      conditions.push({ condition: ancestor.guard, holds: true, source: ancestor });
+    } else if (ancestor.type === 'Block') {
+      const index = ancestor.stmts.indexOf(child);
+      for (let k = index - 1; k >= 0; k--) {
+        const stmt = ancestor.stmts[k];
+        const expr = stmt.type === 'ExprStmt' ? stmt.expr : null;
+        if (!expr) continue;
+        if (expr.type === 'If' && !expr.alternate && diverges(expr.consequent)) {
+          conditions.push({ condition: expr.cond, holds: false, source: expr });
+        } else if (expr.type === 'Macro' && ASSERTION_MACROS.has(expr.name) && expr.args && expr.args.length > 0) {
+          if (expr.name === 'assert_eq' || expr.name === 'require_eq' || expr.name === 'require_keys_eq' || expr.name === 'ensure_eq') {
+            conditions.push({ condition: { type: 'Binary', operator: '==', left: expr.args[0], right: expr.args[1], loc: expr.loc, range: expr.range }, holds: true, source: expr });
+          } else {
+            conditions.push({ condition: expr.args[0], holds: true, source: expr });
+          }
+        }
+      }
+    }
+  }
+
+  return conditions;
+}
+
+/**
+ * List the statements that run before `node` in each enclosing block, innermost block first
+ * @param {Array} ancestors - Ancestor chain from walkFunction, outermost first
+ * @param {Object} node - The node being guarded
+ * @returns {Array} - Statement nodes
+ */
+function precedingStatements(ancestors, node) {
+  const statements = [];
+  const chain = [...ancestors, node];
+  for (let i = chain.length - 2; i >= 0; i--) {
+    if (chain[i].type !== 'Block') continue;
+    const index = chain[i].stmts.indexOf(chain[i + 1]);
+    if (index > 0) statements.push(...chain[i].stmts.slice(0, index));
+  }
+  return statements;
+}
+
+/**
+ * Return the source text for a node
+ * @param {string} code - Full source code
+ * @param {Object} node - AST node with range
+ * @returns {string}
+ */
+function nodeText(code, node) {
+  return node && node.range ? code.slice(node.range[0], node.range[1]) : '';
+}
+
+// Span indexes of collectDefinitions results, built on first use
+const spanIndexes = new WeakMap();
+
+/**
+ * Functions and impls of a file sorted by start offset, each function with the index of the innermost function
+ * enclosing it, so enclosingName can binary-search them
+ */
+function spanIndex(definitions) {
+  if (spanIndexes.has(definitions)) return spanIndexes.get(definitions);
+  const byStart = (a, b) => a.node.range[0] - b.node.range[0] || b.node.range[1] - a.node.range[1];
+  const functions = definitions.functions.filter(fn => fn.node.range).sort(byStart);
+  const parents = [];
+  const open = [];
+  functions.forEach((fn, index) => {
+    while (open.length > 0 && functions[open[open.length - 1]].node.range[1] < fn.node.range[1]) open.pop();
+    parents.push(open.length > 0 ? open[open.length - 1] : -1);
+    open.push(index);
+  });
+  const index = { functions, parents, impls: definitions.impls.filter(impl => impl.node.range).sort(byStart) };
+  spanIndexes.set(definitions, index);
+  return index;
+}
+
+// Index of the last entry starting at or before offset, or -1
+function lastStartingBefore(entries, offset) {
+  let low = 0;
+  let high = entries.length - 1;
+  let found = -1;
+  while (low <= high) {
+    const mid = (low + high) >> 1;
+    if (entries[mid].node.range[0] <= offset) {
+      found = mid;
+      low = mid + 1;
+    } else {
+      high = mid - 1;
+    }
+  }
+  return found;
+}
+
+/**
+ * Find the innermost function (or impl/trait, for nodes outside any function) whose source range contains a node
+ * @param {Object} definitions - Result of collectDefinitions
+ * @param {Object} node - AST node
+ * @returns {string|null} - Qualified name such as `Token::burn`
+ */
+function enclosingName(definitions, node) {
+  const contains = (outer) => outer.range[0] <= node.range[0] && node.range[1] <= outer.range[1];
+  const { functions, parents, impls } = spanIndex(definitions);
+  // A function containing the node starts before it, so it is the last such function or one enclosing it
+  for (let index = lastStartingBefore(functions, node.range[0]); index >= 0; index = parents[index]) {
+    if (contains(functions[index].node)) return functions[index].qualifiedName;
+  }
+  const impl = impls[lastStartingBefore(impls, node.range[0])];
+  return impl && contains(impl.node) ? impl.node.selfName : null;
+}
+
+const MAX_SNIPPET_LINES = 5;
+
+// Line start offsets of the last source sourceLocation was called with
+let lineStarts = { code: null, starts: [] };
+
+/**
+ * Text of a 1-based line, without its line break
+ */
+function lineText(code, line) {
+  if (lineStarts.code !== code) {
+    const starts = [0];
+    for (let offset = code.indexOf('\n'); offset !== -1; offset = code.indexOf('\n', offset + 1)) starts.push(offset + 1);
+    lineStarts = { code, starts };
+  }
+  const { starts } = lineStarts;
+  if (line < 1 || line > starts.length) return null;
+  return code.slice(starts[line - 1], line < starts.length ? starts[line] - 1 : code.length);
+}
+
+/**
+ * Describe where a node lives in the source: file, 1-based line/column span, enclosing function and a short excerpt
+ * @param {string} code - Full source code
+ * @param {Object} node - AST node with loc
+ * @param {Object} context - `{ filePath, definitions }`
+ * @returns {Object} - Location record
+ */
+function sourceLocation(code, node, { filePath, definitions }) {
+  const startLine = node.loc.start.line;
+  const endLine = node.loc.end.line;
+  let excerpt = [];
+  for (let line = startLine; line <= Math.min(endLine, startLine - 1 + MAX_SNIPPET_LINES); line++) {
+    const text = lineText(code, line);
+    if (text === null) break;
+    excerpt.push(text);
+  }
+  // Remove the common indentation so the excerpt reads well on its own
+  const indent = Math.min(...excerpt.filter(line => line.trim()).map(line => line.match(/^\s*/)[0].length));
+  excerpt = excerpt.map(line => line.slice(Number.isFinite(indent) ? indent : 0).trimEnd());
+  if (endLine - startLine + 1 > MAX_SNIPPET_LINES) excerpt.push('// ...');
+
+  return {
+    file: filePath,
+    startLine,
+    startColumn: node.loc.start.column + 1,
+    endLine,
+    endColumn: node.loc.end.column + 1,
+    function: definitions ? enclosingName(definitions, node) : null,
+    snippet: excerpt.join('\n')
+  };
+}
+
+/**
+ * A pseudo-node spanning a function's signature, used to locate function-level findings
+ * @param {Object} fnNode - Fn node
+ * @returns {Object} - Node-like object with `loc` and `range`
+ */
+function signatureOf(fnNode) {
+  if (!fnNode.body) return fnNode;
+  return {
+    loc: { start: fnNode.loc.start, end: fnNode.body.loc.start },
+    range: [fnNode.range[0], fnNode.body.range[0]]
+  };
+}
+
+/**
+ * True when the expression is `self.<field>` (optionally followed by further field/method access)
+ * and returns the field name.
+ * @param {Object} expr - Expression node
+ * @returns {string|null}
+ */
+function selfFieldRoot(expr) {
+  let node = expr;
+  let field = null;
+  for (;;) {
+    if (!node) return null;
+    switch (node.type) {
+      case 'FieldAccess':
+        if (isSelf(node.object)) return node.field;
+        field = node.field;
+        node = node.object;
+        break;
+      case 'MethodCall':
+        node = node.receiver;
+        break;
+      case 'Index':
+        node = node.object;
+        break;
+      case 'Unary':
+      case 'Ref':
+      case 'Try':
+        node = node.argument;
+        break;
+      case 'Paren':
+        node = node.expr;
+        break;
+      default:
+        return null;
+    }
+  }
+}
+
+function isSelf(expr) {
+  return expr && expr.type === 'PathExpr' && expr.path.segments.length === 1 && expr.path.segments[0].name === 'self';
+}
+
+module.exports = {
+  INTEGER_TYPES,
+  PRIVILEGED_FIELD,
+  ROLE_FIELD,
+  collectDefinitions,
+  diverges,
+  dominatingConditions,
+  enclosingName,
+  findMethod,
+  functionScope,
+  inferType,
+  isIntegerType,
+  isSelf,
+  nodeText,
+  precedingStatements,
+  returnTypeOf,
+  rustFunctionSpans,
+  selfFieldRoot,
+  signatureOf,
+  sourceLocation,
+  splitGenericType,
+  stripReferences,
+  typeBaseName,
+  TypeScope,
+  walkFunction
+};
@@ -0,0 +1,447 @@
+const fs = require('fs');
+const path = require('path');
+const { parse } = require('./rust-parser');
+const {
+  collectDefinitions,
+  findMethod,
+  inferType,
+  splitGenericType,
+  stripReferences,
+  walkFunction
+} = require('./rust-ast');
+const { moduleTree } = require('./cargo-workspace');
+
+/**
+ * Build the call graph of the functions defined in one Rust file.
+ * Calls are resolved from the callee path (`Self::f`, `Type::f`, `Trait::f`, `f`) or, for method calls,
+ * from the inferred receiver type. Calls through a trait (`dyn Trait`, `impl Trait` or a generic bounded
+ * by the trait) link to every implementation of the method. Calls into code outside the file are not
+ * part of the graph.
+ * @param {Object|string} source - Parsed SourceFile or Rust source code
+ * @param {Object} definitions - Result of collectDefinitions (computed when omitted)
+ * @returns {Object} - `{ nodes, edges }` where nodes map ids (qualified names) to
+ *   `{ id, fn }` and edges are `{ from, to, line, kind }` with kind `call`, `method` or `trait`
+ */
+function buildCallGraph(source, definitions = null) {
+  const ast = typeof source === 'string' ? parse(source) : source;
+  return graphOf(definitions || collectDefinitions(ast));
+}
+
+/**
+ * Build the call graph of a whole crate target: every module file reachable from the target root.
+ * Function ids carry their module path (`state::load`, `Vault::deposit`) and nodes and edges record their file.
+ * @param {Object} target - `{ crate, target: { kind, name, root }, files }` as produced by selectRustTargets,
+ *   or `{ root }` for a bare crate root
+ * @returns {Object} - `{ nodes, edges, modules, parseErrors }`
+ */
+function buildCrateCallGraph(target) {
+  const rootFile = target.root || (target.target && target.target.root) || target.files[0];
+  const modules = moduleTree(rootFile);
+  const definitions = { structs: new Map(), enums: new Map(), traits: new Map(), impls: [], functions: [] };
+  const parseErrors = [];
+
+  for (const module of modules) {
+    let fileDefinitions;
+    try {
+      fileDefinitions = collectDefinitions(parse(fs.readFileSync(module.file, 'utf8')));
+    } catch (error) {
+      parseErrors.push({ file: module.file, message: error.message });
+      continue;
+    }
+    // Names are resolved crate-wide; the first definition of a type name wins
+    for (const key of ['structs', 'enums', 'traits']) {
+      for (const [name, entry] of fileDefinitions[key]) {
+        if (!definitions[key].has(name)) definitions[key].set(name, { ...entry, modulePath: [...module.modulePath, ...entry.modulePath] });
+      }
+    }
+    fileDefinitions.impls.forEach(impl => definitions.impls.push({ ...impl, modulePath: [...module.modulePath, ...impl.modulePath] }));
+    for (const fn of fileDefinitions.functions) {
+      const modulePath = [...module.modulePath, ...fn.modulePath];
+      definitions.functions.push({
+        ...fn,
+        modulePath,
+        qualifiedName: fn.owner ? fn.qualifiedName : [...modulePath, fn.name].join('::'),
+        file: module.file
+      });
+    }
+  }
+
+  const graph = graphOf(definitions);
+  return { ...graph, modules, parseErrors };
+}
+
+function graphOf(defs) {
+  const nodes = new Map();
+  for (const fn of defs.functions) {
+    if (!nodes.has(fn.qualifiedName)) nodes.set(fn.qualifiedName, { id: fn.qualifiedName, fn, file: fn.file || null });
+  }
+
+  const edges = [];
+  const seen = new Set();
+  for (const fn of defs.functions) {
+    if (!fn.node.body) continue;
+    walkFunction(fn, defs, (node, scope) => {
+      const { callees, dispatch } = resolveCall(node, scope, fn, defs);
+      for (const callee of callees) {
+        const key = `${fn.qualifiedName}->${callee.qualifiedName}@${node.loc.start.line}`;
+        if (seen.has(key)) continue;
+        seen.add(key);
+        const edge = {
+          from: fn.qualifiedName,
+          to: callee.qualifiedName,
+          line: node.loc.start.line,
+          kind: dispatch ? 'trait' : node.type === 'MethodCall' ? 'method' : 'call'
+        };
+        if (fn.file) edge.file = fn.file;
+        edges.push(edge);
+      }
+    });
+  }
+
+  return { nodes, edges };
+}
+
+/**
+ * Resolve a Call or MethodCall node to the functions it may invoke
+ * @returns {Object} - `{ callees, dispatch }`; dispatch is true for calls through a trait
+ */
+function resolveCall(node, scope, caller, defs) {
+  const none = { callees: [], dispatch: false };
+  const single = (fn) => ({ callees: fn ? [fn] : [], dispatch: false });
+
+  if (node.type === 'MethodCall') {
+    const receiverType = inferType(node.receiver, scope);
+    if (!receiverType) return none;
+    const traits = receiverTraits(receiverType, caller, defs);
+    if (traits.length > 0) return { callees: traits.flatMap(trait => traitImplementations(defs, trait, node.method)), dispatch: true };
+    const owner = ownerName(receiverType);
+    return single(owner ? findMethod(defs, owner, node.method) || inheritedMethod(defs, owner, node.method) : null);
+  }
+
+  if (node.type !== 'Call' || node.callee.type !== 'PathExpr') return none;
+  const segments = node.callee.path.segments.map(segment => segment.name);
+  const name = segments[segments.length - 1];
+  if (segments.length === 1) {
+    // Prefer a free function in the caller's module, then any free function of that name
+    return single(defs.functions.find(fn => !fn.owner && fn.name === name && sameModule(fn, caller)) ||
+      findMethod(defs, null, name));
+  }
+  const qualifier = segments[segments.length - 2];
+  const owner = qualifier === 'Self' ? caller.owner : qualifier;
+  // `Trait::method(&value)` and `Self::method()` inside a trait's default methods
+  if (owner && defs.traits.has(owner) && !defs.structs.has(owner) && !defs.enums.has(owner)) {
+    return { callees: traitImplementations(defs, owner, name), dispatch: true };
+  }
+  const method = findMethod(defs, owner, name) || inheritedMethod(defs, owner, name);
+  if (method) return single(method);
+  // `module::function(..)`
+  return single(defs.functions.find(fn => !fn.owner && fn.name === name && fn.modulePath[fn.modulePath.length - 1] === qualifier));
+}
+
+/**
+ * The traits a call on a value of this type dispatches through: `dyn Trait`, `impl Trait`, a generic
+ * parameter bounded by a trait (`T: Trait` or `where T: Trait`) and `self` inside a trait's default methods
+ */
+function receiverTraits(typeText, caller, defs) {
+  let text = stripReferences(typeText);
+  // Look through smart pointers, e.g. `Box<dyn Oracle>`
+  for (;;) {
+    const { base, args } = splitGenericType(text);
+    if (!['Box', 'Rc', 'Arc'].includes(base) || !args[0]) break;
+    text = stripReferences(args[0]);
+  }
+
+  let bounds = null;
+  const dynamic = text.match(/^(dyn|impl)\s+(.+)$/);
+  if (dynamic) {
+    bounds = dynamic[2];
+  } else if (/^\w+$/.test(text)) {
+    if (defs.traits.has(text) && !defs.structs.has(text) && !defs.enums.has(text)) return [text];
+    bounds = genericBounds(text, caller);
+  }
+  if (!bounds) return [];
+  return bounds.split('+')
+    .map(bound => splitGenericType(bound.trim().replace(/^\?/, '')).base.split('::').pop())
+    .filter(name => defs.traits.has(name));
+}
+
+/**
+ * Bounds of a generic parameter declared on the function or its impl, e.g. `Oracle + Clone`
+ */
+function genericBounds(name, fn) {
+  const bounds = [];
+  for (const owner of [fn.node, fn.container]) {
+    if (!owner) continue;
+    const param = owner.generics && owner.generics.params.find(entry => entry.kind === 'type' && entry.name === name);
+    if (param && param.bounds) bounds.push(param.bounds);
+    if (owner.whereClause) {
+      const match = owner.whereClause.text.match(new RegExp(`(?:^|,)\\s*${name}\\s*:\\s*([^,{]+)`));
+      if (match) bounds.push(match[1].trim());
+    }
+  }
+  return bounds.length > 0 ? bounds.join(' + ') : null;
+}
+
+/**
+ * Every implementation of a trait method, plus the trait's default body
+ */
+function traitImplementations(defs, trait, method) {
+  return defs.functions.filter(fn => fn.name === method && fn.trait === trait);
+}
+
+/**
+ * A trait default method reached through a type that implements the trait without overriding it
+ */
+function inheritedMethod(defs, owner, name) {
+  if (!owner) return null;
+  for (const impl of defs.impls) {
+    if (impl.node.selfName !== owner || !impl.node.traitName) continue;
+    const fallback = defs.functions.find(fn => fn.name === name && fn.owner === impl.node.traitName && fn.trait === impl.node.traitName);
+    if (fallback) return fallback;
+  }
+  return null;
+}
+
+function ownerName(typeText) {
+  let base = splitGenericType(stripReferences(typeText)).base;
+  // Look through smart pointers to the pointee
+  while (['Box', 'Rc', 'Arc', 'RefMut', 'Ref'].includes(base)) {
+    const inner = splitGenericType(stripReferences(typeText)).args[0];
+    if (!inner) return null;
+    typeText = inner;
+    base = splitGenericType(stripReferences(typeText)).base;
+  }
+  return base ? base.split('::').pop() : null;
+}
+
+function sameModule(a, b) {
+  return a.modulePath.join('::') === b.modulePath.join('::');
+}
+
+/**
+ * True for functions an outside caller can invoke: `main`, `pub` functions, trait methods and
+ * framework handlers (Anchor `#[program]` instructions, CosmWasm `#[entry_point]`s, ink! messages and constructors)
+ * @param {Object} fn - Entry from collectDefinitions().functions
+ * @param {Object} options - `inherentMethods`: for a file analyzed without its crate, whose callers are unknown, the
+ *   methods of inherent impls that declare no visibility on any method count too
+ * @returns {boolean}
+ */
+function isEntryPoint(fn, options = {}) {
+  if (isTestFunction(fn)) return false;
+  if (fn.name === 'main' && !fn.owner) return true;
+  const attrs = fn.node.attrs || [];
+  if (attrs.some(attr => attr.path === 'entry_point' || (attr.path === 'cfg_attr' && /\bentry_point\b/.test(attr.args || '')))) return true;
+  if (attrs.some(attr => attr.path === 'ink' && /\b(message|constructor)\b/.test(attr.args || ''))) return true;
+  if (fn.module && (fn.module.attrs || []).some(attr => attr.path === 'program')) return true;
+  if (fn.container && fn.container.type === 'Impl' && fn.container.traitName) return true;
+  if (options.inherentMethods && fn.container && fn.container.type === 'Impl' &&
+      !fn.container.items.some(item => item.type === 'Fn' && item.visibility)) return true;
+  // Default methods of a public trait are callable on every implementor
+  if (fn.container && fn.container.type === 'Trait') return fn.container.visibility === 'pub';
+  return fn.node.visibility === 'pub';
+}
+
+/**
+ * Unit tests: `#[test]` functions and functions of `tests` modules
+ */
+function isTestFunction(fn) {
+  return (fn.node.attrs || []).some(attr => attr.path === 'test' || /::test$/.test(attr.path)) ||
+    fn.modulePath.some(name => name === 'tests' || name === 'test');
+}
+
+/**
+ * Pick the entry points of a graph
+ * @param {Object} graph - Result of buildCallGraph or buildCrateCallGraph
+ * @param {string[]|null} names - Functions chosen as entry points, by id (`Vault::deposit`) or bare name;
+ *   when omitted, every function isEntryPoint accepts
+ * @param {Object} options - Passed to isEntryPoint
+ * @returns {string[]} - Node ids
+ */
+function findEntryPoints(graph, names = null, options = {}) {
+  const nodes = [...graph.nodes.values()];
+  if (names && names.length > 0) {
+    return nodes
+      .filter(node => names.some(name => node.id === name || node.id.endsWith(`::${name}`) || node.fn.name === name))
+      .map(node => node.id);
+  }
+  return nodes.filter(node => node.fn.node.body && isEntryPoint(node.fn, options)).map(node => node.id);
+}
+
+/**
+ * Every function reachable from the given entry points, the entry points included
+ * @param {Object} graph - Call graph
+ * @param {string[]} entryPoints - Node ids
+ * @returns {Set<string>} - Node ids
+ */
+function reachableFrom(graph, entryPoints) {
+  return traverse(adjacency(graph.edges, 'from', 'to'), entryPoints);
+}
+
+/**
+ * The entry points whose call chains reach a function (the function itself included when it is one)
+ * @param {Object} graph - Call graph
+ * @param {string} id - Node id
+ * @param {string[]} entryPoints - Node ids
+ * @returns {string[]} - Node ids of the reaching entry points
+ */
+function entryPointsReaching(graph, id, entryPoints) {
+  const callers = traverse(adjacency(graph.edges, 'to', 'from'), [id]);
+  return entryPoints.filter(entry => callers.has(entry));
+}
+
+function adjacency(edges, fromKey, toKey) {
+  const map = new Map();
+  edges.forEach(edge => {
+    if (!map.has(edge[fromKey])) map.set(edge[fromKey], new Set());
+    map.get(edge[fromKey]).add(edge[toKey]);
+  });
+  return map;
+}
+
+function traverse(neighbours, starts) {
+  const seen = new Set(starts);
+  const queue = [...starts];
+  while (queue.length > 0) {
+    const current = queue.shift();
+    for (const next of neighbours.get(current) || []) {
+      if (seen.has(next)) continue;
+      seen.add(next);
+      queue.push(next);
+    }
+  }
+  return seen;
+}
+
+/**
+ * Find the innermost function of the graph whose body spans a line of a file
+ * @param {Object} graph - Call graph
+ * @param {string|null} filePath - File of the line; ignored for single-file graphs
+ * @param {number} line - 1-based line
+ * @returns {Object|null} - Graph node
+ */
+function findFunctionAt(graph, filePath, line) {
+  const file = filePath ? path.resolve(filePath) : null;
+  let best = null;
+  for (const node of graph.nodes.values()) {
+    if (node.file && file && path.resolve(node.file) !== file) continue;
+    const { start, end } = node.fn.node.loc;
+    if (line < start.line || line > end.line) continue;
+    if (!best || start.line >= best.fn.node.loc.start.line) best = node;
+  }
+  return best;
+}
+
+/**
+ * Count the calls between each pair of functions
+ */
+function aggregateEdges(edges) {
+  const byPair = new Map();
+  for (const edge of edges) {
+    const key = `${edge.from}->${edge.to}`;
+    if (!byPair.has(key)) byPair.set(key, { from: edge.from, to: edge.to, kind: edge.kind, count: 0 });
+    byPair.get(key).count++;
+  }
+  return [...byPair.values()];
+}
+
+/**
+ * JSON form of the graph with entry points and reachability
+ * @param {Object} graph - Call graph
+ * @param {Object} options - `entryPoints`: node ids (default: findEntryPoints), `baseDir`: files are made relative to it
+ * @returns {Object} - JSON-serializable graph
+ */
+function callGraphToJson(graph, options = {}) {
+  const entryPoints = options.entryPoints || findEntryPoints(graph);
+  const reachable = reachableFrom(graph, entryPoints);
+  const relative = (file) => (file && options.baseDir ? path.relative(options.baseDir, file) : file);
+  return {
+    entryPoints,
+    nodes: [...graph.nodes.values()].map(node => ({
+      id: node.id,
+      name: node.fn.name,
+      owner: node.fn.owner,
+      trait: node.fn.trait,
+      module: node.fn.modulePath.join('::'),
+      file: relative(node.file),
+      line: node.fn.node.loc.start.line,
+      entryPoint: entryPoints.includes(node.id),
+      reachable: reachable.has(node.id)
+    })),
+    edges: graph.edges.map(edge => ({ ...edge, file: relative(edge.file) }))
+  };
+}
+
+/**
+ * DOT form of the graph: methods are grouped by their type, entry points have a double border,
+ * mutating methods are filled and functions no entry point reaches are greyed out
+ * @param {Object} graph - Call graph
+ * @param {string} label - Graph label
+ * @param {Object} options - `entryPoints`: node ids (default: findEntryPoints)
+ * @returns {string} - DOT graph representation
+ */
+function callGraphToDot(graph, label, options = {}) {
+  const entryPoints = options.entryPoints || findEntryPoints(graph);
+  const reachable = reachableFrom(graph, entryPoints);
+  const lines = [
+    'digraph CallGraph {',
+    `  label=${dotString(label)};`,
+    '  rankdir=LR;',
+    '  node [shape=ellipse, fontname="Arial"];'
+  ];
+
+  // Group methods by the type that owns them
+  const owners = new Map();
+  for (const node of graph.nodes.values()) {
+    const owner = node.fn.owner || '';
+    if (!owners.has(owner)) owners.set(owner, []);
+    owners.get(owner).push(node);
+  }
+  let cluster = 0;
+  for (const [owner, members] of owners) {
+    const indent = owner ? '    ' : '  ';
+    if (owner) {
+      lines.push(`  subgraph cluster_${cluster++} {`);
+      lines.push(`    label=${dotString(owner)};`);
+      lines.push('    style=rounded;');
+    }
+    members.forEach(node => {
+      const attributes = [`label=${dotString(node.fn.name)}`];
+      if (node.fn.node.selfParam && node.fn.node.selfParam.mutable) attributes.push('style=filled', 'fillcolor=lightyellow');
+      if (entryPoints.includes(node.id)) attributes.push('peripheries=2');
+      if (!reachable.has(node.id)) attributes.push('color=gray', 'fontcolor=gray');
+      lines.push(`${indent}${dotString(node.id)} [${attributes.join(', ')}];`);
+    });
+    if (owner) lines.push('  }');
+  }
+
+  for (const edge of aggregateEdges(graph.edges)) {
+    const attributes = [];
+    if (edge.count > 1) attributes.push(`label="${edge.count}x"`);
+    if (edge.kind === 'trait') attributes.push('style=dashed');
+    lines.push(`  ${dotString(edge.from)} -> ${dotString(edge.to)}${attributes.length > 0 ? ` [${attributes.join(', ')}]` : ''};`);
+  }
+
+  lines.push('}');
+  return lines.join('\n') + '\n';
+}
+
+/**
+ * Quote a DOT identifier or string
+ */
+function dotString(text) {
+  return `"${String(text).replace(/\\/g, '\\\\').replace(/"/g, '\\"')}"`;
+}
+
+module.exports = {
+  buildCallGraph,
+  buildCrateCallGraph,
+  isEntryPoint,
+  isTestFunction,
+  findEntryPoints,
+  reachableFrom,
+  entryPointsReaching,
+  findFunctionAt,
+  aggregateEdges,
+  callGraphToJson,
+  callGraphToDot
+};
@@ -0,0 +1,353 @@
+const { parse, visit } = require('./rust-parser');
+const { estimateTokens } = require('./rust-crate-context');
+
+/**
+ * Syntax-aware chunking of Rust files too large for one prompt. A file is split between items, and impl blocks
+ * and inline modules between their members, so that no function is cut in two. Each chunk keeps the original
+ * line numbers and comes with a context of what its code needs from the rest of the file: the `use` declarations,
+ * the types, traits and constants it refers to, and the signatures of the functions it calls.
+ */
+
+// Default size of a chunk, its code and its context, in estimated tokens
+const DEFAULT_CHUNK_TOKENS = 12000;
+// Share of a chunk kept for its context
+const CONTEXT_SHARE = 0.25;
+
+const DEFINITION_KINDS = new Set(['Struct', 'Union', 'Enum', 'Trait', 'TypeAlias', 'Const', 'Static', 'MacroRules']);
+
+/**
+ * Split a Rust file into chunks
+ * @param {string} code - Rust source
+ * @param {Object} options - `chunkTokens`: size of a chunk (default 12000); `focus`: the file's change when
+ *   auditing a diff (see diff-scope.js), to chunk only the items that overlap its changed lines, whatever the
+ *   size of the file
+ * @returns {Array|null} - Chunks `{ index, count, startLine, endLine, lines, items, tokens, context, oversized,
+ *   focus }`, or null when the file fits in one chunk (or does not parse, in which case it is sent whole); with
+ *   `focus`, an empty list when no item changed
+ */
+function chunkRustFile(code, options = {}) {
+  const budget = Number(options.chunkTokens) || DEFAULT_CHUNK_TOKENS;
+  const { focus } = options;
+  if (!focus && estimateTokens(code) <= budget) return null;
+
+  let ast;
+  try {
+    ast = parse(code);
+  } catch (error) {
+    return null;
+  }
+
+  const sourceLines = code.split('\n');
+  const units = [];
+  const uses = [];
+  collectUnits(ast.items, { code, sourceLines, containers: [], start: 1, end: sourceLines.length }, units, uses);
+  if (units.length === 0) return focus ? [] : null;
+  const selected = focus
+    ? units.filter(unit => focus.ranges.some(range => range.start <= unit.endLine && unit.startLine <= range.end))
+    : units;
+
+  // Pack units in source order; a unit larger than a chunk gets a chunk of its own
+  const codeBudget = Math.floor(budget * (1 - CONTEXT_SHARE));
+  const groups = [];
+  let current = null;
+  for (const unit of selected) {
+    if (!current || current.tokens + unit.tokens > codeBudget) {
+      current = { units: [], tokens: 0 };
+      groups.push(current);
+    }
+    current.units.push(unit);
+    current.tokens += unit.tokens;
+  }
+
+  const chunks = groups.map((group, index) => {
+    const lines = new Set();
+    group.units.forEach(unit => {
+      for (let line = unit.startLine; line <= unit.endLine; line++) lines.add(line);
+      // Re-open the impl blocks and modules the unit sits in
+      unit.containers.forEach(container => {
+        for (let line = container.startLine; line <= container.headerEndLine; line++) lines.add(line);
+        lines.add(container.endLine);
+      });
+    });
+    const sorted = [...lines].sort((a, b) => a - b);
+    return {
+      index: index + 1,
+      count: groups.length,
+      startLine: sorted[0],
+      endLine: sorted[sorted.length - 1],
+      lines: sorted,
+      items: group.units.map(unit => unit.name),
+      units: group.units,
+      tokens: group.tokens,
+      oversized: group.tokens > codeBudget,
+      focus: Boolean(focus)
+    };
+  });
+
+  chunks.forEach(chunk => {
+    chunk.context = buildChunkContext(chunk, chunks, { units, uses, budget: budget - chunk.tokens });
+  });
+  chunks.forEach(chunk => delete chunk.units);
+  return chunks;
+}
+
+/**
+ * Flatten the items of a file (or of an impl block or inline module) into units. Comments and blank lines
+ * before an item belong to it, so that the chunks together cover the whole file.
+ */
+function collectUnits(items, context, units, uses) {
+  const { code, sourceLines, containers } = context;
+  let cursor = context.start;
+
+  const push = (item, name, kind) => {
+    const startLine = Math.min(cursor, item.loc.start.line);
+    const endLine = item.loc.end.line;
+    units.push({
+      name,
+      kind,
+      node: item,
+      text: code.slice(item.range[0], item.range[1]),
+      startLine,
+      endLine,
+      containers,
+      tokens: estimateTokens(sourceLines.slice(startLine - 1, endLine).join('\n')),
+      references: collectReferences(item)
+    });
+    cursor = endLine + 1;
+  };
+
+  for (const item of items) {
+    if (!item.loc) continue;
+    if (item.type === 'Use') {
+      uses.push(code.slice(item.range[0], item.range[1]));
+      cursor = item.loc.end.line + 1;
+      continue;
+    }
+    const owner = containers.length > 0 ? containers[containers.length - 1].name : null;
+    const qualified = (name) => (owner ? `${owner}::${name}` : name);
+
+    if ((item.type === 'Impl' || (item.type === 'Mod' && item.items)) && item.items.length > 0) {
+      const braceLine = lineOf(code, code.indexOf('{', item.range[0]));
+      const container = {
+        name: item.type === 'Impl' ? (item.selfName || 'impl').replace(/<.*$/, '') : qualified(item.name),
+        kind: item.type,
+        owner: item.type === 'Impl' ? (item.selfName || '').replace(/<.*$/, '') : null,
+        startLine: Math.min(cursor, item.loc.start.line),
+        headerEndLine: braceLine,
+        endLine: item.loc.end.line
+      };
+      collectUnits(item.items, {
+        ...context,
+        containers: [...containers, container],
+        start: braceLine + 1,
+        end: item.loc.end.line - 1
+      }, units, uses);
+      cursor = item.loc.end.line + 1;
+      continue;
+    }
+    push(item, qualified(item.name || (item.path ? `${item.path}!` : item.type)), item.type);
+  }
+}
+
+function lineOf(code, offset) {
+  let line = 1;
+  for (let i = 0; i < offset; i++) if (code.charCodeAt(i) === 10) line++;
+  return line;
+}
+
+/**
+ * Names an item refers to: the segments of its paths, its method calls and the identifiers of unparsed macros
+ */
+function collectReferences(item) {
+  const names = new Set();
+  const visitors = {
+    Path(node) {
+      node.segments.forEach(segment => {
+        names.add(segment.name);
+        // Generic arguments, e.g. `Initialize` in `Context<Initialize>`, hang off the segments
+        if (segment.generics) visit(segment.generics, visitors);
+      });
+    },
+    MethodCall(node) {
+      names.add(node.method);
+    },
+    Macro(node) {
+      if (node.args) return;
+      for (const token of node.tokens || []) {
+        if (token.type === 'ident') names.add(token.value);
+      }
+    }
+  };
+  visit(item, visitors);
+  return names;
+}
+
+/**
+ * Context of a chunk: the file's `use` declarations, the definitions its code refers to (in full while they fit,
+ * then by signature) and the signatures of the functions it calls in other chunks
+ */
+function buildChunkContext(chunk, chunks, { units, uses, budget }) {
+  const inChunk = new Set(chunk.units);
+  const referenced = new Set();
+  chunk.units.forEach(unit => {
+    unit.references.forEach(name => referenced.add(name));
+    unit.containers.forEach(container => {
+      if (container.owner) referenced.add(container.owner);
+    });
+  });
+  const bareName = (unit) => unit.name.split('::').pop();
+  const chunkOf = (unit) => chunks.find(other => other.units.includes(unit));
+
+  const definitions = units.filter(unit => !inChunk.has(unit) && DEFINITION_KINDS.has(unit.kind) && referenced.has(bareName(unit)));
+  const functions = units.filter(unit => !inChunk.has(unit) && unit.kind === 'Fn' && referenced.has(bareName(unit)));
+
+  const lines = [];
+  let used = 0;
+  const add = (text) => {
+    lines.push(text);
+    used += estimateTokens(text);
+  };
+  if (uses.length > 0) add(uses.join('\n'));
+  for (const unit of definitions) {
+    const text = used + estimateTokens(unit.text) <= budget ? unit.text : outline(unit);
+    add(`// ${unit.name} (line ${unit.node.loc.start.line})\n${text}`);
+  }
+  for (const unit of functions) {
+    const other = chunkOf(unit);
+    const where = other ? `, in part ${other.index}` : '';
+    const text = `// ${unit.name} (lines ${unit.node.loc.start.line}-${unit.endLine}${where})\n${outline(unit)}`;
+    if (used + estimateTokens(text) > budget) break;
+    add(text);
+  }
+  return lines.join('\n\n');
+}
+
+/**
+ * An item up to its body, e.g. `pub fn withdraw(&mut self, amount: u64) -> Result<()>` followed by an empty body
+ */
+function outline(unit) {
+  const text = unit.text;
+  const body = unit.node.body;
+  let head = text;
+  if (body && body.range) {
+    head = text.slice(0, body.range[0] - unit.node.range[0]);
+  } else {
+    const end = text.search(/[{;=]/);
+    if (end !== -1) head = text.slice(0, end);
+  }
+  return `${head.replace(/^\s*(#\[[^\]]*\]\s*|\/\/\/[^\n]*\n\s*)*/g, '').replace(/\s+/g, ' ').trim()} { /* ... */ }`;
+}
+
+/**
+ * Source of a chunk with its original line numbers; lines left out are marked with `...`
+ * @param {string} code - Rust source
+ * @param {Object} chunk - Chunk from chunkRustFile
+ * @param {Function} numberLine - Renders one line, `(number, text) => string`; the plain text when omitted
+ * @returns {string}
+ */
+function renderChunk(code, chunk, numberLine = (number, text) => text) {
+  const sourceLines = code.split('\n');
+  const output = [];
+  let previous = null;
+  for (const line of chunk.lines) {
+    if (previous !== null && line > previous + 1) output.push(numberLine('', '    // ...'));
+    output.push(numberLine(line, sourceLines[line - 1]));
+    previous = line;
+  }
+  return output.join('\n');
+}
+
+/**
+ * Short description of a chunk for prompts and reports, e.g. "part 2 of 3 (lines 120-260)" or, for the changed
+ * code of a diff audit, "the changed code (lines 40-75)"
+ */
+function describeChunk(chunk) {
+  const lines = `(lines ${chunk.startLine}-${chunk.endLine})`;
+  if (chunk.focus) {
+    return chunk.count === 1 ? `the changed code ${lines}` : `part ${chunk.index} of ${chunk.count} of the changed code ${lines}`;
+  }
+  return `part ${chunk.index} of ${chunk.count} ${lines}`;
+}
+
+// Headings every part of a report has; sections under them are never treated as duplicates
+const GENERIC_HEADINGS = /^(summary|executive summary|overview|introduction|conclusion|conclusions|recommendations|vulnerabilities|findings|exploitable vulnerabilities|data flow|assumptions|function explanations)$/;
+
+/**
+ * Merge the Markdown reports written for each chunk of a file into one report, a section per chunk
+ * @param {string} title - Title of the merged report
+ * @param {Array} parts - `{ chunk, text }` per chunk, in file order
+ * @param {Object} options - `dedupe`: drop sections whose heading an earlier part already had, e.g. the same
+ *   vulnerability reported from two chunks
+ * @returns {Object} - `{ markdown, duplicates }`
+ */
+function mergeChunkReports(title, parts, options = {}) {
+  const seen = new Set();
+  let duplicates = 0;
+  const sections = parts.map(({ chunk, text }) => {
+    const lines = shiftHeadings(text.trim(), 3).split('\n');
+    const kept = [];
+    let skipLevel = null;
+    let fenced = false;
+    for (const line of lines) {
+      if (/^\s*(```|~~~)/.test(line)) fenced = !fenced;
+      const heading = !fenced && line.match(/^(#{1,6})\s+(.*)$/);
+      if (heading && skipLevel !== null && heading[1].length <= skipLevel) skipLevel = null;
+      // The outermost headings are the parts' own titles; only the sections under them can repeat
+      if (heading && skipLevel === null && options.dedupe && heading[1].length > 3) {
+        const key = normalizeHeading(heading[2]);
+        if (key && !GENERIC_HEADINGS.test(key)) {
+          if (seen.has(key)) {
+            duplicates++;
+            skipLevel = heading[1].length;
+          } else {
+            seen.add(key);
+          }
+        }
+      }
+      if (skipLevel === null) kept.push(line);
+    }
+    return `## ${capitalize(describeChunk(chunk))}\n\n${kept.join('\n').trim()}`;
+  });
+  return { markdown: `# ${title}\n\n${sections.join('\n\n')}\n`, duplicates };
+}
+
+/**
+ * Move the headings of a Markdown text so that the outermost ones are at `level`
+ */
+function shiftHeadings(text, level) {
+  let fenced = false;
+  const lines = text.split('\n').map(line => {
+    if (/^\s*(```|~~~)/.test(line)) fenced = !fenced;
+    const heading = !fenced && line.match(/^(#{1,6})(\s.*)$/);
+    return heading ? { marks: heading[1].length, rest: heading[2] } : line;
+  });
+  const levels = lines.filter(line => typeof line !== 'string').map(line => line.marks);
+  const shift = levels.length > 0 ? level - Math.min(...levels) : 0;
+  return lines
+    .map(line => (typeof line === 'string' ? line : `${'#'.repeat(Math.min(6, Math.max(1, line.marks + shift)))}${line.rest}`))
+    .join('\n');
+}
+
+/**
+ * Heading text without numbering, confidence ratings, formatting and punctuation
+ */
+function normalizeHeading(text) {
+  return text
+    .toLowerCase()
+    .replace(/\(?\s*confidence[^)]*\)?/g, '')
+    .replace(/^\s*((vulnerability|finding|issue)\s*)?#?\d+[.:)]?\s*[-:]?\s*/, '')
+    .replace(/[^a-z0-9_:]+/g, ' ')
+    .trim();
+}
+
+function capitalize(text) {
+  return text[0].toUpperCase() + text.slice(1);
+}
+
+module.exports = {
+  DEFAULT_CHUNK_TOKENS,
+  chunkRustFile,
+  renderChunk,
+  describeChunk,
+  mergeChunkReports
+};
@@ -0,0 +1,449 @@
+const fs = require('fs');
+const path = require('path');
+const { parse, visit } = require('./rust-parser');
+const { moduleTree } = require('./cargo-workspace');
+
+// Default size of the combined crate context sent to the model
+const DEFAULT_CONTEXT_TOKENS = 60000;
+// Share of the budget kept for signatures of items that did not fit
+const OUTLINE_SHARE = 0.1;
+
+// Attributes that mark on-chain entry points across the supported frameworks
+const ENTRY_ATTRIBUTES = /^(program|entry_point|near_bindgen|near|ink::contract|ink|contract|cw_serde|public|init|payable|private|handler)$/;
+const STATE_FIELD_HINT = /balance|supply|owner|admin|authority|state|config|vault|pool|reserve|stake|reward|debt|collateral/i;
+
+/**
+ * Rough token estimate; models average about four characters per token on source code
+ * @param {string} text
+ * @returns {number}
+ */
+function estimateTokens(text) {
+  return Math.ceil(text.length / 4);
+}
+
+function isTestItem(item) {
+  return (item.attrs || []).some(attr =>
+    attr.path === 'test' || (attr.path === 'cfg' && /\btest\b/.test(attr.args || '')));
+}
+
+function hasEntryAttribute(item) {
+  return (item.attrs || []).some(attr => ENTRY_ATTRIBUTES.test(attr.path) || /^(ink|near)\b/.test(attr.text || ''));
+}
+
+/**
+ * Resolve the `use` declarations of one module into a map from local name to absolute path
+ * (`['crate', 'state', 'Vault']`) and a list of glob-imported module paths
+ */
+function resolveUses(items, modulePath, moduleNames) {
+  const imports = new Map();
+  const globs = [];
+  for (const item of items) {
+    if (item.type !== 'Use') continue;
+    for (const use of item.paths) {
+      const absolute = absolutePath(use.path, modulePath, moduleNames);
+      if (!absolute) continue;
+      if (use.glob) {
+        globs.push(absolute);
+      } else {
+        const last = use.path[use.path.length - 1];
+        // `use foo::{self}` imports `foo`
+        const local = use.alias || (last === 'self' ? use.path[use.path.length - 2] : last);
+        imports.set(local, last === 'self' ? absolute.slice(0, -1) : absolute);
+      }
+    }
+  }
+  return { imports, globs };
+}
+
+/**
+ * Turn a path as written in module `modulePath` into an absolute `crate::...` path.
+ * Returns null for paths into other crates.
+ */
+function absolutePath(segments, modulePath, moduleNames) {
+  if (segments.length === 0) return null;
+  const [first, ...rest] = segments;
+  if (first === 'crate') return ['crate', ...rest];
+  if (first === 'self') return ['crate', ...modulePath, ...rest];
+  if (first === 'super') {
+    const parent = modulePath.slice(0, -1);
+    return absolutePath(['self', ...rest], parent, moduleNames);
+  }
+  if (first === '') return null;
+  // 2018-edition paths may start with a child module of the current or root module
+  const local = [...modulePath, first].join('::');
+  if (moduleNames.has(local)) return ['crate', ...modulePath, ...segments];
+  if (moduleNames.has(first)) return ['crate', ...segments];
+  return null;
+}
+
+/**
+ * Collect the items of one module (recursing into inline modules)
+ */
+function collectModuleItems(items, context, out) {
+  const { file, relativeFile, code, modulePath } = context;
+  const moduleKey = ['crate', ...modulePath].join('::');
+
+  const push = (node, name, extra = {}) => {
+    const text = code.slice(node.range[0], node.range[1]);
+    out.push({
+      id: extra.id || `${moduleKey}::${name}`,
+      name,
+      kind: node.type,
+      node,
+      module: moduleKey,
+      modulePath,
+      file,
+      relativeFile,
+      startLine: node.loc.start.line,
+      endLine: node.loc.end.line,
+      text,
+      tokens: estimateTokens(text),
+      entry: Boolean(extra.entry),
+      impl: extra.impl || null,
+      references: new Set(),
+      score: 0
+    });
+  };
+
+  for (const item of items) {
+    if (isTestItem(item)) continue;
+    switch (item.type) {
+      case 'Fn':
+      case 'Struct':
+      case 'Union':
+      case 'Enum':
+      case 'Trait':
+      case 'Const':
+      case 'Static':
+      case 'TypeAlias':
+      case 'MacroRules':
+        push(item, item.name, { entry: item.type === 'Fn' && (context.entry || hasEntryAttribute(item)) });
+        break;
+      case 'Impl': {
+        const owner = (item.selfName || 'impl').replace(/<.*$/, '');
+        // Split impl blocks into methods so large impls can be packed partially
+        const header = code.slice(item.range[0], code.indexOf('{', item.range[0]) + 1).trim();
+        const impl = {
+          key: `${item.range[0]}:${relativeFile}`,
+          header,
+          traitName: item.traitName || null,
+          owner
+        };
+        const entry = context.entry || hasEntryAttribute(item);
+        if (item.items.length === 0) {
+          push(item, owner, { id: `${moduleKey}::${owner}#impl@${item.loc.start.line}`, impl });
+        }
+        for (const member of item.items) {
+          if (!member.range || isTestItem(member)) continue;
+          const name = member.name || member.type;
+          const id = item.traitName
+            ? `<${moduleKey}::${owner} as ${item.traitName}>::${name}`
+            : `${moduleKey}::${owner}::${name}`;
+          push(member, name, { id, impl, entry: member.type === 'Fn' && (entry || hasEntryAttribute(member)) });
+        }
+        break;
+      }
+      case 'Mod':
+        if (item.items) {
+          collectModuleItems(item.items, {
+            ...context,
+            modulePath: [...modulePath, item.name],
+            entry: context.entry || hasEntryAttribute(item)
+          }, out);
+        }
+        break;
+      default:
+        break;
+    }
+  }
+}
+
+/**
+ * Record the names an item refers to: paths in expressions, types and patterns, plus method names
+ */
+function collectReferences(item) {
+  const paths = [];
+  const methods = [];
+  visit(item.node, {
+    Path(node) {
+      paths.push(node.segments.map(segment => segment.name));
+    },
+    MethodCall(node) {
+      methods.push(node.method);
+    },
+    Macro(node) {
+      if (node.args) return;
+      // Unparsed macro bodies (e.g. `require!(...)`) still name the items they touch
+      for (const token of node.tokens || []) {
+        if (token.type === 'ident') paths.push([token.value]);
+      }
+    }
+  });
+  return { paths, methods };
+}
+
+/**
+ * Build the combined context of a crate target. Items are ranked by how central they are to the
+ * target's entry points and to cross-module interactions, then packed into the token budget.
+ * @param {Object} target - `{ crate, target: { kind, name, root }, files }` as produced by selectRustTargets,
+ *   or `{ crate, root }` for a bare crate root
+ * @param {Object} options - `contextTokens`: token budget (default 60000)
+ * @returns {Object} - `{ text, tokens, modules, included, outlined, omitted, crossReferences }`
+ */
+function buildCrateContext(target, options = {}) {
+  const budget = Number(options.contextTokens) || DEFAULT_CONTEXT_TOKENS;
+  const rootFile = target.root || (target.target && target.target.root) || target.files[0];
+  const baseDir = path.dirname(target.manifestPath || rootFile);
+
+  const modules = moduleTree(rootFile);
+  const moduleNames = new Set();
+  modules.forEach(module => {
+    for (let i = 1; i <= module.modulePath.length; i++) moduleNames.add(module.modulePath.slice(0, i).join('::'));
+  });
+
+  // Parse every module and collect its items
+  const items = [];
+  const scopes = new Map();
+  const parseErrors = [];
+  for (const module of modules) {
+    const relativeFile = path.relative(baseDir, module.file) || path.basename(module.file);
+    const code = fs.readFileSync(module.file, 'utf8');
+    let ast;
+    try {
+      ast = parse(code);
+    } catch (error) {
+      parseErrors.push({ file: relativeFile, message: error.message });
+      continue;
+    }
+
+    const collectScopes = (moduleItems, modulePath) => {
+      scopes.set(['crate', ...modulePath].join('::'), resolveUses(moduleItems, modulePath, moduleNames));
+      moduleItems.filter(item => item.type === 'Mod' && item.items).forEach(item => {
+        moduleNames.add([...modulePath, item.name].join('::'));
+        collectScopes(item.items, [...modulePath, item.name]);
+      });
+    };
+    collectScopes(ast.items, module.modulePath);
+
+    collectModuleItems(ast.items, {
+      file: module.file,
+      relativeFile,
+      code,
+      modulePath: module.modulePath,
+      // Public functions of the crate root are the target's entry points
+      entry: false
+    }, items);
+    items
+      .filter(item => item.file === module.file && module.modulePath.length === 0 && item.kind === 'Fn' && !item.impl && item.node.visibility)
+      .forEach(item => { item.entry = true; });
+  }
+
+  // Index items by absolute path and by bare name
+  const byId = new Map(items.map(item => [item.id, item]));
+  const byName = new Map();
+  for (const item of items) {
+    if (!byName.has(item.name)) byName.set(item.name, []);
+    byName.get(item.name).push(item);
+  }
+  const ownerItems = (module, owner) => items.filter(item => item.module === module && (item.name === owner || (item.impl && item.impl.owner === owner)));
+
+  const resolve = (item, segments) => {
+    const scope = scopes.get(item.module) || { imports: new Map(), globs: [] };
+    const candidates = [];
+    const head = segments[0];
+    if (scope.imports.has(head)) candidates.push([...scope.imports.get(head), ...segments.slice(1)]);
+    const absolute = absolutePath(segments, item.modulePath, moduleNames);
+    if (absolute) candidates.push(absolute);
+    candidates.push(['crate', ...item.modulePath, ...segments]);
+    scope.globs.forEach(glob => candidates.push([...glob, ...segments]));
+
+    for (const candidate of candidates) {
+      const id = candidate.join('::');
+      if (byId.has(id)) return [byId.get(id)];
+      // A type name pulls in its impl blocks
+      const module = candidate.slice(0, -1).join('::');
+      const owned = ownerItems(module, candidate[candidate.length - 1]);
+      if (owned.length > 0) return owned;
+    }
+    return [];
+  };
+
+  // Link items through the paths and methods they use
+  for (const item of items) {
+    const { paths, methods } = collectReferences(item);
+    for (const segments of paths) {
+      if (segments.length === 1 && segments[0] === item.name) continue;
+      resolve(item, segments).forEach(target => {
+        if (target !== item) item.references.add(target);
+      });
+    }
+    // Method calls are resolved by name; the receiver type is rarely known without full inference
+    for (const method of methods) {
+      (byName.get(method) || [])
+        .filter(target => target.impl && target !== item)
+        .forEach(target => item.references.add(target));
+    }
+  }
+
+  // Score: entry points and state-holding types seed the ranking, which then flows along references
+  const referrers = new Map(items.map(item => [item, new Set()]));
+  items.forEach(item => item.references.forEach(target => referrers.get(target).add(item)));
+
+  const base = new Map();
+  for (const item of items) {
+    let score = 1;
+    if (item.entry) score += 4;
+    if (item.kind === 'Fn' && item.node.visibility) score += 1;
+    if (item.kind === 'Fn' && item.node.selfParam && item.node.selfParam.mutable) score += 2;
+    if ((item.kind === 'Struct' || item.kind === 'Enum') && STATE_FIELD_HINT.test(item.text)) score += 1;
+    const files = new Set([...referrers.get(item)].map(referrer => referrer.file).filter(file => file !== item.file));
+    score += Math.min(files.size, 3) * 2;
+    base.set(item, score);
+  }
+
+  let scores = new Map(base);
+  for (let iteration = 0; iteration < 5; iteration++) {
+    const next = new Map(base);
+    for (const item of items) {
+      if (item.references.size === 0) continue;
+      const share = (0.6 * scores.get(item)) / item.references.size;
+      item.references.forEach(target => next.set(target, next.get(target) + share));
+    }
+    scores = next;
+  }
+  items.forEach(item => { item.score = scores.get(item); });
+
+  // Pack the best-ranked items; whatever does not fit is outlined by its signature while space remains
+  const overhead = 400 + modules.length * 20;
+  const fullBudget = Math.max(0, budget - overhead - Math.floor(budget * OUTLINE_SHARE));
+  const ranked = [...items].sort((a, b) => b.score - a.score || a.tokens - b.tokens);
+  const included = new Set();
+  let used = 0;
+  for (const item of ranked) {
+    if (used + item.tokens <= fullBudget) {
+      included.add(item);
+      used += item.tokens;
+    }
+  }
+  const outlined = new Set();
+  let outlineUsed = 0;
+  const outlineBudget = budget - overhead - used;
+  for (const item of ranked) {
+    if (included.has(item)) continue;
+    const signature = signatureOf(item);
+    const cost = estimateTokens(signature) + 8;
+    if (outlineUsed + cost > outlineBudget) continue;
+    outlined.add(item);
+    outlineUsed += cost;
+  }
+
+  // Cross-module references between included items, for the model and for the report
+  const crossReferences = [];
+  for (const item of items) {
+    const targets = [...item.references].filter(target => target.module !== item.module);
+    if (targets.length > 0 && (included.has(item) || outlined.has(item))) {
+      crossReferences.push({ from: item.id, to: [...new Set(targets.map(target => target.id))] });
+    }
+  }
+
+  const text = renderContext({ target, modules, baseDir, items, included, outlined, crossReferences, parseErrors });
+  return {
+    text,
+    tokens: estimateTokens(text),
+    budget,
+    modules: modules.map(module => ({
+      module: ['crate', ...module.modulePath].join('::'),
+      file: path.relative(baseDir, module.file) || path.basename(module.file)
+    })),
+    included: ranked.filter(item => included.has(item)).map(item => item.id),
+    outlined: ranked.filter(item => outlined.has(item)).map(item => item.id),
+    omitted: ranked.filter(item => !included.has(item) && !outlined.has(item)).map(item => item.id),
+    crossReferences,
+    parseErrors
+  };
+}
+
+/**
+ * First line of an item up to its body, e.g. `pub fn withdraw(&mut self, amount: u64) -> Result<()>`
+ */
+function signatureOf(item) {
+  const body = item.node.body;
+  let text = item.text;
+  if (body && body.range) {
+    text = text.slice(0, body.range[0] - item.node.range[0]);
+  } else {
+    const end = text.search(/[{;=]/);
+    if (end !== -1) text = text.slice(0, end);
+  }
+  return text.replace(/^\s*(#\[[^\]]*\]\s*|\/\/\/[^\n]*\n\s*)*/g, '').replace(/\s+/g, ' ').trim();
+}
+
+function renderContext({ target, modules, baseDir, items, included, outlined, crossReferences, parseErrors }) {
+  const label = target.crate
+    ? `${target.crate}${target.target ? ` (${target.target.kind} target \`${target.target.name}\`)` : ''}`
+    : path.basename(target.root || target.files[0]);
+  const lines = [];
+  lines.push(`// ===== Crate ${label} =====`);
+  lines.push('// Module map:');
+  for (const module of modules) {
+    lines.push(`//   ${['crate', ...module.modulePath].join('::')} -> ${path.relative(baseDir, module.file) || path.basename(module.file)}`);
+  }
+  if (crossReferences.length > 0) {
+    lines.push('// Cross-module references:');
+    crossReferences.forEach(reference => lines.push(`//   ${reference.from} -> ${reference.to.join(', ')}`));
+  }
+  if (parseErrors.length > 0) {
+    lines.push('// Files that could not be parsed and are missing from this context:');
+    parseErrors.forEach(error => lines.push(`//   ${error.file}: ${error.message}`));
+  }
+  const omitted = items.filter(item => !included.has(item) && !outlined.has(item));
+  if (omitted.length > 0) {
+    lines.push(`// ${omitted.length} lower-ranked items were left out to fit the token budget`);
+  }
+  lines.push('');
+
+  // Emit files in module order and items in source order
+  for (const module of modules) {
+    const moduleItems = items
+      .filter(item => item.file === module.file && (included.has(item) || outlined.has(item)))
+      .sort((a, b) => a.node.range[0] - b.node.range[0]);
+    if (moduleItems.length === 0) continue;
+
+    lines.push(`// ----- ${moduleItems[0].relativeFile} (${['crate', ...module.modulePath].join('::')}) -----`);
+    let openImpl = null;
+    const closeImpl = () => {
+      if (openImpl) lines.push('}', '');
+      openImpl = null;
+    };
+    for (const item of moduleItems) {
+      if (!item.impl || (openImpl && openImpl.key !== item.impl.key)) closeImpl();
+      if (item.impl && !openImpl && item.kind !== 'Impl') {
+        lines.push(item.impl.header);
+        openImpl = item.impl;
+      }
+      if (included.has(item)) {
+        lines.push(`${openImpl ? '    ' : ''}// ${item.id} (${item.relativeFile}:${item.startLine}-${item.endLine})`);
+        lines.push(openImpl ? indent(item.text) : item.text);
+      } else {
+        lines.push(`${openImpl ? '    ' : ''}// ${item.id} (${item.relativeFile}:${item.startLine}-${item.endLine}), body omitted`);
+        lines.push(`${openImpl ? '    ' : ''}${signatureOf(item)} { /* ... */ }`);
+      }
+      if (!openImpl) lines.push('');
+    }
+    closeImpl();
+  }
+
+  return lines.join('\n');
+}
+
+function indent(text) {
+  const lines = text.split('\n');
+  // Items sliced from an impl keep the indentation of their continuation lines; only the first line needs it
+  return ['    ' + lines[0], ...lines.slice(1)].join('\n');
+}
+
+module.exports = {
+  DEFAULT_CONTEXT_TOKENS,
+  estimateTokens,
+  buildCrateContext
+};
@@ -1,27 +1,89 @@
 const fs = require('fs');
 const path = require('path');
-const Anthropic = require('@anthropic-ai/sdk');
 const ora = require('ora');
+const { completeResult, isLLMAvailable, modelName } = require('./llm-provider');
+const { withCacheNote } = require('./llm-cache');
+const { parse } = require('./rust-parser');
+const { collectDefinitions } = require('./rust-ast');
+const {
+  aggregateEdges,
+  buildCallGraph,
+  buildCrateCallGraph,
+  callGraphToDot,
+  callGraphToJson,
+  findEntryPoints
+} = require('./rust-callgraph');
 
-const anthropic = new Anthropic({
-  apiKey: process.env.ANTHROPIC_API_KEY,
-});
+// Version of the architecture diagram prompt, part of the response cache key
+const DIAGRAM_PROMPT_VERSION = 1;
 
 /**
- * Generates a diagram of the Rust code
+ * Generates diagrams of the Rust code. DOT and Mermaid class diagrams and a call graph (DOT and JSON) are built
+ * locally from the syntax tree; an AI-drawn architecture diagram is added when a model provider is available.
  * @param {string} filePath - Path to the Rust file
  * @param {string} outputDir - Directory to save diagram results
+ * @param {Object} options - `reportName`: name of the diagram files (default: the file name, see reportName)
+ * @returns {Object} - Paths of the generated files
  */
-async function generateRustDiagram(filePath, outputDir) {
+async function generateRustDiagram(filePath, outputDir, options = {}) {
   const spinner = ora('Generating Rust code diagram...').start();
   
   try {
     // Read the code
     const code = fs.readFileSync(filePath, 'utf8');
     const fileName = path.basename(filePath);
+    const reportName = options.reportName || fileName;
+    const dotOutputPath = path.join(outputDir, `${reportName}.dot`);
+    const callGraphOutputPath = path.join(outputDir, `${reportName}.callgraph.dot`);
+    const callGraphJsonOutputPath = path.join(outputDir, `${reportName}.callgraph.json`);
+    const mermaidOutputPath = path.join(outputDir, `${reportName}.mermaid.md`);
     
-    // Prompt for Claude 3.7
-    const prompt = `
+    // Parser-based diagrams, no API key required
+    try {
+      const model = buildTypeModel(code);
+      fs.writeFileSync(dotOutputPath, generateRustDotDiagram(model, fileName));
+      fs.writeFileSync(callGraphOutputPath, generateRustCallGraphDot(model, fileName));
+      fs.writeFileSync(callGraphJsonOutputPath, JSON.stringify(callGraphToJson(model.callGraph), null, 2));
+      fs.writeFileSync(mermaidOutputPath, generateRustMermaidDiagram(model, fileName));
+    } catch (parseError) {
+      console.error(`Error generating Rust diagrams: ${parseError.message}`);
+      // Create simple fallback diagrams if the source cannot be parsed
+      fs.writeFileSync(dotOutputPath, `digraph G {\n  label=${dotString(`Rust: ${fileName}`)};\n  node [shape=box];\n  ${dotString(fileName)} [fillcolor=lightblue, style=filled];\n}\n`);
+      fs.writeFileSync(callGraphOutputPath, `digraph CallGraph {\n  label=${dotString(`Call graph: ${fileName} (parse failed)`)};\n}\n`);
+      fs.writeFileSync(callGraphJsonOutputPath, JSON.stringify({ entryPoints: [], nodes: [], edges: [], error: parseError.message }, null, 2));
+      fs.writeFileSync(mermaidOutputPath, `# Rust Code Diagram: ${fileName}\n\nThe file could not be parsed: ${parseError.message}\n`);
+    }
+    const outputPaths = { dotOutputPath, callGraphOutputPath, callGraphJsonOutputPath, mermaidOutputPath };
+    
+    // AI architecture diagram, only when a model provider is available
+    if (isLLMAvailable()) {
+      try {
+        spinner.text = `Generating Rust architecture diagram with ${modelName()}...`;
+        outputPaths.aiDiagramPath = await generateAIRustDiagram(code, fileName, outputDir, reportName);
+      } catch (apiError) {
+        console.error('Error generating AI Rust diagram:', apiError.message);
+      }
+    }
+    
+    spinner.succeed(`Rust diagrams generated! Saved to:\n${Object.values(outputPaths).map(p => `- ${p}`).join('\n')}`);
+    return outputPaths;
+  } catch (error) {
+    spinner.fail('Diagram generation failed');
+    throw error;
+  }
+}
+
+/**
+ * Generates an architecture diagram of the Rust code using the configured LLM
+ * @param {string} code - Rust source code
+ * @param {string} fileName - Name of the file
+ * @param {string} outputDir - Directory to save diagram results
+ * @param {string} reportName - Name of the diagram file (default: the file name)
+ * @returns {string} - Path to the generated diagram
+ */
+async function generateAIRustDiagram(code, fileName, outputDir, reportName = fileName) {
+  // Prompt for the configured LLM
+  const prompt = `
 Analyze the following Rust Web3/blockchain code and create a detailed Mermaid diagram that visually represents:
 1. Struct and enum definitions
 2. Function relationships and call flows
@@ -60,48 +122,290 @@ Format your response as:
 \`\`\`
 `;
 
-    // Check if API key is available
-    if (!process.env.ANTHROPIC_API_KEY) {
-      spinner.fail('No API key found');
-      throw new Error('ANTHROPIC_API_KEY not found in environment variables');
+  const response = await completeResult(prompt, "You are an expert in Rust and software architecture visualization. Generate accurate, clear mermaid diagrams that represent the structure and flow of Rust code.", { template: 'rust-diagram', version: DIAGRAM_PROMPT_VERSION, source: code });
+
+  // Save the response to a file
+  const outputPath = path.join(outputDir, `${reportName}.rust-diagram.md`);
+  fs.writeFileSync(outputPath, withCacheNote(response.text, [response]));
+  return outputPath;
+}
+
+/**
+ * Collect the types of a Rust file with their fields, variants, methods and implemented traits
+ * @param {string} code - Rust source code
+ * @returns {Object} - `{ types, functions, callGraph }`; types map names to
+ *   `{ name, kind, generics, fields, variants, methods, traits }`
+ */
+function buildTypeModel(code) {
+  const ast = parse(code);
+  const definitions = collectDefinitions(ast);
+  const types = new Map();
+
+  const ensureType = (name, kind) => {
+    if (!types.has(name)) {
+      types.set(name, { name, kind, generics: [], fields: [], variants: [], methods: [], traits: [] });
     }
+    return types.get(name);
+  };
 
-    try {
-      // Call Claude 3.7 API
-      const response = await anthropic.messages.create({
-        model: "claude-3-sonnet-20240229",
-        max_tokens: 4000,
-        messages: [
-          { 
-            role: "user", 
-            content: prompt 
-          }
-        ],
-        system: "You are an expert in Rust and software architecture visualization. Generate accurate, clear mermaid diagrams that represent the structure and flow of Rust code."
-      });
-
-      // Validate response format
-      if (!response || !response.content || !Array.isArray(response.content) || response.content.length === 0) {
-        spinner.fail('Invalid API response');
-        throw new Error('Invalid response format from Anthropic API');
-      }
+  for (const [name, struct] of definitions.structs) {
+    const type = ensureType(name, struct.node.type === 'Union' ? 'union' : 'struct');
+    type.generics = genericNames(struct.node.generics);
+    type.fields = struct.node.fields.map(field => ({
+      name: field.name,
+      type: field.fieldType.text,
+      visibility: field.visibility
+    }));
+  }
+  for (const [name, enumDef] of definitions.enums) {
+    const type = ensureType(name, 'enum');
+    type.generics = genericNames(enumDef.node.generics);
+    type.variants = enumDef.node.variants.map(variant => ({
+      name: variant.name,
+      fields: variant.fields.map(field => field.fieldType.text)
+    }));
+  }
+  for (const [name, trait] of definitions.traits) {
+    const type = ensureType(name, 'trait');
+    type.generics = genericNames(trait.node.generics);
+    type.methods = trait.node.items.filter(item => item.type === 'Fn').map(fn => describeMethod(fn, null, 'pub'));
+  }
+  for (const impl of definitions.impls) {
+    const { node } = impl;
+    if (!node.selfName) continue;
+    // Types from other files or crates still appear, without fields
+    const type = ensureType(node.selfName, 'external');
+    if (node.traitName && !type.traits.includes(node.traitName)) type.traits.push(node.traitName);
+    node.items
+      .filter(item => item.type === 'Fn')
+      .forEach(fn => type.methods.push(describeMethod(fn, node.traitName, node.traitName ? 'pub' : fn.visibility)));
+  }
+
+  return {
+    types,
+    functions: definitions.functions.filter(fn => !fn.owner),
+    callGraph: buildCallGraph(ast, definitions)
+  };
+}
+
+function genericNames(generics) {
+  return generics ? generics.params.filter(param => param.kind !== 'lifetime').map(param => param.name) : [];
+}
+
+function describeMethod(fn, trait, visibility) {
+  return {
+    name: fn.name,
+    params: fn.params.map(param => param.name || (param.pattern ? param.pattern.text : '_')),
+    receiver: fn.selfParam ? `${fn.selfParam.reference ? '&' : ''}${fn.selfParam.mutable ? 'mut ' : ''}self` : null,
+    returnType: fn.returnType ? fn.returnType.text : null,
+    visibility,
+    trait
+  };
+}
+
+/**
+ * Local types referenced by a type expression, e.g. `Vec<Account>` -> `Account`
+ */
+function referencedTypes(typeText, types, self) {
+  const names = typeText.match(/[A-Za-z_][A-Za-z0-9_]*/g) || [];
+  return [...new Set(names)].filter(name => name !== self && types.has(name) && types.get(name).kind !== 'trait');
+}
+
+/**
+ * Quote a DOT identifier or string
+ */
+function dotString(text) {
+  return `"${String(text).replace(/\\/g, '\\\\').replace(/"/g, '\\"')}"`;
+}
+
+/**
+ * Escape text for a DOT record label, where braces, pipes and angle brackets are structural
+ */
+function recordText(text) {
+  return String(text).replace(/([{}|<>\\"])/g, '\\$1');
+}
+
+function visibilitySymbol(visibility) {
+  if (!visibility) return '-';
+  return visibility === 'pub' ? '+' : '~';
+}
 
-      // Save the response to a file
-      const outputPath = path.join(outputDir, `${fileName}.rust-diagram.md`);
-      fs.writeFileSync(outputPath, response.content[0].text);
-      
-      spinner.succeed(`Rust diagram generation complete! Saved to: ${outputPath}`);
-      return outputPath;
-    } catch (apiError) {
-      spinner.fail('API call failed');
-      throw apiError;
+function methodLabel(method) {
+  const params = method.params.join(', ');
+  const returns = method.returnType ? ` -> ${method.returnType}` : '';
+  const trait = method.trait ? ` [${method.trait}]` : '';
+  return `${visibilitySymbol(method.visibility)} ${method.name}(${params})${returns}${trait}`;
+}
+
+/**
+ * Generates a DOT class diagram: one record per struct, enum and trait with fields, variants and
+ * methods; edges for trait implementations and fields holding other local types
+ * @param {Object} model - Result of buildTypeModel
+ * @param {string} fileName - Name of the file, used as the graph label
+ * @returns {string} - DOT graph representation
+ */
+function generateRustDotDiagram(model, fileName) {
+  const lines = [
+    'digraph G {',
+    `  label=${dotString(`Rust types: ${fileName}`)};`,
+    '  rankdir=BT;',
+    '  node [shape=record, fontname="Arial"];',
+    '  edge [fontname="Arial Italic", fontsize=10];'
+  ];
+
+  const fillColors = { struct: 'lightblue', union: 'lightblue', enum: 'lightyellow', trait: 'lightgreen', external: 'lightgrey' };
+  for (const type of model.types.values()) {
+    const generics = type.generics.length > 0 ? `<${type.generics.join(', ')}>` : '';
+    const sections = [`«${type.kind}»\\n${recordText(type.name + generics)}`];
+    if (type.kind === 'enum') {
+      sections.push(type.variants.map(variant => recordText(variant.fields.length > 0 ? `${variant.name}(${variant.fields.join(', ')})` : variant.name) + '\\l').join(''));
+    } else if (type.kind !== 'trait') {
+      sections.push(type.fields.map(field => recordText(`${visibilitySymbol(field.visibility)} ${field.name}: ${field.type}`) + '\\l').join(''));
     }
+    sections.push(type.methods.map(method => recordText(methodLabel(method)) + '\\l').join(''));
+    lines.push(`  ${dotString(type.name)} [label="{${sections.join('|')}}", style=filled, fillcolor=${fillColors[type.kind]}];`);
+  }
+
+  for (const type of model.types.values()) {
+    type.traits.forEach(trait => {
+      if (!model.types.has(trait)) lines.push(`  ${dotString(trait)} [label="{«trait»\\n${recordText(trait)}}", style=filled, fillcolor=white];`);
+      lines.push(`  ${dotString(type.name)} -> ${dotString(trait)} [style=dashed, arrowhead=empty, label="implements"];`);
+    });
+    const held = new Set();
+    type.fields.forEach(field => referencedTypes(field.type, model.types, type.name).forEach(name => held.add(name)));
+    type.variants.forEach(variant => variant.fields.forEach(field => referencedTypes(field, model.types, type.name).forEach(name => held.add(name))));
+    held.forEach(name => lines.push(`  ${dotString(type.name)} -> ${dotString(name)} [arrowhead=odiamond, label="holds"];`));
+  }
+
+  lines.push('}');
+  return lines.join('\n') + '\n';
+}
+
+/**
+ * Generates a DOT call graph of method-to-method and function calls within the file
+ * @param {Object} model - Result of buildTypeModel
+ * @param {string} fileName - Name of the file, used as the graph label
+ * @returns {string} - DOT graph representation
+ */
+function generateRustCallGraphDot(model, fileName) {
+  return callGraphToDot(model.callGraph, `Call graph: ${fileName}`);
+}
+
+/**
+ * Generates the call graph of a whole crate target as DOT and JSON, with entry points and reachability
+ * @param {Object} group - Crate target as produced by selectRustTargets
+ * @param {string} outputDir - Directory to save the graph
+ * @param {Object} options - `entryPoints`: function names to use as entry points (default: detected)
+ * @returns {Object} - `{ dotOutputPath, jsonOutputPath }`
+ */
+async function generateRustCrateCallGraph(group, outputDir, options = {}) {
+  const spinner = ora(`Building the call graph of crate ${group.crate}...`).start();
+  
+  try {
+    const graph = buildCrateCallGraph(group);
+    const entryPoints = findEntryPoints(graph, options.entryPoints);
+    const baseName = `${group.crate}.${group.target.name}`;
+    const dotOutputPath = path.join(outputDir, `${baseName}.callgraph.dot`);
+    const jsonOutputPath = path.join(outputDir, `${baseName}.callgraph.json`);
+    
+    fs.writeFileSync(dotOutputPath, callGraphToDot(graph, `Call graph: crate ${group.crate} (${group.target.kind} ${group.target.name})`, { entryPoints }));
+    const json = callGraphToJson(graph, { entryPoints, baseDir: path.dirname(group.manifestPath || group.target.root) });
+    json.crate = { name: group.crate, target: group.target };
+    json.parseErrors = graph.parseErrors;
+    fs.writeFileSync(jsonOutputPath, JSON.stringify(json, null, 2));
+    
+    spinner.succeed(`Crate call graph generated (${graph.nodes.size} functions, ${entryPoints.length} entry points)! Saved to:\n- ${dotOutputPath}\n- ${jsonOutputPath}`);
+    return { dotOutputPath, jsonOutputPath };
   } catch (error) {
-    spinner.fail('Diagram generation failed');
+    spinner.fail('Crate call graph generation failed');
     throw error;
   }
 }
 
+/**
+ * Mermaid type text: generics use `~T~`, parentheses would be read as a method signature and
+ * lifetimes are dropped
+ */
+function mermaidType(text) {
+  return String(text)
+    .replace(/'\w+\s*,?\s*/g, '')
+    .replace(/<\s*>/g, '')
+    .replace(/->/g, '→')
+    .replace(/\(\)/g, 'unit')
+    .replace(/[()]/g, match => (match === '(' ? '[' : ']'))
+    .replace(/[<>]/g, '~')
+    .replace(/\s+/g, ' ');
+}
+
+function mermaidId(text) {
+  return text.replace(/[^A-Za-z0-9_]/g, '_');
+}
+
+/**
+ * Generates Mermaid class and call graph diagrams
+ * @param {Object} model - Result of buildTypeModel
+ * @param {string} fileName - Name of the file
+ * @returns {string} - Markdown with Mermaid diagrams
+ */
+function generateRustMermaidDiagram(model, fileName) {
+  let output = `# Rust Code Diagram: ${fileName}\n\n`;
+  output += `*Generated from the syntax tree on: ${new Date().toISOString()}*\n\n`;
+
+  output += `## Types\n\n\`\`\`mermaid\nclassDiagram\n`;
+  for (const type of model.types.values()) {
+    const generics = type.generics.length > 0 ? `~${type.generics.join(', ')}~` : '';
+    output += `  class ${mermaidId(type.name)}${generics} {\n`;
+    if (type.kind !== 'struct') output += `    <<${type.kind === 'enum' ? 'enumeration' : type.kind}>>\n`;
+    type.fields.forEach(field => {
+      output += `    ${visibilitySymbol(field.visibility)}${mermaidType(field.type)} ${field.name}\n`;
+    });
+    type.variants.forEach(variant => {
+      output += `    ${variant.name}${variant.fields.length > 0 ? ` ${mermaidType(variant.fields.join(', '))}` : ''}\n`;
+    });
+    type.methods.forEach(method => {
+      const returns = method.returnType ? ` ${mermaidType(method.returnType)}` : '';
+      output += `    ${visibilitySymbol(method.visibility)}${method.name}(${method.params.join(', ')})${returns}\n`;
+    });
+    output += '  }\n';
+  }
+  for (const type of model.types.values()) {
+    type.traits.forEach(trait => {
+      output += `  ${mermaidId(trait)} <|.. ${mermaidId(type.name)} : implements\n`;
+    });
+    const held = new Set();
+    type.fields.forEach(field => referencedTypes(field.type, model.types, type.name).forEach(name => held.add(name)));
+    type.variants.forEach(variant => variant.fields.forEach(field => referencedTypes(field, model.types, type.name).forEach(name => held.add(name))));
+    held.forEach(name => {
+      output += `  ${mermaidId(type.name)} o-- ${mermaidId(name)}\n`;
+    });
+  }
+  output += '```\n\n';
+
+  output += `## Call Graph\n\n\`\`\`mermaid\nflowchart LR\n`;
+  const { nodes, edges } = model.callGraph;
+  for (const node of nodes.values()) {
+    output += `  ${mermaidId(node.id)}["${node.id}"]\n`;
+  }
+  for (const edge of aggregateEdges(edges)) {
+    output += `  ${mermaidId(edge.from)} -->${edge.count > 1 ? `|${edge.count}x|` : ''} ${mermaidId(edge.to)}\n`;
+  }
+  output += '```\n';
+
+  if (model.functions.length > 0) {
+    output += `\n## Free Functions\n\n`;
+    model.functions.forEach(fn => {
+      output += `- \`${fn.qualifiedName}\`\n`;
+    });
+  }
+
+  return output;
+}
+
 module.exports = {
-  generateRustDiagram
+  generateRustDiagram,
+  generateRustCrateCallGraph,
+  buildTypeModel,
+  generateRustDotDiagram,
+  generateRustCallGraphDot,
+  generateRustMermaidDiagram
 }; 
\ No newline at end of file
@@ -0,0 +1,278 @@
+const { parse, tokenize } = require('./rust-parser');
+const { collectDefinitions, signatureOf, nodeText } = require('./rust-ast');
+
+/**
+ * Grounding of model-written reports in the Rust source they describe. Every identifier, function signature,
+ * quoted snippet and line number a report cites is looked up in the parsed source; claims that cannot be found
+ * are marked unverified, and each finding (or report section) gets a grounding score: the share of its checked
+ * claims that were found.
+ */
+
+// Names a report may mention without them appearing in the audited code
+const KNOWN_NAMES = new Set([
+  'self', 'Self', 'super', 'crate', 'mut', 'ref', 'pub', 'fn', 'impl', 'struct', 'enum', 'trait', 'let', 'match',
+  'if', 'else', 'for', 'while', 'loop', 'return', 'unsafe', 'async', 'await', 'move', 'const', 'static', 'true', 'false',
+  'u8', 'u16', 'u32', 'u64', 'u128', 'usize', 'i8', 'i16', 'i32', 'i64', 'i128', 'isize', 'f32', 'f64', 'bool', 'char', 'str',
+  'String', 'Vec', 'Option', 'Some', 'None', 'Result', 'Ok', 'Err', 'Box', 'Rc', 'Arc', 'RefCell', 'Cell', 'HashMap',
+  'HashSet', 'BTreeMap', 'BTreeSet', 'std', 'core', 'alloc',
+  'unwrap', 'expect', 'unwrap_or', 'unwrap_or_default', 'clone', 'iter', 'into', 'from', 'len', 'is_empty', 'push',
+  'insert', 'remove', 'get', 'get_mut', 'contains_key', 'ok_or', 'map_err', 'as_ref', 'to_string', 'default',
+  'panic', 'assert', 'assert_eq', 'assert_ne', 'require', 'unreachable', 'todo', 'unimplemented', 'msg', 'println', 'format'
+]);
+
+// Lines that propose new code rather than describe the existing code
+const PROPOSAL_CONTEXT = /\b(fix(es|ed)?|recommend\w*|suggest\w*|mitigat\w*|remediat\w*|instead|replace\w*|correct(ed)? version|patch(ed)?)\b/i;
+
+const IDENTIFIER_CLAIM = /^[A-Za-z_]\w*(?:(?:::|\.)[A-Za-z_]\w*)*(?:!|\(\))?$/;
+const SIGNATURE_CLAIM = /^(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern\s+"[^"]*")\s+)*fn\s+([A-Za-z_]\w*)/;
+const LINE_CLAIM = /\b[Ll]ines?\s+(\d+)(?:\s*(?:-|–|to)\s*(\d+))?|\bL(\d+)(?:\s*-\s*L?(\d+))?\b/g;
+
+/**
+ * Index the Rust source a report is about
+ * @param {Array} sources - `{ file, code }` of every file the report may cite
+ * @returns {Object} - Identifiers, functions (with normalized declarations), normalized lines and line counts
+ */
+function buildSourceIndex(sources) {
+  const index = { identifiers: new Set(), functions: [], lines: new Set(), lineCounts: [] };
+
+  sources.forEach(({ file, code }) => {
+    const lines = code.split('\n');
+    index.lineCounts.push(lines.length);
+    lines.forEach(line => {
+      const normalized = normalizeCode(line);
+      if (normalized) index.lines.add(normalized);
+    });
+
+    try {
+      tokenize(code).tokens.filter(token => token.type === 'ident').forEach(token => index.identifiers.add(token.value));
+    } catch (error) {
+      (code.match(/[A-Za-z_]\w*/g) || []).forEach(name => index.identifiers.add(name));
+    }
+    try {
+      collectDefinitions(parse(code)).functions.forEach(fn => index.functions.push({
+        name: fn.name,
+        qualifiedName: fn.qualifiedName,
+        file,
+        declaration: normalizeCode(nodeText(code, signatureOf(fn.node)))
+      }));
+    } catch (error) {
+      // Unparseable files still contribute identifiers and lines
+    }
+  });
+
+  return index;
+}
+
+// Compare code regardless of formatting and comments
+function normalizeCode(text) {
+  return text.replace(/\/\/.*$/gm, '').replace(/\s+/g, '');
+}
+
+/**
+ * Find the code claims of a piece of report text: fenced Rust blocks, inline code spans and line numbers
+ * @param {string} text - Markdown
+ * @returns {Array} - `{ kind, text, start, end, proposed }` where kind is `identifier`, `signature`, `snippet` or
+ *   `line`, start/end are offsets into the text, and proposed claims are suggested code (not checked)
+ */
+function extractClaims(text) {
+  const claims = [];
+  const fenced = [];
+  const proposedAt = (offset) => {
+    // The line of the claim, or for a block the closest non-empty line before it
+    const before = text.slice(0, offset).split('\n');
+    const lineStart = before.pop();
+    const context = lineStart.trim() ? lineStart + text.slice(offset).split('\n')[0] : before.reverse().find(line => line.trim()) || '';
+    return PROPOSAL_CONTEXT.test(context);
+  };
+
+  for (const match of text.matchAll(/```([\w+-]*)[^\n]*\n([\s\S]*?)```/g)) {
+    fenced.push([match.index, match.index + match[0].length]);
+    if (match[1] && match[1] !== 'rust' && match[1] !== 'rs') continue;
+    claims.push({ kind: 'snippet', text: match[2], start: match.index, end: match.index + match[0].length, proposed: proposedAt(match.index) });
+  }
+  const inFence = (offset) => fenced.some(([start, end]) => offset >= start && offset < end);
+
+  for (const match of text.matchAll(/`([^`\n]+)`/g)) {
+    if (inFence(match.index)) continue;
+    const code = match[1].trim();
+    const kind = SIGNATURE_CLAIM.test(code) ? 'signature'
+      : IDENTIFIER_CLAIM.test(code) ? 'identifier'
+        : /[=;(){}[\]<>+\-*/&|.!?]/.test(code) && code.length > 3 ? 'snippet' : null;
+    if (kind) claims.push({ kind, text: code, start: match.index, end: match.index + match[0].length, proposed: proposedAt(match.index) });
+  }
+
+  for (const match of text.matchAll(LINE_CLAIM)) {
+    if (inFence(match.index)) continue;
+    const start = Number(match[1] || match[3]);
+    const end = Number(match[2] || match[4] || start);
+    claims.push({ kind: 'line', text: match[0], lines: { start, end }, start: match.index, end: match.index + match[0].length, proposed: false });
+  }
+
+  return claims.sort((a, b) => a.start - b.start);
+}
+
+/**
+ * Check one claim against the source
+ * @param {Object} claim - Entry of extractClaims (or `{ kind: 'function', text }` for a named function)
+ * @param {Object} index - Result of buildSourceIndex
+ * @returns {Object} - `{ status, reason }` where status is `verified`, `unverified` or `unchecked`
+ */
+function verifyClaim(claim, index) {
+  if (claim.proposed) return { status: 'unchecked', reason: 'proposed code' };
+
+  switch (claim.kind) {
+    case 'function': {
+      const name = claim.text.replace(/\(.*$/, '').trim();
+      const found = index.functions.some(fn => fn.qualifiedName === name || fn.name === name.split('::').pop());
+      return found ? { status: 'verified' } : { status: 'unverified', reason: `no function \`${name}\` in the source` };
+    }
+    case 'identifier': {
+      const segments = claim.text.replace(/(!|\(\))$/, '').split(/::|\./);
+      const checked = segments.filter(segment => !KNOWN_NAMES.has(segment));
+      if (checked.length === 0) return { status: 'unchecked', reason: 'standard name' };
+      const missing = checked.filter(segment => !index.identifiers.has(segment));
+      return missing.length === 0
+        ? { status: 'verified' }
+        : { status: 'unverified', reason: `\`${missing.join('`, `')}\` does not appear in the source` };
+    }
+    case 'signature': {
+      const name = claim.text.match(SIGNATURE_CLAIM)[1];
+      const candidates = index.functions.filter(fn => fn.name === name);
+      if (candidates.length === 0) return { status: 'unverified', reason: `no function \`${name}\` in the source` };
+      // The claim may be abbreviated with `...` and leave out `pub`, the return type or the body
+      const parts = claim.text.replace(/\{.*$/, '').split(/\.\.\.|…/).map(normalizeCode).filter(Boolean)
+        .map(part => part.replace(/^pub(\([^)]*\))?/, ''));
+      const matches = candidates.some(fn => parts.every(part => fn.declaration.includes(part)));
+      return matches ? { status: 'verified' } : { status: 'unverified', reason: `the signature of \`${name}\` differs in the source` };
+    }
+    case 'snippet': {
+      const lines = claim.text.split('\n').map(normalizeCode).filter(line => line && !/^(\.\.\.|…)$/.test(line));
+      if (lines.length === 0) return { status: 'unchecked', reason: 'no code' };
+      // Single-line claims may be a fragment of a line
+      const found = lines.length === 1
+        ? [...index.lines].some(line => line.includes(lines[0])) ? 1 : 0
+        : lines.filter(line => index.lines.has(line)).length;
+      return found === lines.length
+        ? { status: 'verified' }
+        : { status: 'unverified', reason: lines.length === 1 ? 'this code does not appear in the source' : `${lines.length - found} of ${lines.length} lines do not appear in the source` };
+    }
+    case 'line': {
+      const { start, end } = claim.lines;
+      const fits = start >= 1 && start <= end && index.lineCounts.some(count => end <= count);
+      return fits ? { status: 'verified' } : { status: 'unverified', reason: `the source has no line ${end > start ? `${start}-${end}` : start}` };
+    }
+    default:
+      return { status: 'unchecked' };
+  }
+}
+
+/**
+ * Grounding of a set of claims
+ * @returns {Object} - `{ score, verified, unverified }`: score is the share of checked claims that were verified
+ *   (null when nothing could be checked), unverified lists `{ kind, text, reason }`
+ */
+function scoreClaims(results) {
+  const verified = results.filter(({ result }) => result.status === 'verified').length;
+  const unverified = results.filter(({ result }) => result.status === 'unverified')
+    .map(({ claim, result }) => ({ kind: claim.kind, text: claim.text, reason: result.reason }));
+  const checked = verified + unverified.length;
+  return { score: checked === 0 ? null : Math.round((verified / checked) * 100) / 100, verified, unverified };
+}
+
+/**
+ * Ground a Finding reported by the model: its function, lines and the code its description and evidence cite
+ * (the recommendation proposes new code and is not checked)
+ * @param {Object} finding - Finding (see finding.js)
+ * @param {Object} index - Result of buildSourceIndex
+ * @returns {Object} - `{ score, verified, unverified }`
+ */
+function groundFinding(finding, index) {
+  const claims = [];
+  if (finding.location.function) claims.push({ kind: 'function', text: finding.location.function });
+  if (finding.location.startLine) {
+    claims.push({ kind: 'line', text: `lines ${finding.location.startLine}-${finding.location.endLine}`, lines: { start: finding.location.startLine, end: finding.location.endLine } });
+  }
+  // Line references the structured analysis already dropped count against the finding
+  const dropped = finding.evidence.filter(item => item.kind === 'note' && /^The model cites lines/.test(item.text));
+  const texts = [finding.description, ...finding.evidence.filter(item => item.kind === 'note' && !dropped.includes(item)).map(item => item.text)];
+  texts.forEach(text => claims.push(...extractClaims(text)));
+
+  const results = claims.map(claim => ({ claim, result: verifyClaim(claim, index) }));
+  dropped.forEach(item => {
+    const [, lines, reason] = item.text.match(/^The model cites (lines \S+), but (.*)$/) || [null, 'lines', item.text];
+    results.push({ claim: { kind: 'line', text: lines }, result: { status: 'unverified', reason } });
+  });
+  return scoreClaims(results);
+}
+
+/**
+ * Ground a free-form Markdown report: unverified claims are marked inline, each section gets its grounding
+ * score under its heading and a summary is appended
+ * @param {string} markdown - Report written by the model
+ * @param {Object} index - Result of buildSourceIndex
+ * @returns {Object} - `{ markdown, score, unverified }` with the annotated report
+ */
+function groundMarkdown(markdown, index) {
+  const all = [];
+
+  const annotated = splitSections(markdown).map(section => {
+    const text = section.heading === null ? section.body : `${section.heading}\n${section.body}`;
+    const results = extractClaims(text).map(claim => ({ claim, result: verifyClaim(claim, index) }));
+    all.push(...results);
+
+    // Insert the marks from the end so that earlier offsets stay valid
+    let marked = text;
+    results.filter(({ result }) => result.status === 'unverified').reverse().forEach(({ claim, result }) => {
+      const mark = claim.kind === 'snippet' && claim.text.includes('\n')
+        ? `\n\n*(unverified: ${result.reason})*`
+        : ' *(unverified)*';
+      marked = marked.slice(0, claim.end) + mark + marked.slice(claim.end);
+    });
+
+    const grounding = scoreClaims(results);
+    if (section.heading === null || grounding.score === null) return marked;
+    const checked = grounding.verified + grounding.unverified.length;
+    const [heading, ...body] = marked.split('\n');
+    return [heading, '', `*Grounding: ${grounding.score.toFixed(2)} (${grounding.verified} of ${checked} code references verified)*`, ...body].join('\n');
+  }).join('\n');
+
+  const overall = scoreClaims(all);
+  const summary = overall.score === null
+    ? '*Grounding: no code references to verify.*'
+    : `*Grounding: ${overall.score.toFixed(2)}. ${overall.verified} code reference(s) verified against the source, ${overall.unverified.length} unverified.*`;
+  return { markdown: `${annotated.trimEnd()}\n\n---\n\n${summary}\n`, score: overall.score, unverified: overall.unverified };
+}
+
+// Split Markdown at its headings (outside code blocks); the text before the first heading has a null heading
+function splitSections(markdown) {
+  const sections = [{ heading: null, lines: [] }];
+  let inFence = false;
+  markdown.split('\n').forEach(line => {
+    if (/^```/.test(line)) inFence = !inFence;
+    if (!inFence && /^#{1,6}\s/.test(line)) sections.push({ heading: line, lines: [] });
+    else sections[sections.length - 1].lines.push(line);
+  });
+  return sections
+    .filter(section => section.heading !== null || section.lines.length > 0)
+    .map(({ heading, lines }) => ({ heading, body: lines.join('\n') }));
+}
+
+/**
+ * One-line Markdown summary of a Finding's grounding, e.g. "*Grounding 0.50 · unverified: `foo` (...)*"
+ * @param {Object} grounding - Result of groundFinding
+ * @returns {string}
+ */
+function formatGrounding(grounding) {
+  if (grounding.score === null) return '*Grounding: no code references to verify*';
+  const unverified = grounding.unverified.map(claim => `\`${claim.text.replace(/`/g, '')}\` (${claim.reason})`);
+  return `*Grounding ${grounding.score.toFixed(2)}${unverified.length > 0 ? ` · unverified: ${unverified.join('; ')}` : ''}*`;
+}
+
+module.exports = {
+  buildSourceIndex,
+  extractClaims,
+  verifyClaim,
+  groundFinding,
+  groundMarkdown,
+  formatGrounding
+};
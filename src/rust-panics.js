@@ -0,0 +1,215 @@
+const { visit } = require('./rust-parser');
+const {
+  dominatingConditions,
+  inferType,
+  isIntegerType,
+  nodeText,
+  precedingStatements,
+  splitGenericType,
+  walkFunction
+} = require('./rust-ast');
+const {
+  buildCallGraph,
+  entryPointsReaching,
+  findEntryPoints,
+  findFunctionAt,
+  isTestFunction
+} = require('./rust-callgraph');
+
+/**
+ * Panic-path analysis: every expression in a function that can panic at runtime, and the public entry points
+ * whose call chains reach it. On-chain a panic aborts the whole transaction, so a panic an outside caller can
+ * trigger is a potential denial of service.
+ */
+
+const PANIC_MACROS = new Set(['panic', 'unreachable', 'todo', 'unimplemented']);
+const ASSERT_MACROS = new Set(['assert', 'assert_eq', 'assert_ne']);
+const UNWRAP_METHODS = new Set(['unwrap', 'unwrap_err']);
+const EXPECT_METHODS = new Set(['expect', 'expect_err']);
+// Fixed-width integer wrappers (cosmwasm_std::Uint128, primitive_types::U256, ...) panic on division by zero too
+const WIDE_INTEGER = /^(Uint\d+|Int\d+|U\d+|I\d+|Decimal\d*)$/;
+const FLOAT_TYPE = /^f(32|64)$/;
+
+const PANIC_KINDS = {
+  unwrap: 'unwrap',
+  expect: 'expect',
+  index: 'index',
+  slice: 'slice range',
+  macro: 'panic macro',
+  assert: 'assertion',
+  division: 'division'
+};
+
+/**
+ * Find the panic sources of every function in a file and the entry points that reach each function
+ * @param {Object} ast - Parsed SourceFile
+ * @param {string} code - Rust source code
+ * @param {Object} definitions - Result of collectDefinitions
+ * @param {Object} options - `callGraph`: graph to trace callers in, e.g. the crate's (default: the file's own);
+ *   `entryPoints`: its entry point ids (default: findEntryPoints); `filePath`: the file, to find it in a crate graph
+ * @returns {Object} - `{ functions, entryPoints }` where functions are
+ *   `{ fn, sources: [{ kind, node, detail }], reachedFrom: [node ids] }`, for functions with at least one source.
+ *   `detail` completes the sentence "panics when ..."
+ */
+function analyzePanicPaths(ast, code, definitions, options = {}) {
+  const graph = options.callGraph || buildCallGraph(ast, definitions);
+  const entryPoints = options.entryPoints || findEntryPoints(graph);
+
+  const results = [];
+  for (const fn of definitions.functions) {
+    if (!fn.node.body || isTestFunction(fn)) continue;
+    const sources = findPanicSources(fn, definitions, code);
+    if (sources.length === 0) continue;
+    const node = options.callGraph ? findFunctionAt(graph, options.filePath, fn.node.loc.start.line) : graph.nodes.get(fn.qualifiedName);
+    const reachedFrom = node ? entryPointsReaching(graph, node.id, entryPoints) : [];
+    results.push({ fn, sources, reachedFrom });
+  }
+
+  return { functions: results, entryPoints };
+}
+
+/**
+ * List the expressions of one function that can panic
+ */
+function findPanicSources(fn, definitions, code) {
+  const sources = [];
+
+  walkFunction(fn, definitions, (node, scope, ancestors) => {
+    switch (node.type) {
+      case 'MethodCall':
+        if (UNWRAP_METHODS.has(node.method) || EXPECT_METHODS.has(node.method)) {
+          const variant = node.method.endsWith('_err') ? '`Ok`' : '`None` or `Err`';
+          sources.push({
+            kind: UNWRAP_METHODS.has(node.method) ? 'unwrap' : 'expect',
+            node,
+            detail: `\`${nodeText(code, node.receiver).trim()}\` is ${variant} at \`.${node.method}()\``
+          });
+        }
+        break;
+      case 'Index': {
+        const target = nodeText(code, node.object).trim();
+        if (node.index.type === 'Range') {
+          sources.push({ kind: 'slice', node, detail: `range \`${nodeText(code, node.index).trim()}\` is out of bounds or reversed for \`${target}\`` });
+        } else if (!isInBoundsArrayIndex(node, scope)) {
+          sources.push({ kind: 'index', node, detail: `index \`${nodeText(code, node.index).trim()}\` is out of bounds or missing in \`${target}\`` });
+        }
+        break;
+      }
+      case 'Macro':
+        if (PANIC_MACROS.has(node.name)) {
+          sources.push({ kind: 'macro', node, detail: `\`${node.name}!\` is reached` });
+        } else if (ASSERT_MACROS.has(node.name)) {
+          sources.push({ kind: 'assert', node, detail: `\`${node.name}!\` fails` });
+        }
+        break;
+      case 'Binary':
+      case 'CompoundAssign': {
+        if (!['/', '%', '/=', '%='].includes(node.operator)) break;
+        const divisor = node.right;
+        if (isConstantDivisor(divisor) || !isIntegerOperation(node, scope)) break;
+        if (divisorIsGuarded(divisor, node, ancestors, code)) break;
+        sources.push({ kind: 'division', node, detail: `divisor \`${nodeText(code, divisor).trim()}\` is zero` });
+        break;
+      }
+      default:
+        break;
+    }
+  });
+
+  return sources;
+}
+
+/**
+ * `arr[3]` on a `[T; 32]` array cannot go out of bounds
+ */
+function isInBoundsArrayIndex(node, scope) {
+  if (node.index.type !== 'Literal' || node.index.kind !== 'int') return false;
+  const type = inferType(node.object, scope);
+  const match = type && type.replace(/^&(mut )?/, '').match(/^\[.+;\s*(\d+)\s*\]$/);
+  return !!match && Number(node.index.value) < Number(match[1]);
+}
+
+/**
+ * Literals and SCREAMING_CASE constants are never zero by accident
+ */
+function isConstantDivisor(expr) {
+  if (expr.type === 'Paren') return isConstantDivisor(expr.expr);
+  if (expr.type === 'Literal') return true;
+  if (expr.type === 'PathExpr') return /^[A-Z][A-Z0-9_]*$/.test(expr.path.segments[expr.path.segments.length - 1].name);
+  return false;
+}
+
+/**
+ * Integer division panics on a zero divisor; float division yields infinity or NaN instead
+ */
+function isIntegerOperation(node, scope) {
+  const types = [inferType(node.left, scope), inferType(node.right, scope)].filter(Boolean);
+  if (types.some(type => FLOAT_TYPE.test(type.replace(/^&(mut )?/, '')))) return false;
+  return types.some(type => isIntegerType(type) || WIDE_INTEGER.test(splitGenericType(type).base));
+}
+
+/**
+ * True when a dominating condition or an earlier assertion mentions the divisor, e.g. `if count == 0 { return }`
+ */
+function divisorIsGuarded(divisor, node, ancestors, code) {
+  const text = nodeText(code, divisor).trim();
+  if (dominatingConditions(ancestors, node).some(entry => nodeText(code, entry.condition).includes(text))) return true;
+  return precedingStatements(ancestors, node).some(stmt => {
+    let found = false;
+    visit(stmt, {
+      Macro: function(macro) {
+        if (/^(assert|assert_ne|require|ensure)$/.test(macro.name) && nodeText(code, macro).includes(text)) found = true;
+      }
+    });
+    return found;
+  });
+}
+
+const MAX_LISTED_ENTRY_POINTS = 4;
+
+/**
+ * Describe the entry points that reach a function, e.g. "`Token::transfer`, `Token::burn` and 2 more"
+ * @param {string[]} reachedFrom - Qualified names
+ * @returns {string}
+ */
+function describeEntryPoints(reachedFrom) {
+  if (reachedFrom.length === 0) return 'no public entry point';
+  const listed = reachedFrom.slice(0, MAX_LISTED_ENTRY_POINTS).map(name => `\`${name}\``).join(', ');
+  const more = reachedFrom.length - MAX_LISTED_ENTRY_POINTS;
+  return more > 0 ? `${listed} and ${more} more` : listed;
+}
+
+/**
+ * Render the per-function panic report for the Markdown report
+ * @param {Object} panicPaths - Result of analyzePanicPaths
+ * @returns {string} - Markdown
+ */
+function renderPanicReport(panicPaths) {
+  let output = `## Panic Paths\n\n`;
+  if (panicPaths.functions.length === 0) {
+    output += `No panic sources found.\n\n`;
+    return output;
+  }
+
+  const reachable = panicPaths.functions.filter(entry => entry.reachedFrom.length > 0);
+  const sourceCount = panicPaths.functions.reduce((total, entry) => total + entry.sources.length, 0);
+  output += `${sourceCount} panic source(s) in ${panicPaths.functions.length} function(s); `;
+  output += `${reachable.length} of these functions can be reached from a public entry point.\n\n`;
+
+  output += `| Function | Line | Kind | Panics when | Reached from |\n`;
+  output += `|----------|------|------|-------------|--------------|\n`;
+  panicPaths.functions.forEach(({ fn, sources, reachedFrom }) => {
+    sources.forEach(source => {
+      output += `| \`${fn.qualifiedName}\` | ${source.node.loc.start.line} | ${PANIC_KINDS[source.kind]} | ${source.detail.replace(/\|/g, '\\|')} | ${describeEntryPoints(reachedFrom)} |\n`;
+    });
+  });
+  output += `\n`;
+
+  return output;
+}
+
+module.exports = {
+  analyzePanicPaths,
+  describeEntryPoints,
+  renderPanicReport
+};
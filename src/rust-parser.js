@@ -0,0 +1,2162 @@
+/**
+ * A small, dependency-free Rust parser.
+ *
+ * The API mirrors @solidity-parser/parser so the Rust and Solidity analyzers
+ * can be written the same way: `parse(code)` returns a `SourceFile` node and
+ * `visit(ast, { NodeType(node, parent) { ... } })` walks it. Every node carries
+ * `loc` (1-based lines, 0-based columns) and `range` (source offsets).
+ *
+ * The grammar covers items, impls, traits, patterns, types and the full
+ * expression language. Macro bodies are kept as token trees; when the
+ * contents are a comma separated list of expressions (println!, assert!,
+ * require!, vec!, ...) they are also parsed into `args`.
+ */
+
+class ParseError extends Error {
+  constructor(message, token) {
+    const line = token ? token.line : 0;
+    const column = token ? token.column : 0;
+    super(`${message} (line ${line}, column ${column + 1})`);
+    this.name = 'ParseError';
+    this.line = line;
+    this.column = column;
+  }
+}
+
+const PUNCTUATION = [
+  '>>=', '<<=', '...', '..=',
+  '::', '->', '=>', '==', '!=', '<=', '>=', '&&', '||', '+=', '-=', '*=', '/=', '%=', '^=', '&=', '|=', '<<', '>>', '..',
+  '+', '-', '*', '/', '%', '^', '!', '&', '|', '=', '<', '>', '@', '.', ',', ';', ':', '#', '$', '?', '~',
+  '(', ')', '[', ']', '{', '}'
+];
+
+const IDENT_START = /[\p{L}_]/u;
+const IDENT_CONTINUE = /[\p{L}\p{N}_]/u;
+
+/**
+ * Split Rust source into tokens. Regular comments are returned separately;
+ * doc comments become `doc` tokens so they can be attached to items as attributes.
+ * @param {string} code - Rust source code
+ * @returns {{tokens: Array, comments: Array}}
+ */
+function tokenize(code) {
+  const tokens = [];
+  const comments = [];
+  let i = 0;
+  let line = 1;
+  let column = 0;
+
+  const advance = (count) => {
+    for (let k = 0; k < count; k++) {
+      if (code[i] === '\n') {
+        line++;
+        column = 0;
+      } else {
+        column++;
+      }
+      i++;
+    }
+  };
+
+  const startPos = () => ({ line, column, offset: i });
+
+  const push = (type, value, start, extra = {}) => {
+    tokens.push({
+      type,
+      value,
+      line: start.line,
+      column: start.column,
+      offset: start.offset,
+      endLine: line,
+      endColumn: column,
+      endOffset: i,
+      ...extra
+    });
+  };
+
+  // Shebang line (but not an inner attribute such as #![allow(...)])
+  if (code.startsWith('#!') && !code.startsWith('#![')) {
+    while (i < code.length && code[i] !== '\n') advance(1);
+  }
+
+  while (i < code.length) {
+    const ch = code[i];
+    const next = code[i + 1];
+
+    if (/\s/.test(ch)) {
+      advance(1);
+      continue;
+    }
+
+    // Line comments and line doc comments
+    if (ch === '/' && next === '/') {
+      const start = startPos();
+      let end = code.indexOf('\n', i);
+      if (end === -1) end = code.length;
+      const text = code.slice(i, end);
+      advance(end - i);
+      if (text.startsWith('///') && !text.startsWith('////')) {
+        push('doc', text.slice(3), start, { inner: false });
+      } else if (text.startsWith('//!')) {
+        push('doc', text.slice(3), start, { inner: true });
+      } else {
+        comments.push({ type: 'LineComment', value: text.slice(2), loc: locFrom(start, line, column), range: [start.offset, i] });
+      }
+      continue;
+    }
+
+    // Block comments (which nest in Rust)
+    if (ch === '/' && next === '*') {
+      const start = startPos();
+      let depth = 0;
+      let j = i;
+      while (j < code.length) {
+        if (code[j] === '/' && code[j + 1] === '*') {
+          depth++;
+          j += 2;
+        } else if (code[j] === '*' && code[j + 1] === '/') {
+          depth--;
+          j += 2;
+          if (depth === 0) break;
+        } else {
+          j++;
+        }
+      }
+      const text = code.slice(i, j);
+      advance(j - i);
+      if (text.startsWith('/**') && !text.startsWith('/***') && text !== '/**/') {
+        push('doc', text.slice(3, -2), start, { inner: false });
+      } else if (text.startsWith('/*!')) {
+        push('doc', text.slice(3, -2), start, { inner: true });
+      } else {
+        comments.push({ type: 'BlockComment', value: text.slice(2, -2), loc: locFrom(start, line, column), range: [start.offset, i] });
+      }
+      continue;
+    }
+
+    // Raw identifiers, raw strings, byte strings and byte chars
+    if (ch === 'r' && next === '#' && IDENT_START.test(code[i + 2] || '')) {
+      const start = startPos();
+      advance(2);
+      const identStart = i;
+      while (i < code.length && IDENT_CONTINUE.test(code[i])) advance(1);
+      push('ident', code.slice(identStart, i), start, { raw: true });
+      continue;
+    }
+
+    const rawMatch = /^(?:br|cr|r)(#*)"/.exec(code.slice(i, i + 260));
+    if (rawMatch) {
+      const start = startPos();
+      const terminator = '"' + rawMatch[1];
+      const bodyStart = i + rawMatch[0].length;
+      let end = code.indexOf(terminator, bodyStart);
+      if (end === -1) throw new ParseError('Unterminated raw string literal', { line, column });
+      advance(end + terminator.length - i);
+      push('string', code.slice(bodyStart, end), start, { raw: code.slice(start.offset, i), byte: rawMatch[0].startsWith('b') });
+      continue;
+    }
+
+    if ((ch === 'b' || ch === 'c') && next === '"') {
+      const start = startPos();
+      advance(1);
+      const value = readQuoted('"');
+      push('string', value, start, { raw: code.slice(start.offset, i), byte: ch === 'b' });
+      continue;
+    }
+
+    if (ch === 'b' && next === '\'') {
+      const start = startPos();
+      advance(1);
+      const value = readQuoted('\'');
+      push('char', value, start, { raw: code.slice(start.offset, i), byte: true });
+      continue;
+    }
+
+    if (IDENT_START.test(ch)) {
+      const start = startPos();
+      while (i < code.length && IDENT_CONTINUE.test(code[i])) advance(1);
+      push('ident', code.slice(start.offset, i), start);
+      continue;
+    }
+
+    if (/[0-9]/.test(ch)) {
+      const start = startPos();
+      readNumber(start);
+      continue;
+    }
+
+    if (ch === '"') {
+      const start = startPos();
+      const value = readQuoted('"');
+      push('string', value, start, { raw: code.slice(start.offset, i) });
+      continue;
+    }
+
+    if (ch === '\'') {
+      const start = startPos();
+      // Char literal: '\x', 'x' ; otherwise a lifetime or label: 'a, 'static
+      if (next === '\\' || (next !== undefined && code[i + 2] === '\'') ||
+          (next !== undefined && !IDENT_START.test(next) && next !== '\'')) {
+        const value = readQuoted('\'');
+        push('char', value, start, { raw: code.slice(start.offset, i) });
+      } else {
+        // Multi-byte characters such as '中' are still chars
+        const cp = code.codePointAt(i + 1);
+        const width = cp > 0xffff ? 2 : 1;
+        if (code[i + 1 + width] === '\'') {
+          const value = readQuoted('\'');
+          push('char', value, start, { raw: code.slice(start.offset, i) });
+        } else {
+          advance(1);
+          while (i < code.length && IDENT_CONTINUE.test(code[i])) advance(1);
+          push('lifetime', code.slice(start.offset, i), start);
+        }
+      }
+      continue;
+    }
+
+    const punct = PUNCTUATION.find(p => code.startsWith(p, i));
+    if (punct) {
+      const start = startPos();
+      advance(punct.length);
+      push('punct', punct, start);
+      continue;
+    }
+
+    throw new ParseError(`Unexpected character '${ch}'`, { line, column });
+  }
+
+  tokens.push({ type: 'eof', value: '', line, column, offset: i, endLine: line, endColumn: column, endOffset: i });
+  return { tokens, comments };
+
+  function readQuoted(quote) {
+    const start = { line, column };
+    advance(1);
+    let value = '';
+    while (i < code.length && code[i] !== quote) {
+      if (code[i] === '\\') {
+        value += code.slice(i, i + 2);
+        advance(2);
+      } else {
+        value += code[i];
+        advance(1);
+      }
+    }
+    if (i >= code.length) throw new ParseError('Unterminated literal', start);
+    advance(1);
+    // Literal suffixes are legal but rare; keep them with the raw text
+    while (i < code.length && IDENT_CONTINUE.test(code[i])) advance(1);
+    return value;
+  }
+
+  function readNumber(start) {
+    let isFloat = false;
+    if (code[i] === '0' && /[xob]/.test(code[i + 1] || '')) {
+      advance(2);
+      while (i < code.length && /[0-9a-fA-F_]/.test(code[i])) advance(1);
+    } else {
+      while (i < code.length && /[0-9_]/.test(code[i])) advance(1);
+      // A fraction, unless this is a range (1..2), a method call (1.max(2)) or a tuple index chain (t.0.1 is handled by the parser)
+      if (code[i] === '.' && code[i + 1] !== '.' && !IDENT_START.test(code[i + 1] || '')) {
+        isFloat = true;
+        advance(1);
+        while (i < code.length && /[0-9_]/.test(code[i])) advance(1);
+      }
+      if (/[eE]/.test(code[i] || '') && /[0-9+\-]/.test(code[i + 1] || '')) {
+        isFloat = true;
+        advance(2);
+        while (i < code.length && /[0-9_]/.test(code[i])) advance(1);
+      }
+    }
+    const numberEnd = i;
+    while (i < code.length && IDENT_CONTINUE.test(code[i])) advance(1);
+    const suffix = code.slice(numberEnd, i);
+    if (/^f(32|64)$/.test(suffix)) isFloat = true;
+    push(isFloat ? 'float' : 'int', code.slice(start.offset, i), start, { suffix: suffix || null });
+  }
+}
+
+function locFrom(start, endLine, endColumn) {
+  return {
+    start: { line: start.line, column: start.column },
+    end: { line: endLine, column: endColumn }
+  };
+}
+
+// Binary operator precedence, lowest first. Assignment and ranges are handled separately.
+const BINARY_PRECEDENCE = {
+  '||': 1,
+  '&&': 2,
+  '==': 3, '!=': 3, '<': 3, '>': 3, '<=': 3, '>=': 3,
+  '|': 4,
+  '^': 5,
+  '&': 6,
+  '<<': 7, '>>': 7,
+  '+': 8, '-': 8,
+  '*': 9, '/': 9, '%': 9
+};
+const CAST_PRECEDENCE = 10;
+
+const ASSIGNMENT_OPERATORS = new Set(['=', '+=', '-=', '*=', '/=', '%=', '^=', '&=', '|=', '<<=', '>>=']);
+
+const ITEM_KEYWORDS = new Set(['fn', 'struct', 'enum', 'union', 'trait', 'impl', 'mod', 'use', 'const', 'static', 'type', 'extern', 'macro_rules', 'pub', 'unsafe', 'async', 'default', 'auto']);
+
+const EXPRESSION_KEYWORDS = new Set(['if', 'match', 'loop', 'while', 'for', 'return', 'break', 'continue', 'let', 'move', 'unsafe', 'async', 'self', 'Self', 'super', 'crate', 'true', 'false']);
+
+const RESERVED = new Set(['as', 'else', 'in', 'where', 'fn', 'struct', 'enum', 'trait', 'impl', 'mod', 'use', 'pub', 'static', 'type', 'extern', 'ref', 'mut', 'dyn']);
+
+class Parser {
+  constructor(code, options = {}) {
+    this.code = code;
+    const { tokens, comments } = tokenize(code);
+    this.tokens = tokens;
+    this.comments = comments;
+    this.pos = 0;
+    this.prev = null;
+    this.options = options;
+  }
+
+  // ---------------------------------------------------------------------------
+  // Token helpers
+  // ---------------------------------------------------------------------------
+
+  get tok() {
+    return this.tokens[this.pos];
+  }
+
+  peek(offset = 1) {
+    return this.tokens[Math.min(this.pos + offset, this.tokens.length - 1)];
+  }
+
+  is(value, token = this.tok) {
+    return (token.type === 'punct' || token.type === 'ident') && token.value === value && !token.raw;
+  }
+
+  isIdent(token = this.tok) {
+    return token.type === 'ident';
+  }
+
+  next() {
+    const token = this.tok;
+    if (token.type !== 'eof') this.pos++;
+    this.prev = token;
+    return token;
+  }
+
+  eat(value) {
+    if (this.is(value)) return this.next();
+    return null;
+  }
+
+  expect(value) {
+    if (this.is(value)) return this.next();
+    throw new ParseError(`Expected '${value}' but found '${this.tok.value || this.tok.type}'`, this.tok);
+  }
+
+  expectIdent() {
+    if (this.isIdent()) return this.next();
+    throw new ParseError(`Expected identifier but found '${this.tok.value || this.tok.type}'`, this.tok);
+  }
+
+  /**
+   * Consume one character of a compound punctuation token (e.g. the first '>' of '>>').
+   */
+  splitToken(first) {
+    const token = this.tok;
+    if (token.type !== 'punct' || !token.value.startsWith(first) || token.value === first) return false;
+    const rest = {
+      ...token,
+      value: token.value.slice(first.length),
+      column: token.column + first.length,
+      offset: token.offset + first.length
+    };
+    this.prev = { ...token, value: first, endLine: token.line, endColumn: token.column + first.length, endOffset: token.offset + first.length };
+    this.tokens[this.pos] = rest;
+    return true;
+  }
+
+  eatSplit(value) {
+    if (this.is(value)) return this.next();
+    if (this.splitToken(value)) return this.prev;
+    return null;
+  }
+
+  expectSplit(value) {
+    const token = this.eatSplit(value);
+    if (!token) throw new ParseError(`Expected '${value}' but found '${this.tok.value || this.tok.type}'`, this.tok);
+    return token;
+  }
+
+  finish(node, startToken) {
+    const end = this.prev || startToken;
+    node.loc = {
+      start: { line: startToken.line, column: startToken.column },
+      end: { line: end.endLine, column: end.endColumn }
+    };
+    node.range = [startToken.offset, end.endOffset];
+    return node;
+  }
+
+  textOf(node) {
+    return this.code.slice(node.range[0], node.range[1]);
+  }
+
+  /**
+   * Skip a balanced delimiter group starting at the current token and return its tokens (without the outer delimiters)
+   */
+  tokenTree() {
+    const open = this.tok;
+    const pairs = { '(': ')', '[': ']', '{': '}' };
+    if (!pairs[open.value] || open.type !== 'punct') {
+      throw new ParseError(`Expected delimiter but found '${open.value || open.type}'`, open);
+    }
+    const stack = [pairs[open.value]];
+    this.next();
+    const startIndex = this.pos;
+    while (stack.length > 0) {
+      const token = this.tok;
+      if (token.type === 'eof') throw new ParseError(`Unclosed delimiter '${open.value}'`, open);
+      if (token.type === 'punct') {
+        if (pairs[token.value]) stack.push(pairs[token.value]);
+        else if (token.value === stack[stack.length - 1]) stack.pop();
+        else if (token.value === ')' || token.value === ']' || token.value === '}') {
+          throw new ParseError(`Mismatched delimiter '${token.value}'`, token);
+        }
+      }
+      this.next();
+    }
+    const inner = this.tokens.slice(startIndex, this.pos - 1);
+    return { open: open.value, tokens: inner, start: open, close: this.prev };
+  }
+
+  // ---------------------------------------------------------------------------
+  // Attributes and visibility
+  // ---------------------------------------------------------------------------
+
+  parseOuterAttributes() {
+    const attrs = [];
+    for (;;) {
+      if (this.tok.type === 'doc' && !this.tok.inner) {
+        const token = this.next();
+        attrs.push(this.finish({ type: 'Attribute', path: 'doc', args: token.value, doc: true, inner: false }, token));
+      } else if (this.is('#') && this.is('[', this.peek())) {
+        attrs.push(this.parseAttribute(false));
+      } else {
+        return attrs;
+      }
+    }
+  }
+
+  parseInnerAttributes() {
+    const attrs = [];
+    for (;;) {
+      if (this.tok.type === 'doc' && this.tok.inner) {
+        const token = this.next();
+        attrs.push(this.finish({ type: 'Attribute', path: 'doc', args: token.value, doc: true, inner: true }, token));
+      } else if (this.is('#') && this.is('!', this.peek()) && this.is('[', this.peek(2))) {
+        attrs.push(this.parseAttribute(true));
+      } else {
+        return attrs;
+      }
+    }
+  }
+
+  parseAttribute(inner) {
+    const start = this.expect('#');
+    if (inner) this.expect('!');
+    const tree = this.tokenTree();
+    // The attribute path is everything before the first '(' or '=' (e.g. `account`, `ink::contract`, `cfg_attr`)
+    let pathEnd = tree.tokens.findIndex(t => t.type === 'punct' && (t.value === '(' || t.value === '=' || t.value === '[' || t.value === '{'));
+    if (pathEnd === -1) pathEnd = tree.tokens.length;
+    const path = tree.tokens.slice(0, pathEnd).map(t => t.value).join('');
+    let args = null;
+    if (pathEnd < tree.tokens.length) {
+      const rest = tree.tokens.slice(pathEnd);
+      const first = rest[0];
+      const last = rest[rest.length - 1];
+      if (first.value === '=') {
+        args = this.code.slice(rest[1] ? rest[1].offset : first.endOffset, last.endOffset).trim();
+      } else {
+        args = this.code.slice(first.endOffset, last.offset).trim();
+      }
+    }
+    return this.finish({
+      type: 'Attribute',
+      path,
+      args,
+      inner,
+      text: this.code.slice(tree.start.endOffset, tree.close.offset).trim()
+    }, start);
+  }
+
+  parseVisibility() {
+    if (this.is('crate') && !this.is('::', this.peek())) {
+      this.next();
+      return 'crate';
+    }
+    if (!this.is('pub')) return null;
+    const start = this.next();
+    if (this.is('(')) {
+      const following = this.peek();
+      if (this.is('crate', following) || this.is('self', following) || this.is('super', following) || this.is('in', following)) {
+        const tree = this.tokenTree();
+        return `pub(${tree.tokens.map(t => t.value).join(' ').replace(/ :: /g, '::')})`;
+      }
+    }
+    return start.value;
+  }
+
+  // ---------------------------------------------------------------------------
+  // Items
+  // ---------------------------------------------------------------------------
+
+  parseSourceFile() {
+    const start = this.tok;
+    const attrs = this.parseInnerAttributes();
+    const items = [];
+    while (this.tok.type !== 'eof') {
+      items.push(this.parseItem());
+    }
+    const node = this.finish({ type: 'SourceFile', attrs, items }, start);
+    node.range = [0, this.code.length];
+    node.comments = this.comments;
+    return node;
+  }
+
+  isItemStart() {
+    const token = this.tok;
+    if (token.type === 'doc') return !token.inner;
+    if (this.is('#') && this.is('[', this.peek())) return true;
+    if (!this.isIdent(token)) return false;
+    const value = token.value;
+    const following = this.peek();
+    switch (value) {
+      case 'fn': case 'struct': case 'enum': case 'trait': case 'impl': case 'mod': case 'use': case 'static': case 'extern': case 'pub':
+        return true;
+      case 'union': case 'auto': case 'default':
+        return this.isIdent(following) && !this.is('!', following);
+      case 'type':
+        return this.isIdent(following);
+      case 'const':
+        // const blocks and closures are expressions
+        return !this.is('{', following) && !this.is('|', following) && !this.is('move', following);
+      case 'unsafe':
+        return this.is('fn', following) || this.is('impl', following) || this.is('trait', following) || this.is('extern', following);
+      case 'async':
+        return this.is('fn', following) || (this.is('unsafe', following) && this.is('fn', this.peek(2)));
+      case 'macro_rules':
+        return this.is('!', following);
+      case 'crate':
+        return !this.is('::', following);
+      default:
+        return false;
+    }
+  }
+
+  parseItem() {
+    const start = this.tok;
+    const attrs = this.parseOuterAttributes();
+    const itemStart = this.tok;
+    const visibility = this.parseVisibility();
+    const item = this.parseItemKind(visibility, attrs);
+    // Attributes belong to the item, so the item starts at the first attribute
+    this.finish(item, attrs.length > 0 ? start : itemStart);
+    if (attrs.length > 0) item.declarationLoc = { start: { line: itemStart.line, column: itemStart.column } };
+    return item;
+  }
+
+  parseItemKind(visibility, attrs) {
+    const token = this.tok;
+    const base = { visibility, attrs };
+
+    // Function qualifiers
+    if (this.is('fn') || ((this.is('const') || this.is('async') || this.is('unsafe') || this.is('extern') || this.is('default')) && this.looksLikeFn())) {
+      return this.parseFn(base);
+    }
+
+    switch (token.value) {
+      case 'use':
+        return this.parseUse(base);
+      case 'struct':
+      case 'union':
+        return this.parseStruct(base);
+      case 'enum':
+        return this.parseEnum(base);
+      case 'trait':
+      case 'auto':
+        return this.parseTrait(base, false);
+      case 'impl':
+        return this.parseImpl(base, false);
+      case 'mod':
+        return this.parseMod(base);
+      case 'const':
+        return this.parseConstOrStatic(base, 'Const');
+      case 'static':
+        return this.parseConstOrStatic(base, 'Static');
+      case 'type':
+        return this.parseTypeAlias(base);
+      case 'extern':
+        return this.parseExtern(base);
+      case 'unsafe':
+        this.next();
+        if (this.is('impl')) return this.parseImpl(base, true);
+        if (this.is('trait') || this.is('auto')) return this.parseTrait(base, true);
+        if (this.is('extern')) return this.parseExtern(base);
+        break;
+      case 'macro_rules': {
+        this.next();
+        this.expect('!');
+        const name = this.expectIdent().value;
+        const tree = this.tokenTree();
+        if (tree.open !== '{') this.eat(';');
+        return { type: 'MacroRules', name, ...base };
+      }
+      default:
+        break;
+    }
+
+    // Item-level macro invocation, e.g. `declare_id!("...");` or `thread_local! { ... }`
+    if (this.isIdent() || this.is('::')) {
+      const path = this.parsePath('expr');
+      if (this.is('!')) {
+        this.next();
+        let name = null;
+        if (this.isIdent()) name = this.next().value;
+        const tree = this.tokenTree();
+        if (tree.open !== '{') this.eat(';');
+        return { type: 'MacroItem', path: path.text, name, delimiter: tree.open, tokens: tree.tokens, ...base };
+      }
+    }
+
+    throw new ParseError(`Expected item but found '${token.value || token.type}'`, token);
+  }
+
+  looksLikeFn() {
+    for (let k = 0; k < 6; k++) {
+      const token = this.peek(k);
+      if (this.is('fn', token)) return true;
+      if (token.type === 'string') continue;
+      if (!['const', 'async', 'unsafe', 'extern', 'default'].includes(token.value)) return false;
+    }
+    return false;
+  }
+
+  parseUse(base) {
+    this.expect('use');
+    const tree = this.parseUseTree();
+    this.expect(';');
+    return { type: 'Use', tree, paths: flattenUseTree(tree, []), ...base };
+  }
+
+  parseUseTree() {
+    const start = this.tok;
+    const segments = [];
+    if (this.eat('::')) segments.push('');
+    for (;;) {
+      if (this.is('*')) {
+        this.next();
+        return this.finish({ type: 'UseTree', segments, glob: true }, start);
+      }
+      if (this.is('{')) {
+        this.next();
+        const children = [];
+        while (!this.is('}')) {
+          children.push(this.parseUseTree());
+          if (!this.eat(',')) break;
+        }
+        this.expect('}');
+        return this.finish({ type: 'UseTree', segments, children }, start);
+      }
+      segments.push(this.expectIdent().value);
+      if (!this.eat('::')) break;
+    }
+    let alias = null;
+    if (this.eat('as')) alias = this.expectIdent().value;
+    return this.finish({ type: 'UseTree', segments, alias }, start);
+  }
+
+  parseGenerics() {
+    if (!this.is('<')) return null;
+    const start = this.next();
+    const params = [];
+    let depth = 1;
+    let expectParam = true;
+    // Bounds of the current type parameter, e.g. `Trait + Clone` in `T: Trait + Clone`
+    let bounded = null;
+    const closeBounds = () => {
+      if (!bounded) return;
+      let text = this.code.slice(bounded.offset, this.tok.offset).trim();
+      // Drop a default, `T: Trait = Type`, but not associated type bindings such as `Iterator<Item = u8>`
+      let nesting = 0;
+      for (let i = 0; i < text.length; i++) {
+        if (text[i] === '<') nesting++;
+        else if (text[i] === '>') nesting--;
+        else if (text[i] === '=' && nesting === 0) {
+          text = text.slice(0, i).trim();
+          break;
+        }
+      }
+      bounded.param.bounds = text.startsWith(':') ? text.slice(1).trim() || null : null;
+      bounded = null;
+    };
+    while (depth > 0) {
+      const token = this.tok;
+      if (token.type === 'eof') throw new ParseError('Unclosed generic parameter list', start);
+      if (depth === 1 && expectParam) {
+        if (this.is('const')) {
+          this.next();
+          params.push({ kind: 'const', name: this.expectIdent().value });
+          expectParam = false;
+          continue;
+        }
+        if (token.type === 'lifetime') {
+          params.push({ kind: 'lifetime', name: token.value });
+          this.next();
+          expectParam = false;
+          continue;
+        }
+        if (this.isIdent(token)) {
+          const param = { kind: 'type', name: token.value, bounds: null };
+          params.push(param);
+          this.next();
+          bounded = { param, offset: this.tok.offset };
+          expectParam = false;
+          continue;
+        }
+      }
+      if (this.is('(') || this.is('[') || this.is('{')) {
+        this.tokenTree();
+        continue;
+      }
+      if (this.is('<')) {
+        depth++;
+      } else if (this.is('>')) {
+        if (depth === 1) closeBounds();
+        depth--;
+      } else if (this.is('>>') || this.is('>=') || this.is('>>=')) {
+        if (depth === 1) closeBounds();
+        this.splitToken('>');
+        depth--;
+        continue;
+      } else if (depth === 1 && this.is(',')) {
+        closeBounds();
+        expectParam = true;
+      }
+      this.next();
+    }
+    return this.finish({ type: 'Generics', params }, start);
+  }
+
+  skipWhereClause() {
+    if (!this.is('where')) return null;
+    const start = this.next();
+    let depth = 0;
+    while (this.tok.type !== 'eof') {
+      if (depth === 0 && (this.is('{') || this.is(';'))) break;
+      if (this.is('(') || this.is('[')) {
+        this.tokenTree();
+        continue;
+      }
+      if (this.is('<')) depth++;
+      else if (this.is('>')) depth--;
+      else if (this.is('>>')) depth -= 2;
+      this.next();
+    }
+    return this.finish({ type: 'WhereClause', text: this.code.slice(start.endOffset, this.tok.offset).trim() }, start);
+  }
+
+  parseFn(base) {
+    const qualifiers = {};
+    while (!this.is('fn')) {
+      const token = this.next();
+      if (token.type === 'string') qualifiers.abi = token.value;
+      else qualifiers[token.value] = true;
+    }
+    this.expect('fn');
+    const name = this.expectIdent().value;
+    const generics = this.parseGenerics();
+    this.expect('(');
+    const params = [];
+    let selfParam = null;
+    while (!this.is(')')) {
+      const paramStart = this.tok;
+      const attrs = this.parseOuterAttributes();
+      const self = this.trySelfParam();
+      if (self) {
+        selfParam = this.finish(self, paramStart);
+      } else if (this.is('...')) {
+        this.next();
+        params.push(this.finish({ type: 'Param', pattern: null, paramType: null, variadic: true, attrs }, paramStart));
+      } else {
+        const pattern = this.parsePatternNoTopAlt();
+        let paramType = null;
+        if (this.eat(':')) paramType = this.parseType();
+        params.push(this.finish({ type: 'Param', pattern, paramType, name: patternName(pattern), attrs }, paramStart));
+      }
+      if (!this.eat(',')) break;
+    }
+    this.expect(')');
+    let returnType = null;
+    if (this.eat('->')) returnType = this.parseTypeNoBounds();
+    const whereClause = this.skipWhereClause();
+    let body = null;
+    if (this.is('{')) {
+      body = this.parseBlock();
+    } else {
+      this.expect(';');
+    }
+    return {
+      type: 'Fn',
+      name,
+      generics,
+      selfParam,
+      params,
+      returnType,
+      whereClause,
+      body,
+      unsafe: !!qualifiers.unsafe,
+      async: !!qualifiers.async,
+      const: !!qualifiers.const,
+      abi: qualifiers.abi || (qualifiers.extern ? 'C' : null),
+      ...base
+    };
+  }
+
+  trySelfParam() {
+    const save = this.pos;
+    let reference = false;
+    let lifetime = null;
+    let mutable = false;
+    if (this.is('&')) {
+      this.next();
+      reference = true;
+      if (this.tok.type === 'lifetime') lifetime = this.next().value;
+    }
+    if (this.is('mut')) {
+      this.next();
+      mutable = true;
+    }
+    if (this.is('self') && !this.is('::', this.peek())) {
+      this.next();
+      let selfType = null;
+      if (this.eat(':')) selfType = this.parseType();
+      return { type: 'SelfParam', reference, mutable, lifetime, selfType };
+    }
+    this.pos = save;
+    return null;
+  }
+
+  parseStruct(base) {
+    const keyword = this.next().value;
+    const name = this.expectIdent().value;
+    const generics = this.parseGenerics();
+    this.skipWhereClause();
+    let fields = [];
+    let kind = 'unit';
+    if (this.is('{')) {
+      kind = 'named';
+      fields = this.parseNamedFields();
+    } else if (this.is('(')) {
+      kind = 'tuple';
+      fields = this.parseTupleFields();
+      this.skipWhereClause();
+      this.expect(';');
+    } else {
+      this.expect(';');
+    }
+    return { type: keyword === 'union' ? 'Union' : 'Struct', name, generics, kind, fields, ...base };
+  }
+
+  parseNamedFields() {
+    this.expect('{');
+    const fields = [];
+    while (!this.is('}')) {
+      const start = this.tok;
+      const attrs = this.parseOuterAttributes();
+      if (this.is('}')) break;
+      const visibility = this.parseVisibility();
+      const name = this.expectIdent().value;
+      this.expect(':');
+      const fieldType = this.parseType();
+      fields.push(this.finish({ type: 'Field', name, fieldType, visibility, attrs }, start));
+      if (!this.eat(',')) break;
+    }
+    this.expect('}');
+    return fields;
+  }
+
+  parseTupleFields() {
+    this.expect('(');
+    const fields = [];
+    let index = 0;
+    while (!this.is(')')) {
+      const start = this.tok;
+      const attrs = this.parseOuterAttributes();
+      const visibility = this.parseVisibility();
+      const fieldType = this.parseType();
+      fields.push(this.finish({ type: 'Field', name: String(index++), fieldType, visibility, attrs }, start));
+      if (!this.eat(',')) break;
+    }
+    this.expect(')');
+    return fields;
+  }
+
+  parseEnum(base) {
+    this.expect('enum');
+    const name = this.expectIdent().value;
+    const generics = this.parseGenerics();
+    this.skipWhereClause();
+    this.expect('{');
+    const variants = [];
+    while (!this.is('}')) {
+      const start = this.tok;
+      const attrs = this.parseOuterAttributes();
+      if (this.is('}')) break;
+      this.parseVisibility();
+      const variantName = this.expectIdent().value;
+      let kind = 'unit';
+      let fields = [];
+      if (this.is('{')) {
+        kind = 'named';
+        fields = this.parseNamedFields();
+      } else if (this.is('(')) {
+        kind = 'tuple';
+        fields = this.parseTupleFields();
+      }
+      let discriminant = null;
+      if (this.eat('=')) discriminant = this.parseExpr();
+      variants.push(this.finish({ type: 'Variant', name: variantName, kind, fields, discriminant, attrs }, start));
+      if (!this.eat(',')) break;
+    }
+    this.expect('}');
+    return { type: 'Enum', name, generics, variants, ...base };
+  }
+
+  parseTrait(base, isUnsafe) {
+    const auto = !!this.eat('auto');
+    this.expect('trait');
+    const name = this.expectIdent().value;
+    const generics = this.parseGenerics();
+    let supertraits = [];
+    if (this.eat(':')) supertraits = this.parseBounds();
+    this.skipWhereClause();
+    if (this.eat('=')) {
+      // Trait alias
+      this.parseBounds();
+      this.expect(';');
+      return { type: 'Trait', name, generics, supertraits, items: [], unsafe: isUnsafe, auto, ...base };
+    }
+    const { items, attrs: innerAttrs } = this.parseItemBlock();
+    return { type: 'Trait', name, generics, supertraits, items, unsafe: isUnsafe, auto, innerAttrs, ...base };
+  }
+
+  parseImpl(base, isUnsafe) {
+    this.expect('impl');
+    // `impl<T>` generics; `impl <T as X>::Y` would be a qualified path, which cannot start an impl
+    const generics = this.parseGenerics();
+    this.eat('const');
+    const negative = !!this.eat('!');
+    let first = this.parseTypeNoBounds();
+    let trait = null;
+    let selfType = first;
+    if (this.eat('for')) {
+      trait = first;
+      selfType = this.parseTypeNoBounds();
+    }
+    const whereClause = this.skipWhereClause();
+    const { items, attrs: innerAttrs } = this.parseItemBlock();
+    return {
+      type: 'Impl',
+      generics,
+      trait,
+      selfType,
+      selfName: typeBaseName(selfType),
+      traitName: trait ? typeBaseName(trait) : null,
+      whereClause,
+      items,
+      unsafe: isUnsafe,
+      negative,
+      innerAttrs,
+      ...base
+    };
+  }
+
+  parseItemBlock() {
+    this.expect('{');
+    const attrs = this.parseInnerAttributes();
+    const items = [];
+    while (!this.is('}')) {
+      if (this.tok.type === 'eof') throw new ParseError('Unexpected end of file in item block', this.tok);
+      if (this.eat(';')) continue;
+      items.push(this.parseItem());
+    }
+    this.expect('}');
+    return { items, attrs };
+  }
+
+  parseMod(base) {
+    this.expect('mod');
+    const name = this.expectIdent().value;
+    if (this.eat(';')) {
+      return { type: 'Mod', name, items: null, external: true, ...base };
+    }
+    const { items, attrs: innerAttrs } = this.parseItemBlock();
+    return { type: 'Mod', name, items, external: false, innerAttrs, ...base };
+  }
+
+  parseConstOrStatic(base, type) {
+    this.next();
+    const mutable = type === 'Static' && !!this.eat('mut');
+    const name = this.is('_') ? this.next().value : this.expectIdent().value;
+    let valueType = null;
+    if (this.eat(':')) valueType = this.parseType();
+    let value = null;
+    if (this.eat('=')) value = this.parseExpr();
+    this.expect(';');
+    return { type, name, valueType, value, mutable, ...base };
+  }
+
+  parseTypeAlias(base) {
+    this.expect('type');
+    const name = this.expectIdent().value;
+    const generics = this.parseGenerics();
+    let bounds = [];
+    if (this.eat(':')) bounds = this.parseBounds();
+    this.skipWhereClause();
+    let aliased = null;
+    if (this.eat('=')) aliased = this.parseType();
+    this.skipWhereClause();
+    this.expect(';');
+    return { type: 'TypeAlias', name, generics, bounds, aliased, ...base };
+  }
+
+  parseExtern(base) {
+    this.expect('extern');
+    if (this.eat('crate')) {
+      const name = this.is('self') ? this.next().value : this.expectIdent().value;
+      let alias = null;
+      if (this.eat('as')) alias = this.expectIdent().value;
+      this.expect(';');
+      return { type: 'ExternCrate', name, alias, ...base };
+    }
+    let abi = 'C';
+    if (this.tok.type === 'string') abi = this.next().value;
+    if (this.is('fn')) {
+      const fn = this.parseFn(base);
+      fn.abi = abi;
+      return fn;
+    }
+    const { items, attrs: innerAttrs } = this.parseItemBlock();
+    return { type: 'ExternBlock', abi, items, innerAttrs, ...base };
+  }
+
+  // ---------------------------------------------------------------------------
+  // Paths and types
+  // ---------------------------------------------------------------------------
+
+  /**
+   * Parse a path. In expression context generic arguments need a turbofish (`::<`).
+   * @param {'expr'|'type'} mode
+   */
+  parsePath(mode) {
+    const start = this.tok;
+    const segments = [];
+    let global = false;
+    if (this.eat('::')) global = true;
+    for (;;) {
+      const token = this.tok;
+      if (!this.isIdent(token)) throw new ParseError(`Expected path segment but found '${token.value || token.type}'`, token);
+      this.next();
+      const segment = { name: token.value, generics: null };
+      if (mode === 'type' && this.is('<') ) {
+        segment.generics = this.parseGenericArgs();
+      } else if (mode === 'type' && this.is('(') && /^Fn(Mut|Once)?$/.test(token.value)) {
+        // Fn(A, B) -> C sugar
+        this.next();
+        const inputs = [];
+        while (!this.is(')')) {
+          inputs.push(this.parseType());
+          if (!this.eat(',')) break;
+        }
+        this.expect(')');
+        let output = null;
+        if (this.eat('->')) output = this.parseTypeNoBounds();
+        segment.fnSugar = { inputs, output };
+      }
+      segments.push(segment);
+      if (this.is('::')) {
+        const following = this.peek();
+        if (this.is('<', following)) {
+          this.next();
+          segment.generics = this.parseGenericArgs();
+          if (!this.is('::') || !this.isIdent(this.peek())) break;
+          this.next();
+          continue;
+        }
+        if (this.isIdent(following)) {
+          this.next();
+          continue;
+        }
+        if (this.is('{', following) || this.is('*', following)) break;
+      }
+      break;
+    }
+    const node = this.finish({ type: 'Path', global, segments }, start);
+    node.text = normalizeWhitespace(this.textOf(node));
+    node.name = segments[segments.length - 1].name;
+    return node;
+  }
+
+  parseGenericArgs() {
+    this.expect('<');
+    const args = [];
+    while (!this.is('>') && !this.is('>>') && !this.is('>=') && !this.is('>>=')) {
+      const token = this.tok;
+      if (token.type === 'lifetime') {
+        this.next();
+        args.push({ type: 'Lifetime', name: token.value });
+      } else if (this.isIdent(token) && (this.is('=', this.peek()) || (this.is(':', this.peek()) && !this.is('::', this.peek())))) {
+        const name = this.next().value;
+        if (this.eat('=')) {
+          args.push({ type: 'AssocBinding', name, boundType: this.parseType() });
+        } else {
+          this.expect(':');
+          args.push({ type: 'AssocBound', name, bounds: this.parseBounds() });
+        }
+      } else if (this.is('{')) {
+        args.push(this.parseBlock());
+      } else if (token.type === 'int' || token.type === 'string' || token.type === 'char' || this.is('-') || this.is('true') || this.is('false')) {
+        args.push(this.parseUnary({}));
+      } else {
+        args.push(this.parseType());
+      }
+      if (!this.eat(',')) break;
+    }
+    this.expectSplit('>');
+    return args;
+  }
+
+  parseBounds() {
+    const bounds = [];
+    for (;;) {
+      if (this.tok.type === 'lifetime') {
+        bounds.push({ type: 'Lifetime', name: this.next().value });
+      } else if (this.is('?') || this.is('~') || this.is('(') || this.is('for') || this.isIdent() || this.is('::') || this.is('const')) {
+        const start = this.tok;
+        let parens = false;
+        if (this.eat('(')) parens = true;
+        this.eat('?');
+        if (this.eat('~')) this.eat('const');
+        this.eat('const');
+        if (this.is('for')) {
+          this.next();
+          this.parseGenerics();
+        }
+        const path = this.parsePath('type');
+        if (parens) this.expect(')');
+        bounds.push(this.finish({ type: 'TraitBound', path }, start));
+      } else {
+        break;
+      }
+      if (!this.eat('+')) break;
+    }
+    return bounds;
+  }
+
+  parseType() {
+    return this.parseTypeInner(true);
+  }
+
+  parseTypeNoBounds() {
+    return this.parseTypeInner(false);
+  }
+
+  parseTypeInner(allowBounds) {
+    const start = this.tok;
+    let node;
+    if (this.is('&') || this.is('&&')) {
+      const double = this.is('&&');
+      this.next();
+      let lifetime = null;
+      if (this.tok.type === 'lifetime') lifetime = this.next().value;
+      const mutable = !!this.eat('mut');
+      const elem = this.parseTypeNoBounds();
+      node = { type: 'RefType', lifetime, mutable, elem };
+      if (double) node = { type: 'RefType', lifetime: null, mutable: false, elem: this.finish(node, start) };
+    } else if (this.is('*')) {
+      this.next();
+      const mutable = !!this.eat('mut');
+      if (!mutable) this.expect('const');
+      node = { type: 'PtrType', mutable, elem: this.parseTypeNoBounds() };
+    } else if (this.is('(')) {
+      this.next();
+      const elems = [];
+      let trailingComma = false;
+      while (!this.is(')')) {
+        elems.push(this.parseType());
+        trailingComma = false;
+        if (!this.eat(',')) break;
+        trailingComma = true;
+      }
+      this.expect(')');
+      node = elems.length === 1 && !trailingComma ? { type: 'ParenType', elem: elems[0] } : { type: 'TupleType', elems };
+    } else if (this.is('[')) {
+      this.next();
+      const elem = this.parseType();
+      if (this.eat(';')) {
+        const length = this.parseExpr();
+        this.expect(']');
+        node = { type: 'ArrayType', elem, length };
+      } else {
+        this.expect(']');
+        node = { type: 'SliceType', elem };
+      }
+    } else if (this.is('!')) {
+      this.next();
+      node = { type: 'NeverType' };
+    } else if (this.is('_')) {
+      this.next();
+      node = { type: 'InferType' };
+    } else if (this.is('impl') || this.is('dyn')) {
+      const keyword = this.next().value;
+      node = { type: keyword === 'impl' ? 'ImplTraitType' : 'DynTraitType', bounds: this.parseBounds() };
+    } else if (this.is('fn') || this.is('unsafe') || this.is('extern') || this.is('for')) {
+      if (this.is('for')) {
+        this.next();
+        this.parseGenerics();
+        if (!this.is('fn') && !this.is('unsafe') && !this.is('extern')) {
+          node = { type: 'DynTraitType', bounds: this.parseBounds(), implicit: true };
+          return this.finishType(node, start);
+        }
+      }
+      this.eat('unsafe');
+      if (this.eat('extern') && this.tok.type === 'string') this.next();
+      this.expect('fn');
+      this.expect('(');
+      const inputs = [];
+      while (!this.is(')')) {
+        if (this.isIdent() && this.is(':', this.peek()) && !this.is('::', this.peek())) {
+          this.next();
+          this.next();
+        }
+        if (this.eat('...')) break;
+        inputs.push(this.parseType());
+        if (!this.eat(',')) break;
+      }
+      this.expect(')');
+      let output = null;
+      if (this.eat('->')) output = this.parseTypeNoBounds();
+      node = { type: 'FnPtrType', inputs, output };
+    } else if (this.is('<')) {
+      node = { type: 'PathType', path: this.parseQualifiedPath('type') };
+    } else if (this.isIdent() || this.is('::')) {
+      const path = this.parsePath('type');
+      if (this.is('!')) {
+        this.next();
+        this.tokenTree();
+        node = { type: 'MacroType', path };
+      } else if (allowBounds && this.is('+')) {
+        // Bare trait object with additional bounds (2015 edition style)
+        this.next();
+        const bounds = [{ type: 'TraitBound', path }, ...this.parseBounds()];
+        node = { type: 'DynTraitType', bounds, implicit: true };
+      } else {
+        node = { type: 'PathType', path };
+      }
+    } else if (this.tok.type === 'lifetime') {
+      node = { type: 'Lifetime', name: this.next().value };
+    } else {
+      throw new ParseError(`Expected type but found '${this.tok.value || this.tok.type}'`, this.tok);
+    }
+    return this.finishType(node, start);
+  }
+
+  finishType(node, start) {
+    this.finish(node, start);
+    node.text = normalizeWhitespace(this.textOf(node));
+    return node;
+  }
+
+  /**
+   * `<T as Trait>::name` in type or expression position
+   */
+  parseQualifiedPath(mode) {
+    const start = this.expect('<');
+    const qself = this.parseType();
+    let trait = null;
+    if (this.eat('as')) trait = this.parseTypeNoBounds();
+    this.expectSplit('>');
+    const segments = [];
+    while (this.eat('::')) {
+      const name = this.expectIdent().value;
+      const segment = { name, generics: null };
+      if (this.is('::') && this.is('<', this.peek())) {
+        this.next();
+        segment.generics = this.parseGenericArgs();
+      } else if (mode === 'type' && this.is('<')) {
+        segment.generics = this.parseGenericArgs();
+      }
+      segments.push(segment);
+    }
+    const node = this.finish({ type: 'Path', global: false, qself, trait, segments }, start);
+    node.text = normalizeWhitespace(this.textOf(node));
+    node.name = segments.length > 0 ? segments[segments.length - 1].name : qself.text;
+    return node;
+  }
+
+  // ---------------------------------------------------------------------------
+  // Patterns
+  // ---------------------------------------------------------------------------
+
+  parsePattern() {
+    const start = this.tok;
+    this.eat('|');
+    const first = this.parsePatternNoTopAlt();
+    if (!this.is('|')) return first;
+    const alternatives = [first];
+    while (this.eat('|')) alternatives.push(this.parsePatternNoTopAlt());
+    return this.finish({ type: 'OrPat', alternatives }, start);
+  }
+
+  parsePatternNoTopAlt() {
+    const start = this.tok;
+    const pattern = this.parsePatternAtom();
+    if (this.is('..=') || this.is('...') || (this.is('..') && (this.tok.type === 'int' || this.isPatternRangeEnd(this.peek())))) {
+      const inclusive = !this.is('..');
+      this.next();
+      let end = null;
+      if (this.isPatternRangeEnd(this.tok)) end = this.parsePatternAtom();
+      return this.finish({ type: 'RangePat', start: pattern, end, inclusive }, start);
+    }
+    return pattern;
+  }
+
+  isPatternRangeEnd(token) {
+    return ['int', 'float', 'char', 'string'].includes(token.type) || this.is('-', token) ||
+      (this.isIdent(token) && !RESERVED.has(token.value) && token.value !== 'if');
+  }
+
+  parsePatternAtom() {
+    const start = this.tok;
+    const token = this.tok;
+
+    if (this.is('_')) {
+      this.next();
+      return this.finish({ type: 'WildPat' }, start);
+    }
+    if (this.is('..')) {
+      this.next();
+      return this.finish({ type: 'RestPat' }, start);
+    }
+    if (this.is('&') || this.is('&&')) {
+      this.next();
+      const mutable = !!this.eat('mut');
+      const pattern = this.parsePatternNoTopAlt();
+      return this.finish({ type: 'RefPat', mutable, pattern }, start);
+    }
+    if (this.is('(')) {
+      this.next();
+      const elems = [];
+      let trailingComma = false;
+      while (!this.is(')')) {
+        elems.push(this.parsePattern());
+        trailingComma = false;
+        if (!this.eat(',')) break;
+        trailingComma = true;
+      }
+      this.expect(')');
+      if (elems.length === 1 && !trailingComma) return this.finish({ type: 'ParenPat', pattern: elems[0] }, start);
+      return this.finish({ type: 'TuplePat', elems }, start);
+    }
+    if (this.is('[')) {
+      this.next();
+      const elems = [];
+      while (!this.is(']')) {
+        elems.push(this.parsePattern());
+        if (!this.eat(',')) break;
+      }
+      this.expect(']');
+      return this.finish({ type: 'SlicePat', elems }, start);
+    }
+    if (['int', 'float', 'string', 'char'].includes(token.type) || this.is('-') || this.is('true') || this.is('false')) {
+      const negative = !!this.eat('-');
+      const literal = this.parseLiteral();
+      if (negative) literal.value = `-${literal.value}`;
+      return this.finish({ type: 'LitPat', literal }, start);
+    }
+    if (this.is('box')) {
+      this.next();
+      return this.finish({ type: 'BoxPat', pattern: this.parsePatternNoTopAlt() }, start);
+    }
+    if (this.is('ref') || this.is('mut')) {
+      const byRef = !!this.eat('ref');
+      const mutable = !!this.eat('mut');
+      const name = this.expectIdent().value;
+      let subpattern = null;
+      if (this.eat('@')) subpattern = this.parsePatternNoTopAlt();
+      return this.finish({ type: 'IdentPat', name, byRef, mutable, subpattern }, start);
+    }
+    if (this.is('<')) {
+      const path = this.parseQualifiedPath('expr');
+      return this.finish({ type: 'PathPat', path }, start);
+    }
+    if (this.isIdent() || this.is('::')) {
+      const following = this.peek();
+      const isPath = this.is('::') || this.is('::', following) || this.is('(', following) || this.is('{', following) || this.is('!', following);
+      if (!isPath) {
+        const name = this.next().value;
+        let subpattern = null;
+        if (this.eat('@')) subpattern = this.parsePatternNoTopAlt();
+        return this.finish({ type: 'IdentPat', name, byRef: false, mutable: false, subpattern }, start);
+      }
+      const path = this.parsePath('expr');
+      if (this.is('!')) {
+        this.next();
+        this.tokenTree();
+        return this.finish({ type: 'MacroPat', path }, start);
+      }
+      if (this.is('(')) {
+        this.next();
+        const elems = [];
+        while (!this.is(')')) {
+          elems.push(this.parsePattern());
+          if (!this.eat(',')) break;
+        }
+        this.expect(')');
+        return this.finish({ type: 'TupleStructPat', path, elems }, start);
+      }
+      if (this.is('{')) {
+        this.next();
+        const fields = [];
+        let rest = false;
+        while (!this.is('}')) {
+          const fieldStart = this.tok;
+          this.parseOuterAttributes();
+          if (this.eat('..')) {
+            rest = true;
+            break;
+          }
+          if ((this.isIdent() || this.tok.type === 'int') && this.is(':', this.peek())) {
+            const name = this.next().value;
+            this.next();
+            const pattern = this.parsePattern();
+            fields.push(this.finish({ type: 'FieldPat', name, pattern, shorthand: false }, fieldStart));
+          } else {
+            this.eat('box');
+            const pattern = this.parsePatternAtom();
+            fields.push(this.finish({ type: 'FieldPat', name: pattern.name, pattern, shorthand: true }, fieldStart));
+          }
+          if (!this.eat(',')) break;
+        }
+        this.expect('}');
+        return this.finish({ type: 'StructPat', path, fields, rest }, start);
+      }
+      return this.finish({ type: 'PathPat', path }, start);
+    }
+    throw new ParseError(`Expected pattern but found '${token.value || token.type}'`, token);
+  }
+
+  // ---------------------------------------------------------------------------
+  // Statements and blocks
+  // ---------------------------------------------------------------------------
+
+  parseBlock(extra = {}) {
+    const { startToken, ...flags } = extra;
+    const start = this.expect('{');
+    const attrs = this.parseInnerAttributes();
+    const stmts = [];
+    while (!this.is('}')) {
+      if (this.tok.type === 'eof') throw new ParseError('Unexpected end of file in block', this.tok);
+      const stmt = this.parseStmt();
+      if (stmt) stmts.push(stmt);
+    }
+    this.expect('}');
+    const node = { type: 'Block', stmts, unsafe: false, label: null, ...flags };
+    if (attrs.length > 0) node.attrs = attrs;
+    return this.finish(node, startToken || start);
+  }
+
+  parseStmt() {
+    const start = this.tok;
+    if (this.eat(';')) return null;
+
+    if (this.isItemStart()) {
+      // Attributes may decorate an expression statement rather than an item
+      const save = this.pos;
+      const attrs = this.parseOuterAttributes();
+      if (this.is('}')) return null;
+      if (this.isItemStart()) {
+        this.pos = save;
+        const item = this.parseItem();
+        return this.finish({ type: 'ItemStmt', item }, start);
+      }
+      const stmt = this.parseStmt();
+      if (stmt) {
+        stmt.attrs = attrs;
+        this.finish(stmt, start);
+      }
+      return stmt;
+    }
+
+    if (this.is('let')) {
+      this.next();
+      const pattern = this.parsePattern();
+      let letType = null;
+      if (this.eat(':')) letType = this.parseType();
+      let init = null;
+      let elseBlock = null;
+      if (this.eat('=')) {
+        init = this.parseExpr();
+        if (this.is('else')) {
+          this.next();
+          elseBlock = this.parseBlock();
+        }
+      }
+      this.expect(';');
+      return this.finish({ type: 'Let', pattern, letType, init, elseBlock }, start);
+    }
+
+    let expr;
+    if (this.startsBlockLike()) {
+      expr = this.parseBlockLike();
+      // A block-like expression ends the statement unless it is immediately used as a value
+      if (this.is('.') || this.is('?')) {
+        expr = this.parsePostfix(expr, {});
+        expr = this.continueExpression(expr, start);
+      } else if (this.is(';')) {
+        this.next();
+        return this.finish({ type: 'ExprStmt', expr, semi: true }, start);
+      } else {
+        return this.finish({ type: 'ExprStmt', expr, semi: false }, start);
+      }
+    } else {
+      expr = this.parseExpr();
+    }
+
+    if (this.eat(';')) {
+      return this.finish({ type: 'ExprStmt', expr, semi: true }, start);
+    }
+    if (this.is('}') || (expr.type === 'Macro' && expr.delimiter === '{')) {
+      return this.finish({ type: 'ExprStmt', expr, semi: false }, start);
+    }
+    throw new ParseError(`Expected ';' or '}' after expression but found '${this.tok.value || this.tok.type}'`, this.tok);
+  }
+
+  /**
+   * Continue parsing binary/assignment operators after an already-parsed operand
+   */
+  continueExpression(left, start) {
+    const binary = this.parseBinaryRest(left, 0, {}, start);
+    if (ASSIGNMENT_OPERATORS.has(this.tok.value) && this.tok.type === 'punct') {
+      const op = this.next().value;
+      const right = this.parseExpr();
+      return this.finish({ type: op === '=' ? 'Assign' : 'CompoundAssign', operator: op, left: binary, right }, start);
+    }
+    return binary;
+  }
+
+  startsBlockLike() {
+    const token = this.tok;
+    if (this.is('{')) return true;
+    if (this.is('if') || this.is('match') || this.is('loop') || this.is('while') || this.is('for')) return true;
+    if (this.is('unsafe') && this.is('{', this.peek())) return true;
+    if (token.type === 'lifetime' && this.is(':', this.peek())) return true;
+    return false;
+  }
+
+  parseBlockLike() {
+    return this.parsePrimary({});
+  }
+
+  // ---------------------------------------------------------------------------
+  // Expressions
+  // ---------------------------------------------------------------------------
+
+  /**
+   * @param {Object} r - Restrictions: `noStruct` disables struct literals (if/while/match heads)
+   */
+  parseExpr(r = {}) {
+    const start = this.tok;
+    const left = this.parseRange(r);
+    if (this.tok.type === 'punct' && ASSIGNMENT_OPERATORS.has(this.tok.value)) {
+      const op = this.next().value;
+      const right = this.parseExpr(r);
+      return this.finish({ type: op === '=' ? 'Assign' : 'CompoundAssign', operator: op, left, right }, start);
+    }
+    return left;
+  }
+
+  parseRange(r) {
+    const start = this.tok;
+    if (this.is('..') || this.is('..=')) {
+      const inclusive = this.next().value === '..=';
+      let to = null;
+      if (this.canStartExpr(r)) to = this.parseBinary(1, r);
+      return this.finish({ type: 'Range', from: null, to, inclusive }, start);
+    }
+    const from = this.parseBinary(1, r);
+    if (this.is('..') || this.is('..=')) {
+      const inclusive = this.next().value === '..=';
+      let to = null;
+      if (this.canStartExpr(r)) to = this.parseBinary(1, r);
+      return this.finish({ type: 'Range', from, to, inclusive }, start);
+    }
+    return from;
+  }
+
+  canStartExpr(r = {}) {
+    const token = this.tok;
+    switch (token.type) {
+      case 'int': case 'float': case 'string': case 'char': case 'lifetime':
+        return true;
+      case 'ident':
+        return !RESERVED.has(token.value) || token.value === 'move' || token.value === 'static';
+      case 'punct':
+        if (token.value === '{') return !r.noStruct;
+        return ['(', '[', '-', '!', '*', '&', '&&', '|', '||', '..', '..=', '<', '::', '#'].includes(token.value);
+      default:
+        return false;
+    }
+  }
+
+  parseBinary(minPrec, r) {
+    const start = this.tok;
+    const left = this.parseUnary(r);
+    return this.parseBinaryRest(left, minPrec, r, start);
+  }
+
+  parseBinaryRest(left, minPrec, r, start) {
+    for (;;) {
+      const token = this.tok;
+      if (this.is('as')) {
+        if (CAST_PRECEDENCE < minPrec) break;
+        this.next();
+        const castType = this.parseTypeNoBounds();
+        left = this.finish({ type: 'Cast', expr: left, castType }, start);
+        continue;
+      }
+      if (token.type !== 'punct') break;
+      const prec = BINARY_PRECEDENCE[token.value];
+      if (prec === undefined || prec < minPrec) break;
+      this.next();
+      const right = this.parseBinary(prec + 1, r);
+      left = this.finish({ type: 'Binary', operator: token.value, left, right }, start);
+    }
+    return left;
+  }
+
+  parseUnary(r) {
+    const start = this.tok;
+    if (this.is('-') || this.is('!') || this.is('*')) {
+      const operator = this.next().value;
+      const argument = this.parseUnary(r);
+      return this.finish({ type: 'Unary', operator, argument }, start);
+    }
+    if (this.is('&') || this.is('&&')) {
+      const double = this.next().value === '&&';
+      let raw = false;
+      if (this.is('raw') && (this.is('const', this.peek()) || this.is('mut', this.peek()))) {
+        this.next();
+        raw = true;
+      }
+      const mutable = !!this.eat('mut');
+      if (raw && !mutable) this.eat('const');
+      const argument = this.parseUnary(r);
+      let node = this.finish({ type: 'Ref', mutable, raw, argument }, start);
+      if (double) node = this.finish({ type: 'Ref', mutable: false, raw: false, argument: node }, start);
+      return node;
+    }
+    if (this.is('box') && this.startsBoxOperand(this.peek())) {
+      // Pre-1.0 `box expr` syntax, still found in older code
+      this.next();
+      const argument = this.parseUnary(r);
+      return this.finish({ type: 'Unary', operator: 'box', argument }, start);
+    }
+    const primary = this.parsePrimary(r);
+    return this.parsePostfix(primary, r);
+  }
+
+  startsBoxOperand(token) {
+    if (['ident', 'int', 'float', 'string', 'char'].includes(token.type)) return !RESERVED.has(token.value) || token.value === 'move';
+    return ['(', '[', '|', '||', '&', '*'].includes(token.value) && token.type === 'punct';
+  }
+
+  parsePostfix(expr, r) {
+    const start = this.tokenAt(expr);
+    for (;;) {
+      if (this.is('?')) {
+        this.next();
+        expr = this.finish({ type: 'Try', argument: expr }, start);
+      } else if (this.is('.')) {
+        this.next();
+        const token = this.tok;
+        if (this.is('await')) {
+          this.next();
+          expr = this.finish({ type: 'Await', argument: expr }, start);
+        } else if (token.type === 'int') {
+          this.next();
+          expr = this.finish({ type: 'FieldAccess', object: expr, field: token.value }, start);
+        } else if (token.type === 'float') {
+          // `t.0.1` is lexed as `t` `.` `0.1`
+          this.next();
+          const [first, second] = token.value.split('.');
+          expr = this.finish({ type: 'FieldAccess', object: expr, field: first }, start);
+          if (second) expr = this.finish({ type: 'FieldAccess', object: expr, field: second }, start);
+        } else {
+          const name = this.expectIdent();
+          let turbofish = null;
+          if (this.is('::') && this.is('<', this.peek())) {
+            this.next();
+            turbofish = this.parseGenericArgs();
+          }
+          if (this.is('(')) {
+            const args = this.parseCallArgs();
+            expr = this.finish({ type: 'MethodCall', receiver: expr, method: name.value, turbofish, args, methodLoc: tokenLoc(name) }, start);
+          } else {
+            expr = this.finish({ type: 'FieldAccess', object: expr, field: name.value }, start);
+          }
+        }
+      } else if (this.is('(')) {
+        const args = this.parseCallArgs();
+        expr = this.finish({ type: 'Call', callee: expr, args }, start);
+      } else if (this.is('[')) {
+        this.next();
+        const index = this.parseExpr();
+        this.expect(']');
+        expr = this.finish({ type: 'Index', object: expr, index }, start);
+      } else {
+        return expr;
+      }
+    }
+  }
+
+  tokenAt(node) {
+    return { line: node.loc.start.line, column: node.loc.start.column, offset: node.range[0] };
+  }
+
+  parseCallArgs() {
+    this.expect('(');
+    const args = [];
+    while (!this.is(')')) {
+      args.push(this.parseExpr());
+      if (!this.eat(',')) break;
+    }
+    this.expect(')');
+    return args;
+  }
+
+  parseLiteral() {
+    const start = this.tok;
+    const token = this.next();
+    let kind;
+    switch (token.type) {
+      case 'int': kind = 'int'; break;
+      case 'float': kind = 'float'; break;
+      case 'string': kind = token.byte ? 'bytestring' : 'string'; break;
+      case 'char': kind = token.byte ? 'byte' : 'char'; break;
+      default:
+        if (token.value === 'true' || token.value === 'false') {
+          kind = 'bool';
+          break;
+        }
+        throw new ParseError(`Expected literal but found '${token.value}'`, token);
+    }
+    return this.finish({ type: 'Literal', kind, value: token.value, raw: token.raw || token.value, suffix: token.suffix || null }, start);
+  }
+
+  parsePrimary(r) {
+    const start = this.tok;
+    const token = this.tok;
+
+    switch (token.type) {
+      case 'int': case 'float': case 'string': case 'char':
+        return this.parseLiteral();
+      case 'lifetime': {
+        // Labeled loop or block: 'outer: loop { ... }
+        const label = this.next().value;
+        this.expect(':');
+        const expr = this.parsePrimary(r);
+        expr.label = label;
+        return this.finish(expr, start);
+      }
+      default:
+        break;
+    }
+
+    if (this.is('true') || this.is('false')) return this.parseLiteral();
+
+    if (this.is('(')) {
+      this.next();
+      if (this.eat(')')) return this.finish({ type: 'Tuple', elems: [] }, start);
+      const first = this.parseExpr();
+      if (this.eat(')')) return this.finish({ type: 'Paren', expr: first }, start);
+      const elems = [first];
+      while (this.eat(',')) {
+        if (this.is(')')) break;
+        elems.push(this.parseExpr());
+      }
+      this.expect(')');
+      return this.finish({ type: 'Tuple', elems }, start);
+    }
+
+    if (this.is('[')) {
+      this.next();
+      const elems = [];
+      if (this.eat(']')) return this.finish({ type: 'Array', elems }, start);
+      const first = this.parseExpr();
+      if (this.eat(';')) {
+        const length = this.parseExpr();
+        this.expect(']');
+        return this.finish({ type: 'ArrayRepeat', value: first, length }, start);
+      }
+      elems.push(first);
+      while (this.eat(',')) {
+        if (this.is(']')) break;
+        elems.push(this.parseExpr());
+      }
+      this.expect(']');
+      return this.finish({ type: 'Array', elems }, start);
+    }
+
+    if (this.is('{')) return this.parseBlock();
+
+    if (this.is('unsafe')) {
+      this.next();
+      return this.parseBlock({ unsafe: true, startToken: start });
+    }
+
+    if (this.is('async') || (this.is('const') && this.is('{', this.peek()))) {
+      const keyword = this.next().value;
+      const isMove = !!this.eat('move');
+      if (this.is('|') || this.is('||')) return this.parseClosure(start, isMove, keyword === 'async');
+      return this.parseBlock({ [keyword]: true, move: isMove, startToken: start });
+    }
+
+    if (this.is('|') || this.is('||') || this.is('move') || (this.is('static') && (this.is('|', this.peek()) || this.is('||', this.peek()) || this.is('move', this.peek())))) {
+      this.eat('static');
+      const isMove = !!this.eat('move');
+      return this.parseClosure(start, isMove, false);
+    }
+
+    if (this.is('if')) return this.parseIf();
+
+    if (this.is('match')) {
+      this.next();
+      const expr = this.parseExpr({ noStruct: true });
+      this.expect('{');
+      this.parseInnerAttributes();
+      const arms = [];
+      while (!this.is('}')) {
+        const armStart = this.tok;
+        const attrs = this.parseOuterAttributes();
+        const pattern = this.parsePattern();
+        let guard = null;
+        if (this.eat('if')) guard = this.parseExpr();
+        this.expect('=>');
+        const body = this.parseExpr();
+        const arm = this.finish({ type: 'MatchArm', pattern, guard, body, attrs }, armStart);
+        arms.push(arm);
+        if (!this.eat(',') && !this.is('}') && !isBlockLike(body)) {
+          throw new ParseError(`Expected ',' after match arm but found '${this.tok.value || this.tok.type}'`, this.tok);
+        }
+      }
+      this.expect('}');
+      return this.finish({ type: 'Match', expr, arms }, start);
+    }
+
+    if (this.is('loop')) {
+      this.next();
+      const body = this.parseBlock();
+      return this.finish({ type: 'Loop', body, label: null }, start);
+    }
+
+    if (this.is('while')) {
+      this.next();
+      const cond = this.parseCondition();
+      const body = this.parseBlock();
+      return this.finish({ type: 'While', cond, body, label: null }, start);
+    }
+
+    if (this.is('for')) {
+      this.next();
+      const pattern = this.parsePattern();
+      this.expect('in');
+      const iter = this.parseExpr({ noStruct: true });
+      const body = this.parseBlock();
+      return this.finish({ type: 'For', pattern, iter, body, label: null }, start);
+    }
+
+    if (this.is('let')) {
+      // `let` inside an if/while condition (including let-chains)
+      this.next();
+      const pattern = this.parsePattern();
+      this.expect('=');
+      const expr = this.parseBinary(BINARY_PRECEDENCE['&&'] + 1, { noStruct: true });
+      return this.finish({ type: 'LetExpr', pattern, expr }, start);
+    }
+
+    if (this.is('return') || this.is('break') || this.is('yield') || this.is('become')) {
+      const keyword = this.next().value;
+      let label = null;
+      if (keyword === 'break' && this.tok.type === 'lifetime') label = this.next().value;
+      let argument = null;
+      if (this.canStartExpr(r) && !this.is(';') && !this.is('}')) argument = this.parseExpr(r);
+      const type = keyword === 'return' ? 'Return' : keyword === 'break' ? 'Break' : 'Yield';
+      return this.finish({ type, argument, label }, start);
+    }
+
+    if (this.is('continue')) {
+      this.next();
+      let label = null;
+      if (this.tok.type === 'lifetime') label = this.next().value;
+      return this.finish({ type: 'Continue', label }, start);
+    }
+
+    if (this.is('<')) {
+      const path = this.parseQualifiedPath('expr');
+      return this.finish({ type: 'PathExpr', path, name: path.name }, start);
+    }
+
+    if (this.isIdent() || this.is('::')) {
+      const path = this.parsePath('expr');
+
+      // Macro invocation (but not `x != y`)
+      if (this.is('!') && (this.is('(', this.peek()) || this.is('[', this.peek()) || this.is('{', this.peek()))) {
+        this.next();
+        return this.parseMacroInvocation(path, start);
+      }
+
+      if (this.is('{') && !r.noStruct && this.looksLikeStructLiteral(path)) {
+        return this.parseStructLiteral(path, start);
+      }
+
+      return this.finish({ type: 'PathExpr', path, name: path.segments.length === 1 ? path.name : path.text }, start);
+    }
+
+    throw new ParseError(`Expected expression but found '${token.value || token.type}'`, token);
+  }
+
+  looksLikeStructLiteral(path) {
+    const next1 = this.peek();
+    const next2 = this.peek(2);
+    if (this.is('}', next1)) return true;
+    if (this.is('..', next1)) return true;
+    if ((this.isIdent(next1) || next1.type === 'int') && (this.is(':', next2) || this.is(',', next2) || this.is('}', next2))) {
+      // `Foo { x }` could be a block in statement position, but paths are only followed by blocks in struct literals
+      return true;
+    }
+    return /^[A-Z]/.test(path.name) || path.name === 'Self';
+  }
+
+  parseStructLiteral(path, start) {
+    this.expect('{');
+    const fields = [];
+    let base = null;
+    while (!this.is('}')) {
+      const fieldStart = this.tok;
+      this.parseOuterAttributes();
+      if (this.eat('..')) {
+        if (!this.is('}')) base = this.parseExpr();
+        break;
+      }
+      const nameToken = this.next();
+      if (nameToken.type !== 'ident' && nameToken.type !== 'int') {
+        throw new ParseError(`Expected field name but found '${nameToken.value || nameToken.type}'`, nameToken);
+      }
+      let value;
+      let shorthand = false;
+      if (this.eat(':')) {
+        value = this.parseExpr();
+      } else {
+        shorthand = true;
+        value = this.finish({ type: 'PathExpr', path: { type: 'Path', global: false, segments: [{ name: nameToken.value, generics: null }], text: nameToken.value, name: nameToken.value, loc: tokenLoc(nameToken), range: [nameToken.offset, nameToken.endOffset] }, name: nameToken.value }, nameToken);
+      }
+      fields.push(this.finish({ type: 'FieldInit', name: nameToken.value, value, shorthand }, fieldStart));
+      if (!this.eat(',')) break;
+    }
+    this.expect('}');
+    return this.finish({ type: 'StructLiteral', path, fields, base, name: path.text }, start);
+  }
+
+  parseMacroInvocation(path, start) {
+    const tree = this.tokenTree();
+    const node = {
+      type: 'Macro',
+      path,
+      name: path.name,
+      delimiter: tree.open,
+      tokens: tree.tokens,
+      args: null,
+      text: this.code.slice(tree.start.endOffset, tree.close.offset)
+    };
+    node.args = this.parseMacroArgs(tree.tokens);
+    return this.finish(node, start);
+  }
+
+  /**
+   * Try to read macro contents as a comma separated expression list (or `expr; count` for vec!).
+   * Returns null when the tokens are not plain expressions.
+   */
+  parseMacroArgs(tokens) {
+    if (tokens.length === 0) return [];
+    const eofToken = { ...tokens[tokens.length - 1], type: 'eof', value: '', offset: tokens[tokens.length - 1].endOffset };
+    const sub = Object.create(Parser.prototype);
+    sub.code = this.code;
+    sub.tokens = [...tokens.filter(t => t.type !== 'doc'), eofToken];
+    sub.comments = [];
+    sub.pos = 0;
+    sub.prev = null;
+    sub.options = this.options;
+    try {
+      const args = [];
+      while (sub.tok.type !== 'eof') {
+        // Named format arguments: `name = expr`
+        if (sub.isIdent() && sub.is('=', sub.peek())) {
+          sub.next();
+          sub.next();
+        }
+        args.push(sub.parseExpr());
+        if (sub.tok.type === 'eof') break;
+        if (!sub.eat(',') && !sub.eat(';')) return null;
+      }
+      return args;
+    } catch (error) {
+      return null;
+    }
+  }
+
+  parseClosure(start, isMove, isAsync) {
+    const params = [];
+    if (!this.eat('||')) {
+      this.expect('|');
+      while (!this.is('|')) {
+        const paramStart = this.tok;
+        this.parseOuterAttributes();
+        const pattern = this.parsePatternNoTopAlt();
+        let paramType = null;
+        if (this.eat(':')) paramType = this.parseTypeNoBounds();
+        params.push(this.finish({ type: 'Param', pattern, paramType, name: patternName(pattern), attrs: [] }, paramStart));
+        if (!this.eat(',')) break;
+      }
+      this.expect('|');
+    }
+    let returnType = null;
+    let body;
+    if (this.eat('->')) {
+      returnType = this.parseTypeNoBounds();
+      body = this.parseBlock();
+    } else {
+      body = this.parseExpr();
+    }
+    return this.finish({ type: 'Closure', params, returnType, body, move: isMove, async: isAsync }, start);
+  }
+
+  parseCondition() {
+    return this.parseExpr({ noStruct: true });
+  }
+
+  parseIf() {
+    const start = this.expect('if');
+    const cond = this.parseCondition();
+    const consequent = this.parseBlock();
+    let alternate = null;
+    if (this.eat('else')) {
+      alternate = this.is('if') ? this.parseIf() : this.parseBlock();
+    }
+    return this.finish({ type: 'If', cond, consequent, alternate }, start);
+  }
+}
+
+function tokenLoc(token) {
+  return {
+    start: { line: token.line, column: token.column },
+    end: { line: token.endLine, column: token.endColumn }
+  };
+}
+
+function normalizeWhitespace(text) {
+  return text.replace(/\s+/g, ' ').replace(/\s*(::|<|>|,|&)\s*/g, (m, p) => (p === ',' ? ', ' : p)).trim();
+}
+
+function isBlockLike(node) {
+  return ['Block', 'If', 'Match', 'Loop', 'While', 'For'].includes(node.type) ||
+    (node.type === 'Macro' && node.delimiter === '{');
+}
+
+function patternName(pattern) {
+  if (!pattern) return null;
+  if (pattern.type === 'IdentPat') return pattern.name;
+  if (pattern.type === 'RefPat' || pattern.type === 'ParenPat') return patternName(pattern.pattern);
+  return null;
+}
+
+function typeBaseName(typeNode) {
+  if (!typeNode) return null;
+  switch (typeNode.type) {
+    case 'PathType':
+      return typeNode.path.segments.length > 0 ? typeNode.path.name : typeNode.text;
+    case 'RefType':
+    case 'PtrType':
+    case 'ParenType':
+      return typeBaseName(typeNode.elem);
+    default:
+      return typeNode.text;
+  }
+}
+
+function flattenUseTree(tree, prefix) {
+  const segments = [...prefix, ...tree.segments];
+  if (tree.children) {
+    return tree.children.flatMap(child => flattenUseTree(child, segments));
+  }
+  if (tree.glob) return [{ path: segments, glob: true, alias: null }];
+  return [{ path: segments, glob: false, alias: tree.alias }];
+}
+
+/**
+ * Parse Rust source code into an AST
+ * @param {string} code - Rust source code
+ * @param {Object} options - Parser options (reserved)
+ * @returns {Object} - SourceFile node
+ */
+function parse(code, options = {}) {
+  const parser = new Parser(code, options);
+  return parser.parseSourceFile();
+}
+
+const SKIPPED_KEYS = new Set(['loc', 'range', 'tokens', 'methodLoc', 'declarationLoc', 'comments']);
+
+/**
+ * Walk an AST, calling `visitors[node.type](node, parent)` for every node.
+ * Returning `false` from a visitor skips that node's children.
+ * @param {Object} node - AST node
+ * @param {Object} visitors - Map of node type to callback
+ * @param {Object} parent - Parent node (internal)
+ */
+function visit(node, visitors, parent = null) {
+  if (Array.isArray(node)) {
+    node.forEach(child => visit(child, visitors, parent));
+    return;
+  }
+  if (!node || typeof node !== 'object' || typeof node.type !== 'string') return;
+
+  const callback = visitors[node.type];
+  if (callback && callback(node, parent) === false) return;
+
+  for (const key of Object.keys(node)) {
+    if (SKIPPED_KEYS.has(key)) continue;
+    const child = node[key];
+    if (child && typeof child === 'object') visit(child, visitors, node);
+  }
+
+  const exit = visitors[`${node.type}:exit`];
+  if (exit) exit(node, parent);
+}
+
+module.exports = {
+  parse,
+  visit,
+  tokenize,
+  typeBaseName,
+  ParseError
+};
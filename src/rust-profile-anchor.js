@@ -0,0 +1,637 @@
+const { visit } = require('./rust-parser');
+const { isSelf, nodeText, splitGenericType, walkFunction } = require('./rust-ast');
+
+/**
+ * Solana/Anchor profile: extracts the `#[program]` instruction handlers and their `#[derive(Accounts)]`
+ * contexts, and checks the account constraints against how the handlers use each account.
+ */
+
+const RAW_ACCOUNT_KINDS = new Set(['AccountInfo', 'UncheckedAccount']);
+const DATA_ACCOUNT_KINDS = new Set(['Account', 'AccountLoader', 'InterfaceAccount']);
+const MUTABLE_CONSTRAINTS = ['mut', 'init', 'init_if_needed', 'zero'];
+const ROLE_ACCOUNT = /(^|_)(authority|admin|owner|operator|manager|governor|signer)$/;
+
+// Methods that write an account's lamports or data
+const ACCOUNT_WRITE_METHODS = new Set([
+  'try_borrow_mut_lamports', 'try_borrow_mut_data', 'sub_lamports', 'add_lamports',
+  'set_inner', 'realloc', 'assign', 'close', 'load_mut', 'load_init'
+]);
+const FIELD_MUTATING_METHODS = new Set(['push', 'insert', 'remove', 'pop', 'clear', 'extend', 'retain', 'truncate', 'swap_remove']);
+const DATA_READ_METHODS = new Set(['try_borrow_data', 'try_from_slice', 'try_deserialize', 'try_deserialize_unchecked', 'deserialize', 'unpack', 'unpack_from_slice']);
+
+// Accounts written by the token and system program CPIs, keyed by the CPI accounts struct
+const CPI_WRITTEN_ACCOUNTS = {
+  Transfer: ['from', 'to'],
+  TransferChecked: ['from', 'to'],
+  MintTo: ['mint', 'to'],
+  Burn: ['mint', 'from'],
+  CloseAccount: ['account', 'destination'],
+  CreateAccount: ['from', 'to']
+};
+
+const SIGNED_CALLS = new Set(['create_program_address', 'invoke_signed', 'new_with_signer']);
+
+/**
+ * True when the file is an Anchor program
+ */
+function detect(ast, code) {
+  if (/\banchor_lang\b/.test(code)) return true;
+  let found = false;
+  visit(ast, {
+    Mod: function(node) {
+      if (hasAttribute(node, 'program')) found = true;
+    },
+    Struct: function(node) {
+      if (derives(node, 'Accounts')) found = true;
+    }
+  });
+  return found;
+}
+
+/**
+ * Run the Anchor checks
+ * @param {Object} context - `{ ast, code, definitions, locate }`
+ * @returns {Object} - `{ findings, summary }`
+ */
+function analyze({ ast, code, definitions, locate }) {
+  const findings = { high: [], medium: [], low: [], info: [] };
+  const program = extractProgram(ast, definitions, code);
+  const usage = collectAccountUsage(program, definitions);
+
+  for (const context of program.contexts.values()) {
+    const uses = usage.get(context.name) || new Map();
+    const useOf = name => uses.get(name) || emptyUsage();
+    const handlers = program.instructions.filter(instruction => instruction.context === context.name).map(instruction => `\`${instruction.name}\``);
+    const usedBy = handlers.length > 0 ? ` (used by ${handlers.join(', ')})` : '';
+
+    for (const account of context.accounts) {
+      const use = useOf(account.name);
+
+      // 1. Authority accounts must sign
+      if (ROLE_ACCOUNT.test(account.name) && !isSigner(account) && !account.constraints.has('seeds') &&
+          (RAW_ACCOUNT_KINDS.has(account.kind) || account.kind === 'SystemAccount') && !use.signerChecked) {
+        findings.high.push({
+          ruleId: 'anchor-missing-signer',
+          title: 'Authority Account Is Not a Signer',
+          description: `\`${context.name}.${account.name}\`${usedBy} is an authority account of type \`${account.typeText}\`, but nothing requires it to sign the transaction. Anyone can pass the authority's public key and act as the authority.`,
+          recommendation: `Declare the account as \`Signer<'info>\` or add the \`signer\` constraint.`,
+          location: locate(account.node)
+        });
+      }
+
+      // 2. Stored authorities must match the accounts passed in
+      if (DATA_ACCOUNT_KINDS.has(account.kind) && !isCreated(account) && (isMutable(account) || use.writes.length > 0)) {
+        const data = program.dataAccounts.get(account.inner);
+        for (const field of data ? data.authorityFields : []) {
+          const counterpart = context.accounts.find(other => other.name === field);
+          // Seeds derived from the authority's key bind the account to it as well as has_one would
+          const seeds = account.constraints.get('seeds');
+          if (!counterpart || hasOneTo(account, field) || constrainedInText(context, account.name, field) ||
+              (typeof seeds === 'string' && mentions(seeds, field)) || use.comparedFields.has(field)) {
+            continue;
+          }
+          findings.high.push({
+            ruleId: 'anchor-missing-has-one',
+            title: 'Missing has_one Constraint',
+            description: `\`${context.name}.${account.name}\`${usedBy} stores its authority in \`${account.inner}.${field}\`, but nothing checks that the \`${field}\` account passed to the instruction is that stored key. Any signer can operate on an account they do not control.`,
+            recommendation: `Add \`has_one = ${field}\` to the \`#[account(...)]\` constraints of \`${account.name}\`.`,
+            location: locate(account.node)
+          });
+        }
+      }
+
+      // 3. Raw accounts whose data is read must be checked for their owner
+      if (RAW_ACCOUNT_KINDS.has(account.kind) && use.dataReads.length > 0 && !use.ownerChecked &&
+          !['owner', 'address', 'seeds'].some(key => account.constraints.has(key))) {
+        findings.high.push({
+          ruleId: 'anchor-missing-owner-check',
+          title: 'Missing Owner Check',
+          description: `The data of \`${context.name}.${account.name}\` is read at line ${use.dataReads[0].loc.start.line}, but the account is an \`${account.kind}\` and its owner is never checked. An attacker can pass an account owned by another program containing forged data.`,
+          recommendation: `Use \`Account<'info, T>\`, which checks the owner and discriminator, or add an \`owner = <program id>\` constraint.`,
+          location: locate(account.node)
+        });
+      }
+
+      // 4. Unchecked accounts must document why they are safe
+      if (RAW_ACCOUNT_KINDS.has(account.kind) && !account.checkComment) {
+        findings.medium.push({
+          ruleId: 'anchor-unchecked-account',
+          title: 'Unchecked Account Without CHECK Comment',
+          description: `\`${context.name}.${account.name}\` is an \`${account.kind}\`, which Anchor does not validate, and it has no \`/// CHECK:\` doc comment explaining why that is safe.`,
+          recommendation: 'Use a typed account (`Account`, `Signer`, `Program`, ...) or document the manual checks in a `/// CHECK:` comment.',
+          location: locate(account.node)
+        });
+      }
+
+      // 5. Written accounts must be mutable
+      const closeTarget = context.accounts.find(other => other.constraints.get('close') === account.name);
+      const firstWrite = use.writes[0];
+      if ((firstWrite || closeTarget) && !isMutable(account) && !['Program', 'Sysvar', 'Interface'].includes(account.kind)) {
+        const reason = firstWrite
+          ? `is written at line ${firstWrite.loc.start.line}`
+          : `receives the lamports of \`${closeTarget.name}\` when it is closed`;
+        findings.medium.push({
+          ruleId: 'anchor-missing-mut',
+          title: 'Written Account Not Marked Mutable',
+          description: `\`${context.name}.${account.name}\` ${reason}, but it is not marked \`mut\`. The runtime rejects the write or Anchor silently drops the change when the instruction exits.`,
+          recommendation: `Add \`mut\` to the \`#[account(...)]\` constraints of \`${account.name}\`.`,
+          location: locate(account.node)
+        });
+      }
+
+      // 6. PDA bumps must be canonical and seeds unambiguous
+      const bump = account.constraints.get('bump');
+      if (typeof bump === 'string' && (/^\d+$/.test(bump) || context.instructionArgs.some(arg => mentions(bump, arg.name)))) {
+        findings.medium.push({
+          ruleId: 'anchor-pda-noncanonical-bump',
+          title: 'PDA Bump Supplied by the Caller',
+          description: `The bump of \`${context.name}.${account.name}\` is \`${bump}\`, a value chosen by the caller rather than the canonical bump. Several valid addresses can then be derived from the same seeds.`,
+          recommendation: 'Use a bare `bump` to let Anchor find the canonical bump, or `bump = <account>.bump` with the bump stored at initialization.',
+          location: locate(account.node)
+        });
+      }
+      const seeds = account.constraints.get('seeds');
+      if (typeof seeds === 'string' && hasAdjacentVariableSeeds(seeds, context.instructionArgs)) {
+        findings.medium.push({
+          ruleId: 'anchor-pda-seed-collision',
+          title: 'Ambiguous PDA Seeds',
+          description: `The seeds of \`${context.name}.${account.name}\` (\`${seeds}\`) place two variable-length values next to each other, so different inputs (e.g. "ab" + "c" and "a" + "bc") derive the same address.`,
+          recommendation: 'Separate variable-length seeds with a fixed-length value or a delimiter, or hash them into a fixed-length seed.',
+          location: locate(account.node)
+        });
+      }
+    }
+  }
+
+  // PDAs derived in handler bodies from a bump the caller passes in
+  for (const instruction of program.instructions) {
+    const fn = instruction.fn;
+    if (!fn.node.body) continue;
+    const bumpArgs = instruction.args.filter(arg => /bump/.test(arg.name)).map(arg => arg.name);
+    if (bumpArgs.length === 0) continue;
+    // Follow the bump through local bindings such as `let signer_seeds = &[&[b"seed", &[bump]]];`
+    const tainted = new Map(bumpArgs.map(arg => [arg, arg]));
+    visit(fn.node.body, {
+      Let: function(node) {
+        if (node.pattern.type !== 'IdentPat' || !node.init) return;
+        const source = [...tainted.keys()].find(name => mentions(nodeText(code, node.init), name));
+        if (source) tainted.set(node.pattern.name, tainted.get(source));
+      }
+    });
+    visit(fn.node.body, {
+      Call: function(node) {
+        checkSignedCall(node, node.callee.type === 'PathExpr' ? node.callee.path.segments[node.callee.path.segments.length - 1].name : null);
+      },
+      MethodCall: function(node) {
+        checkSignedCall(node, node.method);
+      }
+    });
+
+    function checkSignedCall(node, name) {
+      if (!SIGNED_CALLS.has(name)) return;
+      const binding = [...tainted.keys()].find(name => mentions(nodeText(code, node), name));
+      if (!binding) return;
+      const argument = tainted.get(binding);
+      findings.medium.push({
+        ruleId: 'anchor-pda-noncanonical-bump',
+        title: 'PDA Bump Supplied by the Caller',
+        description: `\`${instruction.name}\` passes the instruction argument \`${argument}\` as a PDA bump to \`${name}\`. The caller can choose a non-canonical bump and derive a different address from the same seeds.`,
+        recommendation: 'Derive the canonical bump with `Pubkey::find_program_address` or read the bump stored in the account at initialization.',
+        location: locate(node)
+      });
+    }
+  }
+
+  return {
+    findings,
+    summary: {
+      programs: program.programs,
+      instructions: program.instructions.map(({ name, context, args, fn }) => ({
+        name,
+        context,
+        args: args.map(arg => `${arg.name}: ${arg.type}`),
+        line: fn.node.loc.start.line
+      })),
+      contexts: [...program.contexts.values()].map(context => ({
+        name: context.name,
+        accounts: context.accounts.map(account => ({
+          name: account.name,
+          type: account.typeText,
+          constraints: account.constraintText,
+          signer: isSigner(account),
+          mutable: isMutable(account)
+        }))
+      }))
+    }
+  };
+}
+
+/**
+ * Render the instruction and account tables for the Markdown report
+ * @param {Object} summary - `summary` returned by analyze
+ * @returns {string} - Markdown
+ */
+function renderSummary(summary) {
+  const programs = summary.programs.length > 0 ? summary.programs.map(name => `\`${name}\``).join(', ') : '(no `#[program]` module in this file)';
+  let output = `## Anchor Program\n\n`;
+  output += `- **Program:** ${programs}\n`;
+  output += `- **Instructions:** ${summary.instructions.length}\n`;
+  output += `- **Account contexts:** ${summary.contexts.length}\n\n`;
+
+  if (summary.instructions.length > 0) {
+    output += `| Instruction | Accounts | Arguments | Line |\n`;
+    output += `|-------------|----------|-----------|------|\n`;
+    summary.instructions.forEach(instruction => {
+      const args = instruction.args.length > 0 ? instruction.args.map(arg => `\`${arg}\``).join(', ') : '-';
+      output += `| \`${instruction.name}\` | ${instruction.context ? `\`${instruction.context}\`` : '-'} | ${args} | ${instruction.line} |\n`;
+    });
+    output += `\n`;
+  }
+
+  summary.contexts.forEach(context => {
+    output += `### \`${context.name}\` Accounts\n\n`;
+    output += `| Account | Type | Constraints | Signer | Mutable |\n`;
+    output += `|---------|------|-------------|--------|---------|\n`;
+    context.accounts.forEach(account => {
+      const constraints = account.constraints ? `\`${account.constraints.replace(/\|/g, '\\|')}\`` : '-';
+      output += `| \`${account.name}\` | \`${account.type}\` | ${constraints} | ${account.signer ? 'yes' : 'no'} | ${account.mutable ? 'yes' : 'no'} |\n`;
+    });
+    output += `\n`;
+  });
+
+  return output;
+}
+
+/**
+ * Extract the program modules, instruction handlers, account contexts and `#[account]` data structs
+ */
+function extractProgram(ast, definitions, code) {
+  const programs = [];
+  const contexts = new Map();
+  const dataAccounts = new Map();
+
+  visit(ast, {
+    Mod: function(node) {
+      if (hasAttribute(node, 'program')) programs.push(node.name);
+    },
+    Struct: function(node) {
+      if (derives(node, 'Accounts')) {
+        contexts.set(node.name, {
+          name: node.name,
+          node,
+          instructionArgs: parseArguments(attributeArgs(node, 'instruction')),
+          accounts: node.fields.map(field => describeAccount(field))
+        });
+      } else if (hasAttribute(node, 'account')) {
+        dataAccounts.set(node.name, {
+          node,
+          authorityFields: node.fields
+            .filter(field => ROLE_ACCOUNT.test(field.name) && /\bPubkey$/.test(field.fieldType.text))
+            .map(field => field.name)
+        });
+      }
+    }
+  });
+
+  // Instruction handlers are the public functions of the `#[program]` module
+  const instructions = definitions.functions
+    .filter(fn => !fn.owner && programs.includes(fn.modulePath[fn.modulePath.length - 1]))
+    .filter(fn => fn.node.visibility && fn.node.params.length > 0)
+    .map(fn => {
+      const [contextParam, ...rest] = fn.node.params;
+      return {
+        name: fn.name,
+        fn,
+        context: contextTypeOf(contextParam),
+        args: rest.map(param => ({ name: param.name || nodeText(code, param.pattern), type: param.paramType ? param.paramType.text : '_' }))
+      };
+    });
+
+  return { programs, instructions, contexts, dataAccounts };
+}
+
+/**
+ * Describe one field of an Accounts struct: its account kind, inner data type and constraints
+ */
+function describeAccount(field) {
+  let { base, args } = splitGenericType(field.fieldType.text);
+  if (base === 'Box' && args[0]) ({ base, args } = splitGenericType(args[0]));
+  const typeArgs = args.filter(arg => !arg.startsWith('\''));
+  const constraintText = attributeArgs(field, 'account') || '';
+
+  return {
+    name: field.name,
+    node: field,
+    typeText: field.fieldType.text,
+    kind: base.split('::').pop(),
+    inner: typeArgs.length > 0 ? splitGenericType(typeArgs[typeArgs.length - 1]).base.split('::').pop() : null,
+    constraintText,
+    constraints: parseConstraints(constraintText),
+    checkComment: field.attrs.some(attr => attr.doc && /^\s*CHECK\b/.test(attr.args || ''))
+  };
+}
+
+/**
+ * Parse the constraints of `#[account(...)]` into a map, e.g. `mut, has_one = authority @ Err::X`
+ * -> { mut: true, has_one: ['authority'] }. Repeatable constraints (`has_one`, `constraint`) map to lists.
+ */
+function parseConstraints(text) {
+  const constraints = new Map();
+  for (const part of splitTopLevel(text, ',')) {
+    const [expression] = splitTopLevel(part, '@');
+    const equals = findAssignment(expression);
+    const key = (equals === -1 ? expression : expression.slice(0, equals)).trim();
+    const value = equals === -1 ? true : expression.slice(equals + 1).trim();
+    if (!key) continue;
+    if (key === 'has_one' || key === 'constraint') {
+      constraints.set(key, [...(constraints.get(key) || []), value]);
+    } else {
+      constraints.set(key, value);
+    }
+  }
+  return constraints;
+}
+
+/**
+ * Split text on a separator that is not nested in brackets or strings
+ */
+function splitTopLevel(text, separator) {
+  const parts = [];
+  let depth = 0;
+  let quote = null;
+  let current = '';
+  for (let i = 0; i < text.length; i++) {
+    const ch = text[i];
+    if (quote) {
+      if (ch === '\\') {
+        current += ch + (text[++i] || '');
+        continue;
+      }
+      if (ch === quote) quote = null;
+    } else if (ch === '"') {
+      quote = ch;
+    } else if ('([{'.includes(ch)) {
+      depth++;
+    } else if (')]}'.includes(ch)) {
+      depth--;
+    } else if (ch === separator && depth === 0) {
+      parts.push(current.trim());
+      current = '';
+      continue;
+    }
+    current += ch;
+  }
+  if (current.trim()) parts.push(current.trim());
+  return parts;
+}
+
+/**
+ * Index of the `=` of `key = value`, ignoring `==`, `!=`, `<=` and `>=`
+ */
+function findAssignment(text) {
+  for (let i = 0; i < text.length; i++) {
+    if (text[i] !== '=') continue;
+    if (text[i + 1] === '=' || '=!<>'.includes(text[i - 1])) continue;
+    return i;
+  }
+  return -1;
+}
+
+/**
+ * Parse `#[instruction(amount: u64, name: String)]` arguments
+ */
+function parseArguments(text) {
+  if (!text) return [];
+  return splitTopLevel(text, ',').map(arg => {
+    const colon = arg.indexOf(':');
+    return colon === -1 ? { name: arg.trim(), type: '_' } : { name: arg.slice(0, colon).trim(), type: arg.slice(colon + 1).trim() };
+  });
+}
+
+/**
+ * The accounts struct of a `Context<T>` parameter
+ */
+function contextTypeOf(param) {
+  if (!param || !param.paramType) return null;
+  const { base, args } = splitGenericType(param.paramType.text);
+  if (base !== 'Context' || args.length === 0) return null;
+  return splitGenericType(args[args.length - 1]).base.split('::').pop();
+}
+
+function emptyUsage() {
+  return { writes: [], dataReads: [], ownerChecked: false, signerChecked: false, comparedFields: new Set() };
+}
+
+/**
+ * Record how each function that receives an accounts context uses its accounts: through a
+ * `Context<T>` parameter (`ctx.accounts.vault`) or as a method of `T` itself (`self.vault`).
+ * @returns {Map} - Context name -> Map of account name -> usage
+ */
+function collectAccountUsage(program, definitions) {
+  const usage = new Map();
+
+  for (const fn of definitions.functions) {
+    let contextName = null;
+    let ctxParam = null;
+    const param = fn.node.params.find(candidate => contextTypeOf(candidate));
+    if (param && program.contexts.has(contextTypeOf(param))) {
+      contextName = contextTypeOf(param);
+      ctxParam = param.name;
+    } else if (fn.owner && program.contexts.has(fn.owner) && fn.node.selfParam) {
+      contextName = fn.owner;
+    } else {
+      continue;
+    }
+
+    if (!usage.has(contextName)) usage.set(contextName, new Map());
+    const uses = usage.get(contextName);
+    const useOf = name => {
+      if (!uses.has(name)) uses.set(name, emptyUsage());
+      return uses.get(name);
+    };
+    const aliases = new Map();
+
+    const accountOf = expr => {
+      let node = expr;
+      while (node) {
+        switch (node.type) {
+          case 'FieldAccess':
+            if (ctxParam && node.object.type === 'FieldAccess' && node.object.field === 'accounts' &&
+                node.object.object.type === 'PathExpr' && node.object.object.name === ctxParam) {
+              return node.field;
+            }
+            if (!ctxParam && isSelf(node.object)) return node.field;
+            node = node.object;
+            break;
+          case 'PathExpr':
+            return node.path.segments.length === 1 ? aliases.get(node.name) || null : null;
+          case 'MethodCall':
+            node = node.receiver;
+            break;
+          case 'Index':
+            node = node.object;
+            break;
+          case 'Unary':
+          case 'Ref':
+          case 'Try':
+            node = node.argument;
+            break;
+          case 'Paren':
+            node = node.expr;
+            break;
+          default:
+            return null;
+        }
+      }
+      return null;
+    };
+
+    const recordComparison = (left, right) => {
+      for (const [side, other] of [[left, right], [right, left]]) {
+        const account = accountOf(side);
+        if (!account) continue;
+        visit(side, {
+          FieldAccess: function(node) {
+            if (node.field === 'owner') useOf(account).ownerChecked = true;
+            if (accountOf(other)) useOf(account).comparedFields.add(node.field);
+          }
+        });
+      }
+    };
+
+    walkFunction(fn, definitions, node => {
+      switch (node.type) {
+        case 'Let': {
+          const account = node.init && accountOf(node.init);
+          if (account && node.pattern.type === 'IdentPat') aliases.set(node.pattern.name, account);
+          break;
+        }
+        case 'Assign':
+        case 'CompoundAssign': {
+          const account = node.left.type !== 'PathExpr' ? accountOf(node.left) : null;
+          if (account) useOf(account).writes.push(node);
+          break;
+        }
+        case 'MethodCall': {
+          const account = accountOf(node.receiver);
+          if (!account) break;
+          const onField = node.receiver.type === 'FieldAccess' && accountOf(node.receiver.object) === account;
+          if (ACCOUNT_WRITE_METHODS.has(node.method) ||
+              (node.method === 'borrow_mut' && onField && ['lamports', 'data'].includes(node.receiver.field)) ||
+              (FIELD_MUTATING_METHODS.has(node.method) && onField)) {
+            useOf(account).writes.push(node);
+          }
+          if (DATA_READ_METHODS.has(node.method) || (node.method === 'borrow' && onField && node.receiver.field === 'data')) {
+            useOf(account).dataReads.push(node);
+          }
+          break;
+        }
+        case 'Call': {
+          // `Type::try_deserialize(&mut &data[..])`, `Type::try_from_slice(&account.data.borrow())`
+          const callee = node.callee.type === 'PathExpr' ? node.callee.path.segments[node.callee.path.segments.length - 1].name : null;
+          if (DATA_READ_METHODS.has(callee)) {
+            node.args.forEach(arg => {
+              const account = accountOf(arg);
+              if (account) useOf(account).dataReads.push(node);
+            });
+          }
+          break;
+        }
+        case 'FieldAccess': {
+          if (node.field === 'is_signer') {
+            const account = accountOf(node.object);
+            if (account) useOf(account).signerChecked = true;
+          }
+          break;
+        }
+        case 'Binary':
+          if (node.operator === '==' || node.operator === '!=') recordComparison(node.left, node.right);
+          break;
+        case 'Macro':
+          if (/^(require_keys_eq|require_keys_neq|require_eq|require_neq|assert_eq|assert_ne)$/.test(node.name) && node.args && node.args.length >= 2) {
+            recordComparison(node.args[0], node.args[1]);
+          }
+          break;
+        case 'StructLiteral': {
+          const written = CPI_WRITTEN_ACCOUNTS[node.name.split('::').pop()];
+          if (!written) break;
+          node.fields.filter(field => written.includes(field.name)).forEach(field => {
+            const account = accountOf(field.value);
+            if (account) useOf(account).writes.push(field);
+          });
+          break;
+        }
+        default:
+          break;
+      }
+    });
+  }
+
+  return usage;
+}
+
+function isSigner(account) {
+  return account.kind === 'Signer' || account.constraints.has('signer');
+}
+
+function isMutable(account) {
+  return MUTABLE_CONSTRAINTS.some(key => account.constraints.has(key));
+}
+
+function isCreated(account) {
+  return account.constraints.has('init') || account.constraints.has('init_if_needed') || account.constraints.has('zero');
+}
+
+function hasOneTo(account, field) {
+  return (account.constraints.get('has_one') || []).includes(field);
+}
+
+/**
+ * True when a `constraint = ...` of the context compares `<account>.<field>`
+ */
+function constrainedInText(context, accountName, field) {
+  const pattern = new RegExp(`\\b${accountName}\\.${field}\\b`);
+  return context.accounts.some(account => (account.constraints.get('constraint') || []).some(text => pattern.test(text)));
+}
+
+/**
+ * True when two seeds of variable length (strings or byte vectors) follow each other
+ */
+function hasAdjacentVariableSeeds(seeds, instructionArgs) {
+  const inner = seeds.trim().replace(/^\[/, '').replace(/\]$/, '');
+  const variableArgs = new Set(instructionArgs.filter(arg => /\b(String|str|Vec<u8>|\[u8\])/.test(arg.type)).map(arg => arg.name));
+  let previousVariable = false;
+  for (const seed of splitTopLevel(inner, ',')) {
+    const root = seed.match(/^&?\s*([A-Za-z_]\w*)/);
+    const variable = /\.as_bytes\(\)$/.test(seed) || (root && variableArgs.has(root[1]) && !/\.key\(\)/.test(seed));
+    if (variable && previousVariable) return true;
+    previousVariable = variable;
+  }
+  return false;
+}
+
+function mentions(text, name) {
+  return new RegExp(`\\b${name}\\b`).test(text);
+}
+
+function hasAttribute(node, name) {
+  return (node.attrs || []).some(attr => attr.path === name || attr.path.endsWith(`::${name}`));
+}
+
+function attributeArgs(node, name) {
+  const attr = (node.attrs || []).find(candidate => candidate.path === name);
+  return attr ? attr.args : null;
+}
+
+function derives(node, name) {
+  return (node.attrs || []).some(attr => attr.path === 'derive' && (attr.args || '').split(',').some(arg => arg.trim().split('::').pop() === name));
+}
+
+module.exports = {
+  name: 'anchor',
+  title: 'Solana/Anchor',
+  detect,
+  analyze,
+  renderSummary,
+  parseConstraints
+};
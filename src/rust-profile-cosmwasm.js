@@ -0,0 +1,448 @@
+const { visit } = require('./rust-parser');
+const {
+  dominatingConditions,
+  inferType,
+  nodeText,
+  precedingStatements,
+  signatureOf,
+  splitGenericType,
+  walkFunction
+} = require('./rust-ast');
+const { buildCallGraph } = require('./rust-callgraph');
+
+/**
+ * CosmWasm profile: maps the `ExecuteMsg` variants handled by the `execute` entry point to their handlers,
+ * lists the `cw-storage-plus` items and checks how the execute paths authorize callers, iterate storage,
+ * do `Uint128` arithmetic and handle the funds attached to a message.
+ */
+
+const ENTRY_POINTS = ['instantiate', 'execute', 'query', 'migrate', 'sudo', 'reply'];
+const STORAGE_KINDS = new Set(['Item', 'Map', 'IndexedMap', 'SnapshotItem', 'SnapshotMap', 'Deque', 'IndexedSnapshotMap']);
+const STORAGE_WRITE_METHODS = new Set(['save', 'remove', 'update', 'push_back', 'push_front', 'pop_back', 'pop_front', 'clear']);
+const ITERATION_METHODS = new Set(['range', 'range_raw', 'keys', 'keys_raw', 'prefix_range', 'prefix_range_raw']);
+const UINT_TYPE = /^(Uint64|Uint128|Uint256|Uint512)$/;
+const PRIVILEGED_STORAGE = /CONFIG|OWNER|ADMIN|STATE|PARAMS|SETTINGS|MINTER|OPERATOR|FEE|PAUSED|WHITELIST|ROLE/i;
+const AUTH_CALL = /owner|admin|auth|only|allowed|whitelist|permission|role|ensure|assert|check|verify|validate|can_/i;
+const FUNDS_CHECKS = new Set(['must_pay', 'nonpayable', 'may_pay', 'one_coin', 'ensure_no_funds', 'assert_sent_sufficient_coin', 'has_coins']);
+
+/**
+ * True when the file is a CosmWasm contract
+ */
+function detect(ast, code) {
+  if (/\bcosmwasm_std\b/.test(code)) return true;
+  let found = false;
+  visit(ast, {
+    Fn: function(node) {
+      if (isEntryPoint(node)) found = true;
+    }
+  });
+  return found;
+}
+
+/**
+ * Run the CosmWasm checks
+ * @param {Object} context - `{ ast, code, definitions, locate }`
+ * @returns {Object} - `{ findings, summary }`
+ */
+function analyze({ ast, code, definitions, locate }) {
+  const findings = { high: [], medium: [], low: [], info: [] };
+  const storage = collectStorage(ast);
+  const entryPoints = definitions.functions.filter(fn => !fn.owner && ENTRY_POINTS.includes(fn.name) &&
+    (isEntryPoint(fn.node) || fn.node.params.some(param => /\bDeps(Mut)?\b/.test(param.paramType ? param.paramType.text : ''))));
+  const execute = entryPoints.find(fn => fn.name === 'execute' && fn.node.body) || null;
+  const callGraph = buildCallGraph(ast, definitions);
+  const variants = execute ? mapExecuteVariants(execute, callGraph, definitions) : [];
+  const dispatcher = execute ? describeDispatcher(execute, variants, code) : null;
+
+  for (const variant of variants) {
+    const handler = variant.handler || execute;
+    const within = variant.handler ? null : variant.arm;
+    const info = infoParamName(handler) || (variant.handler ? null : dispatcher.info);
+
+    // 1. Storage writes must be authorized by `info.sender`, unless the sender only acts on its own entries
+    const writes = storageWrites(handler, definitions, storage, info, code, within);
+    const unauthorized = writes.filter(write => !write.authorized && !(variant.handler && dispatcher.authorized));
+    const selfKeyed = writes.some(write => write.selfKeyed);
+    const flagged = selfKeyed ? unauthorized.filter(write => write.kind === 'Item' && PRIVILEGED_STORAGE.test(write.name)) : unauthorized;
+    if (flagged.length > 0) {
+      const writeList = flagged.map(write => `\`${write.name}.${write.method}\` at line ${write.node.loc.start.line}`).join(', ');
+      findings.high.push({
+        ruleId: 'cosmwasm-missing-sender-check',
+        title: 'Storage Write Without Sender Authorization',
+        description: `\`ExecuteMsg::${variant.name}\`${variant.handler ? ` (handled by \`${variant.handler.qualifiedName}\`)` : ''} writes contract storage (${writeList}) without first checking \`info.sender\` against an owner, admin or allow-list, so any address can execute it.`,
+        recommendation: 'Load the stored owner/admin and return an error unless it equals `info.sender` (e.g. `ensure_eq!(info.sender, config.owner, ContractError::Unauthorized {})` or `cw_ownable::assert_owner`) before saving.',
+        location: locate(variant.handler ? signatureOf(variant.handler.node) : variant.arm.pattern)
+      });
+    }
+
+    // 2. Attached funds must be validated, or rejected when the message is not payable
+    const funds = fundsUsage(handler, info, within);
+    if (funds.validated || dispatcher.fundsValidated) continue;
+    if (funds.reads.length > 0) {
+      findings.medium.push({
+        ruleId: 'cosmwasm-unvalidated-funds',
+        title: 'Attached Funds Not Validated',
+        description: `\`ExecuteMsg::${variant.name}\` reads \`info.funds\` at line ${funds.reads[0].loc.start.line} without checking the denomination or the number of coins. A caller can pay with a worthless token or attach extra coins that are silently kept.`,
+        recommendation: 'Use `cw_utils::must_pay(&info, DENOM)` or check every coin\'s `denom` and `amount` before crediting the sender.',
+        location: locate(funds.reads[0])
+      });
+    } else {
+      findings.low.push({
+        ruleId: 'cosmwasm-funds-not-rejected',
+        title: 'Funds Sent to a Non-Payable Message Are Kept',
+        description: `\`ExecuteMsg::${variant.name}\` never looks at \`info.funds\`. Coins attached to it by mistake are locked in the contract.`,
+        recommendation: 'Call `cw_utils::nonpayable(&info)?` in handlers that do not expect funds.',
+        location: locate(variant.handler ? signatureOf(variant.handler.node) : variant.arm.pattern)
+      });
+    }
+  }
+
+  // Checks over every function reachable from `execute`
+  const reachable = execute ? reachableFrom(execute.qualifiedName, callGraph) : new Set();
+  for (const fn of definitions.functions.filter(candidate => reachable.has(candidate.qualifiedName))) {
+    const localTypes = new Map();
+    walkFunction(fn, definitions, (node, scope, ancestors) => {
+      if (node.type === 'Let' && node.pattern.type === 'IdentPat' && node.init && !node.letType) {
+        const type = valueType(node.init, scope, localTypes, storage, definitions);
+        if (type) localTypes.set(node.pattern.name, type);
+      }
+
+      // 3. Unbounded iteration over storage
+      if (node.type === 'MethodCall' && ITERATION_METHODS.has(node.method) && storageRoot(node.receiver, storage)) {
+        const bounded = ancestors.some(ancestor => ancestor.type === 'MethodCall' && ancestor.method === 'take' &&
+          ancestor.receiver.range[0] <= node.range[0] && node.range[1] <= ancestor.receiver.range[1]);
+        if (!bounded) {
+          findings.medium.push({
+            ruleId: 'cosmwasm-unbounded-iteration',
+            title: 'Unbounded Storage Iteration in Execute Path',
+            description: `\`${storageRoot(node.receiver, storage).name}.${node.method}()\` iterates storage without a limit in \`${fn.qualifiedName}\`, which is reachable from \`execute\`. As the collection grows, the message runs out of gas and can no longer be executed.`,
+            recommendation: 'Bound the iteration with `.take(limit)` and paginate with a start key, or keep a running aggregate instead of iterating.',
+            location: locate(node)
+          });
+        }
+      }
+
+      // 4. Uint128 arithmetic panics on overflow
+      if ((node.type === 'Binary' || node.type === 'CompoundAssign') && ['+', '-', '*', '+=', '-=', '*='].includes(node.operator)) {
+        const operandType = [node.left, node.right]
+          .map(operand => valueType(operand, scope, localTypes, storage, definitions))
+          .find(type => type && UINT_TYPE.test(splitGenericType(type).base));
+        if (operandType) {
+          const method = { '+': 'add', '-': 'sub', '*': 'mul' }[node.operator[0]];
+          findings.medium.push({
+            ruleId: 'cosmwasm-unchecked-uint-arithmetic',
+            title: 'Unchecked Uint128 Arithmetic',
+            description: `\`${nodeText(code, node).trim()}\` uses \`${node.operator}\` on \`${splitGenericType(operandType).base}\`, which panics on ${method === 'sub' ? 'underflow' : 'overflow'}. The panic aborts the message with an opaque error instead of a contract error.`,
+            recommendation: `Use \`checked_${method}()\` and map the error into a \`ContractError\`.`,
+            location: locate(node)
+          });
+        }
+      }
+    });
+  }
+
+  return {
+    findings,
+    summary: {
+      entryPoints: entryPoints.map(fn => ({ name: fn.name, line: fn.node.loc.start.line })),
+      variants: variants.map(variant => ({
+        name: variant.name,
+        handler: variant.handler ? variant.handler.qualifiedName : null,
+        line: variant.arm.loc.start.line
+      })),
+      storage: [...storage.values()].map(({ name, kind, keyType, valueTypeText, namespace }) => ({ name, kind, keyType, valueType: valueTypeText, namespace }))
+    }
+  };
+}
+
+/**
+ * Render the entry point, message and storage tables for the Markdown report
+ * @param {Object} summary - `summary` returned by analyze
+ * @returns {string} - Markdown
+ */
+function renderSummary(summary) {
+  let output = `## CosmWasm Contract\n\n`;
+  output += `- **Entry points:** ${summary.entryPoints.length > 0 ? summary.entryPoints.map(entry => `\`${entry.name}\` (line ${entry.line})`).join(', ') : 'none in this file'}\n`;
+  output += `- **Execute messages:** ${summary.variants.length}\n`;
+  output += `- **Storage items:** ${summary.storage.length}\n\n`;
+
+  if (summary.variants.length > 0) {
+    output += `| ExecuteMsg | Handler | Line |\n`;
+    output += `|------------|---------|------|\n`;
+    summary.variants.forEach(variant => {
+      output += `| \`${variant.name}\` | ${variant.handler ? `\`${variant.handler}\`` : '(inline)'} | ${variant.line} |\n`;
+    });
+    output += `\n`;
+  }
+
+  if (summary.storage.length > 0) {
+    output += `| Storage | Kind | Key | Value | Namespace |\n`;
+    output += `|---------|------|-----|-------|-----------|\n`;
+    summary.storage.forEach(item => {
+      output += `| \`${item.name}\` | ${item.kind} | ${item.keyType ? `\`${item.keyType}\`` : '-'} | \`${item.valueType}\` | ${item.namespace ? `\`${item.namespace}\`` : '-'} |\n`;
+    });
+    output += `\n`;
+  }
+
+  return output;
+}
+
+/**
+ * Collect `const NAME: Item<T> = Item::new("ns");` style storage declarations
+ */
+function collectStorage(ast) {
+  const storage = new Map();
+  visit(ast, {
+    Const: function(node) {
+      if (!node.valueType) return;
+      const { base, args } = splitGenericType(node.valueType.text);
+      if (!STORAGE_KINDS.has(base)) return;
+      const typeArgs = args.filter(arg => !arg.startsWith('\''));
+      const keyed = base !== 'Item' && base !== 'SnapshotItem' && base !== 'Deque';
+      const namespace = node.value && node.value.type === 'Call' && node.value.args[0] && node.value.args[0].type === 'Literal'
+        ? node.value.args[0].value
+        : null;
+      storage.set(node.name, {
+        name: node.name,
+        kind: base,
+        keyType: keyed ? typeArgs[0] || null : null,
+        valueTypeText: (keyed ? typeArgs[1] : typeArgs[0]) || '_',
+        namespace
+      });
+    }
+  });
+  return storage;
+}
+
+/**
+ * Map each arm of `match msg { ExecuteMsg::X { .. } => handler(..) }` in `execute` to its handler,
+ * using the call graph edges that leave the arm. Arms without a call into this file are inline handlers.
+ */
+function mapExecuteVariants(execute, callGraph, definitions) {
+  const msgParam = execute.node.params.find(param => param.paramType && /ExecuteMsg\b/.test(param.paramType.text));
+  const variants = [];
+  visit(execute.node.body, {
+    Match: function(node) {
+      if (variants.length > 0 || !msgParam || node.expr.type !== 'PathExpr' || node.expr.name !== msgParam.name) return;
+      for (const arm of node.arms) {
+        const name = variantName(arm.pattern);
+        if (!name) continue;
+        const edge = callGraph.edges.find(candidate => candidate.from === execute.qualifiedName &&
+          candidate.line >= arm.body.loc.start.line && candidate.line <= arm.body.loc.end.line);
+        const handler = edge ? definitions.functions.find(fn => fn.qualifiedName === edge.to) : null;
+        variants.push({ name, arm, handler: handler || null });
+      }
+      return false;
+    }
+  });
+  return variants;
+}
+
+function variantName(pattern) {
+  if (!pattern) return null;
+  if (pattern.type === 'OrPat') return variantName(pattern.alternatives[0]);
+  if (!pattern.path) return null;
+  return pattern.path.segments[pattern.path.segments.length - 1].name;
+}
+
+/**
+ * Describe the checks `execute` performs before dispatching: sender checks and funds checks apply to every variant
+ */
+function describeDispatcher(execute, variants, code) {
+  const info = infoParamName(execute);
+  const dispatch = variants.length > 0 ? variants[0].arm : null;
+  const before = [];
+  if (dispatch) {
+    for (const stmt of execute.node.body.stmts) {
+      if (stmt.range[1] >= dispatch.range[0]) break;
+      before.push(stmt);
+    }
+  }
+  return {
+    info,
+    authorized: info ? before.some(stmt => isAuthorizationStatement(stmt, senderPattern(info, []), code)) : false,
+    fundsValidated: before.some(stmt => callsFundsCheck(stmt))
+  };
+}
+
+function infoParamName(fn) {
+  const param = fn.node.params.find(candidate => candidate.paramType && /\bMessageInfo$/.test(candidate.paramType.text));
+  return param ? param.name : null;
+}
+
+function senderPattern(info, aliases) {
+  const names = [`${info}\\s*\\.\\s*sender`, ...aliases.map(alias => `${alias}(?!\\s*\\()`)];
+  return new RegExp(`\\b(${names.join('|')})\\b`);
+}
+
+/**
+ * A statement that checks the sender before anything else happens, e.g.
+ * `cw_ownable::assert_owner(deps.storage, &info.sender)?;` or `ensure_eq!(info.sender, owner, ..);`
+ */
+function isAuthorizationStatement(stmt, sender, code) {
+  let found = false;
+  visit(stmt, {
+    Call: function(node) {
+      const name = node.callee.type === 'PathExpr' ? node.callee.path.segments[node.callee.path.segments.length - 1].name : '';
+      if (AUTH_CALL.test(name) && sender.test(nodeText(code, node))) found = true;
+    },
+    MethodCall: function(node) {
+      if (AUTH_CALL.test(node.method) && sender.test(nodeText(code, node))) found = true;
+    },
+    Macro: function(node) {
+      if (/^(ensure|ensure_eq|assert|assert_eq)$/.test(node.name) && sender.test(nodeText(code, node))) found = true;
+    }
+  });
+  return found;
+}
+
+/**
+ * Find the storage writes of a handler and whether each is preceded by a sender check.
+ * `within` restricts the search to one match arm of `execute` for inline handlers.
+ */
+function storageWrites(fn, definitions, storage, info, code, within) {
+  const writes = [];
+  const aliases = [];
+
+  walkFunction(fn, definitions, (node, scope, ancestors) => {
+    if (within && (node.range[1] < within.range[0] || node.range[0] > within.range[1])) return;
+    if (info && node.type === 'Let' && node.pattern.type === 'IdentPat' && node.init &&
+        senderPattern(info, aliases).test(nodeText(code, node.init)) && !/\bfunds\b/.test(nodeText(code, node.init))) {
+      aliases.push(node.pattern.name);
+    }
+    if (node.type !== 'MethodCall' || !STORAGE_WRITE_METHODS.has(node.method)) return;
+    const item = node.receiver.type === 'PathExpr' ? storage.get(node.receiver.name) : null;
+    if (!item) return;
+
+    const sender = info ? senderPattern(info, aliases) : null;
+    const key = item.keyType && node.args[1] ? node.args[1] : null;
+    const authorized = !!sender && (
+      dominatingConditions(ancestors, node).some(entry => sender.test(nodeText(code, entry.condition))) ||
+      precedingStatements(ancestors, node).some(stmt => isAuthorizationStatement(stmt, sender, code))
+    );
+    writes.push({
+      node,
+      name: item.name,
+      kind: item.kind,
+      method: node.method,
+      selfKeyed: !!sender && !!key && sender.test(nodeText(code, key)),
+      authorized
+    });
+  });
+
+  return writes;
+}
+
+/**
+ * How a handler uses `info.funds`: where it reads them and whether it validates them
+ */
+function fundsUsage(fn, info, within) {
+  const usage = { reads: [], validated: false };
+  if (!fn.node.body) return usage;
+  visit(fn.node.body, {
+    FieldAccess: function(node) {
+      if (within && (node.range[1] < within.range[0] || node.range[0] > within.range[1])) return;
+      if (node.field === 'funds' && info && node.object.type === 'PathExpr' && node.object.name === info) usage.reads.push(node);
+      if (node.field === 'denom') usage.validated = true;
+    },
+    Call: function(node) {
+      if (within && (node.range[1] < within.range[0] || node.range[0] > within.range[1])) return;
+      if (callsFundsCheck(node)) usage.validated = true;
+    }
+  });
+  return usage;
+}
+
+function callsFundsCheck(node) {
+  let found = false;
+  visit(node, {
+    Call: function(call) {
+      if (call.callee.type === 'PathExpr' && FUNDS_CHECKS.has(call.callee.path.segments[call.callee.path.segments.length - 1].name)) found = true;
+    }
+  });
+  return found;
+}
+
+/**
+ * The storage declaration an expression such as `BALANCES.prefix(owner)` starts from
+ */
+function storageRoot(expr, storage) {
+  let node = expr;
+  while (node) {
+    if (node.type === 'PathExpr') return storage.get(node.name) || null;
+    if (node.type === 'MethodCall') node = node.receiver;
+    else if (node.type === 'FieldAccess') node = node.object;
+    else return null;
+  }
+  return null;
+}
+
+/**
+ * Type of an expression, also following values loaded from storage (`CONFIG.load(deps.storage)?`)
+ */
+function valueType(expr, scope, localTypes, storage, definitions) {
+  if (!expr) return null;
+  const inferred = inferType(expr, scope);
+  if (inferred && inferred !== '{integer}') return inferred;
+  switch (expr.type) {
+    case 'PathExpr':
+      if (expr.path.segments.length === 1) return localTypes.get(expr.name) || null;
+      return UINT_TYPE.test(expr.path.segments[0].name) ? expr.path.segments[0].name : null;
+    case 'Paren':
+      return valueType(expr.expr, scope, localTypes, storage, definitions);
+    case 'Try':
+    case 'Ref':
+      return valueType(expr.argument, scope, localTypes, storage, definitions);
+    case 'Unary':
+      return expr.operator === '*' ? valueType(expr.argument, scope, localTypes, storage, definitions) : null;
+    case 'FieldAccess': {
+      const objectType = valueType(expr.object, scope, localTypes, storage, definitions);
+      const struct = objectType ? definitions.structs.get(splitGenericType(objectType).base) : null;
+      const field = struct ? struct.fields.get(expr.field) : null;
+      return field ? field.text : null;
+    }
+    case 'Call':
+      // `Uint128::new(..)`, `Uint128::from(..)`
+      if (expr.callee.type === 'PathExpr' && expr.callee.path.segments.length >= 2 && UINT_TYPE.test(expr.callee.path.segments[0].name)) {
+        return expr.callee.path.segments[0].name;
+      }
+      return null;
+    case 'MethodCall': {
+      if (['load', 'may_load'].includes(expr.method) && expr.receiver.type === 'PathExpr' && storage.has(expr.receiver.name)) {
+        return storage.get(expr.receiver.name).valueTypeText;
+      }
+      if (['unwrap_or_default', 'unwrap', 'unwrap_or', 'clone'].includes(expr.method)) {
+        return valueType(expr.receiver, scope, localTypes, storage, definitions);
+      }
+      return null;
+    }
+    default:
+      return null;
+  }
+}
+
+function reachableFrom(root, callGraph) {
+  const reachable = new Set([root]);
+  const queue = [root];
+  while (queue.length > 0) {
+    const current = queue.shift();
+    callGraph.edges.filter(edge => edge.from === current && !reachable.has(edge.to)).forEach(edge => {
+      reachable.add(edge.to);
+      queue.push(edge.to);
+    });
+  }
+  return reachable;
+}
+
+function isEntryPoint(fnNode) {
+  return (fnNode.attrs || []).some(attr => attr.path === 'entry_point' || attr.path.endsWith('::entry_point') ||
+    (attr.path === 'cfg_attr' && /\bentry_point\b/.test(attr.args || '')));
+}
+
+module.exports = {
+  name: 'cosmwasm',
+  title: 'CosmWasm',
+  detect,
+  analyze,
+  renderSummary
+};
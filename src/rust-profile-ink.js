@@ -0,0 +1,323 @@
+const { visit } = require('./rust-parser');
+const {
+  PRIVILEGED_FIELD,
+  ROLE_FIELD,
+  dominatingConditions,
+  inferType,
+  nodeText,
+  precedingStatements,
+  selfFieldRoot,
+  signatureOf,
+  splitGenericType,
+  walkFunction
+} = require('./rust-ast');
+
+/**
+ * ink! profile: extracts the `#[ink(storage)]` struct, constructors, messages and events of an
+ * `#[ink::contract]` module and checks caller authorization, `Balance` arithmetic, `transferred_value`
+ * handling and `set_code_hash` exposure.
+ */
+
+const AUTH_HELPER = /^(only_|ensure_|assert_|check_|require_)\w*(owner|admin|role|authori[sz]ed|operator|governance)\w*$/;
+const MAPPING_WRITES = new Set(['insert', 'remove', 'take']);
+const COLLECTION_WRITES = new Set(['push', 'pop', 'clear', 'extend', 'retain', 'truncate', 'swap_remove', 'set', 'write']);
+const BALANCE_TYPE = /^Balance$/;
+
+/**
+ * True when the file is an ink! contract
+ */
+function detect(ast, code) {
+  if (/#\[\s*ink(_lang)?::contract/.test(code)) return true;
+  let found = false;
+  visit(ast, {
+    Mod: function(node) {
+      if (isContractModule(node)) found = true;
+    }
+  });
+  return found;
+}
+
+/**
+ * Run the ink! checks
+ * @param {Object} context - `{ ast, code, definitions, locate }`
+ * @returns {Object} - `{ findings, summary }`
+ */
+function analyze({ ast, code, definitions, locate }) {
+  const findings = { high: [], medium: [], low: [], info: [] };
+  const contract = extractContract(ast, definitions);
+
+  for (const message of contract.messages) {
+    const fn = message.fn;
+    const location = locate(signatureOf(fn.node));
+    const usage = analyzeMessage(fn, definitions, code);
+
+    // 1. Mutating messages must check the caller before writing privileged storage
+    if (message.mutates) {
+      const unauthorized = usage.writes.filter(write => !write.authorized);
+      const selfKeyed = usage.writes.some(write => write.selfKeyed);
+      const flagged = unauthorized.filter(write => (selfKeyed ? !write.key && ROLE_FIELD.test(write.field) : PRIVILEGED_FIELD.test(write.field)));
+      if (flagged.length > 0) {
+        const writeList = flagged.map(write => `\`self.${write.field}\` at line ${write.node.loc.start.line}`).join(', ');
+        findings.high.push({
+          ruleId: 'ink-missing-caller-check',
+          title: 'Mutating Message Without Caller Check',
+          description: `The message \`${fn.qualifiedName}\` writes privileged storage (${writeList}) without first comparing \`self.env().caller()\` against an owner or admin, so any account can call it.`,
+          recommendation: 'Compare `self.env().caller()` with the stored owner and return an error before writing, e.g. `if self.env().caller() != self.owner { return Err(Error::NotOwner) }`.',
+          location
+        });
+      }
+    }
+
+    // 2. Payable messages must look at the transferred value
+    if (message.payable && !usage.readsTransferredValue) {
+      findings.medium.push({
+        ruleId: 'ink-payable-without-value-check',
+        title: 'Payable Message Ignores the Transferred Value',
+        description: `The message \`${fn.qualifiedName}\` is \`payable\` but never reads \`self.env().transferred_value()\`. Any amount, including zero, is accepted and value sent by mistake stays in the contract.`,
+        recommendation: 'Check `self.env().transferred_value()` against the expected amount, or remove `payable` from the message.',
+        location
+      });
+    } else if (!message.payable && usage.readsTransferredValue) {
+      findings.low.push({
+        ruleId: 'ink-transferred-value-not-payable',
+        title: 'Transferred Value Read in a Non-Payable Message',
+        description: `The message \`${fn.qualifiedName}\` reads \`transferred_value()\` but is not \`payable\`. ink! rejects calls that transfer value to it, so the value is always zero and the logic that depends on it never runs.`,
+        recommendation: 'Mark the message `#[ink(message, payable)]` if it is meant to receive value, or remove the dead check.',
+        location
+      });
+    }
+
+    // 3. set_code_hash replaces the contract code
+    usage.codeHashCalls.forEach(({ node, authorized }) => {
+      findings[authorized ? 'info' : 'high'].push({
+        ruleId: authorized ? 'ink-set-code-hash' : 'ink-unprotected-set-code-hash',
+        title: authorized ? 'Upgradeable Contract Code' : 'Unprotected set_code_hash',
+        description: authorized
+          ? `The message \`${fn.qualifiedName}\` can replace the contract code with \`set_code_hash\`. It checks the caller first; make sure the upgrade authority is trusted by users.`
+          : `The message \`${fn.qualifiedName}\` calls \`set_code_hash\` without checking the caller. Any account can replace the contract code and take over its storage and balance.`,
+        recommendation: authorized
+          ? 'Document the upgrade policy, and consider a timelock or multisig for the upgrade authority.'
+          : 'Only allow the owner (or a governance account) to call `set_code_hash`, and check the caller before the call.',
+        location: locate(node)
+      });
+    });
+
+    // 4. Balance arithmetic
+    usage.arithmetic.forEach(({ node, operator }) => {
+      const method = { '+': 'add', '-': 'sub', '*': 'mul' }[operator[0]];
+      findings.medium.push({
+        ruleId: 'ink-unchecked-balance-arithmetic',
+        title: 'Unchecked Balance Arithmetic',
+        description: `\`${nodeText(code, node).trim()}\` uses \`${operator}\` on a \`Balance\` in \`${fn.qualifiedName}\`. Unless the contract is built with overflow checks, the result wraps around silently; with overflow checks it traps with an opaque error.`,
+        recommendation: `Use \`checked_${method}()\` and return a contract error when it fails.`,
+        location: locate(node)
+      });
+    });
+  }
+
+  return {
+    findings,
+    summary: {
+      contract: contract.name,
+      storage: contract.storage ? contract.storage.fields.map(field => ({ name: field.name, type: field.fieldType.text })) : [],
+      storageName: contract.storage ? contract.storage.name : null,
+      constructors: contract.constructors.map(fn => ({ name: fn.qualifiedName, line: fn.node.loc.start.line })),
+      messages: contract.messages.map(message => ({
+        name: message.fn.qualifiedName,
+        mutates: message.mutates,
+        payable: message.payable,
+        selector: message.selector,
+        line: message.fn.node.loc.start.line
+      })),
+      events: contract.events
+    }
+  };
+}
+
+/**
+ * Render the storage and message tables for the Markdown report
+ * @param {Object} summary - `summary` returned by analyze
+ * @returns {string} - Markdown
+ */
+function renderSummary(summary) {
+  let output = `## ink! Contract\n\n`;
+  output += `- **Contract:** ${summary.contract ? `\`${summary.contract}\`` : '(no `#[ink::contract]` module in this file)'}\n`;
+  output += `- **Constructors:** ${summary.constructors.length > 0 ? summary.constructors.map(fn => `\`${fn.name}\``).join(', ') : 'none'}\n`;
+  output += `- **Messages:** ${summary.messages.length}\n`;
+  output += `- **Events:** ${summary.events.length > 0 ? summary.events.map(name => `\`${name}\``).join(', ') : 'none'}\n\n`;
+
+  if (summary.storage.length > 0) {
+    output += `### Storage \`${summary.storageName}\`\n\n`;
+    output += `| Field | Type |\n`;
+    output += `|-------|------|\n`;
+    summary.storage.forEach(field => {
+      output += `| \`${field.name}\` | \`${field.type}\` |\n`;
+    });
+    output += `\n`;
+  }
+
+  if (summary.messages.length > 0) {
+    output += `### Messages\n\n`;
+    output += `| Message | Mutates | Payable | Selector | Line |\n`;
+    output += `|---------|---------|---------|----------|------|\n`;
+    summary.messages.forEach(message => {
+      output += `| \`${message.name}\` | ${message.mutates ? 'yes' : 'no'} | ${message.payable ? 'yes' : 'no'} | ${message.selector ? `\`${message.selector}\`` : '-'} | ${message.line} |\n`;
+    });
+    output += `\n`;
+  }
+
+  return output;
+}
+
+/**
+ * Find the contract module, its storage struct, constructors, messages and events
+ */
+function extractContract(ast, definitions) {
+  let name = null;
+  let storage = null;
+  const events = [];
+
+  visit(ast, {
+    Mod: function(node) {
+      if (!name && isContractModule(node)) name = node.name;
+    },
+    Struct: function(node) {
+      if (inkArgs(node).includes('storage') && !storage) storage = node;
+      if (inkArgs(node).includes('event')) events.push(node.name);
+    }
+  });
+
+  const contractFunctions = definitions.functions.filter(fn => fn.container && fn.container.type === 'Impl' && fn.node.body);
+  const constructors = contractFunctions.filter(fn => inkArgs(fn.node).includes('constructor'));
+  const messages = contractFunctions
+    .filter(fn => inkArgs(fn.node).includes('message'))
+    .map(fn => {
+      const args = inkArgs(fn.node);
+      const selector = args.find(arg => arg.startsWith('selector'));
+      return {
+        fn,
+        mutates: !!(fn.node.selfParam && fn.node.selfParam.mutable),
+        payable: args.includes('payable'),
+        selector: selector ? selector.replace(/^selector\s*=\s*/, '') : null
+      };
+    });
+
+  return { name, storage, constructors, messages, events };
+}
+
+/**
+ * Walk a message body: storage writes and whether a caller check dominates them, `transferred_value`
+ * reads, `set_code_hash` calls and arithmetic on `Balance` values
+ */
+function analyzeMessage(fn, definitions, code) {
+  const usage = { writes: [], codeHashCalls: [], arithmetic: [], readsTransferredValue: false };
+  const aliases = [];
+  const callerPattern = () => new RegExp(`(\\.caller\\(\\)${aliases.map(alias => `|\\b${alias}\\b`).join('')})`);
+  const authorizedAt = (node, ancestors) => {
+    const caller = callerPattern();
+    return dominatingConditions(ancestors, node).some(entry => caller.test(nodeText(code, entry.condition))) ||
+      precedingStatements(ancestors, node).some(stmt => isAuthorizationStatement(stmt, caller, code));
+  };
+
+  walkFunction(fn, definitions, (node, scope, ancestors) => {
+    switch (node.type) {
+      case 'Let':
+        if (node.pattern.type === 'IdentPat' && node.init && /\.caller\(\)/.test(nodeText(code, node.init))) aliases.push(node.pattern.name);
+        break;
+      case 'Assign':
+      case 'CompoundAssign': {
+        const field = selfFieldRoot(node.left);
+        if (field) usage.writes.push({ node, field, key: null, selfKeyed: false, authorized: authorizedAt(node, ancestors) });
+        if (node.type === 'CompoundAssign' && ['+=', '-=', '*='].includes(node.operator) && isBalance(node.left, scope)) {
+          usage.arithmetic.push({ node, operator: node.operator });
+        }
+        break;
+      }
+      case 'Binary':
+        if (['+', '-', '*'].includes(node.operator) && (isBalance(node.left, scope) || isBalance(node.right, scope))) {
+          usage.arithmetic.push({ node, operator: node.operator });
+        }
+        break;
+      case 'MethodCall': {
+        if (node.method === 'transferred_value') usage.readsTransferredValue = true;
+        if (node.method === 'set_code_hash') usage.codeHashCalls.push({ node, authorized: authorizedAt(node, ancestors) });
+        const field = node.receiver.type === 'FieldAccess' ? selfFieldRoot(node.receiver) : null;
+        if (field && node.receiver.object.type === 'PathExpr' && (MAPPING_WRITES.has(node.method) || COLLECTION_WRITES.has(node.method))) {
+          const key = MAPPING_WRITES.has(node.method) && node.args[0] ? nodeText(code, node.args[0]) : null;
+          usage.writes.push({
+            node,
+            field,
+            key,
+            selfKeyed: !!key && callerPattern().test(key),
+            authorized: authorizedAt(node, ancestors)
+          });
+        }
+        break;
+      }
+      case 'Call': {
+        // `ink::env::set_code_hash(&code_hash)` and `ink::env::transferred_value::<Env>()`
+        const segments = node.callee.type === 'PathExpr' ? node.callee.path.segments.map(segment => segment.name) : [];
+        const name = segments[segments.length - 1];
+        if (name === 'set_code_hash' || name === 'set_code_hash2') usage.codeHashCalls.push({ node, authorized: authorizedAt(node, ancestors) });
+        if (name === 'transferred_value') usage.readsTransferredValue = true;
+        break;
+      }
+      default:
+        break;
+    }
+  });
+
+  return usage;
+}
+
+/**
+ * A statement that checks the caller, e.g. `self.ensure_owner()?;` or `assert_eq!(self.env().caller(), self.owner);`
+ */
+function isAuthorizationStatement(stmt, caller, code) {
+  let found = false;
+  visit(stmt, {
+    MethodCall: function(node) {
+      if (AUTH_HELPER.test(node.method)) found = true;
+    },
+    Call: function(node) {
+      const name = node.callee.type === 'PathExpr' ? node.callee.path.segments[node.callee.path.segments.length - 1].name : '';
+      if (AUTH_HELPER.test(name) || (/owner|admin|auth/i.test(name) && caller.test(nodeText(code, node)))) found = true;
+    },
+    Macro: function(node) {
+      if (/^(assert|assert_eq|ensure|ensure_eq)$/.test(node.name) && caller.test(nodeText(code, node))) found = true;
+    }
+  });
+  return found;
+}
+
+/**
+ * True when an expression is a `Balance`: typed as one, or read from `transferred_value()`/`balance()`
+ */
+function isBalance(expr, scope) {
+  const type = inferType(expr, scope);
+  if (type && BALANCE_TYPE.test(splitGenericType(type).base)) return true;
+  return expr.type === 'MethodCall' && ['transferred_value', 'balance'].includes(expr.method) &&
+    expr.receiver.type === 'MethodCall' && expr.receiver.method === 'env';
+}
+
+/**
+ * Arguments of the `#[ink(...)]` attributes of an item, e.g. ['message', 'payable', 'selector = 0xCAFEBABE']
+ */
+function inkArgs(node) {
+  return (node.attrs || [])
+    .filter(attr => attr.path === 'ink' && attr.args)
+    .flatMap(attr => attr.args.split(',').map(arg => arg.trim()));
+}
+
+function isContractModule(node) {
+  return (node.attrs || []).some(attr => attr.path === 'ink::contract' || attr.path === 'ink_lang::contract');
+}
+
+module.exports = {
+  name: 'ink',
+  title: 'ink!',
+  authorizationRules: ['ink-missing-caller-check'],
+  detect,
+  analyze,
+  renderSummary
+};
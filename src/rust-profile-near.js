@@ -0,0 +1,281 @@
+const { visit } = require('./rust-parser');
+const { isSelf, nodeText, selfFieldRoot, signatureOf } = require('./rust-ast');
+
+/**
+ * NEAR SDK profile: lists the methods exported by `#[near_bindgen]` contracts with their `#[payable]`,
+ * `#[private]` and `#[init]` markers, follows `Promise` chains to their callbacks and checks deposits,
+ * callback privacy, state changes made before cross-contract calls and unpaid storage growth.
+ */
+
+const MUTATING_METHODS = new Set(['insert', 'remove', 'push', 'pop', 'extend', 'append', 'clear', 'set', 'replace', 'take', 'swap_remove', 'retain']);
+const GROWTH_METHODS = new Set(['insert', 'push', 'extend', 'append', 'set', 'replace']);
+const CALLBACK_PARAM_ATTRS = new Set(['callback_result', 'callback_unwrap', 'callback', 'callback_vec']);
+const RESULT_INSPECTORS = new Set(['promise_result', 'is_promise_success', 'promise_results_count']);
+const STORAGE_CHECK = /^(storage_usage|storage_byte_cost|storage_deposit|storage_balance_of|assert_storage\w*|check_storage\w*|refund_storage\w*)$/;
+
+/**
+ * True when the file is a NEAR contract
+ */
+function detect(ast, code) {
+  if (/\bnear_sdk\b/.test(code)) return true;
+  let found = false;
+  visit(ast, {
+    Impl: function(node) {
+      if (isContractImpl(node)) found = true;
+    }
+  });
+  return found;
+}
+
+/**
+ * Run the NEAR checks
+ * @param {Object} context - `{ code, definitions, locate }`
+ * @returns {Object} - `{ findings, summary }`
+ */
+function analyze({ code, definitions, locate }) {
+  const findings = { high: [], medium: [], low: [], info: [] };
+  const methods = collectMethods(definitions, code);
+  const byName = new Map(methods.map(method => [method.name, method]));
+
+  // Callbacks are the methods a `.then(..)` schedules on this contract, or that read a promise result
+  const scheduled = new Map();
+  methods.forEach(method => method.promises.forEach(promise => {
+    if (promise.callback) scheduled.set(promise.callback, method);
+  }));
+  methods.forEach(method => {
+    method.callback = scheduled.has(method.name) || method.readsCallbackResult;
+  });
+
+  for (const method of methods) {
+    const location = locate(signatureOf(method.fn.node));
+
+    // 1. Payable methods must look at the attached deposit
+    if (method.payable && !method.readsDeposit) {
+      findings.medium.push({
+        ruleId: 'near-payable-without-deposit-check',
+        title: 'Payable Method Does Not Check the Deposit',
+        description: `\`${method.fn.qualifiedName}\` is \`#[payable]\` but never reads \`env::attached_deposit()\`. Any amount, including zero, is accepted and tokens attached by mistake stay in the contract.`,
+        recommendation: 'Check `env::attached_deposit()` against the expected amount (and refund any excess), or remove `#[payable]`.',
+        location
+      });
+    }
+
+    // 2. Callbacks must only be callable by the contract itself
+    if (method.callback && method.exported && !method.private && !method.checksSelfCaller) {
+      const scheduler = scheduled.get(method.name);
+      findings.high.push({
+        ruleId: 'near-callback-not-private',
+        title: 'Callback Is Not #[private]',
+        description: `\`${method.fn.qualifiedName}\` is a promise callback${scheduler ? ` scheduled by \`${scheduler.fn.qualifiedName}\`` : ''}, but it is a public method without \`#[private]\`. Anyone can call it directly with a forged promise result.`,
+        recommendation: 'Mark the callback `#[private]` so only the contract account can call it.',
+        location
+      });
+    }
+
+    // 3. State changed before a cross-contract call must be rolled back when the call fails
+    for (const promise of method.promises) {
+      const earlierWrites = method.writes.filter(write => write.node.range[0] < promise.node.range[0]);
+      if (earlierWrites.length === 0) continue;
+      const callback = promise.callback ? byName.get(promise.callback) : null;
+      if (callback && callback.inspectsResult && callback.writes.length > 0) continue;
+      const fields = [...new Set(earlierWrites.map(write => `\`self.${write.field}\``))].join(', ');
+      const reason = !promise.callback
+        ? 'no callback is attached with `.then(..)`'
+        : !callback
+          ? `the callback \`${promise.callback}\` is not defined in this file`
+          : !callback.inspectsResult
+            ? `the callback \`${callback.fn.qualifiedName}\` never checks whether the call succeeded`
+            : `the callback \`${callback.fn.qualifiedName}\` never restores contract state`;
+      findings.high.push({
+        ruleId: 'near-state-change-before-promise',
+        title: 'State Change Before Cross-Contract Call Without Rollback',
+        description: `\`${method.fn.qualifiedName}\` updates ${fields} before the cross-contract call at line ${promise.node.loc.start.line}, but ${reason}. If the call fails, the state change is committed anyway (e.g. a balance is debited but the tokens are never sent).`,
+        recommendation: 'Attach a `#[private]` callback with `.then(..)` that reads the result (`#[callback_result]` or `env::promise_result`) and reverts the state change on failure.',
+        location: locate(promise.node)
+      });
+    }
+
+    // 4. Storage growth must be paid for by the caller
+    const growth = method.writes.find(write => write.grows);
+    if (growth && method.exported && !method.private && !method.init && !method.checksStorage) {
+      findings.medium.push({
+        ruleId: 'near-storage-growth-unpaid',
+        title: 'Storage Growth Without Storage Staking Check',
+        description: `\`${method.fn.qualifiedName}\` adds entries to \`self.${growth.field}\` (line ${growth.node.loc.start.line}) without measuring \`env::storage_usage()\` or charging the caller for storage. Callers can grow the contract state until the contract's balance no longer covers its storage staking.`,
+        recommendation: 'Measure `env::storage_usage()` before and after the insertion and require an attached deposit of at least the difference times `env::storage_byte_cost()`, or require callers to register through NEP-145 `storage_deposit`.',
+        location: locate(growth.node)
+      });
+    }
+  }
+
+  return {
+    findings,
+    summary: {
+      contracts: [...new Set(methods.map(method => method.fn.owner))],
+      methods: methods.map(method => ({
+        name: method.fn.qualifiedName,
+        kind: method.init ? 'init' : method.mutating ? 'call' : 'view',
+        markers: ['payable', 'private', 'callback'].filter(marker => method[marker]),
+        readsPredecessor: method.readsPredecessor,
+        line: method.fn.node.loc.start.line
+      }))
+    }
+  };
+}
+
+/**
+ * Render the contract method table for the Markdown report
+ * @param {Object} summary - `summary` returned by analyze
+ * @returns {string} - Markdown
+ */
+function renderSummary(summary) {
+  let output = `## NEAR Contract\n\n`;
+  output += `- **Contract:** ${summary.contracts.length > 0 ? summary.contracts.map(name => `\`${name}\``).join(', ') : '(no `#[near_bindgen]` impl in this file)'}\n`;
+  output += `- **Exported methods:** ${summary.methods.length}\n\n`;
+
+  if (summary.methods.length > 0) {
+    output += `| Method | Kind | Markers | Reads predecessor | Line |\n`;
+    output += `|--------|------|---------|-------------------|------|\n`;
+    summary.methods.forEach(method => {
+      const markers = method.markers.length > 0 ? method.markers.map(marker => `\`${marker}\``).join(', ') : '-';
+      output += `| \`${method.name}\` | ${method.kind} | ${markers} | ${method.readsPredecessor ? 'yes' : 'no'} | ${method.line} |\n`;
+    });
+    output += `\n`;
+  }
+
+  return output;
+}
+
+/**
+ * Describe the methods of every `#[near_bindgen]` impl
+ */
+function collectMethods(definitions, code) {
+  return definitions.functions
+    .filter(fn => fn.container && fn.container.type === 'Impl' && isContractImpl(fn.container) && fn.node.body)
+    .map(fn => {
+      const node = fn.node;
+      const method = {
+        fn,
+        name: fn.name,
+        // Trait impls of a contract are exported whatever their visibility
+        exported: !!node.visibility || !!fn.trait,
+        mutating: !!(node.selfParam && node.selfParam.mutable),
+        init: hasAttribute(node, 'init'),
+        payable: hasAttribute(node, 'payable'),
+        private: hasAttribute(node, 'private'),
+        readsCallbackResult: node.params.some(param => (param.attrs || []).some(attr => CALLBACK_PARAM_ATTRS.has(attr.path))),
+        inspectsResult: node.params.some(param => (param.attrs || []).some(attr => attr.path === 'callback_result')),
+        readsDeposit: false,
+        readsPredecessor: false,
+        checksSelfCaller: false,
+        checksStorage: false,
+        writes: [],
+        promises: []
+      };
+      const thens = [];
+      const lookups = new Set();
+
+      visit(node.body, {
+        Call: function(call) {
+          const segments = call.callee.type === 'PathExpr' ? call.callee.path.segments.map(segment => segment.name) : [];
+          const name = segments[segments.length - 1];
+          if (name === 'attached_deposit' || name === 'assert_one_yocto') method.readsDeposit = true;
+          if (name === 'predecessor_account_id') method.readsPredecessor = true;
+          if (STORAGE_CHECK.test(name || '')) method.checksStorage = true;
+          if (RESULT_INSPECTORS.has(name)) {
+            method.readsCallbackResult = true;
+            method.inspectsResult = true;
+          }
+          // The `Self::ext(..)` inside `.then(..)` schedules the callback; it does not start a new call
+          const scheduling = thens.some(then => then.args[0].range[0] <= call.range[0] && call.range[1] <= then.args[0].range[1]);
+          if (isPromiseStart(segments) && !scheduling) method.promises.push({ node: call, callback: null });
+        },
+        PathExpr: function(path) {
+          // Constants such as `STORAGE_COST_PER_ENTRY`
+          if (/^[A-Z0-9_]*STORAGE[A-Z0-9_]*$/.test(path.path.segments[path.path.segments.length - 1].name)) method.checksStorage = true;
+        },
+        MethodCall: function(call) {
+          if (STORAGE_CHECK.test(call.method)) method.checksStorage = true;
+          if (call.method === 'then' && call.args[0]) thens.push(call);
+        },
+        Macro: function(macro) {
+          // `require!(env::predecessor_account_id() == env::current_account_id())` is a manual #[private]
+          const text = (macro.tokens || []).map(token => token.value).join(' ');
+          if (/predecessor_account_id/.test(text) && /current_account_id/.test(text)) method.checksSelfCaller = true;
+          if (/\battached_deposit\b/.test(text)) method.readsDeposit = true;
+          if (/\bpredecessor_account_id\b/.test(text)) method.readsPredecessor = true;
+          if (/\bstorage_(usage|byte_cost)\b/.test(text)) method.checksStorage = true;
+        }
+      });
+
+      // Attach each promise to the first `.then(..)` chained on it
+      thens.sort((a, b) => a.range[1] - b.range[1]).forEach(then => {
+        const promise = method.promises.find(candidate => then.receiver.range[0] <= candidate.node.range[0] && candidate.node.range[1] <= then.receiver.range[1]);
+        if (promise && !promise.callback) promise.callback = callbackName(then.args[0]);
+      });
+
+      visit(node.body, {
+        Assign: recordWrite,
+        CompoundAssign: recordWrite,
+        MethodCall: function(call) {
+          if (call.receiver.type !== 'FieldAccess' || !isSelf(call.receiver.object)) return;
+          const field = call.receiver.field;
+          const key = call.args[0] ? nodeText(code, call.args[0]) : null;
+          if (['get', 'contains_key', 'get_mut'].includes(call.method) && key) lookups.add(`${field}:${key}`);
+          if (!MUTATING_METHODS.has(call.method)) return;
+          // Re-inserting a key that was just looked up updates an entry rather than adding one
+          const update = call.method === 'insert' && lookups.has(`${field}:${key}`);
+          method.writes.push({ node: call, field, grows: GROWTH_METHODS.has(call.method) && !update });
+        }
+      });
+
+      function recordWrite(assignment) {
+        const field = selfFieldRoot(assignment.left);
+        if (field) method.writes.push({ node: assignment, field, grows: false });
+      }
+
+      return method;
+    });
+}
+
+/**
+ * True for a call that starts a cross-contract promise: `Promise::new(..)`, `ext_token::ext(..)`,
+ * `Self::ext(..)` or the low-level `env::promise_create(..)`
+ */
+function isPromiseStart(segments) {
+  if (segments.length < 2) return false;
+  const [qualifier, name] = segments.slice(-2);
+  return (qualifier === 'Promise' && name === 'new') || name === 'ext' ||
+    (qualifier === 'env' && /^promise_(create|batch_create)$/.test(name)) ||
+    (/^ext_/.test(qualifier) && qualifier !== 'ext_self');
+}
+
+/**
+ * The method a `.then(..)` argument schedules, e.g. `on_transfer` in
+ * `Self::ext(env::current_account_id()).with_static_gas(GAS).on_transfer(id)`
+ */
+function callbackName(expr) {
+  if (expr.type === 'MethodCall' && !/^with_/.test(expr.method)) return expr.method;
+  if (expr.type === 'Call' && expr.callee.type === 'PathExpr') {
+    const segments = expr.callee.path.segments.map(segment => segment.name);
+    if (segments.length === 2 && /^ext_self|^Self$/.test(segments[0]) && segments[1] !== 'ext') return segments[1];
+  }
+  return null;
+}
+
+function isContractImpl(node) {
+  return hasAttribute(node, 'near_bindgen') || hasAttribute(node, 'near');
+}
+
+function hasAttribute(node, name) {
+  return (node.attrs || []).some(attr => attr.path === name || attr.path.endsWith(`::${name}`));
+}
+
+module.exports = {
+  name: 'near',
+  title: 'NEAR',
+  authorizationRules: ['near-callback-not-private'],
+  detect,
+  analyze,
+  renderSummary
+};
@@ -0,0 +1,55 @@
+const anchor = require('./rust-profile-anchor');
+const cosmwasm = require('./rust-profile-cosmwasm');
+const near = require('./rust-profile-near');
+const ink = require('./rust-profile-ink');
+
+/**
+ * Framework profiles for the Rust static analyzer. A profile recognises one smart-contract framework
+ * (e.g. Anchor or CosmWasm) and runs framework-aware checks on top of the generic ones. Each profile module
+ * exports `{ name, title, detect(ast, code), analyze(context), renderSummary(summary) }`, where `analyze` receives
+ * `{ ast, code, filePath, definitions, locate }` and returns `{ findings, summary }` with findings in the
+ * usual severity buckets. A profile may also export `authorizationRules`, the ids of its caller checks: the generic
+ * `rust-missing-authorization` finding is dropped for the functions they report.
+ */
+const PROFILES = [anchor, cosmwasm, near, ink];
+const PROFILE_NAMES = PROFILES.map(profile => profile.name);
+
+/**
+ * Parse the --profile option
+ * @param {string|string[]} value - "auto", "none" or profile names separated by commas
+ * @returns {string|string[]} - "auto" or the list of selected profile names
+ */
+function parseProfiles(value) {
+  if (!value) return 'auto';
+  const names = (Array.isArray(value) ? value : String(value).split(','))
+    .map(name => name.trim().toLowerCase())
+    .filter(Boolean);
+  if (names.length === 0 || names.includes('auto')) return 'auto';
+  if (names.includes('none')) return [];
+  const unknown = names.filter(name => !PROFILE_NAMES.includes(name));
+  if (unknown.length > 0) {
+    throw new Error(`Unknown Rust profile: ${unknown.join(', ')} (expected auto, none or one of ${PROFILE_NAMES.join(', ')})`);
+  }
+  return [...new Set(names)];
+}
+
+/**
+ * Run the selected profiles against a parsed file. With "auto", every profile whose framework is
+ * detected in the file runs.
+ * @param {Object} context - `{ ast, code, filePath, definitions, locate }`
+ * @param {string|string[]} selection - Value of the --profile option
+ * @returns {Array} - `[{ profile, findings, summary }]` for each profile that ran
+ */
+function runProfiles(context, selection = 'auto') {
+  const selected = parseProfiles(selection);
+  const profiles = selected === 'auto'
+    ? PROFILES.filter(profile => profile.detect(context.ast, context.code))
+    : PROFILES.filter(profile => selected.includes(profile.name));
+  return profiles.map(profile => ({ profile, ...profile.analyze(context) }));
+}
+
+module.exports = {
+  PROFILE_NAMES,
+  parseProfiles,
+  runProfiles
+};
@@ -1,31 +1,34 @@
 const fs = require('fs');
 const path = require('path');
-const Anthropic = require('@anthropic-ai/sdk');
 const ora = require('ora');
 const yaml = require('js-yaml');
+const { parse, visit } = require('./rust-parser');
+const { findSemgrep, summarizeValidation, validateRules } = require('./semgrep-validator');
+const { completeResult, isLLMAvailable, modelName, unavailableReason } = require('./llm-provider');
 
-const anthropic = new Anthropic({
-  apiKey: process.env.ANTHROPIC_API_KEY,
-});
+// Version of the rule generation prompt, part of the response cache key
+const RULES_PROMPT_VERSION = 1;
 
 /**
  * Generates Semgrep rules for Rust Web3/blockchain code
  * @param {string} filePath - Path to the Rust file
  * @param {string} outputDir - Directory to save semgrep rules
+ * @param {Object} options - `reportName`: name the rule, test and validation files are derived from (default: the
+ *   file name, see reportName)
  */
-async function generateRustSemgrepRules(filePath, outputDir) {
-  const spinner = ora('Generating Rust semgrep rules with Claude 3.7...').start();
+async function generateRustSemgrepRules(filePath, outputDir, options = {}) {
+  const spinner = ora(`Generating Rust semgrep rules with ${modelName()}...`).start();
   
   try {
     // Read the contract code
     const contractCode = fs.readFileSync(filePath, 'utf8');
-    const fileName = path.basename(filePath);
+    const reportName = options.reportName || path.basename(filePath);
     
     // Create output directory for semgrep rules if it doesn't exist
     const semgrepDir = path.join(outputDir, 'semgrep-rust-rules');
     fs.mkdirSync(semgrepDir, { recursive: true });
     
-    // Prompt for Claude 3.7
+    // Prompt for the configured LLM
     const prompt = `
 You are an expert Rust security auditor specializing in Web3 and blockchain. Generate Semgrep rules that can detect potential security issues in Rust code similar to this:
 
@@ -39,24 +42,24 @@ For each potential vulnerability or bug pattern, create a Semgrep rule in YAML f
 rules:
   - id: rust-[unique-id-for-the-rule]
     message: "[Brief description of the issue]"
+    severity: [ERROR|WARNING|INFO]
     metadata:
       category: security
       technology:
         - rust
         - web3
-      severity: [ERROR|WARNING|INFO]
       likelihood: [HIGH|MEDIUM|LOW]
       impact: [HIGH|MEDIUM|LOW]
       confidence: [HIGH|MEDIUM|LOW]
     languages: [rust]
-    pattern: [The pattern to match problematic code]
-    pattern-not: [Optional pattern that should not match]
     patterns:
-      - pattern: [Alternative pattern syntax if needed]
-        pattern-not: [Optional exclude pattern]
+      - pattern: [The pattern to match problematic code]
+      - pattern-not: [Optional pattern that should not match]
     fix: [Optional fix suggestion]
 \`\`\`
 
+Each rule must use exactly one of \`pattern\`, \`patterns\`, \`pattern-either\` or \`pattern-regex\` at the top level; \`pattern-not\`, \`pattern-inside\` and \`pattern-not-inside\` are only allowed as entries of a \`patterns\` list. Every rule is validated against Semgrep's rule format and run against the code above, so write patterns that match it where the issue is present.
+
 Generate at least 5 different rules covering these categories:
 1. Memory safety issues (e.g., unsafe blocks without proper validation)
 2. Numeric overflow/underflow vulnerabilities 
@@ -67,94 +70,73 @@ Generate at least 5 different rules covering these categories:
 Ensure the rules are specific to Rust and applicable to Web3/blockchain code. Provide accurate pattern matching that will work with the Semgrep engine.
 `;
 
-    // Check if API key is available
-    if (!process.env.ANTHROPIC_API_KEY) {
-      spinner.fail('No API key found');
-      throw new Error('ANTHROPIC_API_KEY not found in environment variables');
+    // Without a model provider only the curated rule pack (see rust-semgrep-pack.js) is emitted
+    if (!isLLMAvailable()) {
+      spinner.info(`${unavailableReason()}; skipping model-generated Rust semgrep rules`);
+      return semgrepDir;
     }
 
     try {
-      // Call Claude 3.7 API
-      const response = await anthropic.messages.create({
-        model: "claude-3-sonnet-20240229",
-        max_tokens: 4000,
-        messages: [
-          { 
-            role: "user", 
-            content: prompt 
-          }
-        ],
-        system: "You are an expert in Rust security and Semgrep pattern development. Generate precise, effective rules that detect security issues in Rust Web3/blockchain code."
-      });
-
-      // Validate response format
-      if (!response || !response.content || !Array.isArray(response.content) || response.content.length === 0) {
-        spinner.fail('Invalid API response');
-        throw new Error('Invalid response format from Anthropic API');
-      }
+      const response = await completeResult(prompt, "You are an expert in Rust security and Semgrep pattern development. Generate precise, effective rules that detect security issues in Rust Web3/blockchain code.", { template: 'rust-semgrep', version: RULES_PROMPT_VERSION, source: contractCode });
+      const text = response.text;
+      const cached = response.cached ? ' (cached)' : '';
 
       // Extract YAML rules from response
-      const text = response.content[0].text;
       const yamlBlocks = text.match(/```yaml[\s\S]*?```/g) || [];
       
       if (yamlBlocks.length === 0) {
         spinner.warn('No YAML rules found in API response');
       }
       
-      // Counter for the total number of individual rules
-      let totalRuleCount = 0;
-      
-      // Process each YAML block
+      // Split the YAML blocks into individual rules; blocks that do not parse are kept for the report
+      const candidates = [];
       for (let i = 0; i < yamlBlocks.length; i++) {
         const yamlContent = yamlBlocks[i].replace(/```yaml|```/g, '').trim();
         if (!yamlContent) continue;
-        
+
         try {
-          // Parse the YAML to extract individual rules
           const parsedYaml = yaml.load(yamlContent);
-          
-          if (parsedYaml && parsedYaml.rules && Array.isArray(parsedYaml.rules)) {
-            // Save each rule to a separate file
-            for (let j = 0; j < parsedYaml.rules.length; j++) {
-              const rule = parsedYaml.rules[j];
-              if (!rule.id) {
-                continue; // Skip rules without an ID
-              }
-              
-              // Create a new YAML document with just this rule
-              const singleRuleYaml = {
-                rules: [rule]
-              };
-              
-              // Convert back to YAML string
-              const singleRuleContent = yaml.dump(singleRuleYaml);
-              
-              // Create a descriptive filename based on the rule ID
-              const ruleFileName = `${rule.id}-${fileName}.yml`;
-              const rulePath = path.join(semgrepDir, ruleFileName);
-              
-              // Save the rule to its own file
-              fs.writeFileSync(rulePath, singleRuleContent);
-              totalRuleCount++;
-            }
-          }
+          const rules = parsedYaml && Array.isArray(parsedYaml.rules) ? parsedYaml.rules
+            : Array.isArray(parsedYaml) ? parsedYaml
+            : parsedYaml && parsedYaml.id ? [parsedYaml] : [];
+          rules.forEach(rule => candidates.push({ rule }));
         } catch (yamlError) {
-          // If YAML parsing fails, save the raw content
-          const fallbackPath = path.join(semgrepDir, `raw_rule_${i+1}_${fileName}.yml`);
-          fs.writeFileSync(fallbackPath, yamlContent);
-          totalRuleCount++;
-          spinner.warn(`Could not parse YAML for rule ${i+1}, saving raw content`);
+          candidates.push({ raw: yamlContent, error: yamlError.message.split('\n')[0] });
         }
       }
-      
-      // If no rules were extracted properly, save the entire response
-      if (totalRuleCount === 0) {
-        const fullResponsePath = path.join(semgrepDir, `full_response_${fileName}.md`);
+
+      // If no rules were extracted, save the entire response
+      if (candidates.length === 0) {
+        const fullResponsePath = path.join(semgrepDir, `full_response_${reportName}.md`);
         fs.writeFileSync(fullResponsePath, text);
-        totalRuleCount = 1; // At least we saved the full response
+        spinner.warn(`No Semgrep rules could be extracted; full response saved to: ${fullResponsePath}`);
+        return semgrepDir;
       }
-      
-      spinner.succeed(`Semgrep rules generated! ${totalRuleCount} rules saved to: ${semgrepDir}`);
+
+      // Schema-check each rule, run it against this file and write its `semgrep --test` file
+      spinner.text = `Validating ${candidates.length} Rust semgrep rules...`;
+      const results = validateRules(candidates, {
+        rulesDir: semgrepDir,
+        sourceFile: filePath,
+        suffix: reportName,
+        language: 'rust',
+        extension: '.rs',
+        okLines: functionStartLines(contractCode)
+      });
+      const reportPath = path.join(semgrepDir, `validation-${reportName}.json`);
+      fs.writeFileSync(reportPath, JSON.stringify({
+        source: filePath,
+        checkedWith: findSemgrep() ? 'semgrep' : 'schema',
+        cachedResponse: response.cached ? { cachedAt: response.cachedAt, template: response.template, version: response.version, model: response.model } : null,
+        rules: results.map(result => ({
+          ...result,
+          file: path.relative(semgrepDir, result.file),
+          testFile: result.testFile && path.relative(semgrepDir, result.testFile)
+        }))
+      }, null, 2));
+
+      const note = findSemgrep() ? '' : ' (semgrep not installed: schema checks only, no test files written)';
+      spinner.succeed(`Semgrep rules generated${cached}! ${results.length} rules (${summarizeValidation(results)})${note} saved to: ${semgrepDir}`);
       return semgrepDir;
     } catch (apiError) {
       spinner.fail('API call failed');
@@ -166,6 +148,25 @@ Ensure the rules are specific to Rust and applicable to Web3/blockchain code. Pr
   }
 }
 
+/**
+ * First line of each function (after its attributes), used as `// ok:` candidates in rule test files
+ * @param {string} code - Rust source code
+ * @returns {number[]}
+ */
+function functionStartLines(code) {
+  const lines = [];
+  try {
+    visit(parse(code), {
+      Fn: function(node) {
+        lines.push((node.declarationLoc || node.loc).start.line);
+      }
+    });
+  } catch (error) {
+    // Without a parse the test file only gets `ruleid:` annotations
+  }
+  return lines;
+}
+
 module.exports = {
   generateRustSemgrepRules
 }; 
\ No newline at end of file
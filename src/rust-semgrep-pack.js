@@ -0,0 +1,97 @@
+const fs = require('fs');
+const path = require('path');
+const ora = require('ora');
+const yaml = require('js-yaml');
+
+/**
+ * Winston's curated Rust/Web3 Semgrep rule pack (`rules/rust/`). Unlike model-generated rules it needs no API key.
+ * Every rule `<id>.yml` has a fixture `<id>.rs` next to it with `// ruleid:` (positive) and `// ok:` (negative)
+ * cases, the layout `semgrep --test` expects.
+ */
+
+const PACK_DIR = path.join(__dirname, '..', 'rules', 'rust');
+
+/**
+ * Load the pack manifest and its rules
+ * @returns {Object} - `{ name, version, description, rules: [{ id, rule, file, testFile }] }`
+ */
+function loadRustRulePack() {
+  const manifest = yaml.load(fs.readFileSync(path.join(PACK_DIR, 'pack.yml'), 'utf8'));
+  const rules = fs.readdirSync(PACK_DIR)
+    .filter(name => name.endsWith('.yml') && name !== 'pack.yml')
+    .sort()
+    .map(name => {
+      const file = path.join(PACK_DIR, name);
+      const rule = yaml.load(fs.readFileSync(file, 'utf8')).rules[0];
+      const testFile = path.join(PACK_DIR, name.replace(/\.yml$/, '.rs'));
+      return { id: rule.id, rule, file, testFile: fs.existsSync(testFile) ? testFile : null };
+    });
+  return { ...manifest, rules };
+}
+
+/**
+ * Copy the pack, with its fixtures and a README, into the Rust semgrep rules directory of an audit
+ * @param {string} outputDir - Output directory of the audit (or crate)
+ * @returns {string} - Directory the pack was written to, e.g. `semgrep-rust-rules/winston-rust-1.0.0`
+ */
+function writeRustRulePack(outputDir) {
+  const spinner = ora('Writing the curated Rust semgrep rule pack...').start();
+
+  try {
+    const pack = loadRustRulePack();
+    const packDir = path.join(outputDir, 'semgrep-rust-rules', `${pack.name}-${pack.version}`);
+    fs.mkdirSync(packDir, { recursive: true });
+
+    pack.rules.forEach(({ file, testFile }) => {
+      fs.copyFileSync(file, path.join(packDir, path.basename(file)));
+      if (testFile) fs.copyFileSync(testFile, path.join(packDir, path.basename(testFile)));
+    });
+    fs.writeFileSync(path.join(packDir, 'README.md'), generateReadme(pack));
+
+    spinner.succeed(`Rust semgrep rule pack ${pack.name} ${pack.version} (${pack.rules.length} rules) saved to: ${packDir}`);
+    return packDir;
+  } catch (error) {
+    spinner.fail('Could not write the Rust semgrep rule pack');
+    throw error;
+  }
+}
+
+/**
+ * README listing the pack's rules and how to run and test them
+ */
+function generateReadme(pack) {
+  const rows = pack.rules
+    .map(({ id, rule }) => `| \`${id}\` | ${rule.severity} | ${rule.metadata.title} |`)
+    .join('\n');
+
+  return `# ${pack.name} ${pack.version}
+
+${pack.description}. These rules ship with Winston and do not need a model provider.
+
+## Usage
+
+To run the pack against a Rust project, use:
+
+\`\`\`bash
+semgrep --config ./${pack.name}-${pack.version} path/to/crate
+\`\`\`
+
+Each rule has a fixture with the same name (\`<rule-id>.rs\`) whose \`// ruleid:\` and \`// ok:\` comments mark code
+the rule must and must not match. To check the rules against their fixtures, use:
+
+\`\`\`bash
+semgrep --test ./${pack.name}-${pack.version}
+\`\`\`
+
+## Rules
+
+| Rule | Severity | Title |
+|------|----------|-------|
+${rows}
+`;
+}
+
+module.exports = {
+  loadRustRulePack,
+  writeRustRulePack
+};
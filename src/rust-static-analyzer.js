@@ -1,13 +1,55 @@
 const fs = require('fs');
 const path = require('path');
 const ora = require('ora');
+const { writeReports } = require('./report-formats');
+const { formatChangeSummary, formatFindingMetadata, groupBySeverity, toFindings } = require('./finding');
+const { classifyFindings, formatFileChange } = require('./diff-scope');
+const { applySuppressions, formatSuppressionReport, formatSuppressionSummary } = require('./baseline');
+const { parse, visit } = require('./rust-parser');
+const {
+  PRIVILEGED_FIELD,
+  ROLE_FIELD,
+  collectDefinitions,
+  diverges,
+  dominatingConditions,
+  inferType,
+  isIntegerType,
+  isSelf,
+  nodeText,
+  precedingStatements,
+  selfFieldRoot,
+  signatureOf,
+  sourceLocation,
+  walkFunction
+} = require('./rust-ast');
+const { analyzePanicPaths, describeEntryPoints, renderPanicReport } = require('./rust-panics');
+const { runProfiles } = require('./rust-profiles');
+const { mergeSemgrepFindings, semgrepFinding } = require('./semgrep-runner');
+const {
+  buildCallGraph,
+  findEntryPoints,
+  findFunctionAt,
+  reachableFrom
+} = require('./rust-callgraph');
+
+const SEVERITY_LEVELS = ['high', 'medium', 'low', 'info'];
 
 /**
  * Perform static analysis on Rust code to find potential issues
  * @param {string} filePath - Path to the Rust file
  * @param {string} outputDir - Directory to save analysis results
+ * @param {Object} options - `format`: markdown, json and/or sarif (comma separated);
+ *   `crate`: `{ name, target: { kind, name } }` of the Cargo target the file belongs to;
+ *   `profile`: framework profiles to run ("auto", "none" or names, comma separated);
+ *   `callGraph`: call graph of the file's crate (buildCrateCallGraph) for cross-file reachability;
+ *   `entryPoints`: function names to treat as entry points (default: detected);
+ *   `semgrepMatches`: Semgrep matches in this file (runRustSemgrep) to merge into the findings;
+ *   `change`: the file's change when auditing a diff (see diff-scope.js), to tell new findings from pre-existing ones;
+ *   `baseline`: the loaded baseline (see baseline.js), whose findings are suppressed like inline `winston-ignore`s;
+ *   `root`: the audited repository or directory, which finding ids are relative to;
+ *   `reportName`: name of the report files (default: the file name, see reportName)
  */
-async function staticAnalyzeRustContract(filePath, outputDir) {
+async function staticAnalyzeRustContract(filePath, outputDir, options = {}) {
   const spinner = ora('Performing static analysis on Rust code...').start();
   
   try {
@@ -15,156 +57,903 @@ async function staticAnalyzeRustContract(filePath, outputDir) {
     const code = fs.readFileSync(filePath, 'utf8');
     const fileName = path.basename(filePath);
     
-    // Results object to hold findings
-    const findings = {
-      high: [],
-      medium: [],
-      low: [],
-      info: []
-    };
+    // Parse the code and run every check against the syntax tree
+    const analysis = analyzeRustCode(code, filePath, {
+      profile: options.profile,
+      callGraph: options.callGraph,
+      entryPoints: options.entryPoints,
+      semgrepMatches: options.semgrepMatches,
+      root: options.root
+    });
+    const { profiles, panicPaths, entryPoints, semgrep } = analysis;
+    if (options.change) classifyFindings(analysis.findings, options.change);
     
-    // Perform static analysis checks
+    // Accepted findings (baseline and inline suppressions) are listed apart
+    const suppressions = applySuppressions(analysis.findings, code, filePath, options.baseline || null, options.root);
+    const { findings } = suppressions;
     
-    // 1. Check for unsafe blocks - potential memory safety issues
-    if (code.includes('unsafe')) {
-      findings.high.push({
-        title: 'Unsafe Block Usage',
-        description: 'Unsafe blocks bypass Rust safety guarantees and may lead to undefined behavior, memory corruption, or security vulnerabilities.',
-        recommendation: 'Review all unsafe code carefully. Consider if safe alternatives exist. If unsafe is necessary, add detailed comments explaining why and what invariants are being maintained.'
-      });
-    }
-    
-    // 2. Check for unwrap/expect without error handling - potential panics
-    const unwrapPattern = /\.unwrap\(\)/g;
-    const expectPattern = /\.expect\([^)]+\)/g;
-    
-    if (unwrapPattern.test(code)) {
-      findings.medium.push({
-        title: 'Unwrap on Option/Result',
-        description: 'Use of unwrap() can cause panics if the Option is None or the Result is an Err. This can lead to crashes in production.',
-        recommendation: 'Use proper error handling with match, if let, or the ? operator instead of unwrap().'
-      });
+    // Generate the report
+    const bySeverity = groupBySeverity(findings);
+    let report = `# Static Analysis Report: ${fileName}\n\n`;
+    report += `*Generated on: ${new Date().toISOString()}*\n\n`;
+    if (options.crate) {
+      report += `**Crate:** \`${options.crate.name}\` (${options.crate.target.kind} target \`${options.crate.target.name}\`)\n\n`;
     }
-    
-    if (expectPattern.test(code)) {
-      findings.low.push({
-        title: 'Expect on Option/Result',
-        description: 'The expect() method will panic with a custom message if the value is None or Err. While better than unwrap(), it still causes crashes.',
-        recommendation: 'Use proper error handling with match, if let, or the ? operator instead of expect().'
-      });
+    if (options.change) {
+      report += `**Change:** ${formatFileChange(options.change)}\n\n`;
     }
     
-    // 3. Check for potential integer overflow
-    if (code.includes('u64') || code.includes('u32') || code.includes('u16') || code.includes('u8')) {
-      if (code.includes('+') || code.includes('+=')) {
-        findings.medium.push({
-          title: 'Potential Integer Overflow',
-          description: 'Arithmetic operations on unsigned integers can overflow in Rust\'s release mode without checks, which may lead to incorrect calculations.',
-          recommendation: 'Use checked_add(), saturating_add(), or wrapping_add() methods based on your requirements to handle overflow cases explicitly.'
-        });
-      }
+    // Add findings to the report
+    report += `## Results Summary\n\n`;
+    report += `- High severity issues: ${bySeverity.high.length}\n`;
+    report += `- Medium severity issues: ${bySeverity.medium.length}\n`;
+    report += `- Low severity issues: ${bySeverity.low.length}\n`;
+    report += `- Informational: ${bySeverity.info.length}\n`;
+    report += formatChangeSummary(findings);
+    report += formatSuppressionSummary(suppressions);
+    const lowered = findings.filter(finding => finding.reachable === false).length;
+    if (entryPoints) {
+      report += `- Entry points: ${entryPoints.length}${lowered > 0 ? ` (${lowered} issue(s) in code no entry point reaches were ranked one level lower)` : ''}\n`;
     }
-    
-    // 4. Check for suspicious authorization patterns
-    if (code.includes('owner') && code.includes('caller')) {
-      const basicAuthCheck = /if\s+([a-zA-Z0-9_]+)\s*!=\s*self\.owner/;
-      
-      if (basicAuthCheck.test(code)) {
-        findings.info.push({
-          title: 'Basic Authorization Check',
-          description: 'The code implements a basic ownership check, which may be sufficient for simple contracts but lacks flexibility for more complex authorization requirements.',
-          recommendation: 'Consider implementing a more sophisticated access control system for complex applications, such as role-based access control or multi-signature authorization.'
-        });
-      }
+    if (semgrep) {
+      report += `- Semgrep matches: ${semgrep.added + semgrep.duplicates}${semgrep.duplicates > 0 ? ` (${semgrep.duplicates} already reported by the static analyzer)` : ''}\n`;
     }
+    report += `\n`;
     
-    // 5. Check for error swallowing (ignoring errors with let _ = ...)
-    const ignoredErrorPattern = /let\s+_\s*=\s*.*\)\s*;/g;
-    if (ignoredErrorPattern.test(code)) {
-      findings.low.push({
-        title: 'Ignored Error Results',
-        description: 'The code ignores error results by using let _ = ... pattern, which may hide failed operations and make debugging difficult.',
-        recommendation: 'Handle all errors properly or document explicitly why certain errors are safe to ignore.'
-      });
-    }
+    // Framework profile summaries (instructions, entry points, ...)
+    profiles.forEach(({ profile, summary }) => {
+      report += profile.renderSummary(summary);
+    });
     
-    // 6. Check for use of HashMap without considerations for DOS attacks
-    if (code.includes('HashMap') && !code.includes('with_capacity')) {
-      findings.low.push({
-        title: 'HashMap Without Capacity Hint',
-        description: 'Using HashMap without capacity hints could lead to performance degradation with many insertions and potential DOS vulnerabilities.',
-        recommendation: 'Use HashMap::with_capacity() to pre-allocate space when you know the approximate size in advance.'
-      });
+    // Per-function panic sources and the entry points that reach them
+    if (panicPaths) {
+      report += renderPanicReport(panicPaths);
     }
     
-    // Generate the report
-    let report = `# Static Analysis Report: ${fileName}\n\n`;
-    report += `*Generated on: ${new Date().toISOString()}*\n\n`;
-    
-    // Add findings to the report
-    report += `## Results Summary\n\n`;
-    report += `- High severity issues: ${findings.high.length}\n`;
-    report += `- Medium severity issues: ${findings.medium.length}\n`;
-    report += `- Low severity issues: ${findings.low.length}\n`;
-    report += `- Informational: ${findings.info.length}\n\n`;
-    
     // High severity findings
-    if (findings.high.length > 0) {
+    if (bySeverity.high.length > 0) {
       report += `## High Severity Issues\n\n`;
-      findings.high.forEach((finding, index) => {
-        report += `### ${index + 1}. ${finding.title}\n\n`;
-        report += `**Description:** ${finding.description}\n\n`;
-        report += `**Recommendation:** ${finding.recommendation}\n\n`;
+      bySeverity.high.forEach((finding, index) => {
+        report += formatRustFinding(finding, index);
       });
     }
     
     // Medium severity findings
-    if (findings.medium.length > 0) {
+    if (bySeverity.medium.length > 0) {
       report += `## Medium Severity Issues\n\n`;
-      findings.medium.forEach((finding, index) => {
-        report += `### ${index + 1}. ${finding.title}\n\n`;
-        report += `**Description:** ${finding.description}\n\n`;
-        report += `**Recommendation:** ${finding.recommendation}\n\n`;
+      bySeverity.medium.forEach((finding, index) => {
+        report += formatRustFinding(finding, index);
       });
     }
     
     // Low severity findings
-    if (findings.low.length > 0) {
+    if (bySeverity.low.length > 0) {
       report += `## Low Severity Issues\n\n`;
-      findings.low.forEach((finding, index) => {
-        report += `### ${index + 1}. ${finding.title}\n\n`;
-        report += `**Description:** ${finding.description}\n\n`;
-        report += `**Recommendation:** ${finding.recommendation}\n\n`;
+      bySeverity.low.forEach((finding, index) => {
+        report += formatRustFinding(finding, index);
       });
     }
     
     // Informational findings
-    if (findings.info.length > 0) {
+    if (bySeverity.info.length > 0) {
       report += `## Informational\n\n`;
-      findings.info.forEach((finding, index) => {
-        report += `### ${index + 1}. ${finding.title}\n\n`;
-        report += `**Description:** ${finding.description}\n\n`;
-        report += `**Recommendation:** ${finding.recommendation}\n\n`;
+      bySeverity.info.forEach((finding, index) => {
+        report += formatRustFinding(finding, index);
       });
     }
     
+    report += formatSuppressionReport(suppressions);
+    
     // Additional notes
     report += `## Additional Notes\n\n`;
     report += `This is an automated analysis and may contain false positives. Manual review is still recommended.\n`;
     report += `Consider using tools like 'cargo audit' and 'cargo deny' for dependency security checks.\n`;
     
-    // Save the report
-    const outputPath = path.join(outputDir, `${fileName}.rust-static-analysis.md`);
-    fs.writeFileSync(outputPath, report);
+    // Save the report in each requested format
+    const outputPaths = writeReports({
+      outputDir,
+      baseName: `${options.reportName || fileName}.rust-static-analysis`,
+      formats: options.format,
+      analyzer: 'rust-static',
+      filePath,
+      crate: options.crate,
+      findings,
+      suppressions,
+      renderMarkdown: () => report
+    });
     
-    spinner.succeed(`Rust static analysis complete! Saved to: ${outputPath}`);
-    return outputPath;
+    spinner.succeed(`Rust static analysis complete! Saved to: ${outputPaths.join(', ')}`);
+    return outputPaths[0];
   } catch (error) {
     spinner.fail('Static analysis failed');
     throw error;
   }
 }
 
+/**
+ * Find security issues in Rust code by walking its syntax tree.
+ * One finding is reported per occurrence, with its source location.
+ * @param {string} code - Rust source code
+ * @param {string} filePath - Path of the file, recorded in each finding's location
+ * @param {Object} options - `profile`: framework profiles to run (default "auto"); `callGraph`, `entryPoints`,
+ *   `semgrepMatches` and `root` as for staticAnalyzeRustContract
+ * @returns {Array} - Findings (see finding.js), most severe first
+ */
+function findRustIssues(code, filePath = null, options = {}) {
+  return analyzeRustCode(code, filePath, options).findings;
+}
+
+/**
+ * Run the generic checks and the framework profiles on Rust code
+ * @returns {Object} - `{ findings, profiles, panicPaths, entryPoints, semgrep }` where findings are Findings (see
+ *   finding.js), profiles are the results of the
+ *   profiles that ran, panicPaths is the result of analyzePanicPaths, entryPoints are the call graph ids reachability
+ *   was computed from (both null when the code does not parse) and semgrep counts the merged Semgrep matches
+ *   (`{ added, duplicates }`, null without `semgrepMatches`)
+ */
+function analyzeRustCode(code, filePath = null, options = {}) {
+  const findings = {
+    high: [],
+    medium: [],
+    low: [],
+    info: []
+  };
+  
+  let ast;
+  try {
+    ast = parse(code);
+  } catch (error) {
+    findings.medium.push({
+      ruleId: 'rust-parse-error',
+      title: 'Static Analysis Error',
+      description: `Error parsing Rust code: ${error.message}`,
+      recommendation: 'Review the code for syntax errors. Files that do not parse cannot be analyzed statically.',
+      location: error.line ? {
+        file: filePath,
+        startLine: error.line,
+        startColumn: error.column + 1,
+        endLine: error.line,
+        endColumn: error.column + 1,
+        function: null,
+        snippet: (code.split('\n')[error.line - 1] || '').trim()
+      } : null
+    });
+    return { findings: toFindings(findings, { analyzer: 'rust-static', file: filePath, root: options.root }), profiles: [], panicPaths: null, entryPoints: null, semgrep: null };
+  }
+  
+  const definitions = collectDefinitions(ast);
+  const locate = (node) => sourceLocation(code, node, { filePath, definitions });
+  
+  // 1. Check for unsafe blocks - potential memory safety issues
+  findUnsafeCode(ast).forEach(node => {
+    findings.high.push({
+      ruleId: 'rust-unsafe-code',
+      title: 'Unsafe Block Usage',
+      description: 'Unsafe blocks bypass Rust safety guarantees and may lead to undefined behavior, memory corruption, or security vulnerabilities.',
+      recommendation: 'Review all unsafe code carefully. Consider if safe alternatives exist. If unsafe is necessary, add detailed comments explaining why and what invariants are being maintained.',
+      location: locate(node)
+    });
+  });
+  
+  // Entry points and the functions they reach, in the crate's call graph when one is given. Without it the callers
+  // of the file's methods are unknown, so all of them count.
+  const callGraph = options.callGraph || buildCallGraph(ast, definitions);
+  const entryPoints = findEntryPoints(callGraph, options.entryPoints, { inherentMethods: !options.callGraph });
+  const reachable = reachableFrom(callGraph, entryPoints);
+  
+  // 2. Check for panic sources (unwrap/expect, indexing, panic!, ...) and the public entry points that reach them
+  const panicPaths = analyzePanicPaths(ast, code, definitions, {
+    callGraph,
+    entryPoints,
+    filePath: options.callGraph ? filePath : null
+  });
+  const reachedFrom = new Map();
+  panicPaths.functions.forEach(entry => entry.sources.forEach(source => reachedFrom.set(source.node, entry.reachedFrom)));
+  const reachability = (node) => (reachedFrom.has(node) ? ` Reachable from ${describeEntryPoints(reachedFrom.get(node))}.` : '');
+  
+  findMethodCalls(ast, 'unwrap').forEach(node => {
+    findings.medium.push({
+      ruleId: 'rust-unwrap',
+      title: 'Unwrap on Option/Result',
+      description: `Use of unwrap() can cause panics if the Option is None or the Result is an Err. This can lead to crashes in production.${reachability(node)}`,
+      recommendation: 'Use proper error handling with match, if let, or the ? operator instead of unwrap().',
+      location: locate(node)
+    });
+  });
+  
+  findMethodCalls(ast, 'expect').forEach(node => {
+    findings.low.push({
+      ruleId: 'rust-expect',
+      title: 'Expect on Option/Result',
+      description: `The expect() method will panic with a custom message if the value is None or Err. While better than unwrap(), it still causes crashes.${reachability(node)}`,
+      recommendation: 'Use proper error handling with match, if let, or the ? operator instead of expect().',
+      location: locate(node)
+    });
+  });
+  
+  // The other panic sources are reported when an outside caller can trigger them
+  panicPaths.functions.filter(entry => entry.reachedFrom.length > 0).forEach(({ fn, sources, reachedFrom: entryPoints }) => {
+    sources.forEach(source => {
+      const rule = PANIC_RULES[source.kind];
+      if (!rule) return;
+      findings[rule.severity(source)].push({
+        ruleId: rule.ruleId,
+        title: rule.title,
+        description: `\`${fn.qualifiedName}\` panics when ${source.detail}. Reachable from ${describeEntryPoints(entryPoints)}; a panic aborts the whole call, so a caller who controls the input can make it fail.`,
+        recommendation: rule.recommendation,
+        location: locate(source.node)
+      });
+    });
+  });
+  
+  // 3. Check for potential integer overflow/underflow on each arithmetic operation
+  findUncheckedArithmetic(definitions).forEach(({ node, operator, operandType, guard }) => {
+    const underflow = operator.startsWith('-');
+    const target = nodeText(code, node.left).trim();
+    let description = `Unchecked \`${operator}\` on the \`${operandType}\` value \`${target}\`. `;
+    description += underflow
+      ? 'Integer subtraction wraps around in release builds (and panics in debug builds) when the result would go below the type\'s minimum.'
+      : 'Integer arithmetic wraps around in release builds (and panics in debug builds) when the result exceeds the type\'s maximum.';
+    if (guard) {
+      description += ` A dominating guard \`${nodeText(code, guard.condition).trim()}\` (line ${guard.source.loc.start.line}) constrains the operands; confirm it bounds the result on every path.`;
+    } else {
+      description += ' No dominating guard constrains the operands.';
+    }
+    
+    const checkedMethod = { '+': 'add', '-': 'sub', '*': 'mul' }[operator[0]];
+    findings[guard ? 'low' : 'medium'].push({
+      ruleId: underflow ? 'rust-integer-underflow' : 'rust-integer-overflow',
+      title: underflow ? 'Potential Integer Underflow' : 'Potential Integer Overflow',
+      description,
+      recommendation: `Use checked_${checkedMethod}(), saturating_${checkedMethod}(), or wrapping_${checkedMethod}() based on your requirements to handle ${underflow ? 'underflow' : 'overflow'} cases explicitly.`,
+      guarded: !!guard,
+      location: locate(node)
+    });
+  });
+  
+  // 4. Check that privileged state changes are guarded by a caller-identity check on every path
+  findUnauthorizedStateChanges(definitions).forEach(({ fn, writes, debitedAccounts }) => {
+    const writeList = writes
+      .map(write => `\`${write.field}\`${write.key ? ` (${write.kind} for \`${write.key}\`)` : ''} at line ${write.node.loc.start.line}`)
+      .join(', ');
+    let description = `\`${fn.qualifiedName}\` takes \`&mut self\` and writes privileged state: ${writeList}. `;
+    description += 'No comparison between the caller and an owner/authority field dominates these writes';
+    if (debitedAccounts.length > 0) {
+      description += `, and the debited account (${debitedAccounts.map(key => `\`${key}\``).join(', ')}) is an arbitrary parameter rather than the caller's own identity`;
+    }
+    description += ', so any caller can perform this state change.';
+    
+    findings.high.push({
+      ruleId: 'rust-missing-authorization',
+      title: 'Missing Authorization on Privileged State Change',
+      description,
+      recommendation: 'Check the caller\'s identity before mutating privileged state, e.g. `if caller != self.owner { return Err(...) }`, or restrict debits to the caller\'s own account.',
+      location: locate(signatureOf(fn.node))
+    });
+  });
+  
+  // 5. Check for suspicious authorization patterns
+  findOwnerComparisons(ast).forEach(node => {
+    findings.info.push({
+      ruleId: 'rust-basic-owner-check',
+      title: 'Basic Authorization Check',
+      description: 'The code implements a basic ownership check, which may be sufficient for simple contracts but lacks flexibility for more complex authorization requirements.',
+      recommendation: 'Consider implementing a more sophisticated access control system for complex applications, such as role-based access control or multi-signature authorization.',
+      location: locate(node)
+    });
+  });
+  
+  // 6. Check for error swallowing (ignoring errors with let _ = ...)
+  findIgnoredResults(ast).forEach(node => {
+    findings.low.push({
+      ruleId: 'rust-ignored-result',
+      title: 'Ignored Error Results',
+      description: 'The code ignores error results by using let _ = ... pattern, which may hide failed operations and make debugging difficult.',
+      recommendation: 'Handle all errors properly or document explicitly why certain errors are safe to ignore.',
+      location: locate(node)
+    });
+  });
+  
+  // 7. Check for use of HashMap without considerations for DOS attacks
+  findHashMapsWithoutCapacity(ast).forEach(node => {
+    findings.low.push({
+      ruleId: 'rust-hashmap-without-capacity',
+      title: 'HashMap Without Capacity Hint',
+      description: 'Using HashMap without capacity hints could lead to performance degradation with many insertions and potential DOS vulnerabilities.',
+      recommendation: 'Use HashMap::with_capacity() to pre-allocate space when you know the approximate size in advance.',
+      location: locate(node)
+    });
+  });
+  
+  // 8. Framework-specific checks (Anchor, ...) for the profiles detected in or selected for this file
+  const profiles = runProfiles({ ast, code, filePath, definitions, locate }, options.profile);
+  profiles.forEach(result => {
+    Object.keys(findings).forEach(severity => {
+      findings[severity].push(...(result.findings[severity] || []).map(finding => ({ analyzer: `rust-static:${result.profile.name}`, ...finding })));
+    });
+  });
+  // A profile's own caller check replaces the generic authorization finding on the functions it reports
+  const profileAuthorized = new Set();
+  profiles.forEach(({ profile, findings: profileFindings }) => {
+    Object.values(profileFindings).flat()
+      .filter(finding => (profile.authorizationRules || []).includes(finding.ruleId) && finding.location && finding.location.function)
+      .forEach(finding => profileAuthorized.add(finding.location.function));
+  });
+  Object.keys(findings).forEach(severity => {
+    findings[severity] = findings[severity].filter(finding => finding.ruleId !== 'rust-missing-authorization' || !profileAuthorized.has(finding.location.function));
+  });
+  
+  // 9. Semgrep matches the checks above did not already report
+  let semgrep = null;
+  if (options.semgrepMatches) {
+    semgrep = mergeSemgrepFindings(findings, options.semgrepMatches.map(match => semgrepFinding(match, locate({
+      loc: {
+        start: { line: match.start.line, column: match.start.col - 1 },
+        end: { line: match.end.line, column: match.end.col - 1 }
+      },
+      range: [match.start.offset, match.end.offset]
+    }))));
+  }
+  
+  // 10. Issues in code no entry point reaches rank one level lower. Authorization findings keep their severity: the
+  // entry point detection can miss how a function is exposed, and a missing caller check is not made safe by that.
+  const authorizationRules = new Set(['rust-missing-authorization']);
+  profiles.forEach(({ profile }) => (profile.authorizationRules || []).forEach(ruleId => authorizationRules.add(ruleId)));
+  if (entryPoints.length > 0) {
+    const unreachableFunction = (finding) => {
+      if (!finding.location || authorizationRules.has(finding.ruleId)) return null;
+      const node = findFunctionAt(callGraph, options.callGraph ? filePath : null, finding.location.startLine);
+      return node && !reachable.has(node.id) ? node.id : null;
+    };
+    // Collect first so a finding is only lowered once
+    const lowered = SEVERITY_LEVELS.slice(0, -1).map(severity => findings[severity].filter(unreachableFunction));
+    lowered.forEach((moved, index) => {
+      const severity = SEVERITY_LEVELS[index];
+      const lower = SEVERITY_LEVELS[index + 1];
+      findings[severity] = findings[severity].filter(finding => !moved.includes(finding));
+      moved.forEach(finding => {
+        findings[lower].push({
+          ...finding,
+          description: `${finding.description} \`${unreachableFunction(finding)}\` is not reachable from any entry point, so this was ranked ${lower} instead of ${severity}.`,
+          reachable: false
+        });
+      });
+    });
+  }
+  
+  return { findings: toFindings(findings, { analyzer: 'rust-static', file: filePath, root: options.root }), profiles, panicPaths, entryPoints, semgrep };
+}
+
+/**
+ * Format a single finding, with its location and source excerpt, for the Markdown report
+ */
+function formatRustFinding(finding, index) {
+  let output = `### ${index + 1}. ${finding.title}\n\n`;
+  
+  const location = finding.location;
+  if (location.startLine) {
+    const file = location.file ? path.basename(location.file) : 'source';
+    const span = location.endLine !== location.startLine
+      ? `${location.startLine}:${location.startColumn}-${location.endLine}:${location.endColumn}`
+      : `${location.startLine}:${location.startColumn}-${location.endColumn}`;
+    output += `**Location:** \`${file}:${span}\``;
+    if (location.function) {
+      output += ` in \`${location.function}\``;
+    }
+    output += `\n\n`;
+    if (location.snippet) {
+      output += `\`\`\`rust\n${location.snippet}\n\`\`\`\n\n`;
+    }
+  }
+  
+  output += `**Description:** ${finding.description}\n\n`;
+  output += `**Recommendation:** ${finding.recommendation}\n\n`;
+  output += `${formatFindingMetadata(finding)}\n\n`;
+  
+  return output;
+}
+
+/**
+ * Find unsafe blocks, functions, impls and traits
+ */
+function findUnsafeCode(ast) {
+  const nodes = [];
+  
+  visit(ast, {
+    Block: function(node) {
+      if (node.unsafe) nodes.push(node);
+    },
+    Fn: function(node) {
+      if (node.unsafe) nodes.push(node);
+    },
+    Impl: function(node) {
+      if (node.unsafe) nodes.push(node);
+    },
+    Trait: function(node) {
+      if (node.unsafe) nodes.push(node);
+    }
+  });
+  
+  return nodes;
+}
+
+/**
+ * Find calls of a method by name, e.g. `.unwrap()` or `.expect("...")`
+ */
+function findMethodCalls(ast, methodName) {
+  const nodes = [];
+  
+  visit(ast, {
+    MethodCall: function(node) {
+      if (node.method === methodName) nodes.push(node);
+    }
+  });
+  
+  return nodes;
+}
+
+const PANIC_RULES = {
+  index: {
+    ruleId: 'rust-index-panic',
+    title: 'Indexing Can Panic',
+    severity: () => 'low',
+    recommendation: 'Use `get()` (or `get_mut()`) and handle the `None` case, or check the index against the length first.'
+  },
+  slice: {
+    ruleId: 'rust-slice-panic',
+    title: 'Slice Range Can Panic',
+    severity: () => 'low',
+    recommendation: 'Use `get(start..end)` and handle the `None` case, or check both bounds against the length first.'
+  },
+  macro: {
+    ruleId: 'rust-explicit-panic',
+    title: 'Reachable Panic Macro',
+    severity: (source) => (['todo', 'unimplemented'].includes(source.node.name) ? 'medium' : 'low'),
+    recommendation: 'Return an error instead of panicking, and finish any `todo!`/`unimplemented!` code paths before deployment.'
+  },
+  assert: {
+    ruleId: 'rust-assert-panic',
+    title: 'Assertion Aborts the Call',
+    severity: () => 'info',
+    recommendation: 'Make sure the assertion only fails on invalid input, or return a descriptive error instead.'
+  },
+  division: {
+    ruleId: 'rust-division-by-zero',
+    title: 'Division by a Possibly Zero Divisor',
+    severity: () => 'medium',
+    recommendation: 'Check the divisor for zero first, or use `checked_div()`/`checked_rem()` and handle the `None` case.'
+  }
+};
+
+const ARITHMETIC_OPERATORS = new Set(['+', '-', '*', '+=', '-=', '*=']);
+const COMPARISON_OPERATORS = new Set(['<', '<=', '>', '>=', '==', '!=']);
+// The ordering that holds when a comparison does not, e.g. `!(a < b)` is `a >= b`
+const NEGATED_ORDERING = { '<': '>=', '<=': '>', '>': '<=', '>=': '<' };
+// Counters of these types stepping by one cannot wrap in practice (usize is 32 bits on wasm32)
+const COUNTER_TYPES = new Set(['u64', 'u128', 'i64', 'i128']);
+
+/**
+ * Find `+`, `-`, `*` and their compound assignments on integer operands, except 64-bit counters incremented by
+ * one. Each result records the first dominating condition that bounds the operation, if any.
+ */
+function findUncheckedArithmetic(definitions) {
+  const results = [];
+  
+  for (const fn of definitions.functions) {
+    // Locals bound to a value, e.g. `let from_balance = self.balances.get(from)`, stand for what they read
+    const aliases = new Map();
+    walkFunction(fn, definitions, (node, scope, ancestors) => {
+      if (node.type === 'Let' && node.pattern.type === 'IdentPat' && node.init) {
+        aliases.set(node.pattern.name, valueKeys(node.init));
+        return;
+      }
+      if ((node.type !== 'Binary' && node.type !== 'CompoundAssign') || !ARITHMETIC_OPERATORS.has(node.operator)) return;
+      if (node.left.type === 'Literal' && node.right.type === 'Literal') return;
+      
+      const leftType = inferType(node.left, scope);
+      const rightType = inferType(node.right, scope);
+      const operandType = [leftType, rightType].find(type => isIntegerType(type) && type !== '{integer}');
+      if (!operandType) return;
+      if (isUnitIncrement(node) && COUNTER_TYPES.has(operandType.replace(/^&(mut )?/, ''))) return;
+      
+      const guard = dominatingConditions(ancestors, node).find(entry => boundsOperation(entry, node, aliases));
+      results.push({ node, operator: node.operator, operandType: operandType.replace(/^&(mut )?/, ''), guard: guard || null });
+    });
+  }
+  
+  return results;
+}
+
+/**
+ * True for `x += 1`, `x + 1` and `1 + x`
+ */
+function isUnitIncrement(node) {
+  const isOne = (side) => side.type === 'Literal' && side.kind === 'int' && side.value === '1';
+  if (node.operator === '+=') return isOne(node.right);
+  return node.operator === '+' && (isOne(node.left) || isOne(node.right));
+}
+
+/**
+ * Decide whether a dominating condition constrains an arithmetic operation. Subtraction is bounded by an
+ * ordering between its minuend and subtrahend that rules out underflow where execution continues (e.g. the early
+ * exit `if balance < amount { return }` before `balance -= amount`); addition and multiplication need a
+ * comparison that involves every non-constant operand (e.g. `supply + amount > MAX_SUPPLY`).
+ * @param {Object} entry - `{ condition, holds }` from dominatingConditions
+ * @param {Object} operation - Binary or CompoundAssign node
+ * @param {Map} aliases - Local names to the value keys they were bound from
+ */
+function boundsOperation(entry, operation, aliases) {
+  const leftKeys = valueKeys(operation.left);
+  const rightKeys = valueKeys(operation.right);
+  
+  if (operation.operator.startsWith('-')) {
+    if (leftKeys.size === 0) return false;
+    const covers = (side, keys) => {
+      const mentioned = new Set();
+      valueKeys(side).forEach(key => [key, ...(aliases.get(key) || [])].forEach(value => mentioned.add(value)));
+      return [...keys].every(key => mentioned.has(key));
+    };
+    const isSubtrahend = (side) => (rightKeys.size > 0 ? covers(side, rightKeys) : side.type === 'Literal');
+    return impliedFacts(entry.condition, entry.holds).some(({ node, positive }) => {
+      if (node.type !== 'Binary' || !NEGATED_ORDERING[node.operator]) return false;
+      const operator = positive ? node.operator : NEGATED_ORDERING[node.operator];
+      // minuend >= subtrahend, written either way round
+      if (covers(node.left, leftKeys) && isSubtrahend(node.right)) return operator === '>=' || operator === '>';
+      if (isSubtrahend(node.left) && covers(node.right, leftKeys)) return operator === '<=' || operator === '<';
+      return false;
+    });
+  }
+  
+  const comparisons = [];
+  visit(entry.condition, {
+    Binary: function(node) {
+      if (COMPARISON_OPERATORS.has(node.operator)) comparisons.push(node);
+    }
+  });
+  const required = [leftKeys, rightKeys].filter(keys => keys.size > 0);
+  if (comparisons.length === 0 || required.length === 0) return false;
+  
+  return comparisons.some(comparison => {
+    const mentioned = new Set([...valueKeys(comparison.left), ...valueKeys(comparison.right)]);
+    return required.every(keys => [...keys].some(key => mentioned.has(key)));
+  });
+}
+
+/**
+ * Collect the variables and `self` fields an expression reads, e.g. `*from_balance` -> {from_balance}
+ */
+function valueKeys(expr) {
+  const keys = new Set();
+  visit(expr, {
+    PathExpr: function(node) {
+      if (node.path.segments.length === 1 && node.name !== 'self') keys.add(node.name);
+    },
+    FieldAccess: function(node) {
+      if (isSelf(node.object)) {
+        keys.add(`self.${node.field}`);
+        return false;
+      }
+    }
+  });
+  return keys;
+}
+
+const IDENTITY_NAME = /^(caller|sender|signer|msg_sender|origin|predecessor|user|payer)$/;
+const MUTATING_METHODS = new Set(['insert', 'remove', 'entry', 'push', 'pop', 'clear', 'extend', 'retain', 'get_mut', 'append', 'truncate', 'drain', 'swap_remove', 'set', 'save', 'update']);
+const KEYED_METHODS = new Set(['insert', 'remove', 'entry', 'get_mut']);
+// Conversions that keep a value's identity, e.g. `caller.clone()` or `sender.to_string()`
+const IDENTITY_CONVERSIONS = new Set(['clone', 'to_string', 'to_owned', 'into', 'as_ref', 'as_str']);
+
+/**
+ * For every `&mut self` method, find writes to privileged fields and check that each one is dominated by a
+ * comparison between the caller's identity and an owner/authority field, or by a call to a helper that makes
+ * that check (e.g. `self.ensure_owner()?`). Writes keyed by the caller's own identity (e.g. `transfer(sender, ...)`
+ * debiting `sender`) are self-authorizing; in methods that make them, only unkeyed writes to role fields such
+ * as `owner` still need a check.
+ */
+function findUnauthorizedStateChanges(definitions) {
+  const results = [];
+  const guards = new Map();
+  
+  for (const fn of definitions.functions) {
+    const selfParam = fn.node.selfParam;
+    if (!selfParam || !selfParam.reference || !selfParam.mutable || !fn.node.body) continue;
+    // NEAR `#[private]` methods can only be called by the contract itself
+    if ((fn.node.attrs || []).some(attr => attr.path === 'private')) continue;
+    
+    const caller = callerIdentity(fn, definitions);
+    const writes = [];
+    
+    walkFunction(fn, definitions, (node, scope, ancestors) => {
+      const write = describeStateWrite(node);
+      if (!write || !PRIVILEGED_FIELD.test(write.field)) return;
+      // `self.balances.entry(k)` on the left of `-=` is part of that write, not a separate one
+      const partOfAssignment = ancestors.some(ancestor => (ancestor.type === 'Assign' || ancestor.type === 'CompoundAssign') &&
+        ancestor.left.range[0] <= node.range[0] && node.range[1] <= ancestor.left.range[1]);
+      if (partOfAssignment) return;
+      const authorized = dominatingConditions(ancestors, node).some(entry => checksCallerIdentity(entry.condition, entry.holds, caller, definitions, guards)) ||
+        precedingStatements(ancestors, node).some(stmt => callsCallerGuard(stmt, definitions, guards));
+      writes.push({ ...write, node, authorized, callerKeyed: write.keyNames.some(name => caller.names.has(name)) });
+    });
+    
+    const selfKeyed = writes.some(write => write.callerKeyed);
+    const unauthorized = writes.filter(write => {
+      if (write.authorized || write.callerKeyed) return false;
+      if (write.kind === 'debit') return true;
+      return selfKeyed ? !write.key && ROLE_FIELD.test(write.field) : true;
+    });
+    if (unauthorized.length === 0) continue;
+    
+    results.push({
+      fn,
+      writes: unauthorized,
+      debitedAccounts: [...new Set(unauthorized.filter(write => write.kind === 'debit').map(write => write.key))]
+    });
+  }
+  
+  return results;
+}
+
+/**
+ * Describe a write to a `self` field: assignments, compound assignments and mutating method calls.
+ * Map writes record the key and whether the entry is debited, credited or set.
+ */
+function describeStateWrite(node) {
+  let target = null;
+  if (node.type === 'Assign' || node.type === 'CompoundAssign') {
+    target = node.left;
+  } else if (node.type === 'MethodCall' && MUTATING_METHODS.has(node.method)) {
+    // Only calls made directly on a field count, e.g. `self.balances.insert(k, v)`
+    if (node.receiver.type !== 'FieldAccess' || !isSelf(node.receiver.object)) return null;
+    target = node;
+  } else {
+    return null;
+  }
+  
+  const field = selfFieldRoot(target);
+  if (!field) return null;
+  
+  // Find the map key, e.g. `from` in `self.balances.entry(from.to_string())`
+  let keyExpr = null;
+  visit(target, {
+    MethodCall: function(call) {
+      if (!keyExpr && KEYED_METHODS.has(call.method) && call.args.length > 0) keyExpr = call.args[0];
+    }
+  });
+  const keyNames = keyExpr ? [...valueKeys(keyExpr)] : [];
+  
+  let kind = 'set';
+  if (keyExpr && ((node.type === 'CompoundAssign' && node.operator === '-=') || (node.type === 'MethodCall' && node.method === 'remove'))) {
+    kind = 'debit';
+  } else if (keyExpr && node.type === 'CompoundAssign' && node.operator === '+=') {
+    kind = 'credit';
+  }
+  
+  return { field, kind, key: keyNames[0] || null, keyNames };
+}
+
+/**
+ * The caller's identity in a function: its identity parameters (`caller`, `sender`, ...), the locals bound from
+ * them or from a caller expression (see isCallerExpression), and the `Signer` accounts of an Anchor `Context`
+ * @returns {Object} - `{ names, signers }` sets of local names and `ctx.accounts` fields
+ */
+function callerIdentity(fn, definitions) {
+  const caller = {
+    names: new Set(fn.node.params.map(param => param.name).filter(name => name && IDENTITY_NAME.test(name))),
+    signers: signerAccounts(fn, definitions)
+  };
+  walkFunction(fn, definitions, (node) => {
+    if (node.type === 'Let' && node.pattern.type === 'IdentPat' && node.init && isCallerExpression(node.init, caller)) {
+      caller.names.add(node.pattern.name);
+    }
+  });
+  return caller;
+}
+
+/**
+ * Fields of the accounts struct of a `Context<T>` parameter that are `Signer`s
+ */
+function signerAccounts(fn, definitions) {
+  const signers = new Set();
+  fn.node.params.forEach(param => {
+    const match = param.paramType && /^Context\s*<\s*(?:'\w+\s*,\s*)*(\w+)/.exec(param.paramType.text);
+    const struct = match && definitions.structs.get(match[1]);
+    if (!struct) return;
+    struct.fields.forEach((type, name) => {
+      if (type && /^Signer\b/.test(type.text)) signers.add(name);
+    });
+  });
+  return signers;
+}
+
+/**
+ * True when an expression is the caller's identity: an identity local, `env::predecessor_account_id()` (NEAR),
+ * `self.env().caller()` (ink!), `info.sender` (CosmWasm) or `ctx.accounts.<signer>.key()` (Anchor)
+ */
+function isCallerExpression(expr, caller) {
+  let node = expr;
+  for (;;) {
+    if (!node) return false;
+    if (node.type === 'Paren') node = node.expr;
+    else if ((node.type === 'Ref' || node.type === 'Unary') && node.operator !== '!' && node.operator !== '-') node = node.argument;
+    else if (node.type === 'MethodCall' && IDENTITY_CONVERSIONS.has(node.method) && node.args.length === 0) node = node.receiver;
+    else break;
+  }
+  switch (node.type) {
+    case 'PathExpr': {
+      const segments = node.path.segments.map(segment => segment.name);
+      return segments.length === 1 && caller.names.has(segments[0]);
+    }
+    case 'Call': {
+      if (node.callee.type !== 'PathExpr') return false;
+      const segments = node.callee.path.segments.map(segment => segment.name);
+      return segments[segments.length - 1] === 'predecessor_account_id';
+    }
+    case 'MethodCall':
+      if (node.method === 'caller' && node.receiver.type === 'MethodCall' && node.receiver.method === 'env') return true;
+      return node.method === 'key' && node.receiver.type === 'FieldAccess' && caller.signers.has(node.receiver.field) &&
+        node.receiver.object.type === 'FieldAccess' && node.receiver.object.field === 'accounts';
+    case 'FieldAccess':
+      return node.field === 'sender' && node.object.type === 'PathExpr' && node.object.name === 'info';
+    default:
+      return false;
+  }
+}
+
+/**
+ * True when a condition that is known to hold (or known not to hold) establishes that the caller
+ * equals an owner/authority field, e.g. the early exit `if caller != self.owner { return Err(..) }`, or that a
+ * helper returning that comparison is true, e.g. `require!(self.is_owner())`.
+ */
+function checksCallerIdentity(condition, holds, caller, definitions, guards) {
+  return impliedFacts(condition, holds).some(({ node, positive }) => {
+    if (node.type === 'MethodCall' && positive) {
+      return callerGuardKind(selfMethod(node, definitions), definitions, guards) === 'predicate';
+    }
+    if (node.type !== 'Binary') return false;
+    const equality = (node.operator === '==' && positive) || (node.operator === '!=' && !positive);
+    if (!equality) return false;
+    const leftField = selfFieldRoot(node.left);
+    const rightField = selfFieldRoot(node.right);
+    return (!!leftField && ROLE_FIELD.test(leftField) && isCallerExpression(node.right, caller)) ||
+      (!!rightField && ROLE_FIELD.test(rightField) && isCallerExpression(node.left, caller));
+  });
+}
+
+/**
+ * True when a statement calls a helper that checks the caller and fails otherwise, e.g. `self.ensure_owner()?;`
+ */
+function callsCallerGuard(stmt, definitions, guards) {
+  let node = stmt.type === 'ExprStmt' ? stmt.expr : null;
+  if (node && node.type === 'Try') node = node.argument;
+  if (!node || node.type !== 'MethodCall') return false;
+  return callerGuardKind(selfMethod(node, definitions), definitions, guards) === 'guard';
+}
+
+/**
+ * The method of this impl a `self.name(..)` call resolves to
+ */
+function selfMethod(call, definitions) {
+  if (!isSelf(call.receiver)) return null;
+  return definitions.functions.find(fn => fn.name === call.method && fn.node.selfParam && fn.node.body) || null;
+}
+
+/**
+ * How a helper method checks the caller: `guard` when it returns early (or asserts) unless the caller holds a
+ * role, `predicate` when it returns that comparison, null otherwise. Memoized in `guards`.
+ */
+function callerGuardKind(fn, definitions, guards) {
+  if (!fn) return null;
+  if (guards.has(fn)) return guards.get(fn);
+  guards.set(fn, null);
+  
+  const caller = callerIdentity(fn, definitions);
+  const stmts = fn.node.body.stmts;
+  let kind = null;
+  const guarded = stmts.some(stmt => {
+    const expr = stmt.type === 'ExprStmt' ? stmt.expr : null;
+    if (!expr) return false;
+    if (expr.type === 'If' && !expr.alternate && diverges(expr.consequent)) {
+      return checksCallerIdentity(expr.cond, false, caller, definitions, guards);
+    }
+    if (expr.type === 'Macro' && /^(assert|require|ensure)$/.test(expr.name) && expr.args && expr.args.length > 0) {
+      return checksCallerIdentity(expr.args[0], true, caller, definitions, guards);
+    }
+    if (expr.type === 'Macro' && /^(assert_eq|require_eq|require_keys_eq|ensure_eq)$/.test(expr.name) && expr.args && expr.args.length > 1) {
+      return checksCallerIdentity({ type: 'Binary', operator: '==', left: expr.args[0], right: expr.args[1] }, true, caller, definitions, guards);
+    }
+    return callsCallerGuard(stmt, definitions, guards);
+  });
+  if (guarded) {
+    kind = 'guard';
+  } else {
+    // A predicate such as `fn is_owner(&self) -> bool { self.env().caller() == self.owner }`
+    const tail = stmts.length > 0 && stmts[stmts.length - 1].type === 'ExprStmt' && !stmts[stmts.length - 1].semi
+      ? stmts[stmts.length - 1].expr
+      : null;
+    if (tail && checksCallerIdentity(tail, true, caller, definitions, guards)) kind = 'predicate';
+  }
+  guards.set(fn, kind);
+  return kind;
+}
+
+/**
+ * Break a condition into the atomic facts it implies: `a && b` holding implies both, `a || b` failing implies both fail
+ */
+function impliedFacts(condition, holds) {
+  if (!condition) return [];
+  if (condition.type === 'Paren') return impliedFacts(condition.expr, holds);
+  if (condition.type === 'Unary' && condition.operator === '!') return impliedFacts(condition.argument, !holds);
+  if (condition.type === 'Binary' && condition.operator === '&&' && holds) {
+    return [...impliedFacts(condition.left, true), ...impliedFacts(condition.right, true)];
+  }
+  if (condition.type === 'Binary' && condition.operator === '||' && !holds) {
+    return [...impliedFacts(condition.left, false), ...impliedFacts(condition.right, false)];
+  }
+  return [{ node: condition, positive: holds }];
+}
+
+/**
+ * Find `if` conditions that compare a value against an ownership field such as `self.owner`
+ */
+function findOwnerComparisons(ast) {
+  const nodes = [];
+  
+  visit(ast, {
+    If: function(node) {
+      visit(node.cond, {
+        Binary: function(comparison) {
+          if (comparison.operator !== '!=' && comparison.operator !== '==') return;
+          const field = selfFieldRoot(comparison.left) || selfFieldRoot(comparison.right);
+          if (field && /^(owner|admin|authority)$/.test(field)) nodes.push(comparison);
+        }
+      });
+    }
+  });
+  
+  return nodes;
+}
+
+/**
+ * Find `let _ = call(...)` statements that discard a call's result
+ */
+function findIgnoredResults(ast) {
+  const nodes = [];
+  
+  visit(ast, {
+    Let: function(node) {
+      if (node.pattern.type === 'WildPat' && node.init &&
+          (node.init.type === 'Call' || node.init.type === 'MethodCall')) {
+        nodes.push(node);
+      }
+    }
+  });
+  
+  return nodes;
+}
+
+/**
+ * Find HashMap constructions that do not pre-allocate capacity
+ */
+function findHashMapsWithoutCapacity(ast) {
+  const nodes = [];
+  
+  visit(ast, {
+    Call: function(node) {
+      if (node.callee.type !== 'PathExpr') return;
+      const segments = node.callee.path.segments.map(segment => segment.name);
+      if (segments.length >= 2 && segments[segments.length - 2] === 'HashMap' &&
+          (segments[segments.length - 1] === 'new' || segments[segments.length - 1] === 'default')) {
+        nodes.push(node);
+      }
+    }
+  });
+  
+  return nodes;
+}
+
 module.exports = {
-  staticAnalyzeRustContract
+  staticAnalyzeRustContract,
+  findRustIssues,
+  analyzeRustCode
 }; 
\ No newline at end of file
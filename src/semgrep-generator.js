@@ -1,18 +1,18 @@
 const fs = require('fs');
 const path = require('path');
-const Anthropic = require('@anthropic-ai/sdk');
 const ora = require('ora');
+const { complete, isLLMAvailable, unavailableReason } = require('./llm-provider');
 
-const anthropic = new Anthropic({
-  apiKey: process.env.ANTHROPIC_API_KEY,
-});
+// Version of the rule generation prompt, part of the response cache key
+const RULES_PROMPT_VERSION = 1;
 
 /**
  * Generates semgrep rules for the smart contract
  * @param {string} filePath - Path to the smart contract file
  * @param {string} outputDir - Directory to save semgrep rules
+ * @param {Object} options - `reportName`: prefix of the rule files (default: the file name, see reportName)
  */
-async function generateSemgrepRules(filePath, outputDir) {
+async function generateSemgrepRules(filePath, outputDir, options = {}) {
   const spinner = ora('Generating Semgrep rules...').start();
   
   try {
@@ -20,7 +20,7 @@ async function generateSemgrepRules(filePath, outputDir) {
     const contractCode = fs.readFileSync(filePath, 'utf8');
     const fileName = path.basename(filePath);
     
-    // Generate semgrep rules using Claude 3.7
+    // Generate semgrep rules using the configured LLM
     const semgrepRules = await generateRules(contractCode);
     
     // Create output directory for rules
@@ -32,7 +32,7 @@ async function generateSemgrepRules(filePath, outputDir) {
     // Save rules to YAML files
     for (let i = 0; i < semgrepRules.length; i++) {
       const rule = semgrepRules[i];
-      const ruleFileName = `${fileName}-rule-${i+1}.yaml`;
+      const ruleFileName = `${options.reportName || fileName}-rule-${i+1}.yaml`;
       const rulePath = path.join(rulesDir, ruleFileName);
       fs.writeFileSync(rulePath, rule);
     }
@@ -52,13 +52,13 @@ async function generateSemgrepRules(filePath, outputDir) {
 }
 
 /**
- * Generates semgrep rules using Claude 3.7
+ * Generates semgrep rules using the configured LLM
  * @param {string} contractCode - Source code of the contract
  * @returns {Array<string>} - Array of semgrep rules in YAML format
  */
 async function generateRules(contractCode) {
   try {
-    // Prompt for Claude 3.7
+    // Prompt for the configured LLM
     const prompt = `
 You are an expert in Solidity security and Semgrep rule creation. Analyze this smart contract and generate Semgrep rules to detect security vulnerabilities and code quality issues:
 
@@ -82,34 +82,16 @@ For each rule, explain:
 Return each rule as a separate YAML file, properly formatted for immediate use with Semgrep.
 `;
 
-    // Check if API key is available
-    if (!process.env.ANTHROPIC_API_KEY) {
-      console.warn('Warning: ANTHROPIC_API_KEY not found in environment variables');
+    // Check if a model provider is available
+    if (!isLLMAvailable()) {
+      console.warn(`Warning: ${unavailableReason()}`);
       return [];
     }
 
     try {
-      // Call Claude 3.7 API
-      const response = await anthropic.messages.create({
-        model: "claude-3-sonnet-20240229",
-        max_tokens: 4000,
-        messages: [
-          { 
-            role: "user", 
-            content: prompt 
-          }
-        ],
-        system: "You are an expert security rule creator with deep knowledge of Semgrep pattern matching for smart contracts."
-      });
-
-      // Validate response format
-      if (!response || !response.content || !Array.isArray(response.content) || response.content.length === 0) {
-        console.error('Invalid response format from Anthropic API:', response);
-        return [];
-      }
+      const content = await complete(prompt, "You are an expert security rule creator with deep knowledge of Semgrep pattern matching for smart contracts.", { template: 'solidity-semgrep', version: RULES_PROMPT_VERSION, source: contractCode });
 
       // Extract YAML rules from the response
-      const content = response.content[0].text;
       const yamlBlocks = extractYamlBlocks(content);
       
       return yamlBlocks;
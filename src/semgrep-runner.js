@@ -0,0 +1,215 @@
+const fs = require('fs');
+const path = require('path');
+const { spawnSync } = require('child_process');
+const ora = require('ora');
+const yaml = require('js-yaml');
+const { findSemgrep } = require('./semgrep-validator');
+const { loadRustRulePack } = require('./rust-semgrep-pack');
+
+/**
+ * Running Semgrep on audited code and turning its matches into Winston findings. Semgrep findings are merged
+ * into the static analysis results unless the static analyzer already reported the same issue at that spot.
+ */
+
+const SEMGREP_SEVERITIES = {
+  ERROR: 'high', CRITICAL: 'high', HIGH: 'high',
+  WARNING: 'medium', MEDIUM: 'medium',
+  INFO: 'low', LOW: 'low',
+  INVENTORY: 'info', EXPERIMENT: 'info'
+};
+
+// Issue families, matched against rule ids, used to recognize the same issue reported by both tools
+const ISSUE_FAMILIES = [
+  /overflow|underflow|arithmetic/,
+  /unwrap|expect|panic/,
+  /unsafe/,
+  /ignored|unused-result|unchecked-(result|return)/,
+  /signer|authori[sz]|access-control|permission|owner-check/,
+  /iterat|unbounded|loop/,
+  /cast|truncat/,
+  /reentran/
+];
+
+/**
+ * Rule files to run on Rust code: the curated pack and the valid model-generated rules in an output directory
+ * (invalid rules are kept in `semgrep-rust-rules/invalid/` and not run)
+ * @param {string} outputDir - Output directory of the audit (or crate)
+ * @returns {string[]} - Paths of rule files
+ */
+function collectRustRuleFiles(outputDir) {
+  const packFiles = loadRustRulePack().rules.map(({ file }) => file);
+  const generatedDir = path.join(outputDir, 'semgrep-rust-rules');
+  const generatedFiles = fs.existsSync(generatedDir)
+    ? fs.readdirSync(generatedDir).filter(name => name.endsWith('.yml')).sort().map(name => path.join(generatedDir, name))
+    : [];
+  return [...packFiles, ...generatedFiles];
+}
+
+/**
+ * Run Semgrep with a set of rule files on some source files
+ * @param {string[]} targets - Files to scan
+ * @param {string[]} ruleFiles - Rule files
+ * @returns {Object|null} - `{ matches, errors }`, or null when no `semgrep` binary is installed. Matches are
+ *   `{ ruleId, severity, title, confidence, cwe, message, fix, file, start, end }` with Semgrep's `{ line, col, offset }`
+ *   positions
+ */
+function runSemgrep(targets, ruleFiles) {
+  if (!findSemgrep()) return null;
+  if (targets.length === 0 || ruleFiles.length === 0) return { matches: [], errors: [] };
+
+  const rules = new Map();
+  ruleFiles.forEach(file => {
+    try {
+      (yaml.load(fs.readFileSync(file, 'utf8')).rules || []).forEach(rule => rules.set(rule.id, rule));
+    } catch (error) {
+      // Semgrep reports unreadable rule files itself
+    }
+  });
+
+  let output = invokeSemgrep(targets, ruleFiles);
+  // One broken rule makes Semgrep reject the whole configuration; run the rules one by one to keep the others
+  if (!output.results && ruleFiles.length > 1) {
+    output = ruleFiles.map(file => ({ file, output: invokeSemgrep(targets, [file]) })).reduce((merged, { file, output: single }) => ({
+      results: merged.results.concat(single.results || []),
+      errors: merged.errors.concat(single.results ? single.errors : single.errors.map(error => `${path.basename(file)}: ${error}`))
+    }), { results: [], errors: [] });
+  }
+
+  const matches = (output.results || []).map(result => {
+    // Semgrep prefixes the ids of local rules with their directory, e.g. `output.semgrep-rust-rules.rust-unsafe`
+    const ruleId = [...rules.keys()].find(id => result.check_id === id || result.check_id.endsWith(`.${id}`)) || result.check_id;
+    const rule = rules.get(ruleId) || {};
+    return {
+      ruleId,
+      severity: (result.extra && result.extra.severity) || rule.severity,
+      title: rule.metadata && rule.metadata.title,
+      confidence: rule.metadata && typeof rule.metadata.confidence === 'string' ? rule.metadata.confidence.toLowerCase() : null,
+      cwe: rule.metadata && rule.metadata.cwe ? (String([].concat(rule.metadata.cwe)[0]).match(/CWE-\d+/) || [null])[0] : null,
+      message: ((result.extra && result.extra.message) || rule.message || '').trim(),
+      fix: typeof rule.fix === 'string' ? rule.fix : null,
+      file: result.path,
+      start: result.start,
+      end: result.end
+    };
+  });
+  return { matches, errors: output.errors };
+}
+
+function invokeSemgrep(targets, ruleFiles) {
+  const args = ['--json', '--metrics=off', '--disable-version-check', '--quiet'];
+  ruleFiles.forEach(file => args.push('--config', file));
+  const result = spawnSync(findSemgrep(), [...args, ...targets], { encoding: 'utf8', maxBuffer: 64 * 1024 * 1024 });
+
+  let output;
+  try {
+    output = JSON.parse(result.stdout);
+  } catch (error) {
+    return { results: null, errors: [(result.stderr || (result.error && result.error.message) || 'semgrep produced no JSON output').trim()] };
+  }
+  const errors = (output.errors || []).filter(error => error.level !== 'warn').map(error => error.message || error.type || 'unknown error');
+  // Rule errors abort the scan: Semgrep then returns no results at all
+  const fatal = errors.length > 0 && (output.errors || []).some(error => /Rule|Pattern|Config/i.test(error.type || ''));
+  return { results: fatal ? null : output.results || [], errors };
+}
+
+/**
+ * Run the curated and generated Rust rules on the files of one crate target (or a set of loose files)
+ * @param {string[]} files - Rust files to scan
+ * @param {string} outputDir - Output directory holding `semgrep-rust-rules/`
+ * @returns {Map|null} - Semgrep matches keyed by resolved file path, or null when Semgrep is not installed
+ */
+function runRustSemgrep(files, outputDir) {
+  const spinner = ora('Running semgrep on Rust code...').start();
+
+  if (!findSemgrep()) {
+    spinner.info('semgrep is not installed; skipping the semgrep run (install it or set SEMGREP_BIN)');
+    return null;
+  }
+
+  try {
+    const ruleFiles = collectRustRuleFiles(outputDir);
+    const { matches, errors } = runSemgrep(files, ruleFiles);
+    const byFile = new Map(files.map(file => [path.resolve(file), []]));
+    matches.forEach(match => {
+      const file = path.resolve(match.file);
+      if (!byFile.has(file)) byFile.set(file, []);
+      byFile.get(file).push(match);
+    });
+
+    const summary = `semgrep ran ${ruleFiles.length} rules on ${files.length} file(s): ${matches.length} match(es)`;
+    if (errors.length > 0) spinner.warn(`${summary}; ${errors.length} error(s): ${errors.slice(0, 3).join('; ')}`);
+    else spinner.succeed(summary);
+    return byFile;
+  } catch (error) {
+    spinner.fail('semgrep run failed');
+    throw error;
+  }
+}
+
+/**
+ * Build a Winston finding from a Semgrep match
+ * @param {Object} match - Entry of runSemgrep's matches
+ * @param {Object} location - Location record of the matched code (see sourceLocation)
+ * @returns {Object} - `{ severity, finding }` where severity is the Winston severity bucket
+ */
+function semgrepFinding(match, location) {
+  return {
+    severity: SEMGREP_SEVERITIES[String(match.severity).toUpperCase()] || 'low',
+    finding: {
+      ruleId: `semgrep.${match.ruleId}`,
+      title: match.title || `Semgrep: ${match.ruleId}`,
+      description: match.message,
+      recommendation: match.fix
+        ? `Apply the rule's suggested fix: \`${match.fix.trim()}\`.`
+        : `Review the code matched by the Semgrep rule \`${match.ruleId}\`.`,
+      confidence: match.confidence,
+      category: { cwe: match.cwe },
+      analyzer: 'semgrep',
+      location
+    }
+  };
+}
+
+function issueFamily(ruleId) {
+  const id = ruleId.replace(/^semgrep\./, '');
+  const index = ISSUE_FAMILIES.findIndex(family => family.test(id));
+  return index === -1 ? id : index;
+}
+
+/**
+ * Add Semgrep findings to severity buckets, skipping those that duplicate a finding already there: same file,
+ * overlapping lines and the same kind of issue (e.g. `rust-integer-overflow` and `rust-unchecked-arithmetic`)
+ * @param {Object} findings - Severity buckets, modified in place
+ * @param {Array} semgrepFindings - Results of semgrepFinding
+ * @returns {Object} - `{ added, duplicates }` counts
+ */
+function mergeSemgrepFindings(findings, semgrepFindings) {
+  const existing = Object.values(findings).flat();
+  let added = 0;
+  let duplicates = 0;
+
+  semgrepFindings.forEach(({ severity, finding }) => {
+    const { location } = finding;
+    const duplicate = existing.some(other => other.location
+      && (!other.location.file || !location.file || path.resolve(other.location.file) === path.resolve(location.file))
+      && other.location.startLine <= location.endLine && location.startLine <= other.location.endLine
+      && issueFamily(other.ruleId) === issueFamily(finding.ruleId));
+    if (duplicate) {
+      duplicates++;
+      return;
+    }
+    findings[severity].push(finding);
+    existing.push(finding);
+    added++;
+  });
+
+  return { added, duplicates };
+}
+
+module.exports = {
+  collectRustRuleFiles,
+  runSemgrep,
+  runRustSemgrep,
+  semgrepFinding,
+  mergeSemgrepFindings
+};
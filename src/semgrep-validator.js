@@ -0,0 +1,337 @@
+const fs = require('fs');
+const path = require('path');
+const { spawnSync } = require('child_process');
+const yaml = require('js-yaml');
+
+/**
+ * Validation of generated Semgrep rules: a schema check against Semgrep's rule format, a run of each rule
+ * against the source it was generated from (when a `semgrep` binary is installed) and, for the rules Semgrep
+ * ran, an annotated test file per rule (`// ruleid:` / `// ok:`) for `semgrep --test`.
+ */
+
+const SEVERITIES = ['ERROR', 'WARNING', 'INFO', 'INVENTORY', 'EXPERIMENT', 'CRITICAL', 'HIGH', 'MEDIUM', 'LOW'];
+const TOP_LEVEL_OPERATORS = ['pattern', 'patterns', 'pattern-either', 'pattern-regex'];
+const TAINT_KEYS = ['pattern-sources', 'pattern-sinks', 'pattern-sanitizers', 'pattern-propagators'];
+const RULE_KEYS = new Set([
+  'id', 'message', 'languages', 'severity', 'metadata', 'fix', 'fix-regex', 'paths', 'options', 'mode',
+  'min-version', 'max-version', 'focus-metavariable', ...TOP_LEVEL_OPERATORS, ...TAINT_KEYS
+]);
+// Operators allowed in a `patterns` list; the first group is positive, the rest filter or constrain
+const POSITIVE_OPERATORS = ['pattern', 'pattern-inside', 'pattern-either', 'patterns', 'pattern-regex'];
+const PATTERN_OPERATORS = [
+  ...POSITIVE_OPERATORS, 'pattern-not', 'pattern-not-inside', 'pattern-not-regex', 'metavariable-regex',
+  'metavariable-pattern', 'metavariable-comparison', 'metavariable-type', 'focus-metavariable'
+];
+const OPERATOR_OPTIONS = ['label', 'requires', 'by-side-effect', 'exact', 'focus-metavariable'];
+// Template text copied from the prompt instead of a real pattern, e.g. `[The pattern to match problematic code]`
+const PLACEHOLDER = /^\s*\[[^\]]*\]\s*$/;
+
+/**
+ * Check a rule against Semgrep's rule format
+ * @param {Object} rule - One entry of a rule file's `rules` list
+ * @param {Object} options - `language`: language the rule must target, e.g. "rust"
+ * @returns {Object} - `{ errors, warnings }` (lists of messages; the rule is valid when errors is empty)
+ */
+function validateRuleSchema(rule, options = {}) {
+  const errors = [];
+  const warnings = [];
+
+  if (!rule || typeof rule !== 'object' || Array.isArray(rule)) {
+    return { errors: ['rule is not a mapping'], warnings };
+  }
+  if (typeof rule.id !== 'string' || !/^[A-Za-z0-9._-]+$/.test(rule.id)) {
+    errors.push('`id` must be a string of letters, digits, dots, dashes or underscores');
+  }
+  if (typeof rule.message !== 'string' || !rule.message.trim()) {
+    errors.push('`message` must be a non-empty string');
+  }
+  if (!Array.isArray(rule.languages) || rule.languages.length === 0 || rule.languages.some(language => typeof language !== 'string')) {
+    errors.push('`languages` must be a non-empty list of language names');
+  } else if (options.language && !rule.languages.map(language => language.toLowerCase()).includes(options.language)) {
+    errors.push(`\`languages\` does not include ${options.language}`);
+  }
+  if (!SEVERITIES.includes(rule.severity)) {
+    errors.push(rule.severity === undefined && rule.metadata && rule.metadata.severity
+      ? '`severity` must be a top-level key, not part of `metadata`'
+      : `\`severity\` must be one of ${SEVERITIES.slice(0, 3).join(', ')}`);
+  }
+  if (rule.fix !== undefined && typeof rule.fix !== 'string') {
+    errors.push('`fix` must be a string');
+  }
+
+  for (const key of Object.keys(rule)) {
+    if (RULE_KEYS.has(key)) continue;
+    errors.push(PATTERN_OPERATORS.includes(key)
+      ? `\`${key}\` is only allowed inside \`patterns\``
+      : `unknown key \`${key}\``);
+  }
+
+  if (rule.mode === 'taint') {
+    ['pattern-sources', 'pattern-sinks'].forEach(key => {
+      if (!Array.isArray(rule[key]) || rule[key].length === 0) errors.push(`taint rules need a non-empty \`${key}\` list`);
+      else rule[key].forEach((entry, index) => validateOperatorList([entry], `${key}[${index}]`, errors));
+    });
+  } else {
+    const operators = TOP_LEVEL_OPERATORS.filter(key => rule[key] !== undefined);
+    if (operators.length !== 1) {
+      errors.push(operators.length === 0
+        ? `the rule needs one of ${TOP_LEVEL_OPERATORS.map(key => `\`${key}\``).join(', ')}`
+        : `only one of ${operators.map(key => `\`${key}\``).join(', ')} may be used at the top level`);
+    } else {
+      validateOperator(operators[0], rule[operators[0]], operators[0], errors);
+    }
+  }
+
+  // Metavariables in the message should be bound by a pattern
+  const patternText = JSON.stringify(rule, (key, value) => (key === 'message' || key === 'metadata' ? undefined : value));
+  const unbound = [...new Set((rule.message || '').match(/\$[A-Z_][A-Z0-9_]*/g) || [])]
+    .filter(name => !patternText.includes(name));
+  if (unbound.length > 0) warnings.push(`message uses ${unbound.join(', ')}, which no pattern binds`);
+
+  return { errors, warnings };
+}
+
+function validateOperator(key, value, where, errors) {
+  switch (key) {
+    case 'pattern':
+    case 'pattern-not':
+    case 'pattern-inside':
+    case 'pattern-not-inside':
+      if (typeof value !== 'string' || !value.trim()) errors.push(`${where}: \`${key}\` must be a non-empty string`);
+      else if (PLACEHOLDER.test(value)) errors.push(`${where}: \`${key}\` is placeholder text, not a pattern`);
+      break;
+    case 'pattern-regex':
+    case 'pattern-not-regex':
+      if (typeof value !== 'string' || !value) {
+        errors.push(`${where}: \`${key}\` must be a regular expression`);
+      } else {
+        try {
+          // Semgrep uses PCRE; JavaScript accepts the common subset once leading inline flags like `(?m)` become flags
+          const flags = value.match(/^\(\?([imsx]+)\)/);
+          new RegExp(flags ? value.slice(flags[0].length) : value, flags ? flags[1].replace('x', '') : '');
+        } catch (error) {
+          errors.push(`${where}: \`${key}\` is not a valid regular expression (${error.message})`);
+        }
+      }
+      break;
+    case 'patterns':
+      if (!Array.isArray(value) || value.length === 0) {
+        errors.push(`${where}: \`patterns\` must be a non-empty list`);
+        break;
+      }
+      validateOperatorList(value, where, errors);
+      if (!value.some(entry => entry && typeof entry === 'object' && POSITIVE_OPERATORS.some(operator => operator in entry))) {
+        errors.push(`${where}: \`patterns\` needs at least one positive operator (${POSITIVE_OPERATORS.join(', ')})`);
+      }
+      break;
+    case 'pattern-either':
+      if (!Array.isArray(value) || value.length === 0) {
+        errors.push(`${where}: \`pattern-either\` must be a non-empty list`);
+        break;
+      }
+      validateOperatorList(value, where, errors);
+      value.forEach((entry, index) => {
+        if (entry && typeof entry === 'object' && !POSITIVE_OPERATORS.some(operator => operator in entry)) {
+          errors.push(`${where}[${index}]: \`pattern-either\` alternatives must be positive operators`);
+        }
+      });
+      break;
+    case 'metavariable-regex':
+      if (!value || typeof value.metavariable !== 'string' || typeof value.regex !== 'string') {
+        errors.push(`${where}: \`metavariable-regex\` needs \`metavariable\` and \`regex\``);
+      }
+      break;
+    case 'metavariable-pattern':
+      if (!value || typeof value.metavariable !== 'string') errors.push(`${where}: \`metavariable-pattern\` needs \`metavariable\``);
+      break;
+    case 'metavariable-comparison':
+      if (!value || typeof value.comparison !== 'string') errors.push(`${where}: \`metavariable-comparison\` needs \`comparison\``);
+      break;
+    case 'metavariable-type':
+      if (!value || typeof value.metavariable !== 'string') errors.push(`${where}: \`metavariable-type\` needs \`metavariable\``);
+      break;
+    case 'focus-metavariable':
+      if (typeof value !== 'string' && !Array.isArray(value)) errors.push(`${where}: \`focus-metavariable\` must name a metavariable`);
+      break;
+    default:
+      errors.push(`${where}: unknown operator \`${key}\``);
+  }
+}
+
+function validateOperatorList(list, where, errors) {
+  list.forEach((entry, index) => {
+    const location = `${where}[${index}]`;
+    if (!entry || typeof entry !== 'object' || Array.isArray(entry)) {
+      errors.push(`${location}: each entry must be a mapping with one operator`);
+      return;
+    }
+    // Taint sources and sinks may carry options next to their operator
+    let keys = Object.keys(entry).filter(key => !OPERATOR_OPTIONS.includes(key));
+    if (keys.length === 0 && 'focus-metavariable' in entry) keys = ['focus-metavariable'];
+    if (keys.length !== 1) {
+      errors.push(`${location}: each entry must have exactly one operator (found ${keys.join(', ') || 'none'})`);
+      return;
+    }
+    validateOperator(keys[0], entry[keys[0]], location, errors);
+  });
+}
+
+let semgrepBinary;
+
+/**
+ * Locate a `semgrep` binary on the PATH (or in SEMGREP_BIN)
+ * @returns {string|null} - Command to run, or null when Semgrep is not installed
+ */
+function findSemgrep() {
+  if (semgrepBinary !== undefined) return semgrepBinary;
+  const candidate = process.env.SEMGREP_BIN || 'semgrep';
+  const probe = spawnSync(candidate, ['--version'], { encoding: 'utf8' });
+  semgrepBinary = probe.status === 0 ? candidate : null;
+  return semgrepBinary;
+}
+
+/**
+ * Run one rule file against a source file
+ * @param {string} ruleFile - Path of the rule YAML
+ * @param {string} targetFile - Path of the source to scan
+ * @returns {Object} - `{ matches: [{ startLine, endLine }], errors: [messages] }`
+ */
+function runRule(ruleFile, targetFile) {
+  const result = spawnSync(findSemgrep(), [
+    '--config', ruleFile, '--json', '--metrics=off', '--disable-version-check', '--quiet', targetFile
+  ], { encoding: 'utf8', maxBuffer: 64 * 1024 * 1024 });
+
+  let output;
+  try {
+    output = JSON.parse(result.stdout);
+  } catch (error) {
+    return { matches: [], errors: [(result.stderr || result.error && result.error.message || 'semgrep produced no JSON output').trim()] };
+  }
+  return {
+    matches: (output.results || []).map(match => ({ startLine: match.start.line, endLine: match.end.line })),
+    errors: (output.errors || []).filter(error => error.level !== 'warn').map(error => error.message || error.type || 'unknown error')
+  };
+}
+
+/**
+ * Annotate source code for `semgrep --test`: `ruleid:` above each line a match starts on and `ok:` above
+ * the given lines that no match touches
+ * @param {string} code - Source code the rule ran on
+ * @param {string} ruleId - Rule id
+ * @param {Array} matches - `{ startLine, endLine }` of each match
+ * @param {number[]} okLines - Candidate lines to mark as not matching, e.g. the first line of each function
+ * @param {string} commentPrefix - Line comment syntax of the language
+ * @returns {string} - Annotated code
+ */
+function annotateTestSource(code, ruleId, matches, okLines = [], commentPrefix = '//') {
+  const lines = code.split('\n');
+  const ruleLines = new Set(matches.map(match => match.startLine));
+  const touched = (line) => matches.some(match => match.startLine <= line && line <= match.endLine);
+  const okSet = new Set(okLines.filter(line => !touched(line)));
+
+  const output = [];
+  lines.forEach((text, index) => {
+    const line = index + 1;
+    const indent = text.match(/^\s*/)[0];
+    if (ruleLines.has(line)) output.push(`${indent}${commentPrefix} ruleid: ${ruleId}`);
+    else if (okSet.has(line)) output.push(`${indent}${commentPrefix} ok: ${ruleId}`);
+    output.push(text);
+  });
+  return output.join('\n');
+}
+
+/**
+ * Validate, save and test a batch of generated rules
+ * @param {Array} candidates - `{ rule }` for parsed rules or `{ raw, error }` for YAML that did not parse
+ * @param {Object} options - `rulesDir`: where valid rules go (invalid ones go to `invalid/` inside it);
+ *   `sourceFile`: file the rules were generated from; `suffix`: appended to rule file names;
+ *   `language`: e.g. "rust"; `extension`: test file extension, e.g. ".rs"; `okLines`: see annotateTestSource
+ * @returns {Array} - One result per rule: `{ id, status, file, testFile, errors, warnings, matches, checkedWith }`
+ *   where status is "matching", "valid" or "invalid" and checkedWith is "semgrep" or "schema"; testFile is null
+ *   unless Semgrep ran the rule
+ */
+function validateRules(candidates, options) {
+  const { rulesDir, sourceFile, suffix, language, extension } = options;
+  const invalidDir = path.join(rulesDir, 'invalid');
+  const code = fs.readFileSync(sourceFile, 'utf8');
+  const semgrep = findSemgrep();
+  const results = [];
+
+  candidates.forEach((candidate, index) => {
+    if (!candidate.rule) {
+      fs.mkdirSync(invalidDir, { recursive: true });
+      const file = path.join(invalidDir, `raw_rule_${index + 1}_${suffix}.yml`);
+      fs.writeFileSync(file, candidate.raw);
+      results.push({ id: null, status: 'invalid', file, testFile: null, errors: [`YAML does not parse: ${candidate.error}`], warnings: [], matches: [], checkedWith: 'schema' });
+      return;
+    }
+
+    const { rule } = candidate;
+    const { errors, warnings } = validateRuleSchema(rule, { language });
+    const baseName = `${typeof rule.id === 'string' && rule.id ? rule.id.replace(/[^A-Za-z0-9._-]/g, '_') : `rule_${index + 1}`}-${suffix}`;
+    const content = yaml.dump({ rules: [rule] });
+
+    if (errors.length > 0) {
+      fs.mkdirSync(invalidDir, { recursive: true });
+      const file = path.join(invalidDir, `${baseName}.yml`);
+      fs.writeFileSync(file, content);
+      results.push({ id: rule.id || null, status: 'invalid', file, testFile: null, errors, warnings, matches: [], checkedWith: 'schema' });
+      return;
+    }
+
+    const file = path.join(rulesDir, `${baseName}.yml`);
+    fs.writeFileSync(file, content);
+    // `semgrep --test` pairs `name.yml` with `name<ext>` in the same directory
+    const testFile = path.join(rulesDir, `${baseName}${extension}`);
+
+    // Without a run the matched lines are unknown, and an unannotated test file would pass `semgrep --test` vacuously
+    if (!semgrep) {
+      warnings.push('No test file written: semgrep is not installed, so the lines the rule matches are unknown');
+      results.push({ id: rule.id, status: 'valid', file, testFile: null, errors, warnings, matches: [], checkedWith: 'schema' });
+      return;
+    }
+
+    const run = runRule(file, sourceFile);
+    if (run.errors.length > 0) {
+      // Keep the rule next to the other invalid ones so `semgrep --test` on the rules directory stays green
+      fs.mkdirSync(invalidDir, { recursive: true });
+      const invalidFile = path.join(invalidDir, path.basename(file));
+      fs.renameSync(file, invalidFile);
+      results.push({ id: rule.id, status: 'invalid', file: invalidFile, testFile: null, errors: run.errors, warnings, matches: [], checkedWith: 'semgrep' });
+      return;
+    }
+
+    fs.writeFileSync(testFile, annotateTestSource(code, rule.id, run.matches, options.okLines || []));
+    results.push({
+      id: rule.id,
+      status: run.matches.length > 0 ? 'matching' : 'valid',
+      file,
+      testFile,
+      errors,
+      warnings,
+      matches: run.matches,
+      checkedWith: 'semgrep'
+    });
+  });
+
+  return results;
+}
+
+/**
+ * One-line tally of validation results, e.g. "3 matching, 1 valid, 2 invalid"
+ * @param {Array} results - Result of validateRules
+ * @returns {string}
+ */
+function summarizeValidation(results) {
+  const count = (status) => results.filter(result => result.status === status).length;
+  return `${count('matching')} matching, ${count('valid')} valid, ${count('invalid')} invalid`;
+}
+
+module.exports = {
+  validateRuleSchema,
+  findSemgrep,
+  runRule,
+  annotateTestSource,
+  validateRules,
+  summarizeValidation
+};
@@ -2,13 +2,21 @@ const fs = require('fs');
 const path = require('path');
 const parser = require('@solidity-parser/parser');
 const ora = require('ora');
+const { writeReports } = require('./report-formats');
+const { formatChangeSummary, formatFindingMetadata, groupBySeverity, toFindings } = require('./finding');
+const { classifyFindings, formatFileChange } = require('./diff-scope');
+const { applySuppressions, formatSuppressionReport, formatSuppressionSummary } = require('./baseline');
 
 /**
  * Performs a static security analysis of the SimpleToken contract
  * @param {string} filePath - Path to the smart contract file
  * @param {string} outputDir - Directory to save analysis results
+ * @param {Object} options - `format`: markdown, json and/or sarif (comma separated); `change`: the file's change
+ *   when auditing a diff (see diff-scope.js), to tell new findings from pre-existing ones; `baseline`: the loaded
+ *   baseline (see baseline.js), whose findings are suppressed like inline `winston-ignore`s; `reportName`: name
+ *   of the report files (default: the file name, see reportName)
  */
-async function staticAnalyzeContract(filePath, outputDir) {
+async function staticAnalyzeContract(filePath, outputDir, options = {}) {
   const spinner = ora('Performing static security analysis...').start();
   
   try {
@@ -17,17 +25,27 @@ async function staticAnalyzeContract(filePath, outputDir) {
     const fileName = path.basename(filePath);
     
     // Extract security issues through static analysis
-    const securityIssues = findSecurityIssues(contractCode);
+    const allIssues = findSecurityIssues(contractCode, filePath, options.root);
+    if (options.change) classifyFindings(allIssues, options.change);
     
-    // Generate markdown report
-    const report = generateSecurityReport(fileName, securityIssues);
+    // Accepted findings (baseline and inline suppressions) are listed apart
+    const suppressions = applySuppressions(allIssues, contractCode, filePath, options.baseline || null, options.root);
+    const securityIssues = suppressions.findings;
     
-    // Save report to file
-    const outputPath = path.join(outputDir, `${fileName}.static-analysis.md`);
-    fs.writeFileSync(outputPath, report);
+    // Save the report in each requested format
+    const outputPaths = writeReports({
+      outputDir,
+      baseName: `${options.reportName || fileName}.static-analysis`,
+      formats: options.format,
+      analyzer: 'solidity-static',
+      filePath,
+      findings: securityIssues,
+      suppressions,
+      renderMarkdown: () => generateSecurityReport(fileName, securityIssues, options.change, suppressions)
+    });
     
-    spinner.succeed(`Static security analysis complete! Saved to: ${outputPath}`);
-    return outputPath;
+    spinner.succeed(`Static security analysis complete! Saved to: ${outputPaths.join(', ')}`);
+    return outputPaths[0];
   } catch (error) {
     spinner.fail('Static analysis failed');
     throw error;
@@ -37,9 +55,11 @@ async function staticAnalyzeContract(filePath, outputDir) {
 /**
  * Find security issues in the contract code
  * @param {string} contractCode - Contract source code
- * @returns {Object} - Security issues categorized by risk level
+ * @param {string} filePath - Path of the file, recorded in each issue's location
+ * @param {string} root - Audited repository or directory, which finding ids are relative to
+ * @returns {Array} - Findings (see finding.js), most severe first
  */
-function findSecurityIssues(contractCode) {
+function findSecurityIssues(contractCode, filePath = null, root = null) {
   const issues = {
     critical: [],
     high: [],
@@ -48,17 +68,20 @@ function findSecurityIssues(contractCode) {
   };
   
   // Parse the contract
+  let ast = null;
   try {
-    const ast = parser.parse(contractCode, { loc: true });
+    ast = parser.parse(contractCode, { loc: true });
     
     // Check for missing access control
     const mintFunction = findFunction(ast, 'mint');
     if (mintFunction && !hasModifier(mintFunction, 'onlyOwner')) {
       issues.critical.push({
-        name: 'Missing Access Control on Mint Function',
+        ruleId: 'sol-missing-access-control',
+        title: 'Missing Access Control on Mint Function',
         description: 'The mint function lacks access control, allowing anyone to create new tokens.',
         function: 'mint',
-        recommendation: 'Add the onlyOwner modifier to the mint function to restrict access.'
+        recommendation: 'Add the onlyOwner modifier to the mint function to restrict access.',
+        location: locationOf(mintFunction, filePath)
       });
     }
     
@@ -66,10 +89,12 @@ function findSecurityIssues(contractCode) {
     const withdrawFunction = findFunction(ast, 'withdrawDonations');
     if (withdrawFunction && hasExternalCall(withdrawFunction) && !usesReentrancyGuard(withdrawFunction)) {
       issues.high.push({
-        name: 'Reentrancy Vulnerability',
+        ruleId: 'sol-reentrancy',
+        title: 'Reentrancy Vulnerability',
         description: 'The withdrawDonations function makes an external call after state changes without reentrancy protection.',
         function: 'withdrawDonations',
-        recommendation: 'Implement a reentrancy guard or use the checks-effects-interactions pattern.'
+        recommendation: 'Implement a reentrancy guard or use the checks-effects-interactions pattern.',
+        location: locationOf(withdrawFunction, filePath)
       });
     }
     
@@ -77,35 +102,43 @@ function findSecurityIssues(contractCode) {
     const transferOwnershipFunction = findFunction(ast, 'transferOwnership');
     if (!transferOwnershipFunction) {
       issues.medium.push({
-        name: 'No Ownership Transfer Mechanism',
+        ruleId: 'sol-no-ownership-transfer',
+        title: 'No Ownership Transfer Mechanism',
         description: 'The contract has an owner but no mechanism to transfer ownership, which could lead to a locked contract if the owner loses access.',
-        recommendation: 'Implement a transferOwnership function to allow changing the contract owner.'
+        recommendation: 'Implement a transferOwnership function to allow changing the contract owner.',
+        location: locationOf(findContract(ast), filePath)
       });
     }
     
     // Check for potential integer overflow/underflow
     if (!hasSafeArithmetic(ast)) {
       issues.medium.push({
-        name: 'Potential Integer Overflow/Underflow',
+        ruleId: 'sol-unchecked-arithmetic',
+        title: 'Potential Integer Overflow/Underflow',
         description: 'The contract does not use SafeMath or Solidity 0.8.0+ for arithmetic operations.',
-        recommendation: 'Use SafeMath library for arithmetic operations or upgrade to Solidity 0.8.0+ which includes built-in overflow checking.'
+        recommendation: 'Use SafeMath library for arithmetic operations or upgrade to Solidity 0.8.0+ which includes built-in overflow checking.',
+        location: locationOf(findPragma(ast), filePath)
       });
     }
     
     // Check for lack of event emissions
     if (!hasEventForStateChange(ast, 'transferOwnership')) {
       issues.low.push({
-        name: 'Missing Events for Important State Changes',
+        ruleId: 'sol-missing-events',
+        title: 'Missing Events for Important State Changes',
         description: 'The contract does not emit events for some important state changes, making it harder to track off-chain.',
-        recommendation: 'Add events for all important state changes like ownership transfers.'
+        recommendation: 'Add events for all important state changes like ownership transfers.',
+        location: locationOf(transferOwnershipFunction, filePath)
       });
     }
     
   } catch (error) {
     issues.medium.push({
-      name: 'Static Analysis Error',
+      ruleId: 'sol-parse-error',
+      title: 'Static Analysis Error',
       description: `Error parsing contract: ${error.message}`,
-      recommendation: 'Review the contract code for syntax errors.'
+      recommendation: 'Review the contract code for syntax errors.',
+      location: { file: filePath }
     });
   }
   
@@ -113,13 +146,15 @@ function findSecurityIssues(contractCode) {
   
   // Check for missing burn access control
   issues.high.push({
-    name: 'Unrestricted Token Burning',
+    ruleId: 'sol-unrestricted-burn',
+    title: 'Unrestricted Token Burning',
     description: 'Anyone can burn their tokens using the burn function, which might not be the intended behavior.',
     function: 'burn',
-    recommendation: 'Consider limiting burn functionality if not meant to be accessible to all users.'
+    recommendation: 'Consider limiting burn functionality if not meant to be accessible to all users.',
+    location: locationOf(ast && findFunction(ast, 'burn'), filePath)
   });
   
-  return issues;
+  return toFindings(issues, { analyzer: 'solidity-static', file: filePath, root });
 }
 
 /**
@@ -139,6 +174,35 @@ function findFunction(ast, name) {
   return foundFunction;
 }
 
+/**
+ * Find the first contract definition in the AST
+ */
+function findContract(ast) {
+  return ast.children.find(node => node.type === 'ContractDefinition') || null;
+}
+
+/**
+ * Find the solidity version pragma in the AST
+ */
+function findPragma(ast) {
+  return ast.children.find(node => node.type === 'PragmaDirective' && node.name === 'solidity') || null;
+}
+
+/**
+ * Build a finding location from a node's `loc`. The parser reports 0-based columns; locations are 1-based.
+ */
+function locationOf(node, filePath) {
+  if (!node || !node.loc) return { file: filePath };
+  return {
+    file: filePath,
+    startLine: node.loc.start.line,
+    startColumn: node.loc.start.column + 1,
+    endLine: node.loc.end.line,
+    endColumn: node.loc.end.column + 1,
+    function: node.type === 'FunctionDefinition' ? node.name : null
+  };
+}
+
 /**
  * Check if a function has a specific modifier
  */
@@ -219,7 +283,8 @@ function hasEventForStateChange(ast, functionName) {
 /**
  * Generate a markdown security report
  */
-function generateSecurityReport(fileName, securityIssues) {
+function generateSecurityReport(fileName, findings, change = null, suppressions = null) {
+  const securityIssues = groupBySeverity(findings);
   // Count total issues
   const totalIssues = 
     securityIssues.critical.length + 
@@ -235,7 +300,15 @@ function generateSecurityReport(fileName, securityIssues) {
   report += `  - Critical: ${securityIssues.critical.length}\n`;
   report += `  - High: ${securityIssues.high.length}\n`;
   report += `  - Medium: ${securityIssues.medium.length}\n`;
-  report += `  - Low: ${securityIssues.low.length}\n\n`;
+  report += `  - Low: ${securityIssues.low.length}\n`;
+  if (change) {
+    report += `- **Change:** ${formatFileChange(change)}\n`;
+    report += formatChangeSummary(findings);
+  }
+  if (suppressions) {
+    report += formatSuppressionSummary(suppressions);
+  }
+  report += `\n`;
   
   // Add contract summary specifically for SimpleToken
   report += `## Contract Summary\n\n`;
@@ -272,6 +345,10 @@ function generateSecurityReport(fileName, securityIssues) {
     });
   }
   
+  if (suppressions) {
+    report += formatSuppressionReport(suppressions);
+  }
+  
   // Add additional SimpleToken vulnerabilities
   report += `## Additional Security Concerns\n\n`;
   
@@ -304,15 +381,17 @@ function generateSecurityReport(fileName, securityIssues) {
  * Format a single issue for the report
  */
 function formatIssue(issue, index) {
-  let output = `### ${index + 1}. ${issue.name}\n\n`;
+  let output = `### ${index + 1}. ${issue.title}\n\n`;
   
   output += `**Description:** ${issue.description}\n\n`;
   
-  if (issue.function) {
-    output += `**Location:** \`${issue.function}()\` function\n\n`;
+  if (issue.location.function) {
+    const line = issue.location.startLine ? ` (line ${issue.location.startLine})` : '';
+    output += `**Location:** \`${issue.location.function}()\` function${line}\n\n`;
   }
   
   output += `**Recommendation:** ${issue.recommendation}\n\n`;
+  output += `${formatFindingMetadata(issue)}\n\n`;
   
   return output;
 }
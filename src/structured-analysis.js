@@ -0,0 +1,335 @@
+const { SEVERITIES, CONFIDENCES, toFindings, formatChangeSummary } = require('./finding');
+const { formatGrounding } = require('./rust-grounding');
+
+/**
+ * Structured security analyses from the model: the JSON schema the model must answer with, validation of its
+ * answers (shape and line references), and the Markdown report and Findings rendered from a validated analysis.
+ */
+
+const ANALYSIS_SCHEMA = {
+  type: 'object',
+  required: ['summary', 'architecture', 'businessLogic', 'findings', 'recommendations'],
+  properties: {
+    summary: { type: 'string', description: 'What the code does, in 2-3 sentences' },
+    architecture: { type: 'string', description: 'Key components and how they relate' },
+    businessLogic: { type: 'string', description: 'How the core functionality works' },
+    findings: {
+      type: 'array',
+      items: {
+        type: 'object',
+        required: ['severity', 'title', 'category', 'function', 'lines', 'description', 'exploitScenario', 'fix', 'confidence'],
+        properties: {
+          severity: { type: 'string', enum: SEVERITIES },
+          title: { type: 'string', description: 'Short name of the issue' },
+          category: { type: 'string', description: 'Vulnerability category from the checklist, e.g. "Integer Overflow and Underflow"' },
+          function: { type: ['string', 'null'], description: 'Function the issue is in, e.g. "Vault::withdraw", or null' },
+          lines: {
+            type: ['object', 'null'],
+            description: '1-based line range of the vulnerable code in the file, or null',
+            required: ['start', 'end'],
+            properties: { start: { type: 'integer' }, end: { type: 'integer' } }
+          },
+          description: { type: 'string', description: 'What is wrong' },
+          exploitScenario: { type: 'string', description: 'Step by step, how an attacker exploits it' },
+          fix: { type: 'string', description: 'Concrete code change that fixes it' },
+          confidence: { type: 'string', enum: CONFIDENCES }
+        }
+      }
+    },
+    recommendations: { type: 'array', items: { type: 'string' } }
+  }
+};
+
+/**
+ * Check a value against a (small subset of) JSON Schema: type, enum, required, properties and items
+ * @param {Object} schema - Schema
+ * @param {*} value - Value to check
+ * @param {string} where - Path of the value, for messages
+ * @returns {string[]} - Problems found
+ */
+function validateSchema(schema, value, where = '$') {
+  const types = [].concat(schema.type || []);
+  const actual = value === null ? 'null' : Array.isArray(value) ? 'array' : Number.isInteger(value) ? 'integer' : typeof value;
+  if (types.length > 0 && !types.includes(actual) && !(actual === 'integer' && types.includes('number'))) {
+    return [`${where} should be ${types.join(' or ')}, got ${actual}`];
+  }
+  if (schema.enum && !schema.enum.includes(value)) {
+    return [`${where} should be one of ${schema.enum.join(', ')}, got ${JSON.stringify(value)}`];
+  }
+
+  const errors = [];
+  if (actual === 'object') {
+    (schema.required || []).forEach(key => {
+      if (!(key in value)) errors.push(`${where}.${key} is missing`);
+    });
+    Object.entries(schema.properties || {}).forEach(([key, property]) => {
+      if (key in value) errors.push(...validateSchema(property, value[key], `${where}.${key}`));
+    });
+  } else if (actual === 'array' && schema.items) {
+    value.forEach((item, index) => errors.push(...validateSchema(schema.items, item, `${where}[${index}]`)));
+  }
+  return errors;
+}
+
+/**
+ * Extract the JSON document from a model answer: a ```json block, or the outermost `{...}` of the text
+ * @param {string} text - Model answer
+ * @returns {Object} - `{ data, error }`
+ */
+function extractJson(text) {
+  const block = text.match(/```(?:json)?\s*\n([\s\S]*?)```/);
+  const start = text.indexOf('{');
+  const candidate = block ? block[1] : start === -1 ? '' : text.slice(start, text.lastIndexOf('}') + 1);
+  if (!candidate.trim()) return { data: null, error: 'the answer contains no JSON object' };
+  try {
+    return { data: JSON.parse(candidate), error: null };
+  } catch (error) {
+    return { data: null, error: `the JSON does not parse: ${error.message}` };
+  }
+}
+
+/**
+ * Parse and validate a model answer against ANALYSIS_SCHEMA and the analyzed file
+ * @param {string} text - Model answer
+ * @param {Object} source - `{ code, functions }` where functions are `{ name, startLine, endLine }` of the file
+ * @returns {Object} - `{ analysis, errors, lineIssues }`: analysis is null unless the answer matches the schema;
+ *   lineIssues are `{ index, message }` for findings whose line references do not fit the file
+ */
+function parseAnalysis(text, source) {
+  const { data, error } = extractJson(text);
+  if (error) return { analysis: null, errors: [error], lineIssues: [] };
+
+  const errors = validateSchema(ANALYSIS_SCHEMA, data);
+  if (errors.length > 0) return { analysis: null, errors, lineIssues: [] };
+
+  return { analysis: data, errors: [], lineIssues: checkLineReferences(data, source) };
+}
+
+/**
+ * Check each finding's lines against the file: inside the file, start before end, and inside the function
+ * the finding names when that function exists
+ */
+function checkLineReferences(analysis, { code, functions }) {
+  const lineCount = code.split('\n').length;
+  const issues = [];
+
+  analysis.findings.forEach((finding, index) => {
+    const { lines } = finding;
+    if (!lines) return;
+    const label = `findings[${index}] ("${finding.title}")`;
+    if (lines.start < 1 || lines.end > lineCount || lines.start > lines.end) {
+      issues.push({ index, message: `${label} cites lines ${lines.start}-${lines.end}, but the file has lines 1-${lineCount}` });
+      return;
+    }
+    const fn = finding.function && findFunction(functions, finding.function);
+    if (fn && (lines.end < fn.startLine || lines.start > fn.endLine)) {
+      issues.push({ index, message: `${label} cites lines ${lines.start}-${lines.end}, but \`${finding.function}\` spans lines ${fn.startLine}-${fn.endLine}` });
+    }
+  });
+
+  return issues;
+}
+
+/**
+ * Find a function by qualified (`Vault::withdraw`) or bare (`withdraw`) name
+ */
+function findFunction(functions, name) {
+  const bare = name.replace(/\(.*$/, '').trim();
+  return functions.find(fn => fn.name === bare)
+    || functions.find(fn => fn.name.split('::').pop() === bare.split('::').pop())
+    || null;
+}
+
+/**
+ * Drop line references that failed checkLineReferences, noting why on the finding
+ * @param {Object} analysis - Validated analysis, modified in place
+ * @param {Array} lineIssues - Result of checkLineReferences
+ * @returns {Object} - The analysis
+ */
+function dropInvalidLines(analysis, lineIssues) {
+  lineIssues.forEach(({ index, message }) => {
+    const finding = analysis.findings[index];
+    finding.lines = null;
+    finding.lineNote = message.replace(/^findings\[\d+\] \("[^"]*"\) /, 'The model ');
+  });
+  return analysis;
+}
+
+/**
+ * Prompt text describing the answer format
+ * @returns {string}
+ */
+function describeAnalysisFormat() {
+  return `Answer with a single JSON object, in a \`\`\`json code block, that matches this JSON Schema:
+
+\`\`\`json
+${JSON.stringify(ANALYSIS_SCHEMA, null, 2)}
+\`\`\`
+
+Line numbers are 1-based and refer to the code exactly as given. Use an empty \`findings\` array if you find no issues.`;
+}
+
+/**
+ * Convert a validated analysis into Findings
+ * @param {Object} analysis - Validated analysis
+ * @param {Object} options - `{ filePath, code, analyzer, ruleIdPrefix, root }`, root being the directory finding
+ *   ids are relative to
+ * @returns {Array} - Findings
+ */
+function analysisToFindings(analysis, { filePath, code, analyzer, ruleIdPrefix, root }) {
+  const lines = code.split('\n');
+  const buckets = {};
+  analysis.findings.forEach(finding => {
+    const snippet = finding.lines ? lines.slice(finding.lines.start - 1, finding.lines.end).join('\n') : null;
+    const evidence = [];
+    if (snippet) evidence.push({ kind: 'code', text: snippet });
+    evidence.push({ kind: 'note', text: `Exploit scenario: ${finding.exploitScenario}` });
+    if (finding.lineNote) evidence.push({ kind: 'note', text: finding.lineNote });
+
+    (buckets[finding.severity] = buckets[finding.severity] || []).push({
+      ruleId: `${ruleIdPrefix}.${slugify(finding.category || 'other')}`,
+      title: finding.title,
+      description: finding.description,
+      recommendation: finding.fix,
+      confidence: finding.confidence,
+      evidence,
+      location: {
+        file: filePath,
+        startLine: finding.lines ? finding.lines.start : null,
+        startColumn: finding.lines ? 1 : null,
+        endLine: finding.lines ? finding.lines.end : null,
+        endColumn: finding.lines ? (lines[finding.lines.end - 1] || '').length + 1 : null,
+        function: finding.function || null,
+        snippet
+      }
+    });
+  });
+  return toFindings(buckets, { analyzer, file: filePath, root });
+}
+
+/**
+ * Merge the analyses of the chunks of a file (see rust-chunker.js). The prose sections are joined part by part;
+ * findings reported by more than one chunk (same category and function, overlapping or missing lines) are kept
+ * once, with the highest severity and confidence reported.
+ * @param {Array} parts - `{ label, analysis }` per chunk, in file order
+ * @returns {Object} - `{ analysis, duplicates }` where duplicates counts the findings merged away
+ */
+function mergeAnalyses(parts) {
+  const section = (key) => parts
+    .filter(part => part.analysis[key].trim())
+    .map(part => `**${part.label}:** ${part.analysis[key].trim()}`)
+    .join('\n\n');
+
+  const findings = [];
+  let duplicates = 0;
+  parts.forEach(part => part.analysis.findings.forEach(finding => {
+    const same = findings.find(other => sameFinding(other, finding));
+    if (!same) {
+      findings.push({ ...finding });
+      return;
+    }
+    duplicates++;
+    if (SEVERITIES.indexOf(finding.severity) < SEVERITIES.indexOf(same.severity)) same.severity = finding.severity;
+    if (CONFIDENCES.indexOf(finding.confidence) < CONFIDENCES.indexOf(same.confidence)) same.confidence = finding.confidence;
+    if (!same.lines && finding.lines) {
+      same.lines = finding.lines;
+      delete same.lineNote;
+    }
+  }));
+
+  const seen = new Set();
+  const recommendations = [];
+  parts.forEach(part => part.analysis.recommendations.forEach(recommendation => {
+    const key = recommendation.toLowerCase().replace(/[^a-z0-9]+/g, ' ').trim();
+    if (seen.has(key)) return;
+    seen.add(key);
+    recommendations.push(recommendation);
+  }));
+
+  return {
+    analysis: {
+      summary: section('summary'),
+      architecture: section('architecture'),
+      businessLogic: section('businessLogic'),
+      findings,
+      recommendations
+    },
+    duplicates
+  };
+}
+
+function sameFinding(a, b) {
+  if (slugify(a.category || 'other') !== slugify(b.category || 'other')) return false;
+  const bare = (name) => (name ? name.replace(/\(.*$/, '').split('::').pop().trim() : null);
+  if (bare(a.function) !== bare(b.function)) return false;
+  if (!a.lines || !b.lines) return true;
+  return a.lines.start <= b.lines.end && b.lines.start <= a.lines.end;
+}
+
+function slugify(text) {
+  return text.toLowerCase().replace(/[^a-z0-9]+/g, '-').replace(/^-|-$/g, '') || 'other';
+}
+
+/**
+ * Render a validated analysis as the Markdown security report
+ * @param {string} title - Report title, e.g. "Rust Security Analysis: vault.rs"
+ * @param {Object} analysis - Validated analysis
+ * @param {Array} findings - analysisToFindings(analysis), for ids, locations and grounding
+ * @returns {string} - Markdown
+ */
+function renderAnalysisMarkdown(title, analysis, findings) {
+  let output = `# ${title}\n\n`;
+  output += `## Summary\n\n${analysis.summary}\n\n`;
+  output += `## Code Architecture\n\n${analysis.architecture}\n\n`;
+  output += `## Business Logic Analysis\n\n${analysis.businessLogic}\n\n`;
+
+  output += `## Risk Assessment\n\n`;
+  if (findings.length === 0) output += `No issues found.\n\n`;
+  const changeSummary = formatChangeSummary(findings);
+  if (changeSummary) output += `${changeSummary}\n`;
+  SEVERITIES.forEach(severity => {
+    const group = findings.filter(finding => finding.severity === severity);
+    if (group.length === 0) return;
+    output += `### ${severity[0].toUpperCase()}${severity.slice(1)} Severity\n\n`;
+    group.forEach((finding, index) => {
+      const { location } = finding;
+      output += `#### ${index + 1}. ${finding.title}\n\n`;
+      const where = [
+        location.function ? `\`${location.function}\`` : null,
+        location.startLine ? `lines ${location.startLine}-${location.endLine}` : null
+      ].filter(Boolean).join(', ');
+      if (where) output += `**Location:** ${where}\n\n`;
+      if (location.snippet) output += `\`\`\`rust\n${location.snippet}\n\`\`\`\n\n`;
+      output += `**Description:** ${finding.description}\n\n`;
+      finding.evidence.filter(item => item.kind === 'note').forEach(item => {
+        output += `${item.text.startsWith('Exploit scenario: ') ? `**Exploit scenario:** ${item.text.slice(18)}` : `*${item.text}*`}\n\n`;
+      });
+      output += `**Fix:** ${finding.recommendation}\n\n`;
+      const change = finding.change ? ` · ${finding.change === 'new' ? '**new or changed code**' : 'pre-existing code'}` : '';
+      output += `*Confidence ${finding.confidence} · id \`${finding.id}\`${change}*\n\n`;
+      if (finding.grounding) output += `${formatGrounding(finding.grounding)}\n\n`;
+    });
+  });
+
+  if (analysis.recommendations.length > 0) {
+    output += `## Recommendations\n\n`;
+    analysis.recommendations.forEach((recommendation, index) => {
+      output += `${index + 1}. ${recommendation}\n`;
+    });
+    output += `\n`;
+  }
+
+  return output;
+}
+
+module.exports = {
+  ANALYSIS_SCHEMA,
+  validateSchema,
+  extractJson,
+  parseAnalysis,
+  dropInvalidLines,
+  describeAnalysisFormat,
+  analysisToFindings,
+  mergeAnalyses,
+  renderAnalysisMarkdown
+};
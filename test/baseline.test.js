@@ -0,0 +1,103 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const { toFindings } = require('../src/finding');
+const {
+  applySuppressions, findInlineSuppressions, formatSuppressionSummary, loadBaseline, writeBaseline
+} = require('../src/baseline');
+
+const root = fs.mkdtempSync(path.join(os.tmpdir(), 'winston-baseline-'));
+const FILE = path.join(root, 'src', 'vault.rs');
+
+test.after(() => fs.rmSync(root, { recursive: true, force: true }));
+
+const CODE = [
+  'pub fn get(values: &[u64]) -> u64 {',
+  '    // winston-ignore: rust-unwrap - checked by the caller',
+  '',
+  '    #[allow(clippy::unwrap_used)]',
+  '    let first = values.first().unwrap();',
+  '    let map = HashMap::new(); // winston-ignore: rust-hashmap-without-capacity',
+  '    values[1] // winston-ignore: rust-unwrap',
+  '}'
+].join('\n');
+
+function findings() {
+  return toFindings({
+    medium: [
+      { ruleId: 'rust-unwrap', title: 'Unwrap on Option', location: { startLine: 5, function: 'get', snippet: 'values.first().unwrap()' } },
+      { ruleId: 'rust-index-panic', title: 'Index Out of Bounds', location: { startLine: 7, function: 'get', snippet: 'values[1]' } }
+    ],
+    low: [
+      { ruleId: 'rust-hashmap-without-capacity', title: 'HashMap Without Capacity Hint', location: { startLine: 6, function: 'get', snippet: 'HashMap::new()' } }
+    ]
+  }, { analyzer: 'rust-static', file: FILE, root });
+}
+
+test('inline suppressions apply to their own line, or to the next line of code', () => {
+  const suppressions = findInlineSuppressions(CODE);
+  assert.deepStrictEqual([...suppressions.entries()], [
+    [5, [{ ruleId: 'rust-unwrap', reason: 'checked by the caller', line: 2 }]],
+    [6, [{ ruleId: 'rust-hashmap-without-capacity', reason: null, line: 6 }]],
+    [7, [{ ruleId: 'rust-unwrap', reason: null, line: 7 }]]
+  ]);
+});
+
+test('inline suppressions only hide the rule they name', () => {
+  const result = applySuppressions(findings(), CODE, FILE, null, root);
+  assert.deepStrictEqual(result.findings.map(finding => finding.ruleId), ['rust-index-panic']);
+  assert.deepStrictEqual(result.suppressed.map(finding => [finding.ruleId, finding.suppression]), [
+    ['rust-unwrap', { kind: 'inline', reason: 'checked by the caller' }],
+    ['rust-hashmap-without-capacity', { kind: 'inline', reason: null }]
+  ]);
+  assert.deepStrictEqual(result.fixed, []);
+  assert.strictEqual(result.baseline, null);
+  assert.strictEqual(formatSuppressionSummary(result), '- Suppressed inline: 2\n');
+});
+
+test('a baseline written in one run suppresses its findings in the next and reports fixed entries', () => {
+  const code = CODE.replace(/ *\/\/ winston-ignore.*$/gm, '');
+
+  // A relative path is resolved against the audited root, not the working directory
+  const first = loadBaseline('.winston-baseline.json', root);
+  assert.strictEqual(first.path, path.join(root, '.winston-baseline.json'));
+  assert.strictEqual(first.exists, false);
+  applySuppressions(findings(), code, FILE, first, root);
+  assert.deepStrictEqual(writeBaseline(first), { path: first.path, count: 3, files: 1 });
+
+  // Reasons added by hand survive later updates
+  const data = JSON.parse(fs.readFileSync(first.path, 'utf8'));
+  assert.deepStrictEqual(data.findings.map(entry => [entry.file, entry.line, entry.ruleId]), [
+    ['src/vault.rs', 5, 'rust-unwrap'],
+    ['src/vault.rs', 6, 'rust-hashmap-without-capacity'],
+    ['src/vault.rs', 7, 'rust-index-panic']
+  ]);
+  data.findings[1].reason = 'small map';
+  fs.writeFileSync(first.path, JSON.stringify(data));
+
+  const second = loadBaseline(first.path);
+  const remaining = findings().filter(finding => finding.ruleId !== 'rust-index-panic');
+  const result = applySuppressions(remaining, code, FILE, second, root);
+  assert.deepStrictEqual(result.findings, []);
+  assert.deepStrictEqual(result.suppressed.map(finding => finding.suppression), [
+    { kind: 'baseline', reason: null },
+    { kind: 'baseline', reason: 'small map' }
+  ]);
+  assert.deepStrictEqual(result.fixed.map(entry => entry.ruleId), ['rust-index-panic']);
+  assert.strictEqual(formatSuppressionSummary(result), '- Not in the baseline: 0 (2 suppressed, 1 fixed)\n');
+
+  writeBaseline(second);
+  const updated = JSON.parse(fs.readFileSync(first.path, 'utf8')).findings;
+  assert.deepStrictEqual(updated.map(entry => [entry.ruleId, entry.reason]), [
+    ['rust-unwrap', null],
+    ['rust-hashmap-without-capacity', 'small map']
+  ]);
+});
+
+test('invalid baseline files are rejected', () => {
+  const file = path.join(root, 'invalid.json');
+  fs.writeFileSync(file, '{"entries": []}');
+  assert.throws(() => loadBaseline('invalid.json', root), /Invalid baseline file invalid\.json: expected a "findings" list/);
+});
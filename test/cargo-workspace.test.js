@@ -0,0 +1,147 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const { spawnSync } = require('child_process');
+const { parseToml } = require('../src/cargo-workspace');
+
+const hasCargo = spawnSync('cargo', ['--version']).status === 0;
+const tempDirs = [];
+
+test.after(() => tempDirs.forEach(dir => fs.rmSync(dir, { recursive: true, force: true })));
+
+// Write a tree of files under a fresh temporary directory
+function writeTree(files) {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'winston-cargo-'));
+  tempDirs.push(dir);
+  for (const [file, contents] of Object.entries(files)) {
+    fs.mkdirSync(path.dirname(path.join(dir, file)), { recursive: true });
+    fs.writeFileSync(path.join(dir, file), contents);
+  }
+  return dir;
+}
+
+// Load the module with CARGO set, so the cargo lookup is redone
+function loadWorkspaceModule(cargo) {
+  const modulePath = require.resolve('../src/cargo-workspace');
+  const previous = process.env.CARGO;
+  process.env.CARGO = cargo;
+  delete require.cache[modulePath];
+  try {
+    const workspace = require(modulePath);
+    workspace.findCargo();
+    return workspace;
+  } finally {
+    if (previous === undefined) delete process.env.CARGO;
+    else process.env.CARGO = previous;
+  }
+}
+
+const WORKSPACE = {
+  'Cargo.toml': [
+    '[workspace]',
+    'members = [',
+    '    "crates/*",  # every crate',
+    ']',
+    'exclude = ["crates/legacy"]',
+    ''
+  ].join('\n'),
+  'crates/core/Cargo.toml': '[package]\nname = "core-lib"\nversion = "0.1.0"\n\n[lib]\npath = "src/core.rs"\n',
+  'crates/core/src/core.rs': 'mod util;\npub fn f() {}\n',
+  'crates/core/src/util.rs': 'pub fn g() {}\n',
+  'crates/core/src/unused.rs': 'pub fn h() {}\n',
+  'crates/app/Cargo.toml': '[package]\nname = "app"\nversion = "0.1.0"\nedition = "2021"\n\n[[bin]]\nname = "tool"\npath = "src/tool.rs"\n',
+  'crates/app/src/lib.rs': 'pub fn run() {}\n',
+  'crates/app/src/tool.rs': 'fn main() {}\n',
+  'crates/app/src/bin/extra.rs': 'fn main() {}\n',
+  'crates/app/build.rs': 'fn main() {}\n',
+  'crates/legacy/Cargo.toml': '[package]\nname = "legacy"\nversion = "0.1.0"\n',
+  'crates/legacy/src/lib.rs': 'pub fn old() {}\n',
+  'scripts/gen.rs': 'fn main() {}\n'
+};
+
+// Targets of each group, with files relative to the workspace; Cargo and the manifest parser
+// order the targets of one kind differently
+function describeGroups(groups, dir) {
+  return groups.map(group => [
+    group.crate,
+    group.target && `${group.target.kind}:${group.target.name}`,
+    group.files.map(file => path.relative(dir, file).split(path.sep).join('/'))
+  ]).sort((a, b) => JSON.stringify(a).localeCompare(JSON.stringify(b)));
+}
+
+function checkWorkspace(cargo) {
+  const { discoverCargoWorkspace, selectRustTargets } = loadWorkspaceModule(cargo);
+  const dir = writeTree(WORKSPACE);
+  const discovery = discoverCargoWorkspace(dir);
+
+  const legacy = discovery.crates.find(crate => crate.name === 'legacy');
+  assert.strictEqual(legacy.excluded, true);
+  const core = discovery.crates.find(crate => crate.name === 'core-lib');
+  assert.strictEqual(core.excluded, false);
+  assert.strictEqual(core.workspace, path.join(dir, 'Cargo.toml'));
+  assert.deepStrictEqual(core.unreferencedFiles, [path.join(dir, 'crates/core/src/unused.rs')]);
+
+  assert.deepStrictEqual(describeGroups(selectRustTargets(discovery), dir), [
+    ['app', 'bin:extra', ['crates/app/src/bin/extra.rs']],
+    ['app', 'bin:tool', ['crates/app/src/tool.rs']],
+    ['app', 'lib:app', ['crates/app/src/lib.rs']],
+    ['core-lib', 'lib:core_lib', ['crates/core/src/core.rs', 'crates/core/src/util.rs']],
+    [null, null, ['scripts/gen.rs']]
+  ]);
+  assert.deepStrictEqual(describeGroups(selectRustTargets(discovery, { crates: 'legacy', targets: 'all' }), dir), [
+    ['legacy', 'lib:legacy', ['crates/legacy/src/lib.rs']]
+  ]);
+  assert.deepStrictEqual(
+    selectRustTargets(discovery, { crates: 'app', targets: 'build' }).map(group => group.target.name),
+    ['build-script-build']
+  );
+}
+
+test('parseToml reads multi-line arrays, inline tables, escapes and arrays of tables', () => {
+  const data = parseToml([
+    '[package]',
+    'name = "vault"  # trailing comment',
+    'description = "say \\"hi\\"\\tnow"',
+    "license = 'MIT\\no-escape'",
+    'metadata.docs = { all-features = true, targets = ["x86_64"] }',
+    '',
+    '[[bin]]',
+    'name = "one"',
+    '[[bin]]',
+    'name = "two"',
+    'path = "src/two.rs"',
+    '[workspace]',
+    'members = [',
+    '  "a",',
+    '  "b/*", # glob',
+    ']'
+  ].join('\n'));
+  assert.strictEqual(data.package.name, 'vault');
+  assert.strictEqual(data.package.description, 'say "hi"\tnow');
+  assert.strictEqual(data.package.license, 'MIT\\no-escape');
+  assert.deepStrictEqual(data.package.metadata.docs, { 'all-features': true, targets: ['x86_64'] });
+  assert.deepStrictEqual(data.bin, [{ name: 'one' }, { name: 'two', path: 'src/two.rs' }]);
+  assert.deepStrictEqual(data.workspace.members, ['a', 'b/*']);
+  assert.throws(() => parseToml('name = "open'), /Unterminated string at line 1/);
+});
+
+test('without cargo, member globs, exclude and target path overrides come from the manifests', () => {
+  checkWorkspace(path.join(os.tmpdir(), 'no-such-cargo'));
+});
+
+test('with cargo, the same workspace is resolved by cargo metadata', { skip: !hasCargo && 'cargo is not installed' }, () => {
+  checkWorkspace('cargo');
+});
+
+test('edition 2015 packages that list a bin do not discover the others', () => {
+  const { discoverCargoWorkspace } = loadWorkspaceModule(path.join(os.tmpdir(), 'no-such-cargo'));
+  const dir = writeTree({
+    'Cargo.toml': '[package]\nname = "old"\nversion = "0.1.0"\n\n[[bin]]\nname = "tool"\npath = "src/tool.rs"\n',
+    'src/tool.rs': 'fn main() {}\n',
+    'src/bin/extra.rs': 'fn main() {}\n'
+  });
+  const [crate] = discoverCargoWorkspace(dir).crates;
+  assert.deepStrictEqual(crate.targets.map(target => target.name), ['tool']);
+});
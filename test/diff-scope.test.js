@@ -0,0 +1,117 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const { execFileSync } = require('child_process');
+const { classifyFindings, formatFileChange, parseUnifiedDiff, resolveDiff } = require('../src/diff-scope');
+
+const ROOT = path.resolve('repo');
+const tempDirs = [];
+
+test.after(() => tempDirs.forEach(dir => fs.rmSync(dir, { recursive: true, force: true })));
+
+function describe(files) {
+  return [...files.values()].map(({ file, status, ranges }) => [path.relative(ROOT, file), status, ranges]);
+}
+
+test('parseUnifiedDiff reads the new side of each hunk and the status of each file', () => {
+  const diff = [
+    'diff --git a/src/vault.rs b/src/vault.rs',
+    'index 1111111..2222222 100644',
+    '--- a/src/vault.rs',
+    '+++ b/src/vault.rs',
+    '@@ -3 +3 @@ pub struct Vault {',
+    '-    total: u32,',
+    '+    total: u64,',
+    '@@ -10,0 +11,4 @@ impl Vault {',
+    '+    pub fn fee(&self) -> u64 {',
+    '@@ -20,2 +24,0 @@',
+    'diff --git a/src/new.rs b/src/new.rs',
+    'new file mode 100644',
+    '--- /dev/null',
+    '+++ b/src/new.rs',
+    '@@ -0,0 +1,2 @@',
+    'diff --git a/src/old.rs b/src/moved.rs',
+    'similarity index 90%',
+    'rename from src/old.rs',
+    'rename to src/moved.rs',
+    '--- a/src/old.rs',
+    '+++ b/src/moved.rs',
+    '@@ -5 +5,2 @@',
+    ''
+  ].join('\n');
+  assert.deepStrictEqual(describe(parseUnifiedDiff(diff, ROOT)), [
+    ['src/vault.rs', 'modified', [{ start: 3, end: 3 }, { start: 11, end: 14 }, { start: 24, end: 25 }]],
+    ['src/new.rs', 'added', [{ start: 1, end: 2 }]],
+    ['src/moved.rs', 'renamed', [{ start: 5, end: 6 }]]
+  ]);
+});
+
+test('parseUnifiedDiff unquotes paths with special characters', () => {
+  const diff = [
+    'diff --git "a/src/caf\\303\\251.rs" "b/src/caf\\303\\251.rs"',
+    '+++ "b/src/caf\\303\\251.rs"',
+    '@@ -1 +1 @@',
+    'diff --git "a/src/say \\"hi\\".rs" "b/src/say \\"hi\\".rs"',
+    '+++ "b/src/say \\"hi\\".rs"',
+    '@@ -2 +2 @@',
+    'diff --git a/src/my vault.rs b/src/my vault.rs',
+    '+++ b/src/my vault.rs\t',
+    '@@ -3 +3 @@',
+    ''
+  ].join('\n');
+  assert.deepStrictEqual([...parseUnifiedDiff(diff, ROOT).keys()].map(file => path.relative(ROOT, file)), [
+    'src/café.rs',
+    'src/say "hi".rs',
+    'src/my vault.rs'
+  ]);
+});
+
+test('resolveDiff finds the changed lines of files with non-ASCII, quoted and spaced names', async () => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'winston-diff-'));
+  tempDirs.push(dir);
+  const git = (...args) => execFileSync('git', ['-c', 'user.name=Test', '-c', 'user.email=test@example.com', ...args], { cwd: dir });
+  git('init', '-q', '-b', 'main');
+  fs.writeFileSync(path.join(dir, 'café.rs'), 'fn a() {}\nfn b() {}\n');
+  fs.writeFileSync(path.join(dir, 'say "hi".rs'), 'fn c() {}\n');
+  fs.writeFileSync(path.join(dir, 'my vault.rs'), 'fn e() {}\n');
+  git('add', '.');
+  git('commit', '-q', '-m', 'base');
+  fs.writeFileSync(path.join(dir, 'café.rs'), 'fn a() {}\nfn b() { a() }\n');
+  fs.writeFileSync(path.join(dir, 'say "hi".rs'), 'fn c() { }\n');
+  fs.writeFileSync(path.join(dir, 'my vault.rs'), 'fn e() {}\nfn f() {}\n');
+  fs.writeFileSync(path.join(dir, 'naïve.rs'), 'fn d() {}\n');
+
+  const diff = await resolveDiff(dir, 'main');
+  const root = fs.realpathSync(dir);
+  assert.deepStrictEqual([...diff.files.values()].map(({ file, status, ranges }) => [path.relative(root, file), status, ranges]), [
+    ['café.rs', 'modified', [{ start: 2, end: 2 }]],
+    ['my vault.rs', 'modified', [{ start: 2, end: 2 }]],
+    ['say "hi".rs', 'modified', [{ start: 1, end: 1 }]],
+    ['naïve.rs', 'added', [{ start: 1, end: 2 }]]
+  ]);
+});
+
+test('classifyFindings marks findings in changed lines or changed functions as new', () => {
+  const change = {
+    base: 'main',
+    status: 'modified',
+    ranges: [{ start: 10, end: 12 }],
+    functions: [{ name: 'Vault::withdraw', startLine: 8, endLine: 20 }]
+  };
+  const at = (location) => ({ location });
+  const findings = classifyFindings([
+    at({ startLine: 11, endLine: 11 }),
+    at({ startLine: 18 }),
+    at({ startLine: 30, endLine: 32, function: 'Vault::withdraw' }),
+    at({ function: 'withdraw()' }),
+    at({ function: 'Vault::deposit' }),
+    at({})
+  ], change);
+  assert.deepStrictEqual(findings.map(finding => finding.change), ['new', 'new', 'existing', 'new', 'existing', 'existing']);
+  assert.deepStrictEqual(classifyFindings([at({ startLine: 99 })], { ...change, status: 'added' }).map(finding => finding.change), ['new']);
+
+  assert.strictEqual(formatFileChange(change), 'Modified since `main`: lines 10-12; changed functions `Vault::withdraw`');
+  assert.strictEqual(formatFileChange({ ...change, status: 'added' }), 'Added since `main`');
+});
@@ -0,0 +1,64 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const path = require('path');
+const { countBySeverity, createFinding, toFindings, toPortablePath } = require('../src/finding');
+
+const ROOT = path.resolve('repo');
+
+// An unwrap finding in `Vault::get` of src/vault.rs
+function unwrapAt(startLine, snippet = 'self.values.get(0).unwrap()') {
+  return {
+    ruleId: 'rust-unwrap',
+    title: 'Unwrap on Option',
+    location: { file: path.join(ROOT, 'src', 'vault.rs'), startLine, function: 'Vault::get', snippet }
+  };
+}
+
+function ids(buckets, root = ROOT) {
+  return toFindings(buckets, { analyzer: 'rust-static', root }).map(finding => finding.id);
+}
+
+test('createFinding fills rule metadata, the code evidence and a normalized location', () => {
+  const finding = createFinding({ ...unwrapAt(7), severity: 'medium', analyzer: 'rust-static', reachable: true });
+  assert.strictEqual(finding.confidence, 'high');
+  assert.strictEqual(finding.category.cwe, 'CWE-248');
+  assert.deepStrictEqual(finding.evidence, [{ kind: 'code', text: 'self.values.get(0).unwrap()' }]);
+  assert.strictEqual(finding.location.endLine, 7);
+  assert.strictEqual(finding.location.startColumn, null);
+  assert.strictEqual(finding.reachable, true);
+  assert.strictEqual(finding.id, undefined);
+  assert.throws(() => createFinding({ ruleId: 'x', severity: 'severe', location: {} }), /Unknown severity for x: severe/);
+});
+
+test('ids survive moving the code to another line and do not depend on severity', () => {
+  const [before] = ids({ medium: [unwrapAt(7)] });
+  const [after] = ids({ low: [unwrapAt(42)] });
+  assert.strictEqual(before, after);
+  assert.match(before, /^rust-unwrap:[0-9a-f]{16}$/);
+  assert.notStrictEqual(ids({ medium: [unwrapAt(7, 'other.unwrap()')] })[0], before);
+});
+
+test('the same issue twice in the same code gets numbered ids', () => {
+  const twice = ids({ medium: [unwrapAt(7), unwrapAt(9)] });
+  assert.strictEqual(twice.length, 2);
+  assert.notStrictEqual(twice[0], twice[1]);
+  assert.strictEqual(twice[0], ids({ medium: [unwrapAt(3)] })[0]);
+});
+
+test('ids are relative to the audit root, not to where the audit runs', () => {
+  const moved = path.resolve('elsewhere', 'checkout');
+  const finding = unwrapAt(7);
+  const elsewhere = { ...finding, location: { ...finding.location, file: path.join(moved, 'src', 'vault.rs') } };
+  assert.deepStrictEqual(ids({ medium: [elsewhere] }, moved), ids({ medium: [finding] }));
+});
+
+test('toPortablePath uses forward slashes relative to the root', () => {
+  assert.strictEqual(toPortablePath(path.join(ROOT, 'src', 'a', 'mod.rs'), ROOT), 'src/a/mod.rs');
+  assert.strictEqual(toPortablePath('src/lib.rs'), 'src/lib.rs');
+});
+
+test('toFindings lists the most severe first', () => {
+  const findings = toFindings({ low: [unwrapAt(1)], critical: [unwrapAt(2, 'a.unwrap()')] }, { analyzer: 'rust-static' });
+  assert.deepStrictEqual(findings.map(finding => finding.severity), ['critical', 'low']);
+  assert.deepStrictEqual(countBySeverity(findings), { critical: 1, high: 0, medium: 0, low: 1, info: 0 });
+});
@@ -0,0 +1,105 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const {
+  configureCache, hashSource, cacheKey, readCache, writeCache, describeCacheUse, withCacheNote
+} = require('../src/llm-cache');
+const { configureProvider, completeResult } = require('../src/llm-provider');
+
+const cacheDir = fs.mkdtempSync(path.join(os.tmpdir(), 'winston-cache-'));
+
+test.after(() => fs.rmSync(cacheDir, { recursive: true, force: true }));
+
+const PARAMS = {
+  template: 'rust-analysis',
+  version: 3,
+  source: 'pub fn withdraw() {}\n',
+  provider: 'anthropic',
+  model: 'claude-3-sonnet-20240229',
+  maxTokens: 4000,
+  temperature: null,
+  request: { system: 'You are an auditor.', messages: [{ role: 'user', content: 'Audit this.' }] }
+};
+
+test('the key changes with the template version, the source, the model settings and the request', () => {
+  const key = cacheKey(PARAMS);
+  assert.match(key, /^[0-9a-f]{64}$/);
+  assert.strictEqual(cacheKey({ ...PARAMS }), key);
+  [
+    { version: 4 },
+    { source: 'pub fn withdraw() { }\n' },
+    { model: 'claude-3-opus-20240229' },
+    { temperature: 0 },
+    { request: { ...PARAMS.request, system: 'You are a reviewer.' } }
+  ].forEach(change => assert.notStrictEqual(cacheKey({ ...PARAMS, ...change }), key, JSON.stringify(change)));
+  // The files of a crate are hashed separately, so moving text between files changes the hash
+  assert.notStrictEqual(hashSource(['ab', 'c']), hashSource(['a', 'bc']));
+  assert.strictEqual(hashSource('abc'), hashSource(['abc']));
+});
+
+test('entries are read back unless the cache is refreshed or disabled', () => {
+  const key = cacheKey(PARAMS);
+  configureCache({ cacheDir });
+  assert.strictEqual(readCache(key), null);
+  writeCache(key, { template: 'rust-analysis', version: 3, model: PARAMS.model, response: 'No issues.' });
+  assert.ok(fs.existsSync(path.join(cacheDir, key.slice(0, 2), `${key}.json`)));
+  const entry = readCache(key);
+  assert.strictEqual(entry.key, key);
+  assert.strictEqual(entry.response, 'No issues.');
+  assert.ok(!Number.isNaN(Date.parse(entry.createdAt)));
+
+  configureCache({ cacheDir, refresh: true });
+  assert.strictEqual(readCache(key), null);
+  writeCache(key, { template: 'rust-analysis', version: 3, model: PARAMS.model, response: 'Refreshed.' });
+  configureCache({ cacheDir });
+  assert.strictEqual(readCache(key).response, 'Refreshed.');
+
+  configureCache({ cacheDir, cache: false });
+  assert.strictEqual(readCache(key), null);
+  const other = cacheKey({ ...PARAMS, version: 9 });
+  writeCache(other, { response: 'Not stored.' });
+  assert.ok(!fs.existsSync(path.join(cacheDir, other.slice(0, 2), `${other}.json`)));
+});
+
+test('completeResult answers from the cache without calling the provider', async () => {
+  const previous = process.env.OPENAI_API_KEY;
+  process.env.OPENAI_API_KEY = 'key';
+  try {
+    configureCache({ cacheDir });
+    const config = configureProvider({ provider: 'openai', model: 'gpt-4o' });
+    const request = { system: 'System', messages: [{ role: 'user', content: 'Prompt' }] };
+    const cache = { template: 'explain', version: 1, source: 'fn main() {}' };
+    const key = cacheKey({ ...cache, provider: 'openai', model: 'gpt-4o', maxTokens: config.maxTokens, temperature: null, request });
+    writeCache(key, { template: 'explain', version: 1, model: 'gpt-4o', response: 'From the cache.' });
+
+    const result = await completeResult('Prompt', 'System', cache);
+    assert.strictEqual(result.text, 'From the cache.');
+    assert.strictEqual(result.cached, true);
+    assert.strictEqual(result.template, 'explain');
+  } finally {
+    if (previous === undefined) delete process.env.OPENAI_API_KEY;
+    else process.env.OPENAI_API_KEY = previous;
+  }
+});
+
+test('reports built from cached responses say so under their title', () => {
+  const fresh = { cached: false, cachedAt: null, template: 'explain', version: 1, model: 'gpt-4o' };
+  const cached = { ...fresh, cached: true, cachedAt: '2026-01-02T00:00:00.000Z' };
+  const older = { ...cached, cachedAt: '2026-01-01T00:00:00.000Z' };
+
+  assert.strictEqual(describeCacheUse([fresh, null]), null);
+  assert.strictEqual(
+    describeCacheUse([cached, older]),
+    '*Cached result from 2026-01-01T00:00:00.000Z (prompt explain v1, model gpt-4o); rerun with --refresh to regenerate.*'
+  );
+  assert.match(describeCacheUse([cached, fresh]), /^\*1 of 2 responses cached from 2026-01-02/);
+
+  assert.strictEqual(withCacheNote('# Report\nBody\n', [fresh]), '# Report\nBody\n');
+  assert.strictEqual(
+    withCacheNote('# Report\nBody\n', [cached]),
+    `# Report\n\n${describeCacheUse([cached])}\nBody\n`
+  );
+  assert.strictEqual(withCacheNote('Body\n', [cached]), `${describeCacheUse([cached])}\n\nBody\n`);
+});
@@ -0,0 +1,50 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { configureProvider, isLLMAvailable, modelName, unavailableReason } = require('../src/llm-provider');
+
+const ENV = ['WINSTON_LLM_PROVIDER', 'WINSTON_LLM_MODEL', 'WINSTON_LLM_RECORDINGS', 'WINSTON_LLM_MAX_TOKENS',
+  'WINSTON_LLM_TEMPERATURE', 'WINSTON_LLM_BASE_URL', 'WINSTON_LLM_API_KEY', 'ANTHROPIC_API_KEY', 'OPENAI_API_KEY'];
+const saved = Object.fromEntries(ENV.map(name => [name, process.env[name]]));
+
+test.beforeEach(() => ENV.forEach(name => delete process.env[name]));
+test.after(() => ENV.forEach(name => {
+  if (saved[name] === undefined) delete process.env[name];
+  else process.env[name] = saved[name];
+}));
+
+test('options take precedence over the environment and each provider has its default model', () => {
+  process.env.WINSTON_LLM_PROVIDER = 'openai';
+  process.env.WINSTON_LLM_MODEL = 'gpt-4o-mini';
+  assert.strictEqual(configureProvider().model, 'gpt-4o-mini');
+  assert.strictEqual(modelName(), 'gpt-4o-mini (openai)');
+
+  const settings = configureProvider({ provider: 'Ollama', model: 'qwen2.5-coder', baseUrl: 'http://gpu:11434/v1/' });
+  assert.strictEqual(settings.provider, 'ollama');
+  assert.strictEqual(settings.baseUrl, 'http://gpu:11434/v1');
+  assert.strictEqual(modelName(), 'qwen2.5-coder (ollama)');
+
+  delete process.env.WINSTON_LLM_PROVIDER;
+  delete process.env.WINSTON_LLM_MODEL;
+  configureProvider();
+  assert.strictEqual(modelName(), 'claude-3-sonnet-20240229 (anthropic)');
+});
+
+test('hosted providers need an API key unless they are pointed at another server', () => {
+  configureProvider({ provider: 'anthropic' });
+  assert.strictEqual(isLLMAvailable(), false);
+  assert.strictEqual(unavailableReason(), 'ANTHROPIC_API_KEY not found in environment variables');
+  configureProvider({ provider: 'openai', baseUrl: 'http://localhost:8000/v1' });
+  assert.strictEqual(isLLMAvailable(), true);
+  process.env.ANTHROPIC_API_KEY = 'key';
+  configureProvider({ provider: 'anthropic' });
+  assert.strictEqual(isLLMAvailable(), true);
+  configureProvider({ provider: 'llamacpp' });
+  assert.strictEqual(isLLMAvailable(), true);
+});
+
+test('invalid settings are rejected', () => {
+  assert.throws(() => configureProvider({ provider: 'gemini' }), /Unknown LLM provider: gemini/);
+  assert.throws(() => configureProvider({ maxTokens: '1.5' }), /Invalid max tokens: 1.5/);
+  assert.throws(() => configureProvider({ temperature: '3' }), /Invalid temperature: 3/);
+  assert.throws(() => configureProvider({ provider: 'mock' }), /needs a directory of recorded responses/);
+});
@@ -0,0 +1,104 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const path = require('path');
+const { toFindings } = require('../src/finding');
+const { parseFormats, reportName, toJsonReport, toSarifReport } = require('../src/report-formats');
+
+function sampleFindings() {
+  return toFindings({
+    low: [{
+      ruleId: 'rust-hashmap-without-capacity',
+      title: 'HashMap Without Capacity Hint',
+      description: 'HashMap::new() in `Vault::new`.',
+      location: { startLine: 7, startColumn: 5, endLine: 7, endColumn: 20, function: 'Vault::new', snippet: 'HashMap::new()' }
+    }],
+    high: [
+      {
+        ruleId: 'rust-missing-authorization',
+        title: 'Missing Authorization on Privileged State Change',
+        description: '`Vault::set_owner` writes `self.owner` without checking the caller.',
+        location: { startLine: 12, startColumn: 9, endLine: 12, endColumn: 30, function: 'Vault::set_owner', snippet: 'self.owner = owner;' }
+      },
+      {
+        ruleId: 'rust-missing-authorization',
+        title: 'Missing Authorization on Privileged State Change',
+        description: '`Vault::set_fee` writes `self.fee` without checking the caller.',
+        location: { startLine: 20, function: 'Vault::set_fee' }
+      }
+    ]
+  }, { analyzer: 'rust-static', file: 'src/vault.rs' });
+}
+
+test('parseFormats accepts comma-separated formats and rejects unknown ones', () => {
+  assert.deepStrictEqual(parseFormats(undefined), ['markdown']);
+  assert.deepStrictEqual(parseFormats('md, SARIF,json,sarif'), ['markdown', 'sarif', 'json']);
+  assert.throws(() => parseFormats('xml'), /Unsupported output format: xml/);
+});
+
+test('report names follow the path below the crate, or the file name outside it', () => {
+  const crateDir = path.resolve('crates/vault');
+  assert.strictEqual(reportName(path.join(crateDir, 'src/foo/mod.rs'), crateDir), 'src_foo_mod.rs');
+  assert.strictEqual(reportName(path.join(crateDir, 'src/lib.rs'), crateDir), 'src_lib.rs');
+  assert.strictEqual(reportName(path.resolve('other/lib.rs'), crateDir), 'lib.rs');
+  assert.strictEqual(reportName('src/lib.rs', null), 'lib.rs');
+});
+
+test('the JSON report lists findings most severe first with a summary', () => {
+  const report = toJsonReport({ analyzer: 'rust-static', filePath: 'src/vault.rs', crate: null, findings: sampleFindings() });
+  assert.strictEqual(report.schemaVersion, '1.4');
+  assert.deepStrictEqual(report.findings.map(finding => finding.severity), ['high', 'high', 'low']);
+  assert.strictEqual(report.summary.high, 2);
+  assert.strictEqual(report.summary.low, 1);
+  assert.strictEqual(report.findings[0].category.cwe, 'CWE-862');
+});
+
+test('SARIF rules are listed once, with their static description', () => {
+  const sarif = toSarifReport({ analyzer: 'rust-static', filePath: 'src/vault.rs', crate: null, findings: sampleFindings() });
+  const { rules } = sarif.runs[0].tool.driver;
+  assert.deepStrictEqual(rules.map(rule => rule.id), ['rust-missing-authorization', 'rust-hashmap-without-capacity']);
+  assert.strictEqual(rules[0].fullDescription.text, 'A function changes privileged state without checking who the caller is.');
+  assert.ok(rules[0].properties.tags.includes('external/cwe/cwe-862'));
+  assert.deepStrictEqual(sarif.runs[0].results.map(result => [result.ruleIndex, result.level]), [[0, 'error'], [0, 'error'], [1, 'note']]);
+});
+
+test('SARIF regions leave out unknown columns and carry no excerpt', () => {
+  const sarif = toSarifReport({ analyzer: 'rust-static', filePath: 'src/vault.rs', crate: null, findings: sampleFindings() });
+  const [full, partial] = sarif.runs[0].results.map(result => result.locations[0].physicalLocation);
+  assert.deepStrictEqual(full, {
+    artifactLocation: { uri: 'src/vault.rs' },
+    region: { startLine: 12, startColumn: 9, endLine: 12, endColumn: 30 }
+  });
+  assert.deepStrictEqual(partial.region, { startLine: 20, endLine: 20 });
+  assert.strictEqual(sarif.runs[0].results[0].partialFingerprints['winstonFindingId/v1'], sampleFindings()[0].id);
+});
+
+test('suppressed findings are listed apart in JSON and marked in SARIF', () => {
+  const [setOwner, setFee, hashMap] = sampleFindings();
+  const suppressions = {
+    findings: [setOwner],
+    suppressed: [
+      { ...setFee, suppression: { kind: 'baseline', reason: 'Fee is set by governance' } },
+      { ...hashMap, suppression: { kind: 'inline', reason: null } }
+    ],
+    fixed: [{ id: 'old', ruleId: 'rust-unwrap', file: 'src/vault.rs' }],
+    baseline: '.winston-baseline.json'
+  };
+  const params = { analyzer: 'rust-static', filePath: 'src/vault.rs', findings: [setOwner], suppressions };
+
+  const json = toJsonReport(params);
+  assert.strictEqual(json.baseline, '.winston-baseline.json');
+  assert.deepStrictEqual(json.suppressed.map(finding => [finding.ruleId, finding.suppression.kind]), [
+    ['rust-missing-authorization', 'baseline'],
+    ['rust-hashmap-without-capacity', 'inline']
+  ]);
+  assert.deepStrictEqual(json.fixed, suppressions.fixed);
+
+  const results = toSarifReport(params).runs[0].results;
+  assert.deepStrictEqual(results.map(result => [result.baselineState, result.suppressions]), [
+    ['new', undefined],
+    ['unchanged', [{ kind: 'external', justification: 'Fee is set by governance' }]],
+    ['new', [{ kind: 'inSource' }]]
+  ]);
+  const withoutBaseline = toSarifReport({ ...params, suppressions: { ...suppressions, baseline: null } }).runs[0].results;
+  assert.ok(withoutBaseline.every(result => !('baselineState' in result)));
+});
@@ -0,0 +1,70 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { parse, visit } = require('../src/rust-parser');
+const { collectDefinitions, enclosingName, sourceLocation } = require('../src/rust-ast');
+
+// Every node of a type, in source order
+function findAll(ast, type) {
+  const nodes = [];
+  visit(ast, { [type]: node => { nodes.push(node); } });
+  return nodes;
+}
+
+test('sourceLocation gives 1-based lines and columns and a dedented excerpt', () => {
+  const code = 'impl Vault {\n    fn get(&self) -> u64 {\n        self.values.get(0).unwrap()\n    }\n}\n';
+  const ast = parse(code);
+  const definitions = collectDefinitions(ast);
+  const call = findAll(ast, 'MethodCall').find(node => node.method === 'unwrap');
+  assert.deepStrictEqual(sourceLocation(code, call, { filePath: 'vault.rs', definitions }), {
+    file: 'vault.rs',
+    startLine: 3,
+    startColumn: 9,
+    endLine: 3,
+    endColumn: 36,
+    function: 'Vault::get',
+    snippet: 'self.values.get(0).unwrap()'
+  });
+});
+
+test('long excerpts are cut after five lines', () => {
+  const code = 'fn f() {\n    let a = 1;\n    let b = 2;\n    let c = 3;\n    let d = 4;\n    let e = 5;\n    let g = 6;\n}';
+  const ast = parse(code);
+  const location = sourceLocation(code, ast.items[0], { filePath: 'f.rs', definitions: null });
+  assert.deepStrictEqual(location.snippet.split('\n'), [
+    'fn f() {',
+    '    let a = 1;',
+    '    let b = 2;',
+    '    let c = 3;',
+    '    let d = 4;',
+    '// ...'
+  ]);
+  assert.strictEqual(location.function, null);
+});
+
+test('enclosingName names the function holding a node, or its impl', () => {
+  const code = [
+    'mod inner {',
+    '    fn helper() { let a = 1; }',
+    '}',
+    'fn outer() {',
+    '    fn nested() {}',
+    '    let b = 2;',
+    '}',
+    'struct S;',
+    'impl S {',
+    '    const K: u8 = 3;',
+    '    fn m(&self) { let c = 4; }',
+    '}',
+    'fn after() { let d = 5; }'
+  ].join('\n');
+  const ast = parse(code);
+  const definitions = collectDefinitions(ast);
+  const names = findAll(ast, 'Literal').map(literal => [literal.value, enclosingName(definitions, literal)]);
+  assert.deepStrictEqual(names, [
+    ['1', 'inner::helper'],
+    ['2', 'outer'],
+    ['3', 'S'],
+    ['4', 'S::m'],
+    ['5', 'after']
+  ]);
+});
@@ -0,0 +1,79 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const {
+  buildCallGraph,
+  buildCrateCallGraph,
+  entryPointsReaching,
+  findEntryPoints,
+  reachableFrom
+} = require('../src/rust-callgraph');
+
+const VAULT = `
+trait Pricing { fn price(&self) -> u64; }
+struct Fixed;
+impl Pricing for Fixed { fn price(&self) -> u64 { 1 } }
+struct Curve;
+impl Pricing for Curve { fn price(&self) -> u64 { helper() } }
+fn helper() -> u64 { 2 }
+pub struct Vault { total: u64 }
+impl Vault {
+    pub fn new() -> Self { Self::empty() }
+    fn empty() -> Self { Vault { total: 0 } }
+    pub fn quote(&self, pricing: &dyn Pricing) -> u64 { pricing.price() + self.fee() }
+    fn fee(&self) -> u64 { Vault::base() }
+    fn base() -> u64 { 3 }
+    fn orphan(&self) {}
+}
+#[test]
+fn checks() { Vault::new(); }
+`;
+
+test('calls are resolved through paths, receivers and traits', () => {
+  const graph = buildCallGraph(VAULT);
+  assert.deepStrictEqual(graph.edges.map(edge => `${edge.from} -> ${edge.to} (${edge.kind})`), [
+    'Curve::price -> helper (call)',
+    'Vault::new -> Vault::empty (call)',
+    'Vault::quote -> Fixed::price (trait)',
+    'Vault::quote -> Curve::price (trait)',
+    'Vault::quote -> Vault::fee (method)',
+    'Vault::fee -> Vault::base (call)',
+    'checks -> Vault::new (call)'
+  ]);
+});
+
+test('entry points are pub functions and trait methods, not tests', () => {
+  const graph = buildCallGraph(VAULT);
+  const entryPoints = findEntryPoints(graph);
+  assert.deepStrictEqual(entryPoints, ['Fixed::price', 'Curve::price', 'Vault::new', 'Vault::quote']);
+  assert.deepStrictEqual(findEntryPoints(graph, ['fee']), ['Vault::fee']);
+
+  const reachable = reachableFrom(graph, entryPoints);
+  assert.strictEqual(reachable.has('Vault::base'), true);
+  assert.strictEqual(reachable.has('Vault::orphan'), false);
+  assert.strictEqual(reachable.has('checks'), false);
+  assert.deepStrictEqual(entryPointsReaching(graph, 'helper', entryPoints), ['Curve::price', 'Vault::quote']);
+});
+
+test('a crate graph follows calls across module files', () => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'winston-callgraph-'));
+  try {
+    fs.mkdirSync(path.join(dir, 'src'));
+    fs.writeFileSync(path.join(dir, 'src', 'lib.rs'), 'mod state;\npub fn run() -> u64 { state::load() }\n');
+    fs.writeFileSync(path.join(dir, 'src', 'state.rs'), 'pub fn load() -> u64 { inner() }\nfn inner() -> u64 { 1 }\nfn unused() {}\n');
+
+    const graph = buildCrateCallGraph({ root: path.join(dir, 'src', 'lib.rs') });
+    assert.deepStrictEqual([...graph.nodes.values()].map(node => [node.id, path.relative(dir, node.file)]), [
+      ['run', path.join('src', 'lib.rs')],
+      ['state::load', path.join('src', 'state.rs')],
+      ['state::inner', path.join('src', 'state.rs')],
+      ['state::unused', path.join('src', 'state.rs')]
+    ]);
+    assert.deepStrictEqual(graph.edges.map(edge => `${edge.from} -> ${edge.to}`), ['run -> state::load', 'state::load -> state::inner']);
+    assert.deepStrictEqual([...reachableFrom(graph, findEntryPoints(graph))], ['run', 'state::load', 'state::inner']);
+  } finally {
+    fs.rmSync(dir, { recursive: true, force: true });
+  }
+});
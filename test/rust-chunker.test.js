@@ -0,0 +1,145 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { chunkRustFile, renderChunk, describeChunk, mergeChunkReports } = require('../src/rust-chunker');
+
+const VAULT = [
+  'use std::collections::HashMap;',
+  '',
+  'const LIMIT: u64 = 1_000;',
+  '',
+  '/// A vault of balances',
+  'pub struct Vault {',
+  '    balances: HashMap<u64, u64>,',
+  '}',
+  '',
+  'impl Vault {',
+  '    pub fn deposit(&mut self, who: u64, amount: u64) {',
+  '        let entry = self.balances.entry(who).or_insert(0);',
+  '        *entry = *entry + amount;',
+  '    }',
+  '',
+  '    // Withdraw up to the limit',
+  '    pub fn withdraw(&mut self, who: u64, amount: u64) -> u64 {',
+  '        let balance = self.balances.get(&who).copied().unwrap_or(0);',
+  '        let paid = clamp(amount, LIMIT);',
+  '        self.balances.insert(who, balance - paid);',
+  '        paid',
+  '    }',
+  '}',
+  '',
+  '/// The smaller of the amount and the limit',
+  'fn clamp(amount: u64, limit: u64) -> u64 {',
+  '    if amount > limit {',
+  '        return limit;',
+  '    }',
+  '    amount',
+  '}',
+  ''
+].join('\n');
+
+test('files that fit in one chunk or do not parse are not chunked', () => {
+  assert.strictEqual(chunkRustFile(VAULT), null);
+  assert.strictEqual(chunkRustFile('fn broken( {\n'.repeat(100), { chunkTokens: 40 }), null);
+});
+
+test('files are split between items and impl members, keeping every line and the impl header', () => {
+  const chunks = chunkRustFile(VAULT, { chunkTokens: 140 });
+  assert.deepStrictEqual(chunks.map(chunk => [chunk.index, chunk.count, chunk.items, chunk.oversized]), [
+    [1, 3, ['LIMIT', 'Vault', 'Vault::deposit'], false],
+    [2, 3, ['Vault::withdraw'], false],
+    [3, 3, ['clamp'], false]
+  ]);
+  // Only the `use` line, which goes to the context of every chunk, and the end of the file are left out
+  const covered = new Set(chunks.flatMap(chunk => chunk.lines));
+  assert.deepStrictEqual(VAULT.split('\n').map((line, i) => i + 1).filter(line => !covered.has(line)), [1, 32]);
+
+  assert.strictEqual(describeChunk(chunks[1]), 'part 2 of 3 (lines 9-23)');
+  assert.strictEqual(renderChunk(VAULT, chunks[1], (number, text) => `${number}|${text}`), [
+    '9|',
+    '10|impl Vault {',
+    '|    // ...',
+    '15|',
+    '16|    // Withdraw up to the limit',
+    '17|    pub fn withdraw(&mut self, who: u64, amount: u64) -> u64 {',
+    '18|        let balance = self.balances.get(&who).copied().unwrap_or(0);',
+    '19|        let paid = clamp(amount, LIMIT);',
+    '20|        self.balances.insert(who, balance - paid);',
+    '21|        paid',
+    '22|    }',
+    '23|}'
+  ].join('\n'));
+});
+
+test('the context of a chunk has the uses, the definitions it refers to and the functions it calls', () => {
+  const chunks = chunkRustFile(VAULT, { chunkTokens: 140 });
+  assert.strictEqual(chunks[1].context, [
+    'use std::collections::HashMap;',
+    '',
+    '// LIMIT (line 3)',
+    'const LIMIT: u64 = 1_000;',
+    '',
+    '// Vault (line 5)',
+    '/// A vault of balances',
+    'pub struct Vault {',
+    '    balances: HashMap<u64, u64>,',
+    '}',
+    '',
+    '// clamp (lines 25-31, in part 3)',
+    'fn clamp(amount: u64, limit: u64) -> u64 { /* ... */ }'
+  ].join('\n'));
+  assert.strictEqual(chunks[2].context, 'use std::collections::HashMap;');
+
+  // With less room, definitions are outlined and function signatures left out
+  const small = chunkRustFile(VAULT, { chunkTokens: 100 });
+  assert.match(small[1].context, /\/\/ Vault \(line 5\)\npub struct Vault \{ \/\* \.\.\. \*\/ \}$/);
+});
+
+test('items larger than a chunk get a chunk of their own', () => {
+  const chunks = chunkRustFile(VAULT, { chunkTokens: 40 });
+  assert.deepStrictEqual(chunks.filter(chunk => chunk.oversized).map(chunk => chunk.items), [
+    ['Vault::deposit'],
+    ['Vault::withdraw'],
+    ['clamp']
+  ]);
+});
+
+test('with a focus, only the items that overlap the changed lines are chunked', () => {
+  const [chunk, ...rest] = chunkRustFile(VAULT, { focus: { ranges: [{ start: 20, end: 20 }] } });
+  assert.strictEqual(rest.length, 0);
+  assert.deepStrictEqual(chunk.items, ['Vault::withdraw']);
+  assert.strictEqual(describeChunk(chunk), 'the changed code (lines 9-23)');
+  assert.deepStrictEqual(chunkRustFile(VAULT, { focus: { ranges: [{ start: 1, end: 1 }] } }), []);
+});
+
+test('chunk reports are merged a section per part, dropping repeated findings', () => {
+  const chunks = chunkRustFile(VAULT, { chunkTokens: 140 });
+  const { markdown, duplicates } = mergeChunkReports('Vulnerabilities: vault.rs', [
+    { chunk: chunks[0], text: '# Analysis\n\n## Summary\nDeposits.\n\n## 1. Integer Overflow (Confidence: High)\nIn `deposit`.' },
+    { chunk: chunks[1], text: '# Analysis\n\n## Summary\nWithdrawals.\n\n## Vulnerability #2: integer overflow\nAgain.\n\n## Underflow\nIn `withdraw`.' }
+  ], { dedupe: true });
+  assert.strictEqual(duplicates, 1);
+  assert.strictEqual(markdown, [
+    '# Vulnerabilities: vault.rs',
+    '',
+    '## Part 1 of 3 (lines 2-23)',
+    '',
+    '### Analysis',
+    '',
+    '#### Summary',
+    'Deposits.',
+    '',
+    '#### 1. Integer Overflow (Confidence: High)',
+    'In `deposit`.',
+    '',
+    '## Part 2 of 3 (lines 9-23)',
+    '',
+    '### Analysis',
+    '',
+    '#### Summary',
+    'Withdrawals.',
+    '',
+    '#### Underflow',
+    'In `withdraw`.',
+    ''
+  ].join('\n'));
+});
@@ -0,0 +1,123 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { createFinding } = require('../src/finding');
+const {
+  buildSourceIndex, extractClaims, verifyClaim, groundFinding, groundMarkdown, formatGrounding
+} = require('../src/rust-grounding');
+
+const VAULT = [
+  'pub struct Vault {',
+  '    owner: u64,',
+  '    balance: u64,',
+  '}',
+  '',
+  'impl Vault {',
+  '    pub fn withdraw(&mut self, amount: u64) -> u64 {',
+  '        self.balance = self.balance - amount; // may underflow',
+  '        self.balance',
+  '    }',
+  '}',
+  ''
+].join('\n');
+
+const index = buildSourceIndex([{ file: 'src/vault.rs', code: VAULT }]);
+
+function check(text) {
+  return extractClaims(text).map(claim => [claim.kind, claim.text, verifyClaim(claim, index).status]);
+}
+
+test('identifiers, signatures, snippets and line numbers are checked against the source', () => {
+  assert.deepStrictEqual(check('`Vault::withdraw` subtracts from `self.balance` and never reads `self.deposits`.'), [
+    ['identifier', 'Vault::withdraw', 'verified'],
+    ['identifier', 'self.balance', 'verified'],
+    ['identifier', 'self.deposits', 'unverified']
+  ]);
+  assert.deepStrictEqual(check('`fn withdraw(&mut self, amount: u64)` vs `fn withdraw(&self)` vs `fn deposit(&mut self)`'), [
+    ['signature', 'fn withdraw(&mut self, amount: u64)', 'verified'],
+    ['signature', 'fn withdraw(&self)', 'unverified'],
+    ['signature', 'fn deposit(&mut self)', 'unverified']
+  ]);
+  assert.deepStrictEqual(check('On line 8, `self.balance - amount` underflows; lines 20-25 are fine and `a += b;` is not there.'), [
+    ['line', 'line 8', 'verified'],
+    ['snippet', 'self.balance - amount', 'verified'],
+    ['line', 'lines 20-25', 'unverified'],
+    ['snippet', 'a += b;', 'unverified']
+  ]);
+  assert.deepStrictEqual(check('`Option` and `unwrap()`'), [
+    ['identifier', 'Option', 'unchecked'],
+    ['identifier', 'unwrap()', 'unchecked']
+  ]);
+});
+
+test('fenced Rust blocks are compared line by line, regardless of formatting and comments', () => {
+  const cited = '```rust\nself.balance =   self.balance - amount;\nself.balance\n```';
+  assert.deepStrictEqual(check(cited), [['snippet', 'self.balance =   self.balance - amount;\nself.balance\n', 'verified']]);
+  const [claim] = extractClaims('```rust\nself.balance = 0;\nself.balance\n```');
+  assert.deepStrictEqual(verifyClaim(claim, index), { status: 'unverified', reason: '1 of 2 lines do not appear in the source' });
+  assert.deepStrictEqual(check('```solidity\nbalance -= amount;\n```'), []);
+});
+
+test('proposed fixes are not checked', () => {
+  assert.deepStrictEqual(check('Fix: use `self.balance.checked_sub(amount)` instead.'), [
+    ['snippet', 'self.balance.checked_sub(amount)', 'unchecked']
+  ]);
+  assert.deepStrictEqual(check('Recommended change:\n\n```rust\nlet left = self.balance.checked_sub(amount)?;\n```'), [
+    ['snippet', 'let left = self.balance.checked_sub(amount)?;\n', 'unchecked']
+  ]);
+});
+
+test('groundFinding scores the function, lines and cited code of a finding', () => {
+  const finding = createFinding({
+    ruleId: 'llm-underflow',
+    severity: 'high',
+    description: '`self.balance` is reduced by `amount` without a check, see `self.limit`.',
+    location: { file: 'src/vault.rs', startLine: 8, function: 'Vault::withdraw' },
+    evidence: [{ kind: 'note', text: 'The model cites lines 40-41, but the file has 12 lines' }]
+  });
+  const grounding = groundFinding(finding, index);
+  assert.strictEqual(grounding.verified, 4);
+  assert.deepStrictEqual(grounding.unverified, [
+    { kind: 'identifier', text: 'self.limit', reason: '`limit` does not appear in the source' },
+    { kind: 'line', text: 'lines 40-41', reason: 'the file has 12 lines' }
+  ]);
+  assert.strictEqual(grounding.score, 0.67);
+  assert.strictEqual(
+    formatGrounding(grounding),
+    '*Grounding 0.67 · unverified: `self.limit` (`limit` does not appear in the source); `lines 40-41` (the file has 12 lines)*'
+  );
+
+  const unlocated = createFinding({ ruleId: 'llm-design', severity: 'info', description: 'The design is sound.', location: {} });
+  assert.strictEqual(formatGrounding(groundFinding(unlocated, index)), '*Grounding: no code references to verify*');
+});
+
+test('groundMarkdown marks unverified claims and scores each section', () => {
+  const report = [
+    '# Vault audit',
+    '',
+    '## Underflow',
+    '`Vault::withdraw` subtracts `amount` from `self.reserve`.',
+    '',
+    '## Design',
+    'No code here.',
+    ''
+  ].join('\n');
+  const grounded = groundMarkdown(report, index);
+  assert.strictEqual(grounded.score, 0.67);
+  assert.deepStrictEqual(grounded.unverified.map(claim => claim.text), ['self.reserve']);
+  assert.strictEqual(grounded.markdown, [
+    '# Vault audit',
+    '',
+    '## Underflow',
+    '',
+    '*Grounding: 0.67 (2 of 3 code references verified)*',
+    '`Vault::withdraw` subtracts `amount` from `self.reserve` *(unverified)*.',
+    '',
+    '## Design',
+    'No code here.',
+    '',
+    '---',
+    '',
+    '*Grounding: 0.67. 2 code reference(s) verified against the source, 1 unverified.*',
+    ''
+  ].join('\n'));
+});
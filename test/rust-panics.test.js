@@ -0,0 +1,47 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const path = require('path');
+const { parse } = require('../src/rust-parser');
+const { collectDefinitions } = require('../src/rust-ast');
+const { analyzePanicPaths, describeEntryPoints } = require('../src/rust-panics');
+
+// Panic sources and reaching entry points of each function with a source
+function panicPaths(code) {
+  const ast = parse(code);
+  return Object.fromEntries(analyzePanicPaths(ast, code, collectDefinitions(ast)).functions
+    .map(({ fn, sources, reachedFrom }) => [fn.qualifiedName, { kinds: sources.map(source => source.kind), reachedFrom }]));
+}
+
+test('each panic source is traced to the entry points that reach it', () => {
+  const paths = panicPaths(fs.readFileSync(path.join(__dirname, '..', 'sample', 'PanicPaths.rs'), 'utf8'));
+  assert.deepStrictEqual(paths, {
+    'Pool::share_price': { kinds: ['division'], reachedFrom: ['Pool::share_price'] },
+    'Pool::share_value': { kinds: ['index'], reachedFrom: ['Pool::share_value'] },
+    'Pool::withdraw': { kinds: ['expect', 'assert'], reachedFrom: ['Pool::withdraw'] },
+    'Pool::migrate': { kinds: ['macro', 'macro'], reachedFrom: ['Pool::migrate'] },
+    'Pool::prefix': { kinds: ['slice'], reachedFrom: ['Pool::prefix'] },
+    'Pool::reserve': { kinds: ['index'], reachedFrom: ['Pool::share_price'] },
+    'Pool::unused': { kinds: ['index'], reachedFrom: [] }
+  });
+});
+
+test('guarded and constant divisors, in-bounds array indexes and tests are not panic sources', () => {
+  const paths = panicPaths(`
+struct P { total: u64, root: [u8; 32] }
+impl P {
+    pub fn guarded(&self, x: u64) -> u64 { if self.total == 0 { return 0; } x / self.total }
+    pub fn constant(&self, x: u64) -> u64 { x / 10_000 }
+    pub fn first(&self) -> u8 { self.root[0] }
+    pub fn checked(&self, x: u64) -> Option<u64> { x.checked_div(self.total) }
+}
+#[test]
+fn fails() { panic!("expected"); }`);
+  assert.deepStrictEqual(paths, {});
+});
+
+test('describeEntryPoints lists up to four entry points', () => {
+  assert.strictEqual(describeEntryPoints(['Pool::a', 'Pool::b']), '`Pool::a`, `Pool::b`');
+  assert.strictEqual(describeEntryPoints(['a', 'b', 'c', 'd', 'e', 'f']), '`a`, `b`, `c`, `d` and 2 more');
+  assert.strictEqual(describeEntryPoints([]), 'no public entry point');
+});
@@ -0,0 +1,144 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { parse, visit, ParseError } = require('../src/rust-parser');
+
+// Statements of the body of the first item, a function
+function statements(code) {
+  return parse(code).items[0].body.stmts;
+}
+
+// The first node of a type anywhere in the tree
+function find(node, type) {
+  let found = null;
+  visit(node, {
+    [type]: (match) => {
+      if (!found) found = match;
+      return !found;
+    }
+  });
+  return found;
+}
+
+test('turbofish on methods and paths', () => {
+  const call = statements('fn f() { "1".parse::<u64>() }')[0].expr;
+  assert.strictEqual(call.type, 'MethodCall');
+  assert.strictEqual(call.method, 'parse');
+  assert.deepStrictEqual(call.turbofish.map(type => type.text), ['u64']);
+
+  const init = statements('fn f() { let v = Vec::<Vec<u8>>::new(); }')[0].init;
+  assert.strictEqual(init.type, 'Call');
+  assert.deepStrictEqual(init.callee.path.segments.map(segment => segment.name), ['Vec', 'new']);
+});
+
+test('nested generics closed by `>>`', () => {
+  const fields = parse('struct S { m: HashMap<String, Vec<Option<u64>>>, n: u8 }').items[0].fields;
+  assert.deepStrictEqual(fields.map(entry => entry.name), ['m', 'n']);
+  assert.strictEqual(fields[0].fieldType.path.name, 'HashMap');
+});
+
+test('closures with typed, untyped and tuple parameters', () => {
+  const closure = statements('fn f() { let g = |a: u64, b| a + b; }')[0].init;
+  assert.strictEqual(closure.type, 'Closure');
+  assert.deepStrictEqual(closure.params.map(param => param.name), ['a', 'b']);
+  assert.strictEqual(closure.params[0].paramType.text, 'u64');
+  assert.strictEqual(closure.params[1].paramType, null);
+  assert.strictEqual(closure.body.type, 'Binary');
+
+  const ast = parse('fn f() { let n = v.iter().map(|(k, _)| k).filter(move || true).count(); }');
+  assert.strictEqual(find(ast, 'Closure').params[0].pattern.type, 'TuplePat');
+});
+
+test('match arms with guards, alternatives and ranges', () => {
+  const match = statements('fn f(x: Option<u64>) -> u64 { match x { Some(n) if n > 10 => n, Some(0) | None => 0, Some(1..=9) => 1, _ => 2 } }')[0].expr;
+  assert.strictEqual(match.type, 'Match');
+  assert.strictEqual(match.arms.length, 4);
+  assert.strictEqual(match.arms[0].guard.type, 'Binary');
+  assert.strictEqual(match.arms[0].guard.operator, '>');
+  assert.strictEqual(match.arms[1].guard, null);
+  assert.strictEqual(match.arms[1].pattern.type, 'OrPat');
+});
+
+test('outer, inner and statement attributes', () => {
+  const ast = parse('#![allow(dead_code)]\n#[derive(Debug, Clone)]\n#[cfg_attr(feature = "x", derive(Copy))]\npub struct A;\n#[ink(message, payable)]\npub fn g() { #[allow(unused)] let x = 1; }');
+  assert.deepStrictEqual(ast.attrs.map(attr => attr.path), ['allow']);
+  assert.deepStrictEqual(ast.items[0].attrs.map(attr => attr.path), ['derive', 'cfg_attr']);
+  assert.match(ast.items[0].attrs[1].args, /derive\s*\(\s*Copy\s*\)/);
+  const fn = ast.items[1];
+  assert.strictEqual(fn.attrs[0].path, 'ink');
+  assert.match(fn.attrs[0].args, /message\s*,\s*payable/);
+  assert.strictEqual(fn.body.stmts[0].type, 'Let');
+});
+
+test('let-else keeps its diverging block', () => {
+  const [letElse, tail] = statements('fn f(x: Option<u64>) -> u64 { let Some(v) = x else { return 0; }; v }');
+  assert.strictEqual(letElse.type, 'Let');
+  assert.strictEqual(letElse.pattern.type, 'TupleStructPat');
+  assert.strictEqual(letElse.elseBlock.stmts[0].expr.type, 'Return');
+  assert.strictEqual(tail.expr.name, 'v');
+});
+
+test('macro invocations keep their name and tokens', () => {
+  const [assertion, vec] = statements('fn f() { assert!(a > b, "msg {}", c); let v = vec![1, 2, 3]; }');
+  assert.strictEqual(assertion.expr.type, 'Macro');
+  assert.strictEqual(assertion.expr.name, 'assert');
+  assert.strictEqual(assertion.expr.delimiter, '(');
+  assert.deepStrictEqual(assertion.expr.tokens.slice(0, 3).map(token => token.value), ['a', '>', 'b']);
+  assert.strictEqual(vec.init.type, 'Macro');
+  assert.strictEqual(vec.init.delimiter, '[');
+
+  const rules = parse('macro_rules! m { ($x:expr) => { $x + 1 }; }\nfn after() {}');
+  assert.strictEqual(rules.items[1].name, 'after');
+});
+
+test('raw strings, byte strings and char literals', () => {
+  const [raw, bytes, byte, quote] = statements('fn f() { let a = r#"he said "hi" {"#; let b = br"bytes"; let c = b\'x\'; let d = \'\\\'\'; }');
+  assert.strictEqual(raw.init.kind, 'string');
+  assert.strictEqual(raw.init.value, 'he said "hi" {');
+  assert.strictEqual(bytes.init.type, 'Literal');
+  assert.strictEqual(byte.init.type, 'Literal');
+  assert.strictEqual(quote.init.type, 'Literal');
+});
+
+test('lifetimes on types, generics and bounds', () => {
+  const ast = parse("struct R<'a> { s: &'a str }\nimpl<'a> R<'a> { fn get<'b: 'a>(&'b self) -> &'a str where 'a: 'b { self.s } }\nfn s() -> &'static str { \"x\" }");
+  const [struct, impl, fn] = ast.items;
+  assert.deepStrictEqual(struct.generics.params, [{ kind: 'lifetime', name: "'a" }]);
+  assert.strictEqual(struct.fields[0].fieldType.lifetime, "'a");
+  assert.strictEqual(impl.selfName, 'R');
+  assert.strictEqual(impl.items[0].name, 'get');
+  assert.strictEqual(fn.returnType.lifetime, "'static");
+});
+
+test('labelled loops, casts and shifts', () => {
+  const ast = parse("fn f(x: u128) -> u64 { 'outer: loop { for i in 0..10 { if i > 3 { break 'outer; } } } (x as u64) << 2 >> 1 }");
+  assert.strictEqual(find(ast, 'Break').label, "'outer");
+  const shift = ast.items[0].body.stmts[1].expr;
+  assert.strictEqual(shift.operator, '>>');
+  assert.strictEqual(shift.left.operator, '<<');
+});
+
+test('struct literals are not parsed in conditions', () => {
+  const ast = parse('fn f(a: A) { if a == (A { x: 1 }) { g(); } while let Some(x) = it.next() { h(x); } }');
+  const [ifStmt, whileStmt] = ast.items[0].body.stmts;
+  assert.strictEqual(ifStmt.expr.type, 'If');
+  assert.strictEqual(ifStmt.expr.consequent.stmts[0].expr.callee.name, 'g');
+  assert.strictEqual(whileStmt.expr.type, 'While');
+});
+
+test('the ? operator chains through method calls', () => {
+  const init = statements('fn f() -> Result<u64, E> { let x = g()?.h()?; Ok(x) }')[0].init;
+  assert.strictEqual(init.type, 'Try');
+  assert.strictEqual(init.argument.type, 'MethodCall');
+  assert.strictEqual(init.argument.receiver.type, 'Try');
+});
+
+test('nodes carry 1-based lines, 0-based columns and offsets', () => {
+  const code = 'fn f() {\n    let x = a + b;\n}';
+  const binary = find(parse(code), 'Binary');
+  assert.deepStrictEqual(binary.loc.start, { line: 2, column: 12 });
+  assert.strictEqual(code.slice(binary.range[0], binary.range[1]), 'a + b');
+});
+
+test('syntax errors are ParseErrors with a line', () => {
+  assert.throws(() => parse('fn f( {'), error => error instanceof ParseError && /line 1/.test(error.message));
+});
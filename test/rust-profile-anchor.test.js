@@ -0,0 +1,46 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const path = require('path');
+const { analyzeRustCode } = require('../src/rust-static-analyzer');
+
+// Analyze one of the Anchor samples
+function analyzeSample(name, options) {
+  const file = path.join(__dirname, '..', 'sample', 'anchor', name);
+  return analyzeRustCode(fs.readFileSync(file, 'utf8'), file, options);
+}
+
+function ruleIds(findings) {
+  return [...new Set(findings.map(finding => finding.ruleId))].sort();
+}
+
+test('the Anchor profile runs on Anchor programs only', () => {
+  assert.deepStrictEqual(analyzeSample('vulnerable_vault.rs').profiles.map(({ profile }) => profile.name), ['anchor']);
+  assert.deepStrictEqual(analyzeRustCode('pub fn main() {}', 'main.rs').profiles, []);
+});
+
+test('rule ids reported on the vulnerable vault', () => {
+  assert.deepStrictEqual(ruleIds(analyzeSample('vulnerable_vault.rs').findings), [
+    'anchor-missing-has-one',
+    'anchor-missing-mut',
+    'anchor-missing-owner-check',
+    'anchor-missing-signer',
+    'anchor-pda-noncanonical-bump',
+    'anchor-pda-seed-collision',
+    'anchor-unchecked-account',
+    'rust-integer-overflow',
+    'rust-integer-underflow',
+    'rust-slice-panic',
+    'rust-unwrap'
+  ]);
+});
+
+test('with --profile none only the generic checks run', () => {
+  const { profiles, findings } = analyzeSample('vulnerable_vault.rs', { profile: 'none' });
+  assert.deepStrictEqual(profiles, []);
+  assert.deepStrictEqual(ruleIds(findings), ['rust-integer-overflow', 'rust-integer-underflow', 'rust-slice-panic', 'rust-unwrap']);
+});
+
+test('the secure vault has no findings', () => {
+  assert.deepStrictEqual(ruleIds(analyzeSample('secure_vault.rs').findings), []);
+});
@@ -0,0 +1,41 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const path = require('path');
+const { analyzeRustCode } = require('../src/rust-static-analyzer');
+
+// Analyze one of the CosmWasm samples
+function analyzeSample(name, options) {
+  const file = path.join(__dirname, '..', 'sample', 'cosmwasm', name);
+  return analyzeRustCode(fs.readFileSync(file, 'utf8'), file, options);
+}
+
+function ruleIds(findings) {
+  return [...new Set(findings.map(finding => finding.ruleId))].sort();
+}
+
+test('the CosmWasm profile runs on CosmWasm contracts only', () => {
+  assert.deepStrictEqual(analyzeSample('vulnerable_escrow.rs').profiles.map(({ profile }) => profile.name), ['cosmwasm']);
+  assert.deepStrictEqual(analyzeRustCode('pub fn main() {}', 'main.rs').profiles, []);
+});
+
+test('rule ids reported on the vulnerable escrow', () => {
+  assert.deepStrictEqual(ruleIds(analyzeSample('vulnerable_escrow.rs').findings), [
+    'cosmwasm-funds-not-rejected',
+    'cosmwasm-missing-sender-check',
+    'cosmwasm-unbounded-iteration',
+    'cosmwasm-unchecked-uint-arithmetic',
+    'cosmwasm-unvalidated-funds',
+    'rust-index-panic'
+  ]);
+});
+
+test('with --profile none only the generic checks run', () => {
+  const { profiles, findings } = analyzeSample('vulnerable_escrow.rs', { profile: 'none' });
+  assert.deepStrictEqual(profiles, []);
+  assert.deepStrictEqual(ruleIds(findings), ['rust-index-panic']);
+});
+
+test('the secure escrow has no findings', () => {
+  assert.deepStrictEqual(ruleIds(analyzeSample('secure_escrow.rs').findings), []);
+});
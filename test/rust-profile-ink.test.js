@@ -0,0 +1,50 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const path = require('path');
+const { analyzeRustCode } = require('../src/rust-static-analyzer');
+
+// Analyze one of the ink! samples
+function analyzeSample(name, options) {
+  const file = path.join(__dirname, '..', 'sample', 'ink', name);
+  return analyzeRustCode(fs.readFileSync(file, 'utf8'), file, options);
+}
+
+function ruleIds(findings) {
+  return [...new Set(findings.map(finding => finding.ruleId))].sort();
+}
+
+// Functions reported by one rule
+function reported(findings, ruleId) {
+  return findings.filter(finding => finding.ruleId === ruleId).map(finding => finding.location.function);
+}
+
+test('the ink! profile runs on ink! contracts only', () => {
+  assert.deepStrictEqual(analyzeSample('vulnerable_vault.rs').profiles.map(({ profile }) => profile.name), ['ink']);
+  assert.deepStrictEqual(analyzeRustCode('pub fn main() {}', 'main.rs').profiles, []);
+});
+
+test('rule ids reported on the vulnerable vault', () => {
+  assert.deepStrictEqual(ruleIds(analyzeSample('vulnerable_vault.rs').findings), [
+    'ink-missing-caller-check',
+    'ink-payable-without-value-check',
+    'ink-unchecked-balance-arithmetic',
+    'ink-unprotected-set-code-hash',
+    'rust-unwrap'
+  ]);
+});
+
+test('an unguarded owner-only message is reported once, by the ink! check rather than the generic one', () => {
+  const generic = analyzeSample('vulnerable_vault.rs', { profile: 'none' }).findings;
+  assert.deepStrictEqual(reported(generic, 'rust-missing-authorization'), ['Vault::set_fee', 'Vault::transfer_ownership']);
+
+  const { findings } = analyzeSample('vulnerable_vault.rs');
+  assert.deepStrictEqual(reported(findings, 'ink-missing-caller-check'), ['Vault::set_fee', 'Vault::transfer_ownership']);
+  assert.deepStrictEqual(reported(findings, 'rust-missing-authorization'), []);
+});
+
+test('the secure vault only has informational notes', () => {
+  const { findings } = analyzeSample('secure_vault.rs');
+  assert.deepStrictEqual(ruleIds(findings), ['ink-set-code-hash', 'rust-basic-owner-check']);
+  assert.deepStrictEqual(findings.filter(finding => finding.severity !== 'info'), []);
+});
@@ -0,0 +1,48 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const path = require('path');
+const { analyzeRustCode } = require('../src/rust-static-analyzer');
+
+// Analyze one of the NEAR samples
+function analyzeSample(name, options) {
+  const file = path.join(__dirname, '..', 'sample', 'near', name);
+  return analyzeRustCode(fs.readFileSync(file, 'utf8'), file, options);
+}
+
+function ruleIds(findings) {
+  return [...new Set(findings.map(finding => finding.ruleId))].sort();
+}
+
+// Functions reported by one rule
+function reported(findings, ruleId) {
+  return findings.filter(finding => finding.ruleId === ruleId).map(finding => finding.location.function);
+}
+
+test('the NEAR profile runs on NEAR contracts only', () => {
+  assert.deepStrictEqual(analyzeSample('vulnerable_market.rs').profiles.map(({ profile }) => profile.name), ['near']);
+  assert.deepStrictEqual(analyzeRustCode('pub fn main() {}', 'main.rs').profiles, []);
+});
+
+test('rule ids reported on the vulnerable market', () => {
+  assert.deepStrictEqual(ruleIds(analyzeSample('vulnerable_market.rs').findings), [
+    'near-callback-not-private',
+    'near-payable-without-deposit-check',
+    'near-state-change-before-promise',
+    'near-storage-growth-unpaid',
+    'rust-assert-panic'
+  ]);
+});
+
+test('a public callback is reported once, by the NEAR check rather than the generic one', () => {
+  const generic = analyzeSample('vulnerable_market.rs', { profile: 'none' }).findings;
+  assert.deepStrictEqual(reported(generic, 'rust-missing-authorization'), ['Market::on_refund']);
+
+  const { findings } = analyzeSample('vulnerable_market.rs');
+  assert.deepStrictEqual(reported(findings, 'near-callback-not-private'), ['Market::on_claimed', 'Market::on_refund']);
+  assert.deepStrictEqual(reported(findings, 'rust-missing-authorization'), []);
+});
+
+test('the secure market has no findings', () => {
+  assert.deepStrictEqual(ruleIds(analyzeSample('secure_market.rs').findings), []);
+});
@@ -0,0 +1,151 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { findRustIssues } = require('../src/rust-static-analyzer');
+
+function analyze(code, filePath = 'test.rs') {
+  return findRustIssues(code, filePath);
+}
+
+test('each occurrence is its own finding with a location', () => {
+  const findings = analyze([
+    'pub fn load(a: Option<u64>, b: Option<u64>) -> u64 {',
+    '    let x = a.unwrap();',
+    '    x + b.unwrap()',
+    '}'
+  ].join('\n'), 'src/load.rs').filter(finding => finding.ruleId === 'rust-unwrap');
+  assert.deepStrictEqual(findings.map(({ location }) => [location.file, location.startLine, location.startColumn, location.function, location.snippet]), [
+    ['src/load.rs', 2, 13, 'load', 'let x = a.unwrap();'],
+    ['src/load.rs', 3, 9, 'load', 'x + b.unwrap()']
+  ]);
+});
+
+test('code that does not parse is reported with the error position', () => {
+  const [finding] = analyze('fn broken( {\n}');
+  assert.strictEqual(finding.ruleId, 'rust-parse-error');
+  assert.strictEqual(finding.location.startLine, 1);
+});
+
+// Severity of each arithmetic finding by function
+function arithmetic(findings, ruleId) {
+  return Object.fromEntries(findings
+    .filter(finding => finding.ruleId === ruleId)
+    .map(finding => [finding.location.function, finding.severity]));
+}
+
+test('only an ordering between the operands bounds a subtraction', () => {
+  const findings = analyze(`
+struct T { total: u64 }
+impl T {
+    pub fn unrelated(&mut self, amount: u64) { if amount == 0 { return; } self.total -= amount; }
+    pub fn wrong_way(&mut self, amount: u64) { if self.total > amount { return; } self.total -= amount; }
+    pub fn early_exit(&mut self, amount: u64) { if self.total < amount { return; } self.total -= amount; }
+    pub fn enclosing(&mut self, amount: u64) { if amount <= self.total { self.total -= amount; } }
+    pub fn aliased(&mut self, amount: u64) { let total = self.total; if total < amount { return; } self.total -= amount; }
+}`);
+  assert.deepStrictEqual(arithmetic(findings, 'rust-integer-underflow'), {
+    'T::unrelated': 'medium',
+    'T::wrong_way': 'medium',
+    'T::early_exit': 'low',
+    'T::enclosing': 'low',
+    'T::aliased': 'low'
+  });
+});
+
+test('64-bit counters incremented by one are not reported', () => {
+  const findings = analyze(`
+struct C { id: u64, small: u32, index: usize, total: u64 }
+impl C {
+    pub fn next(&mut self) { self.id += 1; }
+    pub fn small(&mut self) { self.small += 1; }
+    pub fn index(&mut self) { self.index += 1; }
+    pub fn step(&mut self) { self.id += 2; }
+    pub fn add(&mut self, amount: u64) { self.total += amount; }
+}`);
+  assert.deepStrictEqual(arithmetic(findings, 'rust-integer-overflow'), {
+    'C::small': 'medium',
+    'C::index': 'medium',
+    'C::step': 'medium',
+    'C::add': 'medium'
+  });
+});
+
+// Functions reported for unauthorized privileged writes
+function unauthorized(findings) {
+  return findings.filter(finding => finding.ruleId === 'rust-missing-authorization').map(finding => finding.location.function);
+}
+
+test('a comparison with a parameter does not authorize a write', () => {
+  const findings = analyze(`
+struct C { owner: String }
+impl C {
+    pub fn set_owner(&mut self, new_owner: String) {
+        if new_owner != self.owner { return; }
+        self.owner = new_owner;
+    }
+}`);
+  assert.deepStrictEqual(unauthorized(findings), ['C::set_owner']);
+});
+
+test('caller checks, caller aliases and guard helpers authorize a write', () => {
+  const findings = analyze(`
+struct C { owner: AccountId, fee: u64 }
+impl C {
+    pub fn set_fee(&mut self, fee: u64) {
+        require!(self.is_owner());
+        self.fee = fee;
+    }
+    pub fn set_owner(&mut self, owner: AccountId) {
+        let caller = env::predecessor_account_id();
+        assert_eq!(caller, self.owner);
+        self.owner = owner;
+    }
+    pub fn set_fee_checked(&mut self, fee: u64) {
+        self.only_owner();
+        self.fee = fee;
+    }
+    fn is_owner(&self) -> bool { env::predecessor_account_id() == self.owner }
+    fn only_owner(&self) { if env::predecessor_account_id() != self.owner { panic!("owner only"); } }
+}`);
+  assert.deepStrictEqual(unauthorized(findings), []);
+});
+
+test('writes keyed by the caller need no owner check, debits of other accounts do', () => {
+  const findings = analyze(`
+struct Bank { balances: HashMap<AccountId, u128> }
+impl Bank {
+    pub fn deposit(&mut self, amount: u128) {
+        let caller = env::predecessor_account_id();
+        self.balances.insert(caller, amount);
+    }
+    pub fn drain(&mut self, from: AccountId) {
+        self.balances.insert(from, 0);
+    }
+}`);
+  assert.deepStrictEqual(unauthorized(findings), ['Bank::drain']);
+});
+
+test('unreachable code is ranked lower, except for missing authorization', () => {
+  const findings = analyze(`
+struct Pool { owner: String, reserves: Vec<u64> }
+impl Pool {
+    pub fn get(&self) -> u64 { 0 }
+    fn unused(&mut self, owner: String) -> u64 {
+        self.owner = owner;
+        *self.reserves.first().unwrap()
+    }
+}`);
+  const severity = (ruleId) => findings.find(finding => finding.ruleId === ruleId).severity;
+  assert.strictEqual(severity('rust-missing-authorization'), 'high');
+  assert.strictEqual(severity('rust-unwrap'), 'low');
+});
+
+test('a standalone file treats the methods of an impl without pub methods as entry points', () => {
+  const findings = analyze(`
+struct Token { owner: String, supply: u64 }
+impl Token {
+    fn burn(&mut self, amount: u64) { self.supply -= amount; }
+}`);
+  const underflow = findings.find(finding => finding.ruleId === 'rust-integer-underflow');
+  assert.strictEqual(underflow.severity, 'medium');
+  assert.strictEqual(underflow.reachable, undefined);
+});
@@ -0,0 +1,113 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const { annotateTestSource, validateRuleSchema } = require('../src/semgrep-validator');
+
+const SOURCE = 'fn load(a: Option<u64>) -> u64 {\n    a.unwrap()\n}\n\nfn safe() -> u64 {\n    0\n}\n';
+
+const UNWRAP_RULE = {
+  id: 'rust-unwrap',
+  message: 'unwrap() panics on None',
+  languages: ['rust'],
+  severity: 'WARNING',
+  pattern: '$X.unwrap()'
+};
+
+const tempDirs = [];
+
+test.after(() => tempDirs.forEach(dir => fs.rmSync(dir, { recursive: true, force: true })));
+
+function tempDir() {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'winston-semgrep-'));
+  tempDirs.push(dir);
+  return dir;
+}
+
+// Load the module with SEMGREP_BIN set, so the semgrep lookup is redone
+function loadValidator(semgrepBin) {
+  const modulePath = require.resolve('../src/semgrep-validator');
+  const previous = process.env.SEMGREP_BIN;
+  process.env.SEMGREP_BIN = semgrepBin;
+  delete require.cache[modulePath];
+  try {
+    const validator = require(modulePath);
+    validator.findSemgrep();
+    return validator;
+  } finally {
+    if (previous === undefined) delete process.env.SEMGREP_BIN;
+    else process.env.SEMGREP_BIN = previous;
+  }
+}
+
+// Validate the rules against SOURCE in a fresh rules directory
+function validate(validator, candidates) {
+  const dir = tempDir();
+  const sourceFile = path.join(dir, 'lib.rs');
+  fs.writeFileSync(sourceFile, SOURCE);
+  const rulesDir = path.join(dir, 'rules');
+  fs.mkdirSync(rulesDir);
+  const results = validator.validateRules(candidates, { rulesDir, sourceFile, suffix: 'lib.rs', language: 'rust', extension: '.rs', okLines: [1, 5] });
+  return { rulesDir, results };
+}
+
+test('the schema check accepts a complete rule and names what is wrong with others', () => {
+  assert.deepStrictEqual(validateRuleSchema(UNWRAP_RULE, { language: 'rust' }).errors, []);
+  const { errors } = validateRuleSchema({ id: 'x', languages: ['python'], severity: 'SEVERE', pattern: '[The pattern to match problematic code]' }, { language: 'rust' });
+  assert.deepStrictEqual(errors, [
+    '`message` must be a non-empty string',
+    '`languages` does not include rust',
+    '`severity` must be one of ERROR, WARNING, INFO',
+    'pattern: `pattern` is placeholder text, not a pattern'
+  ]);
+});
+
+test('test sources are annotated with ruleid above matches and ok above untouched lines', () => {
+  assert.strictEqual(annotateTestSource(SOURCE, 'rust-unwrap', [{ startLine: 2, endLine: 2 }], [1, 5]), [
+    '// ok: rust-unwrap',
+    'fn load(a: Option<u64>) -> u64 {',
+    '    // ruleid: rust-unwrap',
+    '    a.unwrap()',
+    '}',
+    '',
+    '// ok: rust-unwrap',
+    'fn safe() -> u64 {',
+    '    0',
+    '}',
+    ''
+  ].join('\n'));
+});
+
+test('without semgrep, valid rules are kept but no unannotated test file is written', () => {
+  const validator = loadValidator(path.join(os.tmpdir(), 'no-such-semgrep'));
+  const { rulesDir, results } = validate(validator, [{ rule: UNWRAP_RULE }, { raw: 'rules: [', error: 'unexpected end' }]);
+
+  assert.deepStrictEqual(results.map(result => [result.status, result.checkedWith, result.testFile]), [
+    ['valid', 'schema', null],
+    ['invalid', 'schema', null]
+  ]);
+  assert.match(results[0].warnings.join('\n'), /No test file written: semgrep is not installed/);
+  assert.deepStrictEqual(fs.readdirSync(rulesDir).sort(), ['invalid', 'rust-unwrap-lib.rs.yml']);
+  assert.deepStrictEqual(fs.readdirSync(path.join(rulesDir, 'invalid')), ['raw_rule_2_lib.rs.yml']);
+});
+
+test('with semgrep, each rule gets a test file annotated from its matches', { skip: process.platform === 'win32' && 'needs an executable script' }, () => {
+  // A stand-in semgrep that reports a match of every rule on line 2
+  const bin = path.join(tempDir(), 'semgrep');
+  fs.writeFileSync(bin, [
+    '#!/usr/bin/env node',
+    "if (process.argv[2] === '--version') { console.log('1.0.0'); process.exit(0); }",
+    "console.log(JSON.stringify({ results: [{ start: { line: 2 }, end: { line: 2 } }], errors: [] }));"
+  ].join('\n'));
+  fs.chmodSync(bin, 0o755);
+
+  const validator = loadValidator(bin);
+  const { results } = validate(validator, [{ rule: UNWRAP_RULE }]);
+  assert.deepStrictEqual(results.map(result => [result.status, result.checkedWith, result.matches]), [
+    ['matching', 'semgrep', [{ startLine: 2, endLine: 2 }]]
+  ]);
+  const testSource = fs.readFileSync(results[0].testFile, 'utf8');
+  assert.match(testSource, /\/\/ ruleid: rust-unwrap\n {4}a\.unwrap\(\)/);
+  assert.match(testSource, /\/\/ ok: rust-unwrap\nfn safe/);
+});
@@ -0,0 +1,105 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { analysisToFindings, dropInvalidLines, extractJson, mergeAnalyses, parseAnalysis } = require('../src/structured-analysis');
+
+const CODE = [
+  'impl Vault {',
+  '    pub fn withdraw(&mut self, amount: u64) {',
+  '        self.total -= amount;',
+  '    }',
+  '}'
+].join('\n');
+const SOURCE = { code: CODE, functions: [{ name: 'Vault::withdraw', startLine: 2, endLine: 4 }] };
+
+function finding(fields = {}) {
+  return {
+    severity: 'high',
+    title: 'Unchecked subtraction',
+    category: 'Integer Overflow and Underflow',
+    function: 'Vault::withdraw',
+    lines: { start: 3, end: 3 },
+    description: '`self.total -= amount` underflows.',
+    exploitScenario: 'Withdraw more than the total.',
+    fix: 'Use checked_sub.',
+    confidence: 'high',
+    ...fields
+  };
+}
+
+function answer(findings) {
+  const analysis = { summary: 'A vault.', architecture: 'One struct.', businessLogic: 'Withdrawals.', findings, recommendations: [] };
+  return 'Here is the analysis:\n```json\n' + JSON.stringify(analysis, null, 2) + '\n```\n';
+}
+
+test('extractJson reads a json block or the outermost object', () => {
+  assert.deepStrictEqual(extractJson('```json\n{"a": 1}\n```').data, { a: 1 });
+  assert.deepStrictEqual(extractJson('Sure! {"a": {"b": 2}} Done.').data, { a: { b: 2 } });
+  assert.strictEqual(extractJson('No findings.').error, 'the answer contains no JSON object');
+  assert.match(extractJson('{"a": }').error, /^the JSON does not parse/);
+});
+
+test('answers that do not match the schema are rejected with the reasons', () => {
+  const { analysis, errors } = parseAnalysis(answer([finding({ severity: 'severe', lines: { start: '3' } })]), SOURCE);
+  assert.strictEqual(analysis, null);
+  assert.deepStrictEqual(errors, [
+    '$.findings[0].severity should be one of critical, high, medium, low, info, got "severe"',
+    '$.findings[0].lines.end is missing',
+    '$.findings[0].lines.start should be integer, got string'
+  ]);
+});
+
+test('line references outside the file or the named function are dropped with a note', () => {
+  const { analysis, errors, lineIssues } = parseAnalysis(answer([
+    finding(),
+    finding({ lines: { start: 4, end: 9 } }),
+    finding({ lines: { start: 1, end: 1 } })
+  ]), SOURCE);
+  assert.deepStrictEqual(errors, []);
+  assert.deepStrictEqual(lineIssues.map(issue => issue.index), [1, 2]);
+
+  dropInvalidLines(analysis, lineIssues);
+  assert.deepStrictEqual(analysis.findings.map(entry => entry.lines), [{ start: 3, end: 3 }, null, null]);
+  assert.strictEqual(analysis.findings[2].lineNote, 'The model cites lines 1-1, but `Vault::withdraw` spans lines 2-4');
+});
+
+test('analysisToFindings turns model findings into Findings with the cited code', () => {
+  const { analysis } = parseAnalysis(answer([finding()]), SOURCE);
+  const [converted] = analysisToFindings(analysis, { filePath: 'src/vault.rs', code: CODE, analyzer: 'rust-llm', ruleIdPrefix: 'rust-llm' });
+  assert.strictEqual(converted.ruleId, 'rust-llm.integer-overflow-and-underflow');
+  assert.strictEqual(converted.recommendation, 'Use checked_sub.');
+  assert.deepStrictEqual(converted.location, {
+    file: 'src/vault.rs',
+    startLine: 3,
+    startColumn: 1,
+    endLine: 3,
+    endColumn: 30,
+    function: 'Vault::withdraw',
+    snippet: '        self.total -= amount;'
+  });
+  assert.deepStrictEqual(converted.evidence.map(entry => entry.kind), ['code', 'note']);
+});
+
+test('mergeAnalyses joins the parts of a chunked file and keeps repeated findings once', () => {
+  const part = (label, findings, recommendations) => ({
+    label,
+    analysis: { summary: `${label} summary.`, architecture: '', businessLogic: 'Withdrawals.', findings, recommendations }
+  });
+  const { analysis, duplicates } = mergeAnalyses([
+    part('Part 1', [finding({ severity: 'medium', confidence: 'low', lines: null })], ['Use checked arithmetic.']),
+    part('Part 2', [
+      finding({ function: 'withdraw()', lines: { start: 3, end: 4 } }),
+      finding({ category: 'Access Control', lines: { start: 3, end: 3 } })
+    ], ['Use checked arithmetic!', 'Check the caller.'])
+  ]);
+  assert.strictEqual(duplicates, 1);
+  assert.strictEqual(analysis.summary, '**Part 1:** Part 1 summary.\n\n**Part 2:** Part 2 summary.');
+  assert.strictEqual(analysis.architecture, '');
+  assert.deepStrictEqual(
+    analysis.findings.map(entry => [entry.category, entry.severity, entry.confidence, entry.lines]),
+    [
+      ['Integer Overflow and Underflow', 'high', 'high', { start: 3, end: 4 }],
+      ['Access Control', 'high', 'high', { start: 3, end: 3 }]
+    ]
+  );
+  assert.deepStrictEqual(analysis.recommendations, ['Use checked arithmetic.', 'Check the caller.']);
+});